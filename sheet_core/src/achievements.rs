@@ -0,0 +1,139 @@
+use crate::skill::Skill;
+use crate::stats;
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+//====================================================
+// Achievement
+//====================================================
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Deserialize, JsonSchema, Serialize)]
+pub enum Achievement {
+    FirstSession,
+    TenHours,
+    HundredHours,
+    WeekStreak,
+    MonthStreak,
+    ThousandExp,
+    LevelTen,
+    FiveSkills,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 8] = [
+        Achievement::FirstSession,
+        Achievement::TenHours,
+        Achievement::HundredHours,
+        Achievement::WeekStreak,
+        Achievement::MonthStreak,
+        Achievement::ThousandExp,
+        Achievement::LevelTen,
+        Achievement::FiveSkills,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Achievement::FirstSession => "First Steps",
+            Achievement::TenHours => "Getting Started",
+            Achievement::HundredHours => "Dedicated",
+            Achievement::WeekStreak => "On a Roll",
+            Achievement::MonthStreak => "Unstoppable",
+            Achievement::ThousandExp => "Specialist",
+            Achievement::LevelTen => "Expert",
+            Achievement::FiveSkills => "Jack of All Trades",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstSession => "Log your first session",
+            Achievement::TenHours => "Log 10 hours in total",
+            Achievement::HundredHours => "Log 100 hours in total",
+            Achievement::WeekStreak => "Practice a skill 7 days in a row",
+            Achievement::MonthStreak => "Practice a skill 30 days in a row",
+            Achievement::ThousandExp => "Earn 1000 EXP in one skill",
+            Achievement::LevelTen => "Get a skill to level 10",
+            Achievement::FiveSkills => "Practice 5 different skills",
+        }
+    }
+
+    fn is_met(&self, progress: &Progress) -> bool {
+        match self {
+            Achievement::FirstSession => progress.sessions > 0,
+            Achievement::TenHours => progress.total_minutes >= 10 * 60,
+            Achievement::HundredHours => progress.total_minutes >= 100 * 60,
+            Achievement::WeekStreak => progress.best_streak >= 7,
+            Achievement::MonthStreak => progress.best_streak >= 30,
+            Achievement::ThousandExp => progress.best_skill_exp >= 1000f64,
+            Achievement::LevelTen => progress.best_level >= 10,
+            Achievement::FiveSkills => progress.skills_practiced >= 5,
+        }
+    }
+}
+
+/// Everything the achievements are judged on, gathered in one pass over the sheet.
+struct Progress {
+    sessions: usize,
+    total_minutes: u64,
+    best_streak: u32,
+    best_skill_exp: f64,
+    best_level: u32,
+    skills_practiced: usize,
+}
+
+impl Progress {
+    fn new(skills: &HashMap<Uuid, Skill>) -> Self {
+        let mut progress = Self {
+            sessions: 0,
+            total_minutes: 0,
+            best_streak: 0,
+            best_skill_exp: 0f64,
+            best_level: 0,
+            skills_practiced: 0,
+        };
+        skills.values().for_each(|skill| {
+            let sessions = skill.records.iter().filter(|r| r.approved).count();
+            progress.sessions += sessions;
+            progress.total_minutes += skill
+                .records
+                .iter()
+                .filter(|r| r.approved)
+                .map(|r| r.duration)
+                .sum::<u64>();
+            progress.best_streak = progress.best_streak.max(stats::best_streak(skill));
+            progress.best_skill_exp = progress.best_skill_exp.max(skill.total_exp);
+            progress.best_level = progress.best_level.max(skill.level());
+            if sessions > 0 {
+                progress.skills_practiced += 1;
+            }
+        });
+        progress
+    }
+}
+
+//====================================================
+// Achievements
+//====================================================
+/// Which achievements a sheet has unlocked, and when. Once unlocked an achievement stays
+/// unlocked, even if the records which earned it are later edited or deleted.
+#[derive(Clone, Default, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Achievements {
+    pub unlocked: HashMap<Achievement, NaiveDate>,
+}
+
+impl Achievements {
+    /// Unlocks anything `skills` now qualify for. Expects the skills' EXP to have been
+    /// recalculated already.
+    pub fn evaluate(&mut self, skills: &HashMap<Uuid, Skill>, today: NaiveDate) {
+        let progress = Progress::new(skills);
+        Achievement::ALL
+            .iter()
+            .filter(|a| a.is_met(&progress))
+            .for_each(|a| {
+                self.unlocked.entry(*a).or_insert(today);
+            });
+    }
+}
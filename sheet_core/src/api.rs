@@ -0,0 +1,129 @@
+//! A small API for reading and writing sheets from other Rust programs, without the GUI.
+//!
+//! ```no_run
+//! use chrono::Duration;
+//! use sheet_core::Sheet;
+//!
+//! let mut sheet = Sheet::open("myself.sht".as_ref())?;
+//! sheet.skill("Guitar").log(Duration::minutes(45));
+//! for (week, minutes) in sheet.stats().weekly() {
+//!     println!("{}: {} minutes", week, minutes);
+//! }
+//! sheet.save(1)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::clock;
+use crate::import;
+use crate::sheet::Sheet;
+use crate::skill::{SheetActionRecord, Skill};
+use crate::stats;
+use chrono::{Duration, NaiveDate};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+impl Sheet {
+    /// The skill called `name`, ignoring case, which is added to the sheet if there isn't one.
+    pub fn skill(&mut self, name: &str) -> SkillHandle<'_> {
+        let id = match import::find_skill(self, name) {
+            Some(id) => id,
+            None => {
+                let id = Uuid::new_v4();
+                let skill = Skill {
+                    name: name.trim().to_owned(),
+                    ..Default::default()
+                };
+                self.skills_list.insert(id, skill);
+                self.dirty = true;
+                id
+            }
+        };
+        SkillHandle { sheet: self, id }
+    }
+
+    pub fn stats(&self) -> SheetStats<'_> {
+        SheetStats { sheet: self }
+    }
+}
+
+//====================================================
+// SkillHandle
+//====================================================
+/// One skill on a sheet, from [`Sheet::skill`]. Logging through this keeps the skill's EXP up
+/// to date and marks the sheet as needing a save.
+pub struct SkillHandle<'a> {
+    sheet: &'a mut Sheet,
+    id: Uuid,
+}
+
+impl SkillHandle<'_> {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn get(&self) -> &Skill {
+        &self.sheet.skills_list[&self.id]
+    }
+
+    /// Logs a session today.
+    pub fn log(&mut self, duration: Duration) -> &mut Self {
+        self.log_on(clock::today(), duration)
+    }
+
+    /// Logs a session on `date`. On sheets which require approval it starts out pending.
+    pub fn log_on(&mut self, date: NaiveDate, duration: Duration) -> &mut Self {
+        let record = SheetActionRecord {
+            date,
+            duration: duration.num_minutes().max(0) as u64,
+            approved: !self.sheet.require_approval,
+            ..Default::default()
+        };
+        self.add_record(record)
+    }
+
+    /// Adds `record` as it is, for when it needs more than a date and duration filled in.
+    pub fn add_record(&mut self, record: SheetActionRecord) -> &mut Self {
+        if let Some(skill) = self.sheet.skills_list.get_mut(&self.id) {
+            skill.records.push(record);
+        }
+        self.sheet.recalculate_skill(&self.id);
+        self.sheet.dirty = true;
+        self
+    }
+
+    pub fn total_exp(&self) -> f64 {
+        self.get().total_exp
+    }
+
+    pub fn level(&self) -> u32 {
+        self.get().level()
+    }
+}
+
+//====================================================
+// SheetStats
+//====================================================
+/// Totals across every skill on a sheet, from [`Sheet::stats`]. All times are in minutes.
+pub struct SheetStats<'a> {
+    sheet: &'a Sheet,
+}
+
+impl SheetStats<'_> {
+    /// Minutes logged each week, keyed by the Monday the week starts on.
+    pub fn weekly(&self) -> BTreeMap<NaiveDate, u64> {
+        stats::minutes_per_week(self.sheet.skills_list.values())
+    }
+
+    /// Approved minutes logged each day.
+    pub fn daily(&self) -> BTreeMap<NaiveDate, u64> {
+        stats::minutes_per_day(self.sheet.skills_list.values())
+    }
+
+    pub fn total_minutes(&self) -> u64 {
+        self.daily().values().sum()
+    }
+
+    pub fn total_exp(&self) -> f64 {
+        self.sheet.skills_list.values().map(|s| s.total_exp).sum()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::skill::{self, Skill};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+//====================================================
+// Attribute
+//====================================================
+/// A broad RPG-style stat, like Body or Mind, which grows with the skills that feed into it.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Attribute {
+    pub name: String,
+    pub skills: Vec<Uuid>,
+}
+
+impl Default for Attribute {
+    fn default() -> Self {
+        Self {
+            name: "New Attribute".to_owned(),
+            skills: Vec::new(),
+        }
+    }
+}
+
+impl Attribute {
+    fn named(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Everything the attribute's skills have earned between them. Skills which have since been
+    /// deleted are skipped.
+    pub fn exp(&self, skills: &HashMap<Uuid, Skill>) -> f64 {
+        self.skills
+            .iter()
+            .filter_map(|id| skills.get(id))
+            .map(|skill| skill.total_exp)
+            .sum()
+    }
+
+    /// Attributes level up on the same curve as skills, so they move faster the more skills
+    /// feed into them.
+    pub fn level(&self, skills: &HashMap<Uuid, Skill>) -> u32 {
+        skill::level_for_exp(self.exp(skills))
+    }
+}
+
+/// The attributes a new sheet starts with. None of them have any skills until some are added.
+pub fn default_attributes() -> Vec<Attribute> {
+    ["Body", "Mind", "Craft"]
+        .iter()
+        .map(|name| Attribute::named(name))
+        .collect()
+}
@@ -0,0 +1,585 @@
+use crate::clock;
+use crate::skill::SheetActionRecord;
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// The days on which each skill on a sheet was practiced.
+pub type PracticeDays = HashMap<Uuid, HashSet<NaiveDate>>;
+
+/// Everything a bonus rule gets to look at when working out a record's bonus.
+pub struct RuleContext<'a> {
+    /// The skill the record belongs to.
+    pub skill_id: &'a Uuid,
+    /// The skill's earlier records, oldest first, with their EXP already worked out.
+    pub history: &'a [SheetActionRecord],
+    /// Practice days for every skill on the sheet, for rules which look across skills.
+    pub practice_days: &'a PracticeDays,
+}
+
+/// One step of the bonus pipeline.
+pub trait Rule {
+    /// Takes the bonus worked out by the rules before this one and returns the adjusted bonus.
+    /// `record.base_exp` is already filled in by the time this is called.
+    fn apply(&self, ctx: &RuleContext<'_>, record: &SheetActionRecord, bonus: f64) -> f64;
+
+    /// Why this rule changed `record`'s EXP, for rules whose effect might come as a surprise.
+    fn explain(&self, _ctx: &RuleContext<'_>, _record: &SheetActionRecord) -> Option<String> {
+        None
+    }
+}
+
+//====================================================
+// BonusRule
+//====================================================
+/// A configured bonus rule, as saved in a sheet. Rules run in the order they're listed.
+///
+/// To add a new kind of bonus, write a struct implementing [`Rule`] and give it a variant here.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+pub enum BonusRule {
+    Streak(StreakRule),
+    Synergy(SynergyRule),
+    Event(EventRule),
+    Cap(CapRule),
+    Decay(DecayRule),
+    Cooldown(CooldownRule),
+}
+
+impl BonusRule {
+    pub fn rule(&self) -> &dyn Rule {
+        match self {
+            BonusRule::Streak(r) => r,
+            BonusRule::Synergy(r) => r,
+            BonusRule::Event(r) => r,
+            BonusRule::Cap(r) => r,
+            BonusRule::Decay(r) => r,
+            BonusRule::Cooldown(r) => r,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BonusRule::Streak(_) => "Streak",
+            BonusRule::Synergy(_) => "Synergy",
+            BonusRule::Event(_) => "Event multiplier",
+            BonusRule::Cap(_) => "Cap",
+            BonusRule::Decay(_) => "Decay",
+            BonusRule::Cooldown(_) => "Cooldown",
+        }
+    }
+
+    /// Whether this rule depends on skills other than the one being calculated.
+    pub fn looks_across_skills(&self) -> bool {
+        matches!(self, BonusRule::Synergy(_))
+    }
+}
+
+/// The rules a new sheet starts with.
+pub fn default_rules() -> Vec<BonusRule> {
+    vec![BonusRule::Streak(StreakRule::default())]
+}
+
+/// Runs `record` through every rule in order and returns the bonus it ends up with.
+pub fn apply_rules(rules: &[BonusRule], ctx: &RuleContext<'_>, record: &SheetActionRecord) -> f64 {
+    rules
+        .iter()
+        .fold(0f64, |bonus, rule| rule.rule().apply(ctx, record, bonus))
+}
+
+/// Everything the rules have to say about how they treated `record`.
+pub fn explain_rules(
+    rules: &[BonusRule],
+    ctx: &RuleContext<'_>,
+    record: &SheetActionRecord,
+) -> Vec<String> {
+    rules
+        .iter()
+        .filter_map(|rule| rule.rule().explain(ctx, record))
+        .collect()
+}
+
+//====================================================
+// StreakRule
+//====================================================
+/// Each recent session adds a share of its EXP to the next one, fading out over a few days.
+///
+/// Skills listed in `length_skills` use a simpler model instead, where the bonus grows with the
+/// number of days in a row the skill has been practiced.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct StreakRule {
+    /// The share of a session's EXP passed on to a session the next day.
+    pub max_daily_bonus: f64,
+    /// How many days a session keeps contributing to later ones.
+    pub max_bonus_days: i64,
+    /// Skills whose bonus is based on the length of their streak.
+    pub length_skills: Vec<Uuid>,
+    /// Share of base EXP added for each day of the streak, for `length_skills`.
+    pub per_streak_day: f64,
+    /// The most of the base EXP a streak can add, for `length_skills`.
+    pub max_streak_bonus: f64,
+}
+
+impl Default for StreakRule {
+    fn default() -> Self {
+        Self {
+            max_daily_bonus: 0.5,
+            max_bonus_days: 5,
+            length_skills: Vec::new(),
+            per_streak_day: 0.02,
+            max_streak_bonus: 0.5,
+        }
+    }
+}
+
+impl StreakRule {
+    /// How many days in a row, up to the day before `record`, the skill was practiced.
+    fn streak_days(ctx: &RuleContext<'_>, record: &SheetActionRecord) -> i64 {
+        let mut streak_days = 0;
+        let mut expected = record.date - chrono::Duration::days(1);
+        for r in ctx.history.iter().rev().filter(|r| r.approved) {
+            if r.date > expected {
+                // Another session on a day that's already been counted.
+                continue;
+            }
+            if r.date < expected {
+                break;
+            }
+            streak_days += 1;
+            expected -= chrono::Duration::days(1);
+        }
+        streak_days
+    }
+}
+
+impl Rule for StreakRule {
+    fn apply(&self, ctx: &RuleContext<'_>, record: &SheetActionRecord, bonus: f64) -> f64 {
+        if self.length_skills.contains(ctx.skill_id) {
+            let share = self.per_streak_day * Self::streak_days(ctx, record) as f64;
+            return bonus + record.base_exp * share.min(self.max_streak_bonus);
+        }
+
+        // A hand-edited sheet could ask for no days, which would divide by zero below.
+        if self.max_bonus_days <= 0 {
+            return bonus;
+        }
+        let daily_degredation = self.max_daily_bonus / self.max_bonus_days as f64;
+
+        // Go through all recent items in the history, calculate their total, multiply by the
+        // number of days' degredation, and then add to our running bonus exp.
+        let streak_bonus: f64 = ctx
+            .history
+            .iter()
+            .rev()
+            .filter(|s| s.approved)
+            .map(|s| (s, record.date.signed_duration_since(s.date).num_days()))
+            .take_while(|(_, num_days)| *num_days <= self.max_bonus_days)
+            .map(|(s, num_days)| {
+                let multiplier = self.max_daily_bonus - (daily_degredation * num_days as f64);
+                (s.base_exp + s.bonus_exp) * multiplier
+            })
+            .sum();
+
+        bonus + streak_bonus
+    }
+}
+
+//====================================================
+// SynergyRule
+//====================================================
+/// Practicing any of a set of related skills on the same day boosts this one.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct SynergyRule {
+    pub skills: Vec<Uuid>,
+    /// Extra EXP as a share of the session's base EXP.
+    pub bonus: f64,
+}
+
+impl Default for SynergyRule {
+    fn default() -> Self {
+        Self {
+            skills: Vec::new(),
+            bonus: 0.1,
+        }
+    }
+}
+
+impl Rule for SynergyRule {
+    fn apply(&self, ctx: &RuleContext<'_>, record: &SheetActionRecord, bonus: f64) -> f64 {
+        let has_synergy = self
+            .skills
+            .iter()
+            .filter(|id| *id != ctx.skill_id)
+            .filter_map(|id| ctx.practice_days.get(id))
+            .any(|days| days.contains(&record.date));
+
+        if has_synergy {
+            bonus + record.base_exp * self.bonus
+        } else {
+            bonus
+        }
+    }
+}
+
+//====================================================
+// EventRule
+//====================================================
+/// Multiplies everything a session earns between two dates (inclusive), e.g. a double EXP week.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct EventRule {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub multiplier: f64,
+}
+
+impl Default for EventRule {
+    fn default() -> Self {
+        let today = clock::today();
+        Self {
+            start: today,
+            end: today + chrono::Duration::days(7),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl Rule for EventRule {
+    fn apply(&self, _ctx: &RuleContext<'_>, record: &SheetActionRecord, bonus: f64) -> f64 {
+        if self.start <= record.date && record.date <= self.end {
+            // The extra EXP is counted as bonus so the base stays a plain function of duration.
+            bonus + (record.base_exp + bonus) * (self.multiplier - 1f64)
+        } else {
+            bonus
+        }
+    }
+}
+
+//====================================================
+// CapRule
+//====================================================
+/// Limits the bonus any one session can earn.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct CapRule {
+    pub max_bonus: f64,
+}
+
+impl Default for CapRule {
+    fn default() -> Self {
+        Self { max_bonus: 100.0 }
+    }
+}
+
+impl Rule for CapRule {
+    fn apply(&self, _ctx: &RuleContext<'_>, _record: &SheetActionRecord, bonus: f64) -> f64 {
+        bonus.min(self.max_bonus)
+    }
+}
+
+//====================================================
+// DecayRule
+//====================================================
+/// Coming back after a long break costs some of the session's EXP, as a bit of "rust".
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct DecayRule {
+    /// How many days off are allowed before decay kicks in.
+    pub grace_days: i64,
+    /// Share of the session's base EXP lost for each day past the grace period.
+    pub per_day: f64,
+    /// The most of the session's base EXP which can be lost.
+    pub max_penalty: f64,
+}
+
+impl Default for DecayRule {
+    fn default() -> Self {
+        Self {
+            grace_days: 7,
+            per_day: 0.02,
+            max_penalty: 0.5,
+        }
+    }
+}
+
+impl Rule for DecayRule {
+    fn apply(&self, ctx: &RuleContext<'_>, record: &SheetActionRecord, bonus: f64) -> f64 {
+        let last = match ctx.history.iter().rev().find(|r| r.approved) {
+            Some(last) => last,
+            None => return bonus,
+        };
+
+        let days_off = record.date.signed_duration_since(last.date).num_days() - self.grace_days;
+        if days_off <= 0 {
+            return bonus;
+        }
+        let penalty = (self.per_day * days_off as f64).min(self.max_penalty);
+        bonus - record.base_exp * penalty
+    }
+}
+
+//====================================================
+// CooldownRule
+//====================================================
+/// For skills where rest matters: sessions past a daily limit lose most of what they'd earn.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct CooldownRule {
+    pub skills: Vec<Uuid>,
+    /// How many sessions a day earn in full. `None` for no limit.
+    pub max_sessions: Option<u32>,
+    /// How many minutes a day earn in full. `None` for no limit.
+    pub max_minutes: Option<u64>,
+    /// The share of a session's EXP which is lost past the limit.
+    pub penalty: f64,
+}
+
+impl Default for CooldownRule {
+    fn default() -> Self {
+        Self {
+            skills: Vec::new(),
+            max_sessions: Some(2),
+            max_minutes: Some(120),
+            penalty: 0.75,
+        }
+    }
+}
+
+/// How far past its limits a session went.
+struct Overage {
+    /// Which session of the day it was, counting from 1.
+    session: u32,
+    /// Minutes practiced that day by the end of the session.
+    day_minutes: u64,
+    /// How much of the session is past a limit, from 0 to 1.
+    share: f64,
+}
+
+impl CooldownRule {
+    fn overage(&self, ctx: &RuleContext<'_>, record: &SheetActionRecord) -> Option<Overage> {
+        if !self.skills.contains(ctx.skill_id) {
+            return None;
+        }
+        let earlier: Vec<&SheetActionRecord> = ctx
+            .history
+            .iter()
+            .rev()
+            .take_while(|r| r.date == record.date)
+            .filter(|r| r.approved)
+            .collect();
+        let session = earlier.len() as u32 + 1;
+        let minutes_before: u64 = earlier.iter().map(|r| r.duration).sum();
+        let day_minutes = minutes_before + record.duration;
+
+        let session_share = match self.max_sessions {
+            Some(max) if session > max => 1f64,
+            _ => 0f64,
+        };
+        let minutes_share = match self.max_minutes {
+            Some(max) if day_minutes > max && record.duration > 0 => {
+                // Only the part of the session past the limit counts.
+                let over = (day_minutes - max).min(record.duration);
+                over as f64 / record.duration as f64
+            }
+            _ => 0f64,
+        };
+        let share = session_share.max(minutes_share);
+        (share > 0f64).then_some(Overage {
+            session,
+            day_minutes,
+            share,
+        })
+    }
+}
+
+impl Rule for CooldownRule {
+    fn apply(&self, ctx: &RuleContext<'_>, record: &SheetActionRecord, bonus: f64) -> f64 {
+        match self.overage(ctx, record) {
+            Some(overage) => bonus - (record.base_exp + bonus) * self.penalty * overage.share,
+            None => bonus,
+        }
+    }
+
+    fn explain(&self, ctx: &RuleContext<'_>, record: &SheetActionRecord) -> Option<String> {
+        let overage = self.overage(ctx, record)?;
+        let mut reasons = Vec::new();
+        if let Some(max) = self.max_sessions.filter(|max| overage.session > *max) {
+            reasons.push(format!(
+                "session {} of the day, past the limit of {}",
+                overage.session, max
+            ));
+        }
+        if let Some(max) = self.max_minutes.filter(|max| overage.day_minutes > *max) {
+            reasons.push(format!(
+                "{} minutes that day, past the limit of {}",
+                overage.day_minutes, max
+            ));
+        }
+        Some(format!(
+            "Cooldown: lost {:.0}% ({})",
+            self.penalty * overage.share * 100f64,
+            reasons.join("; ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(n: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2024, 3, n)
+    }
+
+    fn record(date: NaiveDate, base_exp: f64) -> SheetActionRecord {
+        SheetActionRecord {
+            date,
+            duration: 60,
+            base_exp,
+            ..Default::default()
+        }
+    }
+
+    /// The bonus `rule` gives a session of 100 base EXP on `on`, after `history`.
+    fn bonus_of(rule: &dyn Rule, history: &[SheetActionRecord], on: NaiveDate) -> f64 {
+        let ctx = RuleContext {
+            skill_id: &Uuid::nil(),
+            history,
+            practice_days: &PracticeDays::new(),
+        };
+        rule.apply(&ctx, &record(on, 100.0), 0.0)
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn streaks_pass_on_a_fading_share_of_recent_sessions() {
+        let streak = StreakRule::default();
+        let history = [record(day(1), 10.0), record(day(4), 10.0)];
+        // The session a day before passes on 40%, and the one four days before 10%.
+        assert!(close(bonus_of(&streak, &history, day(5)), 5.0));
+    }
+
+    #[test]
+    fn streaks_stop_after_max_bonus_days() {
+        let streak = StreakRule::default();
+        let history = [record(day(1), 10.0)];
+        assert!(close(bonus_of(&streak, &history, day(6)), 0.0));
+        assert!(close(bonus_of(&streak, &history, day(9)), 0.0));
+    }
+
+    #[test]
+    fn unapproved_sessions_dont_count_towards_a_streak() {
+        let streak = StreakRule::default();
+        let mut unapproved = record(day(4), 10.0);
+        unapproved.approved = false;
+        assert!(close(bonus_of(&streak, &[unapproved], day(5)), 0.0));
+    }
+
+    #[test]
+    fn streaks_over_no_days_give_nothing() {
+        let streak = StreakRule {
+            max_bonus_days: 0,
+            ..Default::default()
+        };
+        let history = [record(day(4), 10.0)];
+        assert!(close(bonus_of(&streak, &history, day(4)), 0.0));
+        assert!(close(bonus_of(&streak, &history, day(5)), 0.0));
+    }
+
+    #[test]
+    fn length_streaks_grow_with_days_in_a_row() {
+        let streak = StreakRule {
+            length_skills: vec![Uuid::nil()],
+            ..Default::default()
+        };
+        let history = [
+            record(day(1), 10.0),
+            record(day(3), 10.0),
+            record(day(3), 10.0),
+            record(day(4), 10.0),
+        ];
+        // Days 3 and 4 are in a row, the second session on day 3 isn't another day, and the
+        // gap on day 2 ends the streak.
+        assert!(close(bonus_of(&streak, &history, day(5)), 4.0));
+        assert!(close(bonus_of(&streak, &history, day(6)), 0.0));
+    }
+
+    #[test]
+    fn length_streaks_are_capped() {
+        let streak = StreakRule {
+            length_skills: vec![Uuid::nil()],
+            ..Default::default()
+        };
+        let history: Vec<SheetActionRecord> = (1..=30).map(|n| record(day(n), 10.0)).collect();
+        assert!(close(bonus_of(&streak, &history, day(31)), 50.0));
+    }
+
+    #[test]
+    fn decay_starts_after_the_grace_period() {
+        let decay = DecayRule::default();
+        let history = [record(day(1), 10.0)];
+        assert!(close(bonus_of(&decay, &history, day(8)), 0.0));
+        // Three days past the week's grace, at 2% a day.
+        assert!(close(bonus_of(&decay, &history, day(11)), -6.0));
+        assert!(close(bonus_of(&decay, &[], day(11)), 0.0));
+    }
+
+    #[test]
+    fn decay_is_capped() {
+        let decay = DecayRule::default();
+        let history = [record(NaiveDate::from_ymd(2023, 1, 1), 10.0)];
+        assert!(close(bonus_of(&decay, &history, day(1)), -50.0));
+    }
+
+    #[test]
+    fn events_multiply_sessions_between_their_dates() {
+        let event = EventRule {
+            start: day(10),
+            end: day(12),
+            multiplier: 2.0,
+        };
+        assert!(close(bonus_of(&event, &[], day(10)), 100.0));
+        assert!(close(bonus_of(&event, &[], day(12)), 100.0));
+        assert!(close(bonus_of(&event, &[], day(13)), 0.0));
+    }
+
+    #[test]
+    fn cooldowns_cost_sessions_past_the_limit() {
+        let cooldown = CooldownRule {
+            skills: vec![Uuid::nil()],
+            max_sessions: Some(2),
+            max_minutes: None,
+            penalty: 0.75,
+        };
+        let history = [record(day(1), 10.0), record(day(2), 10.0)];
+        assert!(close(bonus_of(&cooldown, &history, day(2)), 0.0));
+        let history = [record(day(2), 10.0), record(day(2), 10.0)];
+        assert!(close(bonus_of(&cooldown, &history, day(2)), -75.0));
+    }
+
+    #[test]
+    fn rules_run_in_order() {
+        let event = BonusRule::Event(EventRule {
+            start: day(1),
+            end: day(1),
+            multiplier: 2.0,
+        });
+        let cap = BonusRule::Cap(CapRule { max_bonus: 30.0 });
+        let ctx = RuleContext {
+            skill_id: &Uuid::nil(),
+            history: &[],
+            practice_days: &PracticeDays::new(),
+        };
+        let session = record(day(1), 100.0);
+        let rules = [event.clone(), cap.clone()];
+        assert!(close(apply_rules(&rules, &ctx, &session), 30.0));
+        let rules = [cap, event];
+        assert!(close(apply_rules(&rules, &ctx, &session), 100.0));
+    }
+}
@@ -0,0 +1,153 @@
+use crate::export;
+use crate::publish;
+use crate::sheet::Sheet;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+//====================================================
+// BusySettings
+//====================================================
+/// Showing as busy while a timer runs, so meetings aren't booked over practice: a calendar file
+/// with a busy event in it, and commands to run as the first timer starts and the last stops,
+/// such as turning do not disturb on and off.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BusySettings {
+    pub enabled: bool,
+    /// Where to write an iCalendar file for a calendar to subscribe to. Blank for none.
+    pub ics_path: String,
+    /// Run by the shell when the first timer starts. Blank for none.
+    pub start_command: String,
+    /// Run by the shell when the last timer stops. Blank for none.
+    pub stop_command: String,
+    /// How far the busy event reaches. Timers don't know when they'll stop, so it's pushed on by
+    /// as much again each time it's nearly reached.
+    pub block_minutes: u32,
+}
+
+impl Default for BusySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ics_path: String::new(),
+            start_command: String::new(),
+            stop_command: String::new(),
+            block_minutes: 60,
+        }
+    }
+}
+
+impl BusySettings {
+    /// What the marker should show at `now`, or `None` if no timer is running on any of
+    /// `sheets`.
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use sheet_core::busy::BusySettings;
+    /// use sheet_core::Sheet;
+    ///
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// let id = sheet.skill("Guitar").id();
+    /// let now = Utc::now();
+    /// sheet.start_timer(id, now - Duration::minutes(50));
+    ///
+    /// let busy = BusySettings::default().state([&sheet], now).unwrap();
+    /// assert_eq!(busy.skills, ["Guitar"]);
+    /// // The hour is over half gone, so it already reaches into the next one.
+    /// assert_eq!(busy.until - busy.start, Duration::minutes(120));
+    /// ```
+    pub fn state<'a>(
+        &self,
+        sheets: impl IntoIterator<Item = &'a Sheet>,
+        now: DateTime<Utc>,
+    ) -> Option<BusyState> {
+        let mut running: Vec<(DateTime<Utc>, String)> = sheets
+            .into_iter()
+            .flat_map(|sheet| {
+                sheet.timers.iter().filter_map(move |(id, start)| {
+                    Some((*start, sheet.skills_list.get(id)?.name.clone()))
+                })
+            })
+            .collect();
+        running.sort();
+        let start = running.first()?.0;
+
+        // Always at least half a block ahead, so a calendar which only looks now and then doesn't
+        // see a gap before it's pushed on.
+        let block = Duration::minutes(self.block_minutes.max(1) as i64);
+        let elapsed = (now - start).max(Duration::zero());
+        let blocks = (elapsed + block / 2).num_minutes() / block.num_minutes() + 1;
+        Some(BusyState {
+            skills: running.into_iter().map(|(_, name)| name).collect(),
+            start,
+            until: start + block * blocks as i32,
+        })
+    }
+
+    /// Shows `state`, or clears the marker for `None`. The start and stop commands only run when
+    /// that changes from `was_busy`; they're left running in the background.
+    pub fn apply(&self, state: Option<&BusyState>, was_busy: bool) -> io::Result<()> {
+        if !self.ics_path.trim().is_empty() {
+            fs::write(self.ics_path.trim(), busy_ics(state, Utc::now()))?;
+        }
+        let command = match (state.is_some(), was_busy) {
+            (true, false) => &self.start_command,
+            (false, true) => &self.stop_command,
+            _ => return Ok(()),
+        };
+        if !command.trim().is_empty() {
+            publish::shell_command(command.trim(), Path::new(".")).spawn()?;
+        }
+        Ok(())
+    }
+}
+
+//====================================================
+// BusyState
+//====================================================
+/// The timers running across the open sheets, as the busy marker shows them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BusyState {
+    /// The skills being timed, earliest started first.
+    pub skills: Vec<String>,
+    /// When the earliest of them started.
+    pub start: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+}
+
+/// A calendar with one busy event for `state` in it, or none if it's `None`. The event keeps the
+/// same UID, so being pushed on or cleared updates it in place.
+fn busy_ics(state: Option<&BusyState>, now: DateTime<Utc>) -> String {
+    const TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//Sheet Myself//Busy//EN".to_owned(),
+        "X-WR-CALNAME:Practice".to_owned(),
+    ];
+    if let Some(state) = state {
+        lines.extend(vec![
+            "BEGIN:VEVENT".to_owned(),
+            "UID:busy@sheet-myself".to_owned(),
+            format!("DTSTAMP:{}", now.format(TIME_FORMAT)),
+            format!("DTSTART:{}", state.start.format(TIME_FORMAT)),
+            format!("DTEND:{}", state.until.format(TIME_FORMAT)),
+            format!(
+                "SUMMARY:{}",
+                export::escape_ics_text(&format!("Practicing {}", state.skills.join(", ")))
+            ),
+            "TRANSP:OPAQUE".to_owned(),
+            "STATUS:CONFIRMED".to_owned(),
+            "END:VEVENT".to_owned(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_owned());
+    lines
+        .iter()
+        .map(|line| export::fold_ics_line(line))
+        .collect()
+}
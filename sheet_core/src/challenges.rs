@@ -0,0 +1,224 @@
+use crate::goals::{Goal, GoalMeasure};
+use crate::skill::{SheetActionRecord, Skill};
+use crate::stats;
+use crate::units::Unit;
+use chrono::{Datelike, Duration, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// What a boss is called, picked by the week of the year.
+const BOSS_NAMES: [&str; 8] = [
+    "the Procrastination Hydra",
+    "Sir Slumps-a-Lot",
+    "the Plateau Golem",
+    "the Doomscroll Wyrm",
+    "the Excuse Lich",
+    "the Someday Specter",
+    "the Burnout Basilisk",
+    "the Snooze Kraken",
+];
+
+/// The EXP the skills earn per hour, which the reward is a share of.
+const EXP_PER_HOUR: f64 = 55.0;
+
+//====================================================
+// BossPart
+//====================================================
+/// One of the things it takes to beat a week's boss.
+#[derive(Clone, Copy, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub enum BossPart {
+    /// Minutes of practice, across every skill.
+    TotalMinutes(u64),
+    /// Different skills practiced.
+    Skills(usize),
+    /// One session at least this many minutes long.
+    LongSession(u64),
+}
+
+impl BossPart {
+    pub fn describe(&self) -> String {
+        match self {
+            BossPart::TotalMinutes(minutes) => {
+                format!("{:.1}h of practice in total", hours(*minutes))
+            }
+            BossPart::Skills(count) => format!("{} different skills", count),
+            BossPart::LongSession(minutes) => format!("One session of {} minutes", minutes),
+        }
+    }
+
+    /// How far along this part is, out of how much it needs.
+    fn progress(
+        &self,
+        week: &[(&Uuid, &SheetActionRecord)],
+        units: &HashMap<Uuid, Unit>,
+    ) -> (f64, f64) {
+        // Skills logged in other units don't have any minutes to count.
+        let minutes = week
+            .iter()
+            .filter(|(skill_id, _)| !units.contains_key(skill_id))
+            .map(|(_, record)| record.duration);
+        match self {
+            BossPart::TotalMinutes(target) => (minutes.sum::<u64>() as f64, *target as f64),
+            BossPart::Skills(target) => {
+                let skills: HashSet<&Uuid> = week.iter().map(|(skill_id, _)| *skill_id).collect();
+                (skills.len() as f64, *target as f64)
+            }
+            BossPart::LongSession(target) => (minutes.max().unwrap_or(0) as f64, *target as f64),
+        }
+    }
+}
+
+fn hours(minutes: u64) -> f64 {
+    minutes as f64 / 60.0
+}
+
+//====================================================
+// Boss
+//====================================================
+/// A week's challenge, made of several parts which all have to be done that week.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+pub struct Boss {
+    pub name: String,
+    /// The Monday of the week it's for.
+    pub week: NaiveDate,
+    pub parts: Vec<BossPart>,
+    /// EXP added to the pool when it's beaten.
+    pub reward_exp: f64,
+    /// When it was beaten, if it has been.
+    pub defeated: Option<NaiveDate>,
+}
+
+/// How one part of a boss is going.
+pub struct PartProgress {
+    pub part: BossPart,
+    pub amount: f64,
+    /// From 0 to 1.
+    pub fraction: f64,
+}
+
+impl Boss {
+    /// Rolls the boss for the week starting `week`, scaled to the goals which are running then:
+    /// the hours they need per week between them, and how many skills they're for. A sheet
+    /// without any gets a modest one.
+    pub fn roll(skill_count: usize, goals: &[Goal], week: NaiveDate) -> Self {
+        let week_end = week + Duration::days(6);
+        let running: Vec<&Goal> = goals
+            .iter()
+            .filter(|goal| goal.start <= week_end && week <= goal.deadline)
+            .collect();
+
+        let weekly_minutes: f64 = running
+            .iter()
+            .filter(|goal| goal.measure == GoalMeasure::Hours)
+            .map(|goal| {
+                let weeks = ((goal.deadline - goal.start).num_days() + 1) as f64 / 7.0;
+                goal.target * 60.0 / weeks.max(1.0)
+            })
+            .sum();
+        // Rounded up to the half hour.
+        let total = if weekly_minutes > 0.0 {
+            ((weekly_minutes / 30.0).ceil() as u64 * 30).clamp(60, 20 * 60)
+        } else {
+            120
+        };
+        let goal_skills: HashSet<Uuid> = running.iter().map(|goal| goal.skill_id).collect();
+        let skills = if goal_skills.is_empty() {
+            3
+        } else {
+            goal_skills.len().clamp(2, 5)
+        };
+        let long_session = ((total / 4 / 15) * 15).clamp(30, 90);
+
+        let name = BOSS_NAMES[week.iso_week().week() as usize % BOSS_NAMES.len()];
+        Self {
+            name: name.to_owned(),
+            week,
+            parts: vec![
+                BossPart::TotalMinutes(total),
+                BossPart::Skills(skills.min(skill_count.max(1))),
+                BossPart::LongSession(long_session),
+            ],
+            reward_exp: (hours(total) * EXP_PER_HOUR * 0.5).round(),
+            defeated: None,
+        }
+    }
+
+    /// How each part is going, from the skills' approved records in the boss's week.
+    pub fn progress(
+        &self,
+        skills: &HashMap<Uuid, Skill>,
+        units: &HashMap<Uuid, Unit>,
+    ) -> Vec<PartProgress> {
+        let week_end = self.week + Duration::days(6);
+        let week: Vec<(&Uuid, &SheetActionRecord)> = skills
+            .iter()
+            .flat_map(|(skill_id, skill)| {
+                skill.records.iter().map(move |record| (skill_id, record))
+            })
+            .filter(|(_, record)| {
+                record.approved && self.week <= record.date && record.date <= week_end
+            })
+            .collect();
+        self.parts
+            .iter()
+            .map(|part| {
+                let (amount, target) = part.progress(&week, units);
+                PartProgress {
+                    part: *part,
+                    amount,
+                    fraction: if target > 0.0 {
+                        (amount / target).min(1.0)
+                    } else {
+                        1.0
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+//====================================================
+// Challenges
+//====================================================
+/// The weekly boss, and the badges and EXP from the ones beaten so far. A boss stays beaten even
+/// if the records which beat it are later edited or deleted.
+#[derive(Clone, Default, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Challenges {
+    /// This week's boss, once it's been rolled.
+    pub current: Option<Boss>,
+    /// Every boss beaten, as badges, oldest first.
+    pub badges: Vec<Boss>,
+    /// EXP from beaten bosses. It isn't any one skill's, so it's kept apart from theirs.
+    pub pool_exp: f64,
+}
+
+impl Challenges {
+    /// Rolls a new boss when the week has moved on, and beats the current one if its parts are
+    /// all done. Expects the skills' records to be up to date.
+    pub fn update(
+        &mut self,
+        skills: &HashMap<Uuid, Skill>,
+        units: &HashMap<Uuid, Unit>,
+        goals: &[Goal],
+        today: NaiveDate,
+    ) {
+        let week = stats::week_start(today);
+        if self.current.as_ref().map_or(true, |boss| boss.week != week) {
+            self.current = Some(Boss::roll(skills.len(), goals, week));
+        }
+        if let Some(boss) = &mut self.current {
+            let beaten = boss
+                .progress(skills, units)
+                .iter()
+                .all(|part| part.fraction >= 1.0);
+            if boss.defeated.is_none() && beaten {
+                boss.defeated = Some(today);
+                self.pool_exp += boss.reward_exp;
+                self.badges.push(boss.clone());
+            }
+        }
+    }
+}
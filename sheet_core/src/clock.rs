@@ -0,0 +1,133 @@
+use chrono::{
+    DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicI32, AtomicU32, AtomicU8, Ordering};
+
+//====================================================
+// DayBoundary
+//====================================================
+/// Which clock the date is read from.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub enum ClockZone {
+    /// The computer's own time zone.
+    #[default]
+    Local,
+    Utc,
+    /// A fixed number of minutes ahead of UTC, or behind it if negative.
+    Offset(i32),
+}
+
+impl ClockZone {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClockZone::Local => "This computer's time zone",
+            ClockZone::Utc => "UTC",
+            ClockZone::Offset(_) => "Fixed offset from UTC",
+        }
+    }
+}
+
+/// When one day ends and the next begins. Everything which works out "today" goes through this,
+/// so a session logged just after midnight can still count toward the day before.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DayBoundary {
+    pub zone: ClockZone,
+    /// The hour the new day starts at. Anything before it counts toward the day before.
+    pub start_hour: u32,
+}
+
+impl DayBoundary {
+    fn start_hour(&self) -> Duration {
+        Duration::hours(self.start_hour.min(23) as i64)
+    }
+
+    fn fixed_offset(&self) -> Option<FixedOffset> {
+        match self.zone {
+            ClockZone::Local => None,
+            ClockZone::Utc => Some(FixedOffset::east(0)),
+            ClockZone::Offset(minutes) => Some(FixedOffset::east(minutes.clamp(-1439, 1439) * 60)),
+        }
+    }
+
+    /// The time on the wall clock at `now`, ignoring `start_hour`.
+    pub fn wall_clock(&self, now: DateTime<Utc>) -> NaiveDateTime {
+        let offset = self
+            .fixed_offset()
+            .unwrap_or_else(|| now.with_timezone(&Local).offset().fix());
+        now.with_timezone(&offset).naive_local()
+    }
+
+    /// The day `now` falls on.
+    pub fn date_at(&self, now: DateTime<Utc>) -> NaiveDate {
+        (self.wall_clock(now) - self.start_hour()).date()
+    }
+
+    /// The moment `date` begins.
+    pub fn start_of(&self, date: NaiveDate) -> DateTime<Utc> {
+        let start = date.and_hms(0, 0, 0) + self.start_hour();
+        let start = match self.fixed_offset() {
+            Some(offset) => offset
+                .from_local_datetime(&start)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+            None => Local
+                .from_local_datetime(&start)
+                .earliest()
+                .map(|t| t.with_timezone(&Utc)),
+        };
+        // Only a clock change can skip over the start of a day, and then UTC is close enough.
+        start.unwrap_or_else(|| DateTime::from_utc(date.and_hms(0, 0, 0), Utc))
+    }
+}
+
+// The boundary is read from all over, including other threads, so it's kept in atomics rather
+// than passed around.
+static ZONE_KIND: AtomicU8 = AtomicU8::new(0);
+static ZONE_OFFSET: AtomicI32 = AtomicI32::new(0);
+static START_HOUR: AtomicU32 = AtomicU32::new(0);
+
+/// Makes `boundary` the one used by [`today`] and everything else in this module.
+pub fn set(boundary: DayBoundary) {
+    let (kind, offset) = match boundary.zone {
+        ClockZone::Local => (0, 0),
+        ClockZone::Utc => (1, 0),
+        ClockZone::Offset(minutes) => (2, minutes),
+    };
+    ZONE_OFFSET.store(offset, Ordering::Relaxed);
+    ZONE_KIND.store(kind, Ordering::Relaxed);
+    START_HOUR.store(boundary.start_hour, Ordering::Relaxed);
+}
+
+/// The boundary set by [`set`], or the default one.
+pub fn boundary() -> DayBoundary {
+    let zone = match ZONE_KIND.load(Ordering::Relaxed) {
+        1 => ClockZone::Utc,
+        2 => ClockZone::Offset(ZONE_OFFSET.load(Ordering::Relaxed)),
+        _ => ClockZone::Local,
+    };
+    DayBoundary {
+        zone,
+        start_hour: START_HOUR.load(Ordering::Relaxed),
+    }
+}
+
+pub fn today() -> NaiveDate {
+    date_at(Utc::now())
+}
+
+/// The day `now` falls on.
+pub fn date_at(now: DateTime<Utc>) -> NaiveDate {
+    boundary().date_at(now)
+}
+
+/// The moment `date` begins.
+pub fn start_of(date: NaiveDate) -> DateTime<Utc> {
+    boundary().start_of(date)
+}
+
+/// The time on the wall clock right now.
+pub fn wall_clock() -> NaiveDateTime {
+    boundary().wall_clock(Utc::now())
+}
@@ -0,0 +1,93 @@
+use crate::sheet::Sheet;
+use crate::skill::Skill;
+use crate::stats;
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+//====================================================
+// Completion
+//====================================================
+/// A skill which has been seen through to the end, such as passing an exam, along with where it
+/// stood then. The figures are kept as they were, so changing the bonus rules later doesn't
+/// rewrite what was achieved.
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct Completion {
+    pub date: NaiveDate,
+    /// What was achieved, e.g. "Passed JLPT N2".
+    pub achievement: String,
+    pub level: u32,
+    pub exp: f64,
+    /// Approved minutes, or the skill's own unit.
+    pub minutes: u64,
+    /// How many different days it was practiced on.
+    pub days: usize,
+    pub best_streak: u32,
+}
+
+impl Completion {
+    pub fn of(skill: &Skill, achievement: &str, date: NaiveDate) -> Self {
+        let approved = || skill.records.iter().filter(|r| r.approved);
+        let days: HashSet<NaiveDate> = approved().map(|r| r.date).collect();
+        Self {
+            date,
+            achievement: achievement.trim().to_owned(),
+            level: skill.level(),
+            exp: skill.total_exp,
+            minutes: approved().map(|r| r.duration).sum(),
+            days: days.len(),
+            best_streak: stats::best_streak(skill),
+        }
+    }
+}
+
+impl Sheet {
+    /// Marks `skill_id` as completed on `date`, freezing its figures as they are. It stops being
+    /// logged to, suggested or reminded about, and any timer running on it is stopped without
+    /// logging anything.
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use sheet_core::{clock, Sheet};
+    ///
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// let id = sheet.skill("Japanese").log(Duration::hours(3)).id();
+    /// assert!(sheet.complete_skill(&id, "Passed JLPT N2", clock::today()));
+    /// assert_eq!(sheet.completed[&id].level, 1);
+    /// assert_eq!(sheet.active_skills().count(), 0);
+    /// ```
+    pub fn complete_skill(&mut self, skill_id: &Uuid, achievement: &str, date: NaiveDate) -> bool {
+        let skill = match self.skills_list.get(skill_id) {
+            Some(skill) => skill,
+            None => return false,
+        };
+        let completion = Completion::of(skill, achievement, date);
+        self.completed.insert(*skill_id, completion);
+        self.timers.remove(skill_id);
+        self.distractions.remove(skill_id);
+        self.planned.retain(|planned| planned.skill_id != *skill_id);
+        self.dirty = true;
+        true
+    }
+
+    /// Takes `skill_id` back out of the Hall of Fame so it can be practiced again, giving back
+    /// what it was completed with.
+    pub fn reopen_skill(&mut self, skill_id: &Uuid) -> Option<Completion> {
+        let completion = self.completed.remove(skill_id)?;
+        self.dirty = true;
+        Some(completion)
+    }
+
+    pub fn is_completed(&self, skill_id: &Uuid) -> bool {
+        self.completed.contains_key(skill_id)
+    }
+
+    /// The skills which are still being practiced, leaving out completed ones.
+    pub fn active_skills(&self) -> impl Iterator<Item = (&Uuid, &Skill)> {
+        self.skills_list
+            .iter()
+            .filter(move |(id, _)| !self.completed.contains_key(id))
+    }
+}
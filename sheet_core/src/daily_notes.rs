@@ -0,0 +1,234 @@
+use crate::sheet::Sheet;
+use crate::stats;
+use chrono::{Duration, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+//====================================================
+// Mood
+//====================================================
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub enum Mood {
+    Awful,
+    Bad,
+    Okay,
+    Good,
+    Great,
+}
+
+impl Mood {
+    pub const ALL: [Mood; 5] = [Mood::Awful, Mood::Bad, Mood::Okay, Mood::Good, Mood::Great];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Mood::Awful => "Awful",
+            Mood::Bad => "Bad",
+            Mood::Okay => "Okay",
+            Mood::Good => "Good",
+            Mood::Great => "Great",
+        }
+    }
+
+    /// The mood as a number from 1 (awful) to 5 (great), for charting and comparing.
+    pub fn score(&self) -> f64 {
+        match self {
+            Mood::Awful => 1.0,
+            Mood::Bad => 2.0,
+            Mood::Okay => 3.0,
+            Mood::Good => 4.0,
+            Mood::Great => 5.0,
+        }
+    }
+}
+
+//====================================================
+// DailyNote
+//====================================================
+/// A note about a day as a whole, rather than about any one skill's practice.
+#[derive(Clone, Default, PartialEq, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct DailyNote {
+    pub mood: Option<Mood>,
+    /// Hours slept the night before.
+    pub sleep_hours: Option<f64>,
+    /// Anything else about the day, e.g. "travelling" or "had a cold".
+    pub text: String,
+}
+
+impl DailyNote {
+    pub fn is_empty(&self) -> bool {
+        self.mood.is_none() && self.sleep_hours.is_none() && self.text.trim().is_empty()
+    }
+
+    /// e.g. "Mood: Good, slept 7.5h, had a cold".
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(mood) = self.mood {
+            parts.push(format!("Mood: {}", mood.label()));
+        }
+        if let Some(hours) = self.sleep_hours {
+            parts.push(format!("slept {}h", hours));
+        }
+        if !self.text.trim().is_empty() {
+            parts.push(self.text.trim().to_owned());
+        }
+        parts.join(", ")
+    }
+}
+
+impl Sheet {
+    /// Puts `note` down for `date`, or takes the day's note away if `note` is empty.
+    pub fn set_daily_note(&mut self, date: NaiveDate, note: DailyNote) {
+        if note.is_empty() {
+            self.daily_notes.remove(&date);
+        } else {
+            self.daily_notes.insert(date, note);
+        }
+    }
+}
+
+//====================================================
+// Correlation
+//====================================================
+/// Something noted about days which can be compared against practice.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DayMeasure {
+    Mood,
+    Sleep,
+}
+
+impl DayMeasure {
+    pub const ALL: [DayMeasure; 2] = [DayMeasure::Mood, DayMeasure::Sleep];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DayMeasure::Mood => "Mood",
+            DayMeasure::Sleep => "Sleep",
+        }
+    }
+
+    pub fn of(&self, note: &DailyNote) -> Option<f64> {
+        match self {
+            DayMeasure::Mood => note.mood.map(|mood| mood.score()),
+            DayMeasure::Sleep => note.sleep_hours,
+        }
+    }
+}
+
+/// How closely a measure from the daily notes moves with the minutes practiced, from -1 to 1.
+/// `None` means one of the series didn't vary enough to say.
+pub struct NoteCorrelation {
+    pub measure: DayMeasure,
+    /// The skill whose minutes it was compared against, or `None` for every skill together.
+    pub skill: Option<Uuid>,
+    /// How many of the days had the measure noted.
+    pub days: usize,
+    /// Against the minutes practiced the same day.
+    pub same_day: Option<f64>,
+    /// Against the minutes practiced the day after.
+    pub next_day: Option<f64>,
+}
+
+impl NoteCorrelation {
+    /// The further from 0 of its two correlations.
+    pub fn strongest(&self) -> f64 {
+        [self.same_day, self.next_day]
+            .iter()
+            .flatten()
+            .map(|r| r.abs())
+            .fold(0f64, f64::max)
+    }
+}
+
+/// Correlations between each measure in the daily notes and the minutes practiced, on every
+/// skill together and on each skill, over the `days` days up to and including `today`,
+/// strongest first. Measures noted on fewer than `min_days` of those days are left out.
+///
+/// ```
+/// use chrono::{Duration, NaiveDate};
+/// use sheet_core::daily_notes::{self, DailyNote, DayMeasure, Mood};
+/// use sheet_core::Sheet;
+///
+/// let first = NaiveDate::from_ymd(2024, 6, 1);
+/// let mut sheet = Sheet::new("myself.sht".as_ref());
+/// (0..6).for_each(|offset| {
+///     let (minutes, mood) = if offset % 2 == 0 { (60, Mood::Great) } else { (10, Mood::Bad) };
+///     let day = first + Duration::days(offset);
+///     sheet.skill("Guitar").log_on(day, Duration::minutes(minutes));
+///     let note = DailyNote { mood: Some(mood), ..Default::default() };
+///     sheet.set_daily_note(day, note);
+/// });
+///
+/// let today = first + Duration::days(5);
+/// let found = daily_notes::note_correlations(&sheet, today, 30, 4);
+/// let all = found.iter().find(|c| c.skill.is_none() && c.measure == DayMeasure::Mood);
+/// let all = all.unwrap();
+/// assert!((all.same_day.unwrap() - 1.0).abs() < 1e-9);
+/// assert!((all.next_day.unwrap() + 1.0).abs() < 1e-9);
+/// ```
+pub fn note_correlations(
+    sheet: &Sheet,
+    today: NaiveDate,
+    days: i64,
+    min_days: usize,
+) -> Vec<NoteCorrelation> {
+    let from = today - Duration::days(days - 1);
+    let mut practice: Vec<(Option<Uuid>, BTreeMap<NaiveDate, u64>)> =
+        vec![(None, stats::minutes_per_day(sheet.skills_list.values()))];
+    practice.extend(
+        sheet
+            .skills_list
+            .iter()
+            .map(|(id, skill)| (Some(*id), stats::minutes_per_day([skill]))),
+    );
+
+    let mut found = Vec::new();
+    DayMeasure::ALL.iter().for_each(|measure| {
+        let noted: Vec<(NaiveDate, f64)> = sheet
+            .daily_notes
+            .range(from..=today)
+            .filter_map(|(day, note)| Some((*day, measure.of(note)?)))
+            .collect();
+        if noted.len() < min_days {
+            return;
+        }
+        // Today's next day hasn't happened yet, so it would only ever count as a day off.
+        let before_today: Vec<(NaiveDate, f64)> = noted
+            .iter()
+            .copied()
+            .filter(|(day, _)| *day < today)
+            .collect();
+
+        practice.iter().for_each(|(skill, minutes)| {
+            let against = |days: &[(NaiveDate, f64)], offset: i64| {
+                let values: Vec<f64> = days.iter().map(|(_, value)| *value).collect();
+                let practiced: Vec<f64> = days
+                    .iter()
+                    .map(|(day, _)| {
+                        let day = *day + Duration::days(offset);
+                        minutes.get(&day).copied().unwrap_or(0) as f64
+                    })
+                    .collect();
+                stats::correlation(&values, &practiced)
+            };
+            let correlation = NoteCorrelation {
+                measure: *measure,
+                skill: *skill,
+                days: noted.len(),
+                same_day: against(&noted, 0),
+                next_day: against(&before_today, 1),
+            };
+            if correlation.same_day.is_some() || correlation.next_day.is_some() {
+                found.push(correlation);
+            }
+        });
+    });
+    found.sort_by(|a, b| {
+        b.strongest()
+            .partial_cmp(&a.strongest())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    found
+}
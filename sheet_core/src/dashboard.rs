@@ -0,0 +1,155 @@
+use crate::season::SeasonSummary;
+use crate::sheet::Sheet;
+use crate::skill::Skill;
+use crate::stats;
+use chrono::{Datelike, NaiveDate};
+use std::cmp::Reverse;
+
+//====================================================
+// Dashboard
+//====================================================
+/// A skill and how it's doing by one measure, for the dashboard's leaderboards.
+pub struct Ranked<T> {
+    pub name: String,
+    pub value: T,
+}
+
+/// Figures across every skill on a sheet, as of `today`.
+pub struct Dashboard {
+    /// Time logged on skills which are logged in minutes.
+    pub week_minutes: u64,
+    pub month_minutes: u64,
+    /// Time worked out from skills logged in other units, by their estimates.
+    pub week_estimated_minutes: f64,
+    pub month_estimated_minutes: f64,
+    /// The estimates that went into those, e.g. "Reading: 10 pages ≈ 30 min".
+    pub estimates: Vec<String>,
+    /// Skills logged in some other unit without an estimate, which are left out of the times.
+    pub unconverted: Vec<String>,
+    pub pomodoros_today: u32,
+    /// Skills which earned EXP this week, most first.
+    pub top_gains: Vec<Ranked<f64>>,
+    /// Skills with a streak going, longest first.
+    pub streaks: Vec<Ranked<u32>>,
+    /// The bonus each skill's next session would get, biggest first.
+    pub potential_bonuses: Vec<Ranked<f64>>,
+    /// This season and the one before it, if seasonal mode is on.
+    pub seasons: Option<(SeasonSummary, SeasonSummary)>,
+}
+
+impl Dashboard {
+    pub fn new(sheet: &Sheet, today: NaiveDate) -> Self {
+        let week_start = stats::week_start(today);
+        let skills: Vec<&Skill> = sheet.skills_list.values().collect();
+        let in_minutes: Vec<&Skill> = sheet
+            .skills_list
+            .iter()
+            .filter(|(id, _)| !sheet.units.contains_key(id))
+            .map(|(_, skill)| skill)
+            .collect();
+        let approved = || {
+            in_minutes
+                .iter()
+                .flat_map(|skill| skill.records.iter())
+                .filter(|r| r.approved && r.date <= today)
+        };
+        let in_week = |date: NaiveDate| week_start <= date && date <= today;
+        let in_month = |date: NaiveDate| {
+            date <= today && date.year() == today.year() && date.month() == today.month()
+        };
+
+        let mut week_estimated_minutes = 0.0;
+        let mut month_estimated_minutes = 0.0;
+        let mut estimates = Vec::new();
+        let mut unconverted = Vec::new();
+        sheet.units.iter().for_each(|(id, unit)| {
+            let skill = match sheet.skills_list.get(id) {
+                Some(skill) => skill,
+                None => return,
+            };
+            let estimate = match unit.estimate {
+                Some(estimate) => estimate,
+                None => {
+                    unconverted.push(skill.name.clone());
+                    return;
+                }
+            };
+            let approved = || skill.records.iter().filter(|r| r.approved);
+            let week: u64 = approved()
+                .filter(|r| in_week(r.date))
+                .map(|r| r.duration)
+                .sum();
+            let month: u64 = approved()
+                .filter(|r| in_month(r.date))
+                .map(|r| r.duration)
+                .sum();
+            week_estimated_minutes += estimate.minutes_for(week);
+            month_estimated_minutes += estimate.minutes_for(month);
+            estimates.push(format!("{}: {}", skill.name, estimate.describe(&unit.name)));
+        });
+        estimates.sort();
+        unconverted.sort();
+
+        let mut top_gains: Vec<Ranked<f64>> = skills
+            .iter()
+            .map(|skill| Ranked {
+                name: skill.name.clone(),
+                value: skill
+                    .records
+                    .iter()
+                    .filter(|r| r.approved && week_start <= r.date && r.date <= today)
+                    .map(|r| r.base_exp + r.bonus_exp)
+                    .sum(),
+            })
+            .filter(|ranked| ranked.value > 0f64)
+            .collect();
+        top_gains.sort_by(|a, b| b.value.partial_cmp(&a.value).unwrap());
+
+        let mut streaks: Vec<Ranked<u32>> = skills
+            .iter()
+            .map(|skill| Ranked {
+                name: skill.name.clone(),
+                value: stats::current_streak(skill, today),
+            })
+            .filter(|ranked| ranked.value > 1)
+            .collect();
+        streaks.sort_by_key(|ranked| Reverse(ranked.value));
+
+        let mut potential_bonuses: Vec<Ranked<f64>> = skills
+            .iter()
+            .map(|skill| Ranked {
+                name: skill.name.clone(),
+                value: skill.potential_bonus,
+            })
+            .filter(|ranked| ranked.value > 0f64)
+            .collect();
+        potential_bonuses.sort_by(|a, b| b.value.partial_cmp(&a.value).unwrap());
+
+        let seasons = sheet.seasons.current(today).map(|season| {
+            (
+                SeasonSummary::new(sheet, season),
+                SeasonSummary::new(sheet, season.previous()),
+            )
+        });
+
+        Self {
+            week_minutes: approved()
+                .filter(|r| in_week(r.date))
+                .map(|r| r.duration)
+                .sum(),
+            month_minutes: approved()
+                .filter(|r| in_month(r.date))
+                .map(|r| r.duration)
+                .sum(),
+            week_estimated_minutes,
+            month_estimated_minutes,
+            estimates,
+            unconverted,
+            pomodoros_today: sheet.pomodoros.get(&today).copied().unwrap_or(0),
+            top_gains,
+            streaks,
+            potential_bonuses,
+            seasons,
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::skill::SheetActionRecord;
+use chrono::NaiveDate;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+use uuid::Uuid;
+
+//====================================================
+// NoteEdit
+//====================================================
+/// A record's note open in another program. The note is written to a temp file, and every time
+/// the file is saved the new text is picked up, until the edit is dropped.
+pub struct NoteEdit {
+    pub skill_id: Uuid,
+    /// Where the record was in the skill's list when the edit started.
+    pub record: usize,
+    /// The record's date, to find it again if the records have been re-sorted since.
+    pub date: NaiveDate,
+    /// The note as it is in the record.
+    pub note: String,
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+}
+
+impl NoteEdit {
+    /// Opens the record's note with `program`, which may include arguments, e.g. `code --wait`.
+    /// A blank `program` uses `$VISUAL` or `$EDITOR`, then the system's default text editor.
+    /// The temp file can only be read by this user, but is still plain text, so notes from an
+    /// encrypted sheet shouldn't be opened this way.
+    pub fn start(
+        skill_id: Uuid,
+        record: usize,
+        rec: &SheetActionRecord,
+        program: &str,
+    ) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("sheet_myself_note_{}.txt", Uuid::new_v4()));
+        create_private(&path)?.write_all(rec.note.as_bytes())?;
+        let last_modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+
+        let mut command = editor_command(program);
+        command.arg(&path).spawn()?;
+
+        Ok(Self {
+            skill_id,
+            record,
+            date: rec.date,
+            note: rec.note.clone(),
+            path,
+            last_modified,
+        })
+    }
+
+    /// The note's new text, if the file has been saved with changes since the last check.
+    pub fn poll(&mut self) -> Option<String> {
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified == self.last_modified {
+            return None;
+        }
+        self.last_modified = modified;
+        // Editors like to end files with a newline, which isn't part of the note.
+        let text = fs::read_to_string(&self.path).ok()?.trim_end().to_owned();
+        (text != self.note).then_some(text)
+    }
+
+    /// Puts `text` into the record this note came from, even if the records have been re-sorted
+    /// since. Returns false if the record can't be found any more.
+    pub fn apply(&mut self, records: &mut [SheetActionRecord], text: String) -> bool {
+        let in_place = records
+            .get(self.record)
+            .map_or(false, |r| r.date == self.date && r.note == self.note);
+        let found = if in_place {
+            records.get_mut(self.record)
+        } else {
+            records
+                .iter_mut()
+                .find(|r| r.date == self.date && r.note == self.note)
+        };
+        match found {
+            Some(rec) => {
+                rec.note = text.clone();
+                self.note = text;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Drop for NoteEdit {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Creates a new file at `path` which only this user can read or write. Temp folders are often
+/// shared with everyone else on the computer.
+fn create_private(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+fn editor_command(program: &str) -> Command {
+    let configured = Some(program.to_owned())
+        .filter(|p| !p.trim().is_empty())
+        .or_else(|| std::env::var("VISUAL").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .filter(|p| !p.trim().is_empty());
+    match configured {
+        Some(program) => {
+            let mut words = program.split_whitespace();
+            let mut command = Command::new(words.next().unwrap_or_default());
+            command.args(words);
+            command
+        }
+        None => default_editor(),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn default_editor() -> Command {
+    Command::new("notepad")
+}
+
+#[cfg(target_os = "macos")]
+fn default_editor() -> Command {
+    let mut command = Command::new("open");
+    command.arg("-t");
+    command
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn default_editor() -> Command {
+    Command::new("xdg-open")
+}
@@ -0,0 +1,228 @@
+//! Sheets whose notes shouldn't sit in plain text, such as in a synced folder, can have their
+//! file encrypted with a password. The whole file is encrypted with XChaCha20-Poly1305, under a
+//! key derived from the password by Argon2id with a salt kept at the start of the file. An
+//! encrypted sheet keeps its password in memory while it's open, so that its file can be read
+//! again after it's changed somewhere else, but never writes it anywhere.
+
+use crate::file_format::FileFormat;
+use crate::sheet::{self, LoadError, Sheet};
+use crate::storage::{FileStorage, PendingSave, Storage};
+use crate::sync::DiskStamp;
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// What an encrypted file starts with, before its salt, nonce and ciphertext.
+const MAGIC: &[u8] = b"sheet_myself encrypted v1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Whether `data`, the contents of a sheet's file, is encrypted.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+//====================================================
+// Encryption
+//====================================================
+/// A password a sheet's file is encrypted with. As a [`Storage`], it reads and writes files in
+/// any of the [`FileFormat`]s, encrypted.
+#[derive(Clone)]
+pub struct Encryption {
+    password: String,
+    salt: [u8; SALT_LEN],
+    key: Key,
+}
+
+impl Encryption {
+    /// Encryption with `password` and a new salt, for a sheet which is being given a password.
+    pub fn new(password: &str) -> Self {
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self::with_salt(password, salt)
+    }
+
+    fn with_salt(password: &str, salt: [u8; SALT_LEN]) -> Self {
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .expect("Argon2's default parameters take any password and this salt");
+        Self {
+            password: password.to_owned(),
+            salt,
+            key,
+        }
+    }
+
+    /// Opens the encrypted sheet at `path` with `password`. The sheet keeps it, to save with.
+    pub fn open(path: &Path, password: &str) -> Result<Sheet, LoadError> {
+        let data = read(path)?;
+        let salt = Sealed::of(&data)?.salt;
+        Self::with_salt(password, salt).decrypt(&data, path)
+    }
+
+    fn decrypt(&self, data: &[u8], path: &Path) -> Result<Sheet, LoadError> {
+        let sealed = Sealed::of(data)?;
+        let plain = XChaCha20Poly1305::new(&self.key)
+            .decrypt(XNonce::from_slice(sealed.nonce), sealed.ciphertext)
+            .map_err(|_| LoadError::WrongPassword)?;
+        let text = String::from_utf8(plain)
+            .map_err(|e| LoadError::Io(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        let mut sheet = FileStorage::parse(&text, path)?;
+        sheet.on_disk = Some(DiskStamp::of(data));
+        sheet.encryption = Some(self.clone());
+        Ok(sheet)
+    }
+}
+
+impl Storage for Encryption {
+    /// Reads the sheet at `path` with this password. A file which isn't encrypted, such as a
+    /// backup from before the sheet had a password, is read as it is.
+    fn load(&self, path: &Path) -> Result<Sheet, LoadError> {
+        let data = read(path)?;
+        if !is_encrypted(&data) {
+            return FileStorage.load(path);
+        }
+        // The password may have been changed on another computer since, with a new salt.
+        match Sealed::of(&data)?.salt {
+            salt if salt == self.salt => self.decrypt(&data, path),
+            salt => Self::with_salt(&self.password, salt).decrypt(&data, path),
+        }
+    }
+
+    fn prepare_save(&self, sheet: &Sheet) -> io::Result<PendingSave> {
+        let file_format = FileFormat::for_path(&sheet.path).unwrap_or(sheet.file_format);
+        let text = file_format
+            .write(sheet)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = XChaCha20Poly1305::new(&self.key)
+            .encrypt(&nonce, text.as_bytes())
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "couldn't encrypt the sheet"))?;
+        let data = [MAGIC, &self.salt, &nonce, &ciphertext].concat();
+        let path = sheet.path.clone();
+        Ok(Box::new(move |backup_count| {
+            sheet::write_atomically(&path, &data, backup_count)
+        }))
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, LoadError> {
+    fs::read(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => LoadError::NotFound,
+        _ => LoadError::Io(e),
+    })
+}
+
+/// The parts of an encrypted file which come after its magic.
+struct Sealed<'a> {
+    salt: [u8; SALT_LEN],
+    nonce: &'a [u8],
+    ciphertext: &'a [u8],
+}
+
+impl<'a> Sealed<'a> {
+    fn of(data: &'a [u8]) -> Result<Self, LoadError> {
+        let damaged = || {
+            LoadError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the encrypted file has been cut short",
+            ))
+        };
+        let rest = data.strip_prefix(MAGIC).ok_or_else(damaged)?;
+        if rest.len() < SALT_LEN + NONCE_LEN {
+            return Err(damaged());
+        }
+        let (salt, rest) = rest.split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        Ok(Self {
+            salt: salt.try_into().map_err(|_| damaged())?,
+            nonce,
+            ciphertext,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::path::PathBuf;
+
+    fn sheet_at(name: &str) -> Sheet {
+        let path = std::env::temp_dir().join(format!("{}-{}", uuid::Uuid::new_v4(), name));
+        let mut sheet = Sheet::new(&path);
+        let mut guitar = sheet.skill("Guitar");
+        guitar.log(Duration::minutes(45));
+        let id = guitar.id();
+        sheet.skills_list.get_mut(&id).unwrap().records[0].note = "a private note".to_owned();
+        sheet
+    }
+
+    fn remove(path: &PathBuf) {
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn sheets_are_encrypted_and_decrypted() {
+        let mut sheet = sheet_at("encrypted.sht");
+        sheet.encryption = Some(Encryption::new("correct horse"));
+        sheet.save(0).unwrap();
+
+        let data = fs::read(&sheet.path).unwrap();
+        assert!(is_encrypted(&data));
+        let private = b"a private note";
+        assert!(!data.windows(private.len()).any(|w| w == private));
+        assert!(matches!(
+            Sheet::open(&sheet.path),
+            Err(LoadError::NeedsPassword)
+        ));
+
+        let opened = Encryption::open(&sheet.path, "correct horse").unwrap();
+        assert!(opened.skills_list == sheet.skills_list);
+        assert!(opened.encryption.is_some());
+        // Reading it again, such as after it's changed on disk, doesn't need asking.
+        assert!(opened.open_copy(&sheet.path).unwrap().skills_list == sheet.skills_list);
+        remove(&sheet.path);
+    }
+
+    #[test]
+    fn a_wrong_password_is_an_error() {
+        let mut sheet = sheet_at("wrong.sht");
+        sheet.encryption = Some(Encryption::new("correct horse"));
+        sheet.save(0).unwrap();
+
+        assert!(matches!(
+            Encryption::open(&sheet.path, "battery staple"),
+            Err(LoadError::WrongPassword)
+        ));
+        remove(&sheet.path);
+    }
+
+    #[test]
+    fn damaged_files_are_an_error() {
+        let mut sheet = sheet_at("damaged.sht");
+        sheet.encryption = Some(Encryption::new("correct horse"));
+        sheet.save(0).unwrap();
+        let data = fs::read(&sheet.path).unwrap();
+
+        fs::write(&sheet.path, &data[..MAGIC.len() + 8]).unwrap();
+        assert!(matches!(
+            Encryption::open(&sheet.path, "correct horse"),
+            Err(LoadError::Io(_))
+        ));
+        let mut flipped = data;
+        let last = flipped.len() - 1;
+        flipped[last] ^= 1;
+        fs::write(&sheet.path, &flipped).unwrap();
+        assert!(matches!(
+            Encryption::open(&sheet.path, "correct horse"),
+            Err(LoadError::WrongPassword)
+        ));
+        remove(&sheet.path);
+    }
+}
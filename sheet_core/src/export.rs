@@ -0,0 +1,255 @@
+use crate::sheet::Sheet;
+use crate::skill::{self, SheetActionRecord, Skill};
+use chrono::{Duration, NaiveDate, SecondsFormat, Utc};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+//====================================================
+// CSV
+//====================================================
+#[derive(Clone, Copy, PartialEq)]
+pub enum CsvColumn {
+    Skill,
+    Date,
+    Duration,
+    BaseExp,
+    BonusExp,
+}
+
+impl CsvColumn {
+    pub const ALL: [CsvColumn; 5] = [
+        CsvColumn::Skill,
+        CsvColumn::Date,
+        CsvColumn::Duration,
+        CsvColumn::BaseExp,
+        CsvColumn::BonusExp,
+    ];
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            CsvColumn::Skill => "skill",
+            CsvColumn::Date => "date",
+            CsvColumn::Duration => "duration",
+            CsvColumn::BaseExp => "base_exp",
+            CsvColumn::BonusExp => "bonus_exp",
+        }
+    }
+
+    fn value(&self, skill: &Skill, record: &SheetActionRecord) -> String {
+        match self {
+            CsvColumn::Skill => skill.name.clone(),
+            CsvColumn::Date => record.date.to_string(),
+            CsvColumn::Duration => record.duration.to_string(),
+            CsvColumn::BaseExp => record.base_exp.to_string(),
+            CsvColumn::BonusExp => record.bonus_exp.to_string(),
+        }
+    }
+}
+
+pub struct CsvOptions {
+    /// Only export this skill, or the whole sheet if `None`.
+    pub skill: Option<Uuid>,
+    pub columns: Vec<CsvColumn>,
+    pub delimiter: char,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            skill: None,
+            columns: CsvColumn::ALL.to_vec(),
+            delimiter: ',',
+        }
+    }
+}
+
+/// Quotes `field` if it would otherwise be misread, doubling up any quotes inside it.
+fn escape_csv_field(field: &str, delimiter: char) -> String {
+    if field.contains(delimiter) || field.contains('"') || field.contains('\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Writes one row per record, with a header row first. Skills are written in name order and
+/// records in date order.
+pub fn records_to_csv(sheet: &Sheet, options: &CsvOptions) -> String {
+    let delimiter = options.delimiter.to_string();
+    let mut rows = vec![options
+        .columns
+        .iter()
+        .map(|c| escape_csv_field(c.header(), options.delimiter))
+        .collect::<Vec<_>>()
+        .join(&delimiter)];
+
+    let mut skills: Vec<(&Uuid, &Skill)> = sheet
+        .skills_list
+        .iter()
+        .filter(|(id, _)| options.skill.map_or(true, |skill| skill == **id))
+        .collect();
+    skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+
+    skills.iter().for_each(|(_, skill)| {
+        skill.records.iter().for_each(|record| {
+            let row = options
+                .columns
+                .iter()
+                .map(|c| escape_csv_field(&c.value(skill, record), options.delimiter))
+                .collect::<Vec<_>>()
+                .join(&delimiter);
+            rows.push(row);
+        });
+    });
+
+    let mut csv = rows.join("\n");
+    csv.push('\n');
+    csv
+}
+
+//====================================================
+// Quantified Self
+//====================================================
+/// Writes every record as an event in the CSV layout most self-tracking tools share: ISO 8601
+/// `start` and `end` timestamps, `duration_seconds`, and the `activity` and `category` it was
+/// for. Records only have a date, so each one is written as starting at midnight UTC.
+pub fn records_to_qs_csv(sheet: &Sheet) -> String {
+    const HEADER: [&str; 5] = ["start", "end", "duration_seconds", "activity", "category"];
+
+    let mut skills: Vec<&Skill> = sheet.skills_list.values().collect();
+    skills.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut rows = vec![HEADER.join(",")];
+    skills.iter().for_each(|skill| {
+        skill.records.iter().for_each(|record| {
+            let start = skill::start_of_day(record.date);
+            let end = start + Duration::minutes(record.duration as i64);
+            let row = [
+                start.to_rfc3339_opts(SecondsFormat::Secs, true),
+                end.to_rfc3339_opts(SecondsFormat::Secs, true),
+                (record.duration * 60).to_string(),
+                escape_csv_field(&skill.name, ','),
+                escape_csv_field(&skill.category, ','),
+            ];
+            rows.push(row.join(","));
+        });
+    });
+
+    let mut csv = rows.join("\n");
+    csv.push('\n');
+    csv
+}
+
+//====================================================
+// iCalendar
+//====================================================
+/// Writes every approved record as an event in an iCalendar (`.ics`) file, titled with its
+/// skill and lasting as long as the session, so the history can be shown in a calendar app.
+/// Records only have a date, so each day's sessions are laid end to end from midnight, in
+/// whatever time zone the calendar is in. Skills logged in another unit are written at their
+/// estimated length, or left out if they don't have an estimate.
+///
+/// ```
+/// use chrono::{Duration, NaiveDate};
+/// use sheet_core::{export, Sheet};
+///
+/// let day = NaiveDate::from_ymd(2024, 5, 1);
+/// let mut sheet = Sheet::new("myself.sht".as_ref());
+/// sheet.skill("Guitar").log_on(day, Duration::minutes(45));
+/// sheet.skill("Piano").log_on(day, Duration::minutes(30));
+/// let ics = export::records_to_ics(&sheet);
+/// assert!(ics.contains("SUMMARY:Guitar\r\nDTSTART:20240501T000000\r\nDURATION:PT45M\r\n"));
+/// assert!(ics.contains("SUMMARY:Piano\r\nDTSTART:20240501T004500\r\nDURATION:PT30M\r\n"));
+/// ```
+pub fn records_to_ics(sheet: &Sheet) -> String {
+    let mut skills: Vec<(&Uuid, &Skill)> = sheet.skills_list.iter().collect();
+    skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//Sheet Myself//Practice Log//EN".to_owned(),
+        format!("X-WR-CALNAME:{}", escape_ics_text(&sheet.display_name())),
+    ];
+    // How far into each day the sessions written so far reach.
+    let mut day_minutes: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+    skills.iter().for_each(|(skill_id, skill)| {
+        let unit = sheet.units.get(skill_id);
+        skill
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| record.approved)
+            .for_each(|(idx, record)| {
+                let minutes = match unit {
+                    Some(unit) => match unit.minutes_for(record.duration) {
+                        Some(minutes) => minutes.round() as i64,
+                        None => return,
+                    },
+                    None => record.duration as i64,
+                };
+                let offset = day_minutes.entry(record.date).or_insert(0);
+                let start = record.date.and_hms(0, 0, 0) + Duration::minutes(*offset);
+                *offset += minutes;
+
+                lines.push("BEGIN:VEVENT".to_owned());
+                lines.push(format!(
+                    "UID:{}-{}-{}@sheet-myself",
+                    skill_id, record.date, idx
+                ));
+                lines.push(format!("DTSTAMP:{}", stamp));
+                lines.push(format!("SUMMARY:{}", escape_ics_text(&skill.name)));
+                lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+                lines.push(format!("DURATION:PT{}M", minutes));
+                if !skill.category.is_empty() {
+                    lines.push(format!("CATEGORIES:{}", escape_ics_text(&skill.category)));
+                }
+                let description: Vec<&str> = std::iter::once(record.note.as_str())
+                    .chain(record.links.iter().map(|link| link.target.as_str()))
+                    .filter(|text| !text.is_empty())
+                    .collect();
+                if !description.is_empty() {
+                    let description = escape_ics_text(&description.join("\n"));
+                    lines.push(format!("DESCRIPTION:{}", description));
+                }
+                lines.push("END:VEVENT".to_owned());
+            });
+    });
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines
+        .iter()
+        .map(|line| fold_ics_line(line))
+        .collect::<Vec<_>>()
+        .concat()
+}
+
+/// Escapes the characters which mean something in an iCalendar text value.
+pub(crate) fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\r', "")
+        .replace('\n', "\\n")
+}
+
+/// Ends `line` with CRLF, breaking it onto continuation lines so none is longer than the 75
+/// bytes the format allows, without splitting a character.
+pub(crate) fn fold_ics_line(line: &str) -> String {
+    const MAX_BYTES: usize = 75;
+
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut written = 0;
+    line.chars().for_each(|c| {
+        if written + c.len_utf8() > MAX_BYTES {
+            folded.push_str("\r\n ");
+            // The space starting a continuation line counts toward its length.
+            written = 1;
+        }
+        folded.push(c);
+        written += c.len_utf8();
+    });
+    folded.push_str("\r\n");
+    folded
+}
@@ -0,0 +1,173 @@
+//! The formats a sheet can be saved in. JSON is the original; RON and TOML are easier to edit
+//! by hand and to diff. Whatever the format, a sheet's fields are read into JSON first so that
+//! [`migration`](crate::migration) only has to deal with one shape of data.
+//!
+//! ```
+//! use chrono::{Duration, NaiveDate};
+//! use sheet_core::daily_notes::{DailyNote, Mood};
+//! use sheet_core::file_format::FileFormat;
+//! use sheet_core::links::Link;
+//! use sheet_core::prerequisites::Prerequisite;
+//! use sheet_core::units::{Unit, UnitEstimate};
+//! use sheet_core::{Sheet, SheetActionRecord};
+//!
+//! let day = NaiveDate::from_ymd(2024, 6, 1);
+//! let mut sheet = Sheet::new("myself.sht".as_ref());
+//! sheet.player_name = "Zoë Ñúñez 山田".to_owned();
+//! let theory = sheet.skill("Théorie musicale").log_on(day, Duration::minutes(45)).id();
+//! let record = SheetActionRecord {
+//!     date: day,
+//!     duration: 90,
+//!     note: "Étude n°3, «lento» 🎸".to_owned(),
+//!     links: vec![
+//!         Link::guess("https://example.com/étude"),
+//!         Link::guess("3f9c2ab"),
+//!         Link::guess("recordings/étude.wav"),
+//!     ],
+//!     ..Default::default()
+//! };
+//! let guitar = sheet.skill("ギター").add_record(record).id();
+//! let pages = SheetActionRecord { date: day, duration: 30, ..Default::default() };
+//! let reading = sheet.skill("Lectura").add_record(pages).id();
+//! let estimate = Some(UnitEstimate { amount: 10.0, minutes: 25.0 });
+//! sheet.units.insert(reading, Unit { name: "páginas".to_owned(), estimate });
+//! sheet.prerequisites.insert(guitar, vec![Prerequisite { skill_id: theory, level: 2 }]);
+//! let note = DailyNote {
+//!     mood: Some(Mood::Good),
+//!     sleep_hours: Some(7.5),
+//!     text: "viajando, 疲れた".to_owned(),
+//! };
+//! sheet.set_daily_note(day, note);
+//!
+//! let original = serde_json::to_value(&sheet)?;
+//! for format in FileFormat::ALL {
+//!     let text = format.write(&sheet)?;
+//!     assert_eq!(FileFormat::detect(&text), format);
+//!     let read: Sheet = serde_json::from_value(format.read(&text)?)?;
+//!     assert_eq!(serde_json::to_value(&read)?, original, "{} changed it", format.label());
+//!     assert_eq!(read.skills_list[&guitar].records[0].links.len(), 3);
+//!     assert_eq!(read.units[&reading].name, "páginas");
+//!     assert_eq!(read.prerequisites[&guitar][0].skill_id, theory);
+//!     assert_eq!(read.daily_notes[&day].text, "viajando, 疲れた");
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::sheet::Sheet;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+
+//====================================================
+// FormatError
+//====================================================
+/// Why a sheet couldn't be written in, or read from, one of the formats.
+#[derive(Debug)]
+pub enum FormatError {
+    Json(serde_json::Error),
+    Ron(ron::Error),
+    Toml(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Json(e) => e.fmt(f),
+            FormatError::Ron(e) => e.fmt(f),
+            FormatError::Toml(e) => f.write_str(e),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+//====================================================
+// FileFormat
+//====================================================
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Deserialize, JsonSchema, Serialize)]
+pub enum FileFormat {
+    #[default]
+    Json,
+    Ron,
+    Toml,
+}
+
+impl FileFormat {
+    pub const ALL: [FileFormat; 3] = [FileFormat::Json, FileFormat::Ron, FileFormat::Toml];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileFormat::Json => "JSON",
+            FileFormat::Ron => "RON",
+            FileFormat::Toml => "TOML",
+        }
+    }
+
+    /// The format a file's extension asks for. `.sht` files could be in any of them, so that gives
+    /// `None`.
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(FileFormat::Json),
+            "ron" => Some(FileFormat::Ron),
+            "toml" => Some(FileFormat::Toml),
+            _ => None,
+        }
+    }
+
+    /// Works out which format `text` was written in from how it starts: JSON sheets are an object,
+    /// RON ones a struct, and anything else is taken to be TOML.
+    pub fn detect(text: &str) -> Self {
+        match text.trim_start().chars().next() {
+            Some('{') => FileFormat::Json,
+            Some('(') => FileFormat::Ron,
+            _ => FileFormat::Toml,
+        }
+    }
+
+    pub fn write(&self, sheet: &Sheet) -> Result<String, FormatError> {
+        match self {
+            FileFormat::Json => serde_json::to_string(sheet).map_err(FormatError::Json),
+            FileFormat::Ron => ron::ser::to_string_pretty(sheet, ron::ser::PrettyConfig::default())
+                .map_err(FormatError::Ron),
+            FileFormat::Toml => {
+                // TOML has no null, and can't write enums which hold data straight from the
+                // structs, but their JSON is just tables once the nulls are taken out.
+                let mut value = serde_json::to_value(sheet).map_err(FormatError::Json)?;
+                remove_nulls(&mut value);
+                toml::Value::try_from(&value)
+                    .and_then(|value| toml::to_string_pretty(&value))
+                    .map_err(|e| FormatError::Toml(e.to_string()))
+            }
+        }
+    }
+
+    /// Reads a sheet's fields out of `text`, as they were saved, ready to be migrated.
+    pub fn read(&self, text: &str) -> Result<Value, FormatError> {
+        match self {
+            FileFormat::Json => serde_json::from_str(text).map_err(FormatError::Json),
+            // RON can only be read into the types it was written from, so it goes through a
+            // `Sheet` on the way.
+            FileFormat::Ron => ron::from_str::<Sheet>(text)
+                .map_err(FormatError::Ron)
+                .and_then(|sheet| serde_json::to_value(&sheet).map_err(FormatError::Json)),
+            FileFormat::Toml => {
+                toml::from_str(text).map_err(|e: toml::de::Error| FormatError::Toml(e.to_string()))
+            }
+        }
+    }
+}
+
+/// Takes out every field which is null, leaving it to its default when the sheet is read back.
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|_, field| !field.is_null());
+            object.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
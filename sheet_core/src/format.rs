@@ -0,0 +1,183 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+//====================================================
+// ExpFormat
+//====================================================
+/// How numbers of EXP are written out.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub enum NumberStyle {
+    /// `12345.6`
+    #[default]
+    Plain,
+    /// `12,345.6`
+    Separated,
+    /// `12.3k`
+    Abbreviated,
+}
+
+impl NumberStyle {
+    pub const ALL: [NumberStyle; 3] = [
+        NumberStyle::Plain,
+        NumberStyle::Separated,
+        NumberStyle::Abbreviated,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NumberStyle::Plain => "12345.6",
+            NumberStyle::Separated => "12,345.6",
+            NumberStyle::Abbreviated => "12.3k",
+        }
+    }
+}
+
+/// What EXP is called and how amounts of it are shown. Everything which shows EXP to the user
+/// goes through this, so changing it here changes it everywhere.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ExpFormat {
+    pub name: String,
+    pub style: NumberStyle,
+    /// Shown in front of amounts. Empty for none.
+    pub icon: String,
+}
+
+impl Default for ExpFormat {
+    fn default() -> Self {
+        Self {
+            name: "EXP".to_owned(),
+            style: NumberStyle::default(),
+            icon: String::new(),
+        }
+    }
+}
+
+impl ExpFormat {
+    /// Just the number, e.g. `1.2k`.
+    pub fn number(&self, exp: f64) -> String {
+        match self.style {
+            NumberStyle::Plain => one_decimal(exp),
+            NumberStyle::Separated => separate_thousands(&one_decimal(exp)),
+            NumberStyle::Abbreviated => abbreviate(exp),
+        }
+    }
+
+    /// The number with a leading `+` or `-`, for amounts gained or lost.
+    pub fn signed_number(&self, exp: f64) -> String {
+        if exp >= 0f64 {
+            format!("+{}", self.number(exp))
+        } else {
+            self.number(exp)
+        }
+    }
+
+    /// A full amount with the icon and name, e.g. `★ 1.2k EXP`.
+    pub fn amount(&self, exp: f64) -> String {
+        if self.icon.trim().is_empty() {
+            format!("{} {}", self.number(exp), self.name)
+        } else {
+            format!("{} {} {}", self.icon.trim(), self.number(exp), self.name)
+        }
+    }
+}
+
+//====================================================
+// DurationStyle
+//====================================================
+/// How a sheet writes out lengths of time.
+///
+/// ```
+/// use sheet_core::format::DurationStyle;
+///
+/// assert_eq!(DurationStyle::Minutes.record(90), "90 min");
+/// assert_eq!(DurationStyle::Minutes.total(90), "1.5h");
+/// assert_eq!(DurationStyle::HoursMinutes.record(90), "1:30");
+/// assert_eq!(DurationStyle::HoursMinutes.total(605), "10:05");
+/// ```
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Deserialize, JsonSchema, Serialize)]
+pub enum DurationStyle {
+    /// Records in minutes, like `90`, and totals in hours, like `1.5h`.
+    #[default]
+    Minutes,
+    /// Everything in hours and minutes, like `1:30`.
+    HoursMinutes,
+}
+
+impl DurationStyle {
+    pub const ALL: [DurationStyle; 2] = [DurationStyle::Minutes, DurationStyle::HoursMinutes];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DurationStyle::Minutes => "90 min",
+            DurationStyle::HoursMinutes => "1:30",
+        }
+    }
+
+    /// How a record's length is typed in, e.g. `90` or `1:30`.
+    pub fn editable(&self, minutes: u64) -> String {
+        match self {
+            DurationStyle::Minutes => minutes.to_string(),
+            DurationStyle::HoursMinutes => hours_minutes(minutes),
+        }
+    }
+
+    /// A record's length, e.g. `90 min` or `1:30`.
+    pub fn record(&self, minutes: u64) -> String {
+        match self {
+            DurationStyle::Minutes => format!("{} min", minutes),
+            DurationStyle::HoursMinutes => hours_minutes(minutes),
+        }
+    }
+
+    /// A total over many records, e.g. `1.5h` or `1:30`.
+    pub fn total(&self, minutes: u64) -> String {
+        match self {
+            DurationStyle::Minutes => format!("{:.1}h", minutes as f64 / 60f64),
+            DurationStyle::HoursMinutes => hours_minutes(minutes),
+        }
+    }
+}
+
+fn hours_minutes(minutes: u64) -> String {
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Rounds to one decimal place, dropping it if it's zero.
+fn one_decimal(value: f64) -> String {
+    ((value * 10f64).round() / 10f64).to_string()
+}
+
+/// Puts commas between each group of three digits before the decimal point.
+fn separate_thousands(number: &str) -> String {
+    let (sign, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", number),
+    };
+    let (whole, fraction) = match unsigned.find('.') {
+        Some(idx) => unsigned.split_at(idx),
+        None => (unsigned, ""),
+    };
+
+    let mut separated = String::new();
+    whole.chars().enumerate().for_each(|(idx, c)| {
+        if idx > 0 && (whole.len() - idx) % 3 == 0 {
+            separated.push(',');
+        }
+        separated.push(c);
+    });
+    format!("{}{}{}", sign, separated, fraction)
+}
+
+/// `1234` becomes `1.2k`, `5600000` becomes `5.6M`. Small numbers are left as they are.
+fn abbreviate(value: f64) -> String {
+    const SUFFIXES: [(f64, &str); 3] = [(1e9, "B"), (1e6, "M"), (1e3, "k")];
+
+    SUFFIXES
+        .iter()
+        .find(|(size, _)| value.abs() >= *size)
+        .map_or_else(
+            || one_decimal(value),
+            |(size, suffix)| format!("{}{}", one_decimal(value / size), suffix),
+        )
+}
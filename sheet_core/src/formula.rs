@@ -0,0 +1,707 @@
+//! Custom EXP formulas, for working out a session's base EXP some other way than the built-in 55
+//! an hour, without rebuilding the app. A formula is a single arithmetic expression over what's
+//! known about the session, such as `hours * 60 + if(streak >= 7, 20, 0)`. There are no loops,
+//! assignments or ways to reach outside the sheet, so a formula can't hang or harm anything, and
+//! whenever one can't be read or doesn't give a number of EXP, the built-in formula is used
+//! instead.
+//!
+//! What a formula can use:
+//!
+//! - `minutes` and `hours`: how long the session was, or how many of the skill's unit it logged.
+//! - `base`: what the built-in formula gives.
+//! - `streak`: how many days in a row the skill was practiced up to the day before.
+//! - `days_since`: days since the skill's previous session, or 0 for its first.
+//! - `sessions_today`: how many sessions of the skill came earlier the same day.
+//! - `level`: the skill's level before the session.
+//! - `weekday` (1 for Monday to 7 for Sunday), `day` and `month`: when the session was.
+//! - `+ - * / % ^`, comparisons such as `>=` and `==` (which give 1 or 0), and brackets.
+//! - `min`, `max`, `clamp(x, low, high)`, `abs`, `sqrt`, `ln`, `log` (base 10), `floor`, `ceil`,
+//!   `round` and `if(condition, then, else)`.
+//!
+//! Formulas are read here rather than by a scripting engine such as rhai. A formula is run for
+//! every record each time a skill is recalculated, so it has to be quick and certain to finish,
+//! which a script with loops and functions could only be made by limiting how many operations
+//! it runs. The spreadsheet-like syntax, such as `if(a, b, c)`, is also what people who'd write
+//! a formula already know, and it keeps sheet_core free of a large dependency on every target.
+
+use crate::skill::{self, SheetActionRecord};
+use chrono::{Datelike, Duration, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+/// How deeply brackets and function calls can nest, so a formula can't overflow the stack.
+const MAX_DEPTH: usize = 32;
+const MAX_LENGTH: usize = 1000;
+
+//====================================================
+// ExpFormulas
+//====================================================
+/// The custom formulas a sheet uses, as typed in. Blank ones mean the built-in formula.
+#[derive(Clone, Default, PartialEq, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct ExpFormulas {
+    /// Used for every skill which doesn't have one of its own.
+    pub sheet: String,
+    pub skills: HashMap<Uuid, String>,
+}
+
+impl ExpFormulas {
+    /// The formula text `skill_id` is calculated with, if it isn't the built-in one.
+    pub fn text_for(&self, skill_id: &Uuid) -> Option<&str> {
+        let own = self.skills.get(skill_id).map(|text| text.trim());
+        let text = own
+            .filter(|text| !text.is_empty())
+            .unwrap_or_else(|| self.sheet.trim());
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// The formula `skill_id` is calculated with, or `None` for the built-in one. A formula
+    /// which can't be read counts as the built-in one too.
+    pub fn for_skill(&self, skill_id: &Uuid) -> Option<Formula> {
+        self.text_for(skill_id)
+            .and_then(|text| Formula::parse(text).ok())
+    }
+}
+
+//====================================================
+// FormulaInputs
+//====================================================
+/// What's known about a session when its base EXP is worked out.
+#[derive(Clone, Debug)]
+pub struct FormulaInputs {
+    pub minutes: f64,
+    pub base: f64,
+    pub streak: u32,
+    pub days_since: u32,
+    pub sessions_today: u32,
+    pub level: u32,
+    pub date: NaiveDate,
+}
+
+impl FormulaInputs {
+    fn get(&self, var: Var) -> f64 {
+        match var {
+            Var::Minutes => self.minutes,
+            Var::Hours => self.minutes / 60.0,
+            Var::Base => self.base,
+            Var::Streak => self.streak as f64,
+            Var::DaysSince => self.days_since as f64,
+            Var::SessionsToday => self.sessions_today as f64,
+            Var::Level => self.level as f64,
+            Var::Weekday => self.date.weekday().number_from_monday() as f64,
+            Var::Day => self.date.day() as f64,
+            Var::Month => self.date.month() as f64,
+        }
+    }
+}
+
+//====================================================
+// FormulaHistory
+//====================================================
+/// What a skill's earlier sessions say about the next one, kept up to date as its records are
+/// worked through in date order, so finding each record's inputs takes no searching back.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use sheet_core::formula::FormulaHistory;
+/// use sheet_core::SheetActionRecord;
+///
+/// let on = |day| SheetActionRecord {
+///     date: NaiveDate::from_ymd(2024, 6, day),
+///     duration: 30,
+///     ..Default::default()
+/// };
+/// let mut history = FormulaHistory::of(&[on(1), on(2), on(3), on(3)]);
+/// let inputs = history.inputs_for(&on(3), 27.5, 0.0);
+/// assert_eq!((inputs.streak, inputs.days_since, inputs.sessions_today), (2, 0, 2));
+///
+/// history.add(&on(3));
+/// let inputs = history.inputs_for(&on(6), 27.5, 0.0);
+/// assert_eq!((inputs.streak, inputs.days_since, inputs.sessions_today), (0, 3, 0));
+/// ```
+#[derive(Default)]
+pub struct FormulaHistory {
+    /// How many approved sessions there were on each day.
+    sessions: HashMap<NaiveDate, u32>,
+    /// How many days in a row had been practiced as of each practiced day.
+    streaks: HashMap<NaiveDate, u32>,
+    last_day: Option<NaiveDate>,
+}
+
+impl FormulaHistory {
+    /// The history of `records`, which must be in date order.
+    pub fn of(records: &[SheetActionRecord]) -> Self {
+        let mut history = Self::default();
+        records.iter().for_each(|record| history.add(record));
+        history
+    }
+
+    /// Adds `record`, which mustn't be older than any already added. Records which haven't been
+    /// approved don't count.
+    pub fn add(&mut self, record: &SheetActionRecord) {
+        if !record.approved {
+            return;
+        }
+        *self.sessions.entry(record.date).or_default() += 1;
+        if !self.streaks.contains_key(&record.date) {
+            let streak = self.streak_before(record.date) + 1;
+            self.streaks.insert(record.date, streak);
+        }
+        self.last_day = Some(record.date);
+    }
+
+    /// The days in a row practiced up to the day before `date`.
+    fn streak_before(&self, date: NaiveDate) -> u32 {
+        let yesterday = date - Duration::days(1);
+        self.streaks.get(&yesterday).copied().unwrap_or(0)
+    }
+
+    /// The inputs for `record`, which comes after everything added so far, when those records
+    /// earned `total_exp` between them.
+    pub fn inputs_for(
+        &self,
+        record: &SheetActionRecord,
+        base: f64,
+        total_exp: f64,
+    ) -> FormulaInputs {
+        let days_since = self
+            .last_day
+            .map_or(0, |last| (record.date - last).num_days().max(0) as u32);
+        FormulaInputs {
+            minutes: record.duration as f64,
+            base,
+            streak: self.streak_before(record.date),
+            days_since,
+            sessions_today: self.sessions.get(&record.date).copied().unwrap_or(0),
+            level: skill::level_for_exp(total_exp),
+            date: record.date,
+        }
+    }
+}
+
+//====================================================
+// Formula
+//====================================================
+/// A formula which has been read and checked, ready to work out EXP with.
+#[derive(Clone, Debug)]
+pub struct Formula {
+    expr: Expr,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FormulaError {
+    /// Where in the formula the problem is, in characters.
+    pub at: usize,
+    pub message: String,
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.at + 1)
+    }
+}
+
+impl std::error::Error for FormulaError {}
+
+impl Formula {
+    /// Reads `text`, checking every name and function in it.
+    ///
+    /// ```
+    /// use sheet_core::formula::Formula;
+    ///
+    /// assert!(Formula::parse("hours * 60 + if(streak >= 7, 20, 0)").is_ok());
+    /// let error = Formula::parse("hours * speed").unwrap_err();
+    /// assert_eq!(error.to_string(), "there's nothing called \"speed\" (at character 9)");
+    /// let error = Formula::parse("hours * bonus(2)").unwrap_err();
+    /// assert_eq!(error.message, "there's no function called \"bonus\"");
+    /// assert_eq!(Formula::parse("if(streak, 1)").unwrap_err().message, "if takes 3 arguments");
+    /// assert_eq!(Formula::parse("(hours").unwrap_err().message, "this bracket is never closed");
+    ///
+    /// // However it's written, it can't go deep enough to overflow the stack.
+    /// let too_deep = |text: &str| {
+    ///     Formula::parse(text).unwrap_err().message == "the formula is nested too deeply"
+    /// };
+    /// assert!(too_deep(&format!("{}hours{}", "(".repeat(100), ")".repeat(100))));
+    /// assert!(too_deep(&format!("{}hours", "-".repeat(100))));
+    /// assert!(too_deep(&vec!["2"; 100].join(" ^ ")));
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, FormulaError> {
+        if text.chars().count() > MAX_LENGTH {
+            return Err(FormulaError {
+                at: MAX_LENGTH,
+                message: format!("formulas can be at most {} characters", MAX_LENGTH),
+            });
+        }
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+            end: text.chars().count(),
+            depth: 0,
+        };
+        let expr = parser.comparison()?;
+        match parser.peek() {
+            None => Ok(Self { expr }),
+            Some(token) => Err(parser.error(token.at, "expected an operator or the end")),
+        }
+    }
+
+    /// Works out the formula for `inputs`, with no checking of the result.
+    pub fn eval(&self, inputs: &FormulaInputs) -> f64 {
+        self.expr.eval(inputs)
+    }
+
+    /// The base EXP the formula gives for `inputs`, or `None` if that isn't a number of EXP, in
+    /// which case the built-in formula should be used.
+    ///
+    /// ```
+    /// use sheet_core::formula::{Formula, FormulaInputs};
+    /// use sheet_core::clock;
+    ///
+    /// let inputs = FormulaInputs {
+    ///     minutes: 90.0,
+    ///     base: 82.5,
+    ///     streak: 7,
+    ///     days_since: 1,
+    ///     sessions_today: 0,
+    ///     level: 3,
+    ///     date: clock::today(),
+    /// };
+    /// let streaky = Formula::parse("hours * 60 + if(streak >= 7, 20, 0)")?;
+    /// assert_eq!(streaky.base_exp(&inputs), Some(110.0));
+    /// assert_eq!(Formula::parse("base / (streak - 7)")?.base_exp(&inputs), None);
+    /// assert_eq!(Formula::parse("0 / 0")?.base_exp(&inputs), None);
+    /// assert_eq!(Formula::parse("minutes % 0")?.base_exp(&inputs), None);
+    /// assert_eq!(Formula::parse("sqrt(0 - minutes)")?.base_exp(&inputs), None);
+    /// assert_eq!(Formula::parse("base - 100")?.base_exp(&inputs), None);
+    /// # Ok::<(), sheet_core::formula::FormulaError>(())
+    /// ```
+    pub fn base_exp(&self, inputs: &FormulaInputs) -> Option<f64> {
+        Some(self.eval(inputs)).filter(|exp| exp.is_finite() && *exp >= 0.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Var {
+    Minutes,
+    Hours,
+    Base,
+    Streak,
+    DaysSince,
+    SessionsToday,
+    Level,
+    Weekday,
+    Day,
+    Month,
+}
+
+impl Var {
+    fn named(name: &str) -> Option<Self> {
+        Some(match name {
+            "minutes" => Var::Minutes,
+            "hours" => Var::Hours,
+            "base" => Var::Base,
+            "streak" => Var::Streak,
+            "days_since" => Var::DaysSince,
+            "sessions_today" => Var::SessionsToday,
+            "level" => Var::Level,
+            "weekday" => Var::Weekday,
+            "day" => Var::Day,
+            "month" => Var::Month,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Func {
+    Min,
+    Max,
+    Clamp,
+    Abs,
+    Sqrt,
+    Ln,
+    Log,
+    Floor,
+    Ceil,
+    Round,
+    If,
+}
+
+impl Func {
+    fn named(name: &str) -> Option<Self> {
+        Some(match name {
+            "min" => Func::Min,
+            "max" => Func::Max,
+            "clamp" => Func::Clamp,
+            "abs" => Func::Abs,
+            "sqrt" => Func::Sqrt,
+            "ln" => Func::Ln,
+            "log" => Func::Log,
+            "floor" => Func::Floor,
+            "ceil" => Func::Ceil,
+            "round" => Func::Round,
+            "if" => Func::If,
+            _ => return None,
+        })
+    }
+
+    /// How many arguments it takes, at least and at most.
+    fn arity(&self) -> (usize, usize) {
+        match self {
+            Func::Min | Func::Max => (1, usize::MAX),
+            Func::Clamp | Func::If => (3, 3),
+            _ => (1, 1),
+        }
+    }
+
+    fn call(&self, args: &[f64]) -> f64 {
+        match self {
+            Func::Min => args.iter().copied().fold(f64::INFINITY, f64::min),
+            Func::Max => args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Func::Clamp => args[0].max(args[1]).min(args[2]),
+            Func::Abs => args[0].abs(),
+            Func::Sqrt => args[0].sqrt(),
+            Func::Ln => args[0].ln(),
+            Func::Log => args[0].log10(),
+            Func::Floor => args[0].floor(),
+            Func::Ceil => args[0].ceil(),
+            Func::Round => args[0].round(),
+            Func::If => {
+                if args[0] != 0.0 {
+                    args[1]
+                } else {
+                    args[2]
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    Eq,
+    NotEq,
+}
+
+impl Op {
+    fn apply(&self, a: f64, b: f64) -> f64 {
+        let truth = |yes: bool| if yes { 1.0 } else { 0.0 };
+        match self {
+            Op::Add => a + b,
+            Op::Sub => a - b,
+            Op::Mul => a * b,
+            Op::Div => a / b,
+            Op::Rem => a % b,
+            Op::Pow => a.powf(b),
+            Op::Less => truth(a < b),
+            Op::LessEq => truth(a <= b),
+            Op::Greater => truth(a > b),
+            Op::GreaterEq => truth(a >= b),
+            Op::Eq => truth(a == b),
+            Op::NotEq => truth(a != b),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Number(f64),
+    Var(Var),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, inputs: &FormulaInputs) -> f64 {
+        match self {
+            Expr::Number(n) => *n,
+            Expr::Var(var) => inputs.get(*var),
+            Expr::Neg(expr) => -expr.eval(inputs),
+            Expr::Binary(op, a, b) => op.apply(a.eval(inputs), b.eval(inputs)),
+            Expr::Call(func, args) => {
+                let args: Vec<f64> = args.iter().map(|arg| arg.eval(inputs)).collect();
+                func.call(&args)
+            }
+        }
+    }
+}
+
+//====================================================
+// Parsing
+//====================================================
+#[derive(Clone, Debug, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Name(String),
+    Op(Op),
+    Open,
+    Close,
+    Comma,
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    kind: TokenKind,
+    /// Where it starts, in characters.
+    at: usize,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, FormulaError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let at = pos;
+        let c = chars[pos];
+        let next = chars.get(pos + 1).copied();
+        let kind = if c.is_whitespace() {
+            pos += 1;
+            continue;
+        } else if c.is_ascii_digit() || c == '.' {
+            while pos < chars.len() && (chars[pos].is_ascii_digit() || chars[pos] == '.') {
+                pos += 1;
+            }
+            let number: String = chars[at..pos].iter().collect();
+            let number = number.parse().map_err(|_| FormulaError {
+                at,
+                message: format!("\"{}\" isn't a number", number),
+            })?;
+            tokens.push(Token {
+                kind: TokenKind::Number(number),
+                at,
+            });
+            continue;
+        } else if c.is_alphabetic() || c == '_' {
+            while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_') {
+                pos += 1;
+            }
+            let name = chars[at..pos].iter().collect::<String>().to_lowercase();
+            tokens.push(Token {
+                kind: TokenKind::Name(name),
+                at,
+            });
+            continue;
+        } else {
+            match (c, next) {
+                ('<', Some('=')) => TokenKind::Op(Op::LessEq),
+                ('>', Some('=')) => TokenKind::Op(Op::GreaterEq),
+                ('=', Some('=')) => TokenKind::Op(Op::Eq),
+                ('!', Some('=')) => TokenKind::Op(Op::NotEq),
+                ('<', _) => TokenKind::Op(Op::Less),
+                ('>', _) => TokenKind::Op(Op::Greater),
+                ('+', _) => TokenKind::Op(Op::Add),
+                ('-', _) => TokenKind::Op(Op::Sub),
+                ('*', _) => TokenKind::Op(Op::Mul),
+                ('/', _) => TokenKind::Op(Op::Div),
+                ('%', _) => TokenKind::Op(Op::Rem),
+                ('^', _) => TokenKind::Op(Op::Pow),
+                ('(', _) => TokenKind::Open,
+                (')', _) => TokenKind::Close,
+                (',', _) => TokenKind::Comma,
+                _ => {
+                    return Err(FormulaError {
+                        at,
+                        message: format!("\"{}\" can't be used in a formula", c),
+                    })
+                }
+            }
+        };
+        pos += match kind {
+            TokenKind::Op(Op::LessEq | Op::GreaterEq | Op::Eq | Op::NotEq) => 2,
+            _ => 1,
+        };
+        tokens.push(Token { kind, at });
+    }
+    Ok(tokens)
+}
+
+/// Reads tokens into an expression by recursive descent, from the loosest binding operators
+/// (comparisons) down to the tightest (`^` and negation).
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Where the formula ends, for problems found there.
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn error(&self, at: usize, message: &str) -> FormulaError {
+        FormulaError {
+            at,
+            message: message.to_owned(),
+        }
+    }
+
+    /// Takes the next token if it's one of `ops`.
+    fn take_op(&mut self, ops: &[Op]) -> Option<Op> {
+        match self.peek().map(|token| &token.kind) {
+            Some(TokenKind::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    /// Goes a level deeper into the formula, unless that's too deep. Each call needs a matching
+    /// `self.depth -= 1` on the way back out.
+    fn descend(&mut self) -> Result<(), FormulaError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            let at = self.peek().map_or(self.end, |token| token.at);
+            return Err(self.error(at, "the formula is nested too deeply"));
+        }
+        Ok(())
+    }
+
+    fn comparison(&mut self) -> Result<Expr, FormulaError> {
+        self.descend()?;
+        let ops = [
+            Op::Less,
+            Op::LessEq,
+            Op::Greater,
+            Op::GreaterEq,
+            Op::Eq,
+            Op::NotEq,
+        ];
+        let left = self.sum()?;
+        let expr = match self.take_op(&ops) {
+            Some(op) => Expr::Binary(op, Box::new(left), Box::new(self.sum()?)),
+            None => left,
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn sum(&mut self) -> Result<Expr, FormulaError> {
+        let mut expr = self.product()?;
+        while let Some(op) = self.take_op(&[Op::Add, Op::Sub]) {
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+        Ok(expr)
+    }
+
+    fn product(&mut self) -> Result<Expr, FormulaError> {
+        let mut expr = self.unary()?;
+        while let Some(op) = self.take_op(&[Op::Mul, Op::Div, Op::Rem]) {
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FormulaError> {
+        if self.take_op(&[Op::Sub]).is_some() {
+            self.descend()?;
+            let expr = Expr::Neg(Box::new(self.unary()?));
+            self.depth -= 1;
+            return Ok(expr);
+        }
+        let base = self.atom()?;
+        if self.take_op(&[Op::Pow]).is_some() {
+            // `^` groups to the right, so 2 ^ 3 ^ 2 is 2 ^ 9.
+            self.descend()?;
+            let expr = Expr::Binary(Op::Pow, Box::new(base), Box::new(self.unary()?));
+            self.depth -= 1;
+            return Ok(expr);
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, FormulaError> {
+        let token = match self.next() {
+            Some(token) => token,
+            None => return Err(self.error(self.end, "the formula ends too soon")),
+        };
+        match token.kind {
+            TokenKind::Number(n) => Ok(Expr::Number(n)),
+            TokenKind::Open => {
+                let expr = self.comparison()?;
+                self.expect_close(token.at)?;
+                Ok(expr)
+            }
+            TokenKind::Name(name) => {
+                let called = matches!(
+                    self.peek(),
+                    Some(Token {
+                        kind: TokenKind::Open,
+                        ..
+                    })
+                );
+                if !called {
+                    return Var::named(&name).map(Expr::Var).ok_or_else(|| {
+                        self.error(token.at, &format!("there's nothing called \"{}\"", name))
+                    });
+                }
+                let func = Func::named(&name).ok_or_else(|| {
+                    self.error(
+                        token.at,
+                        &format!("there's no function called \"{}\"", name),
+                    )
+                })?;
+                self.pos += 1;
+                let mut args = vec![self.comparison()?];
+                while matches!(
+                    self.peek(),
+                    Some(Token {
+                        kind: TokenKind::Comma,
+                        ..
+                    })
+                ) {
+                    self.pos += 1;
+                    args.push(self.comparison()?);
+                }
+                self.expect_close(token.at)?;
+                let (least, most) = func.arity();
+                if args.len() < least || args.len() > most {
+                    let expected = if least == most {
+                        format!("{} takes {}", name, least)
+                    } else {
+                        format!("{} takes at least {}", name, least)
+                    };
+                    let plural = if least == 1 { "" } else { "s" };
+                    let message = format!("{} argument{}", expected, plural);
+                    return Err(self.error(token.at, &message));
+                }
+                Ok(Expr::Call(func, args))
+            }
+            _ => Err(self.error(token.at, "expected a number, a name or a bracket")),
+        }
+    }
+
+    /// Takes the `)` closing a bracket opened at `open`.
+    fn expect_close(&mut self, open: usize) -> Result<(), FormulaError> {
+        match self.next() {
+            Some(Token {
+                kind: TokenKind::Close,
+                ..
+            }) => Ok(()),
+            _ => Err(self.error(open, "this bracket is never closed")),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::clock;
+use crate::skill::Skill;
+use chrono::{Duration, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+//====================================================
+// Goal
+//====================================================
+/// What a goal counts.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub enum GoalMeasure {
+    #[default]
+    Hours,
+    Exp,
+    Sessions,
+}
+
+impl GoalMeasure {
+    pub const ALL: [GoalMeasure; 3] = [GoalMeasure::Hours, GoalMeasure::Exp, GoalMeasure::Sessions];
+
+    /// `exp_name` is whatever EXP has been renamed to in the settings.
+    pub fn label<'a>(&self, exp_name: &'a str) -> &'a str {
+        match self {
+            GoalMeasure::Hours => "hours",
+            GoalMeasure::Exp => exp_name,
+            GoalMeasure::Sessions => "sessions",
+        }
+    }
+}
+
+/// Something to achieve with one skill by a deadline, like "50 hours of Japanese by June 30th".
+/// Only records from `start` up to and including `deadline` count towards it.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Goal {
+    pub skill_id: Uuid,
+    pub measure: GoalMeasure,
+    pub target: f64,
+    pub start: NaiveDate,
+    pub deadline: NaiveDate,
+}
+
+impl Default for Goal {
+    fn default() -> Self {
+        let today = clock::today();
+        Self {
+            skill_id: Uuid::nil(),
+            measure: GoalMeasure::default(),
+            target: 10f64,
+            start: today,
+            deadline: today + Duration::days(30),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GoalState {
+    InProgress,
+    Completed,
+    /// The deadline passed before the target was reached.
+    Failed,
+}
+
+impl GoalState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GoalState::InProgress => "In progress",
+            GoalState::Completed => "Completed",
+            GoalState::Failed => "Failed",
+        }
+    }
+}
+
+pub struct GoalProgress {
+    pub amount: f64,
+    /// How much of the target has been reached, from 0 to 1.
+    pub fraction: f64,
+    pub state: GoalState,
+}
+
+impl Goal {
+    /// How the goal is going as of `today`. A skill which has since been deleted counts as no
+    /// progress at all.
+    pub fn progress(&self, skill: Option<&Skill>, today: NaiveDate) -> GoalProgress {
+        let records = skill
+            .into_iter()
+            .flat_map(|skill| skill.records.iter())
+            .filter(|r| r.approved && self.start <= r.date && r.date <= self.deadline);
+        let amount = match self.measure {
+            GoalMeasure::Hours => records.map(|r| r.duration as f64 / 60f64).sum(),
+            GoalMeasure::Exp => records.map(|r| r.base_exp + r.bonus_exp).sum(),
+            GoalMeasure::Sessions => records.count() as f64,
+        };
+
+        let state = if amount >= self.target {
+            GoalState::Completed
+        } else if today > self.deadline {
+            GoalState::Failed
+        } else {
+            GoalState::InProgress
+        };
+        let fraction = if self.target > 0f64 {
+            (amount / self.target).clamp(0f64, 1f64)
+        } else {
+            1f64
+        };
+        GoalProgress {
+            amount,
+            fraction,
+            state,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skill::SheetActionRecord;
+
+    fn day(n: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2024, 6, n)
+    }
+
+    fn skill_with(records: &[(u32, u64)]) -> Skill {
+        Skill {
+            records: records
+                .iter()
+                .map(|(n, minutes)| SheetActionRecord {
+                    date: day(*n),
+                    duration: *minutes,
+                    base_exp: *minutes as f64,
+                    bonus_exp: 1.0,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn goal(measure: GoalMeasure, target: f64) -> Goal {
+        Goal {
+            skill_id: Uuid::nil(),
+            measure,
+            target,
+            start: day(10),
+            deadline: day(20),
+        }
+    }
+
+    #[test]
+    fn only_records_between_start_and_deadline_count() {
+        let skill = skill_with(&[(9, 600), (10, 60), (20, 30), (21, 600)]);
+        let hours = goal(GoalMeasure::Hours, 3.0).progress(Some(&skill), day(15));
+        assert!((hours.amount - 1.5).abs() < 1e-9);
+        assert!((hours.fraction - 0.5).abs() < 1e-9);
+        let exp = goal(GoalMeasure::Exp, 100.0).progress(Some(&skill), day(15));
+        assert!((exp.amount - 92.0).abs() < 1e-9);
+        let sessions = goal(GoalMeasure::Sessions, 2.0).progress(Some(&skill), day(15));
+        assert!(sessions.state == GoalState::Completed);
+    }
+
+    #[test]
+    fn unapproved_records_dont_count() {
+        let mut skill = skill_with(&[(12, 60)]);
+        skill.records[0].approved = false;
+        let progress = goal(GoalMeasure::Sessions, 1.0).progress(Some(&skill), day(15));
+        assert!(progress.amount == 0.0);
+    }
+
+    #[test]
+    fn goals_fail_once_the_deadline_passes() {
+        let skill = skill_with(&[(12, 60)]);
+        let goal = goal(GoalMeasure::Hours, 2.0);
+        assert!(goal.progress(Some(&skill), day(20)).state == GoalState::InProgress);
+        assert!(goal.progress(Some(&skill), day(21)).state == GoalState::Failed);
+    }
+
+    #[test]
+    fn deleted_skills_make_no_progress() {
+        let progress = goal(GoalMeasure::Hours, 2.0).progress(None, day(15));
+        assert!(progress.amount == 0.0);
+        assert!(progress.fraction == 0.0);
+    }
+
+    #[test]
+    fn a_target_of_nothing_is_already_reached() {
+        let progress = goal(GoalMeasure::Hours, 0.0).progress(None, day(15));
+        assert!(progress.state == GoalState::Completed);
+        assert!(progress.fraction == 1.0);
+    }
+}
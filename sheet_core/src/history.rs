@@ -0,0 +1,335 @@
+//! Undo and redo for a sheet's skills. Each `Edit` swaps whole skills for other versions of
+//! them, so it can be applied forwards or backwards. Edits only last while the app is running.
+
+use crate::skill::Skill;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+//====================================================
+// Edit
+//====================================================
+/// An undoable change to a sheet's skills.
+#[derive(Clone)]
+pub enum Edit {
+    /// Swaps one version of a skill for another. `None` stands for the skill not existing, so
+    /// `before: None` is adding a skill and `after: None` is deleting one. They're boxed, as
+    /// skills are much bigger than a batch.
+    Skill {
+        id: Uuid,
+        before: Option<Box<Skill>>,
+        after: Option<Box<Skill>>,
+    },
+    /// Several edits made together, e.g. by a bulk tool, which undo as one.
+    Batch(Vec<Edit>),
+}
+
+impl Edit {
+    /// Works out the edit which turns `before` into `after`, or `None` if they're the same.
+    pub fn between(before: &HashMap<Uuid, Skill>, after: &HashMap<Uuid, Skill>) -> Option<Self> {
+        let ids = before
+            .keys()
+            .chain(after.keys().filter(|id| !before.contains_key(id)));
+        let mut edits: Vec<Edit> = ids
+            .filter(|id| before.get(id) != after.get(id))
+            .map(|id| Edit::Skill {
+                id: *id,
+                before: before.get(id).cloned().map(Box::new),
+                after: after.get(id).cloned().map(Box::new),
+            })
+            .collect();
+
+        match edits.len() {
+            0 => None,
+            1 => edits.pop(),
+            _ => Some(Edit::Batch(edits)),
+        }
+    }
+
+    /// Puts the `after` side of the edit in place, or the `before` side when `undo` is set.
+    pub fn apply(&self, skills: &mut HashMap<Uuid, Skill>, undo: bool) {
+        match self {
+            Edit::Skill { id, before, after } => {
+                let target = if undo { before } else { after };
+                match target {
+                    Some(skill) => skills.insert(*id, Skill::clone(skill)),
+                    None => skills.remove(id),
+                };
+            }
+            Edit::Batch(edits) => {
+                // Undo in reverse, in case two edits in the batch touch the same skill.
+                if undo {
+                    edits.iter().rev().for_each(|e| e.apply(skills, undo));
+                } else {
+                    edits.iter().for_each(|e| e.apply(skills, undo));
+                }
+            }
+        }
+    }
+
+    /// Every skill this edit touches.
+    pub fn skill_ids(&self) -> Vec<Uuid> {
+        match self {
+            Edit::Skill { id, .. } => vec![*id],
+            Edit::Batch(edits) => edits.iter().flat_map(|e| e.skill_ids()).collect(),
+        }
+    }
+
+    /// Folds a later edit of the same skills into this one, keeping this edit's `before`.
+    fn absorb(&mut self, later: Edit) {
+        match (self, later) {
+            (Edit::Skill { after, .. }, Edit::Skill { after: later, .. }) => *after = later,
+            (Edit::Batch(edits), Edit::Batch(later)) => edits
+                .iter_mut()
+                .zip(later)
+                .for_each(|(edit, later)| edit.absorb(later)),
+            _ => {}
+        }
+    }
+}
+
+//====================================================
+// History
+//====================================================
+/// The undo and redo stacks for one sheet.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// When the newest undo entry was last added to, in `egui` input time.
+    last_push_time: f64,
+    /// The skills as they were after the last change recorded, which the next one is worked out
+    /// from. Only the skills an edit touches are copied into it, so keeping it up to date costs
+    /// nothing while nothing changes.
+    tracked: Option<HashMap<Uuid, Skill>>,
+}
+
+impl History {
+    /// Edits to the same skills within this many seconds of each other undo as one, so typing a
+    /// number doesn't take one undo per keystroke.
+    const COALESCE_SECS: f64 = 1.0;
+    /// The oldest edits are forgotten past this point.
+    const MAX_UNDO: usize = 100;
+
+    /// Starts keeping track of `skills`, unless it already is.
+    pub fn track(&mut self, skills: &HashMap<Uuid, Skill>) {
+        if self.tracked.is_none() {
+            self.tracked = Some(skills.clone());
+        }
+    }
+
+    /// The skills as they were after the last change recorded, if they're being tracked.
+    pub fn tracked(&self) -> Option<&HashMap<Uuid, Skill>> {
+        self.tracked.as_ref()
+    }
+
+    /// Records whatever has changed between the tracked skills and `skills` as an edit. Does
+    /// nothing if they aren't being tracked yet.
+    pub fn record(&mut self, skills: &HashMap<Uuid, Skill>, time: f64) {
+        let tracked = match &mut self.tracked {
+            Some(tracked) => tracked,
+            None => return,
+        };
+        if let Some(edit) = Edit::between(tracked, skills) {
+            edit.apply(tracked, false);
+            self.push(edit, time);
+        }
+    }
+
+    pub fn push(&mut self, edit: Edit, time: f64) {
+        self.redo.clear();
+
+        let coalesce = time - self.last_push_time < Self::COALESCE_SECS
+            && self
+                .undo
+                .last()
+                .map_or(false, |last| last.skill_ids() == edit.skill_ids());
+        self.last_push_time = time;
+        if coalesce {
+            if let Some(last) = self.undo.last_mut() {
+                last.absorb(edit);
+                return;
+            }
+        }
+
+        self.undo.push(edit);
+        if self.undo.len() > Self::MAX_UNDO {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Takes the newest edit off the undo stack, to be reverted, and moves it to the redo stack.
+    pub fn undo(&mut self) -> Option<Edit> {
+        let edit = self.undo.pop()?;
+        if let Some(tracked) = &mut self.tracked {
+            edit.apply(tracked, true);
+        }
+        self.redo.push(edit.clone());
+        // Whatever happens next shouldn't be merged into an edit which has been undone.
+        self.last_push_time = f64::NEG_INFINITY;
+        Some(edit)
+    }
+
+    /// Takes the newest undone edit, to be applied again, and moves it back to the undo stack.
+    pub fn redo(&mut self) -> Option<Edit> {
+        let edit = self.redo.pop()?;
+        if let Some(tracked) = &mut self.tracked {
+            edit.apply(tracked, false);
+        }
+        self.undo.push(edit.clone());
+        self.last_push_time = f64::NEG_INFINITY;
+        Some(edit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skill(name: &str) -> Skill {
+        Skill {
+            name: name.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    fn tracked_history(skills: &HashMap<Uuid, Skill>) -> History {
+        let mut history = History::default();
+        history.track(skills);
+        history
+    }
+
+    #[test]
+    fn nothing_is_recorded_until_tracked() {
+        let mut history = History::default();
+        let mut skills = HashMap::new();
+        skills.insert(Uuid::new_v4(), skill("Guitar"));
+        history.record(&skills, 0.0);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn unchanged_skills_record_nothing() {
+        let mut skills = HashMap::new();
+        skills.insert(Uuid::new_v4(), skill("Guitar"));
+        let mut history = tracked_history(&skills);
+        history.record(&skills, 0.0);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn undo_and_redo_swap_the_changed_skill() {
+        let id = Uuid::new_v4();
+        let mut skills = HashMap::new();
+        skills.insert(id, skill("Guitar"));
+        let mut history = tracked_history(&skills);
+
+        skills.get_mut(&id).unwrap().name = "Bass".to_owned();
+        history.record(&skills, 0.0);
+        let edit = history.undo().unwrap();
+        edit.apply(&mut skills, true);
+        assert_eq!(skills[&id].name, "Guitar");
+        assert!(history.can_redo());
+
+        let edit = history.redo().unwrap();
+        edit.apply(&mut skills, false);
+        assert_eq!(skills[&id].name, "Bass");
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn tracking_follows_undo() {
+        let id = Uuid::new_v4();
+        let mut skills = HashMap::new();
+        skills.insert(id, skill("Guitar"));
+        let mut history = tracked_history(&skills);
+
+        skills.get_mut(&id).unwrap().name = "Bass".to_owned();
+        history.record(&skills, 0.0);
+        history.undo().unwrap().apply(&mut skills, true);
+        // Nothing has changed since the undo, so there's nothing new to record.
+        history.record(&skills, 10.0);
+        assert!(!history.can_undo());
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn a_new_edit_clears_redo() {
+        let id = Uuid::new_v4();
+        let mut skills = HashMap::new();
+        skills.insert(id, skill("Guitar"));
+        let mut history = tracked_history(&skills);
+
+        skills.get_mut(&id).unwrap().name = "Bass".to_owned();
+        history.record(&skills, 0.0);
+        history.undo().unwrap().apply(&mut skills, true);
+        skills.get_mut(&id).unwrap().name = "Piano".to_owned();
+        history.record(&skills, 10.0);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn quick_edits_to_the_same_skill_undo_as_one() {
+        let id = Uuid::new_v4();
+        let mut skills = HashMap::new();
+        skills.insert(id, skill("G"));
+        let mut history = tracked_history(&skills);
+
+        ["Gu", "Gui", "Guitar"]
+            .iter()
+            .enumerate()
+            .for_each(|(i, name)| {
+                skills.get_mut(&id).unwrap().name = name.to_string();
+                history.record(&skills, i as f64 * 0.2);
+            });
+        history.undo().unwrap().apply(&mut skills, true);
+        assert_eq!(skills[&id].name, "G");
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn adding_and_deleting_undo_together() {
+        let kept = Uuid::new_v4();
+        let deleted = Uuid::new_v4();
+        let mut skills = HashMap::new();
+        skills.insert(kept, skill("Guitar"));
+        skills.insert(deleted, skill("Piano"));
+        let mut history = tracked_history(&skills);
+
+        skills.remove(&deleted);
+        let added = Uuid::new_v4();
+        skills.insert(added, skill("Drums"));
+        history.record(&skills, 0.0);
+        history.undo().unwrap().apply(&mut skills, true);
+        assert!(skills.contains_key(&deleted));
+        assert!(!skills.contains_key(&added));
+        assert_eq!(skills.len(), 2);
+    }
+
+    #[test]
+    fn only_the_newest_edits_are_kept() {
+        let id = Uuid::new_v4();
+        let mut skills = HashMap::new();
+        skills.insert(id, skill("0"));
+        let mut history = tracked_history(&skills);
+
+        (1..=History::MAX_UNDO + 10).for_each(|i| {
+            skills.get_mut(&id).unwrap().name = i.to_string();
+            history.record(&skills, i as f64 * 10.0);
+        });
+        let mut undone = 0;
+        while let Some(edit) = history.undo() {
+            edit.apply(&mut skills, true);
+            undone += 1;
+        }
+        assert_eq!(undone, History::MAX_UNDO);
+        assert_eq!(skills[&id].name, "10");
+    }
+}
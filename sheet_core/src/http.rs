@@ -0,0 +1,46 @@
+//! Every request goes out through the same agent, so they all give up after the same time and
+//! say which app they're from.
+
+use std::time::Duration;
+
+/// How long a request has to connect and finish before it's given up on.
+pub const TIMEOUT: Duration = Duration::from_secs(20);
+
+pub fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .user_agent(concat!("sheet_myself/", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+/// `url` with any space around it trimmed, as long as it's an `http://` or `https://` one.
+///
+/// ```
+/// use sheet_core::http;
+///
+/// assert_eq!(http::checked_url(" https://example.com/hook "), Ok("https://example.com/hook"));
+/// assert!(http::checked_url("HTTP://example.com").is_ok());
+/// assert!(http::checked_url("file:///etc/passwd").is_err());
+/// assert!(http::checked_url("-o/tmp/x").is_err());
+/// ```
+pub fn checked_url(url: &str) -> Result<&str, String> {
+    let url = url.trim();
+    let scheme = url.split("://").next().unwrap_or_default();
+    if url.contains("://") && ["http", "https"].contains(&scheme.to_ascii_lowercase().as_str()) {
+        Ok(url)
+    } else {
+        Err(format!("\"{}\" isn't an http:// or https:// URL", url))
+    }
+}
+
+/// What went wrong with a request, with what the server said if it answered with an error.
+pub fn describe(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let said = response.into_string().unwrap_or_default();
+            let said: String = said.trim().chars().take(300).collect();
+            format!("the server answered {}: {}", code, said)
+        }
+        error => error.to_string(),
+    }
+}
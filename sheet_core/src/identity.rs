@@ -0,0 +1,212 @@
+//! Working out whether a skill coming in from somewhere else, such as a shared template or
+//! another sheet, is one the sheet already has, and what to do about it if so. Skills are
+//! matched by id first, since a skill keeps its id wherever it's copied, and then by name.
+//! Nothing is brought in until every skill which clashes has a [`Resolution`].
+
+use crate::import;
+use crate::sheet::Sheet;
+use crate::skill::Skill;
+use crate::sync::{self, MergeSummary};
+use crate::units::Unit;
+use uuid::Uuid;
+
+//====================================================
+// Identity
+//====================================================
+/// How a skill coming in lines up with the sheet's own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Identity {
+    /// The sheet has nothing like it.
+    New,
+    /// The sheet has the same skill, copied from the same place.
+    SameId(Uuid),
+    /// The sheet has a different skill by the same name.
+    SameName(Uuid),
+}
+
+impl Identity {
+    /// The sheet's skill it clashes with, if any.
+    pub fn existing(&self) -> Option<Uuid> {
+        match self {
+            Identity::New => None,
+            Identity::SameId(id) | Identity::SameName(id) => Some(*id),
+        }
+    }
+}
+
+/// Which of the sheet's skills, if any, `skill` is, given it has `id` where it came from.
+pub fn identify(sheet: &Sheet, id: &Uuid, skill: &Skill) -> Identity {
+    if sheet.skills_list.contains_key(id) {
+        return Identity::SameId(*id);
+    }
+    match import::find_skill(sheet, &skill.name) {
+        Some(existing) => Identity::SameName(existing),
+        None => Identity::New,
+    }
+}
+
+/// `name`, or the first of "name (2)", "name (3)" and so on which no skill on `sheet` has.
+pub fn unique_name(sheet: &Sheet, name: &str) -> String {
+    let name = name.trim();
+    std::iter::once(name.to_owned())
+        .chain((2..).map(|n| format!("{} ({})", name, n)))
+        .find(|candidate| import::find_skill(sheet, candidate).is_none())
+        .unwrap_or_default()
+}
+
+//====================================================
+// Resolution
+//====================================================
+/// What to do with a skill coming in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Resolution {
+    /// Add it as it is. Only for skills which don't clash.
+    Add,
+    /// Bring its records into the skill it clashes with, leaving out any already there.
+    Merge,
+    /// Add it alongside the one it clashes with, as a separate skill called this.
+    Rename(String),
+    /// Swap the skill it clashes with for it, records and all.
+    Replace,
+    Skip,
+}
+
+impl Resolution {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Resolution::Add => "Add",
+            Resolution::Merge => "Merge",
+            Resolution::Rename(_) => "Rename",
+            Resolution::Replace => "Replace",
+            Resolution::Skip => "Skip",
+        }
+    }
+}
+
+//====================================================
+// IncomingSkill
+//====================================================
+/// A skill to bring in, along with what it clashes with and what's to be done about it.
+#[derive(Clone)]
+pub struct IncomingSkill {
+    /// Its id where it came from.
+    pub id: Uuid,
+    pub skill: Skill,
+    /// What its records count, if it isn't minutes.
+    pub unit: Option<Unit>,
+    pub identity: Identity,
+    pub resolution: Resolution,
+}
+
+impl IncomingSkill {
+    /// What can be done with it. Merging needs both skills to count the same thing, or the
+    /// records would be misread.
+    pub fn resolutions(&self, sheet: &Sheet) -> Vec<Resolution> {
+        let existing = match self.identity.existing() {
+            Some(existing) => existing,
+            None => return vec![Resolution::Add, Resolution::Skip],
+        };
+        let mut resolutions = Vec::new();
+        if sheet.units.get(&existing) == self.unit.as_ref() {
+            resolutions.push(Resolution::Merge);
+        }
+        resolutions.push(Resolution::Rename(unique_name(sheet, &self.skill.name)));
+        resolutions.push(Resolution::Replace);
+        resolutions.push(Resolution::Skip);
+        resolutions
+    }
+}
+
+/// Every skill on `theirs`, each lined up with `sheet`'s and given the first thing it can do:
+/// adding new skills, merging the same skill, and renaming one which only shares a name.
+pub fn incoming(sheet: &Sheet, theirs: &Sheet) -> Vec<IncomingSkill> {
+    let mut skills: Vec<IncomingSkill> = theirs
+        .skills_list
+        .iter()
+        .map(|(id, skill)| {
+            let mut incoming = IncomingSkill {
+                id: *id,
+                skill: skill.clone(),
+                unit: theirs.units.get(id).cloned(),
+                identity: identify(sheet, id, skill),
+                resolution: Resolution::Skip,
+            };
+            incoming.resolution = match incoming.identity {
+                Identity::SameName(_) => Resolution::Rename(unique_name(sheet, &skill.name)),
+                _ => incoming.resolutions(sheet).remove(0),
+            };
+            incoming
+        })
+        .collect();
+    skills.sort_by(|a, b| a.skill.name.cmp(&b.skill.name));
+    skills
+}
+
+impl Sheet {
+    /// Brings in each of `skills` as its resolution says, then recalculates.
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use sheet_core::identity::{self, Identity, Resolution};
+    /// use sheet_core::Sheet;
+    ///
+    /// let mut template = Sheet::new("template.sht".as_ref());
+    /// template.skill("Guitar").log(Duration::minutes(30));
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// let guitar = sheet.skill("guitar").log(Duration::minutes(45)).id();
+    ///
+    /// let incoming = identity::incoming(&sheet, &template);
+    /// assert_eq!(incoming[0].identity, Identity::SameName(guitar));
+    /// assert_eq!(incoming[0].resolution, Resolution::Rename("Guitar (2)".to_owned()));
+    /// let summary = sheet.bring_in(incoming);
+    /// assert_eq!(summary.skills_added, 1);
+    /// assert_eq!(sheet.skills_list.len(), 2);
+    /// ```
+    pub fn bring_in(&mut self, skills: Vec<IncomingSkill>) -> MergeSummary {
+        let mut summary = MergeSummary::default();
+        skills.into_iter().for_each(|incoming| {
+            let IncomingSkill {
+                id,
+                mut skill,
+                unit,
+                identity,
+                resolution,
+            } = incoming;
+            let (id, added) = match (resolution, identity.existing()) {
+                (Resolution::Skip, _) => return,
+                (Resolution::Merge, Some(existing)) => {
+                    if let Some(into) = self.skills_list.get_mut(&existing) {
+                        skill.records.into_iter().for_each(|record| {
+                            if !into.records.iter().any(|r| sync::same_session(r, &record)) {
+                                into.records.push(record);
+                                summary.records_added += 1;
+                            }
+                        });
+                    }
+                    return;
+                }
+                (Resolution::Replace, Some(existing)) => (existing, false),
+                (Resolution::Rename(name), _) => {
+                    skill.name = name.trim().to_owned();
+                    (Uuid::new_v4(), true)
+                }
+                // Other skills are only added under their own id if it's free, so the same
+                // skill can be recognised next time.
+                (_, _) if self.skills_list.contains_key(&id) => (Uuid::new_v4(), true),
+                (_, _) => (id, true),
+            };
+            if added {
+                summary.skills_added += 1;
+            }
+            summary.records_added += skill.records.len();
+            match unit {
+                Some(unit) => self.units.insert(id, unit),
+                None => self.units.remove(&id),
+            };
+            self.skills_list.insert(id, skill);
+        });
+        self.recalculate_all();
+        self.dirty = true;
+        summary
+    }
+}
@@ -0,0 +1,1027 @@
+use crate::clock;
+use crate::sheet::Sheet;
+use crate::skill::{self, SheetActionRecord, Skill};
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+//====================================================
+// ImportedRecord
+//====================================================
+/// A record read from some other program's data, not yet added to a sheet.
+#[derive(Clone)]
+pub struct ImportedRecord {
+    /// Name of the skill this belongs to; matched against existing skills case-insensitively.
+    pub skill: String,
+    pub date: NaiveDate,
+    /// Minutes.
+    pub duration: u64,
+    /// Category for the skill, if it has to be created. Empty leaves it uncategorized.
+    pub category: String,
+}
+
+/// What happened when a batch of imported records was added to a sheet.
+#[derive(Clone, Copy, Default)]
+pub struct ImportSummary {
+    pub records_added: usize,
+    pub skills_created: usize,
+}
+
+//====================================================
+// ImportJob
+//====================================================
+/// Adds a batch of imported records to a sheet a slice at a time, so a big import can show its
+/// progress instead of freezing the UI. Nothing on the sheet changes until [`ImportJob::commit`],
+/// so a cancelled import leaves no trace, and committing part way adds just what's been done.
+pub struct ImportJob {
+    records: Vec<ImportedRecord>,
+    next: usize,
+    require_approval: bool,
+    /// Lowercased skill name to skill, covering both the sheet's skills and the ones to create.
+    skill_ids: HashMap<String, Uuid>,
+    /// Skills which don't exist on the sheet yet, named and categorized but still empty.
+    new_skills: HashMap<Uuid, Skill>,
+    /// Records ready to be added, by the skill they're going into.
+    staged: HashMap<Uuid, Vec<SheetActionRecord>>,
+}
+
+impl ImportJob {
+    pub fn new(sheet: &Sheet, records: Vec<ImportedRecord>) -> Self {
+        let mut skill_ids = HashMap::new();
+        sheet.skills_list.iter().for_each(|(id, skill)| {
+            skill_ids.entry(skill_key(&skill.name)).or_insert(*id);
+        });
+        Self {
+            records,
+            next: 0,
+            require_approval: sheet.require_approval,
+            skill_ids,
+            new_skills: HashMap::new(),
+            staged: HashMap::new(),
+        }
+    }
+
+    /// Gets up to `count` more records ready. Returns whether every record is now ready.
+    pub fn step(&mut self, count: usize) -> bool {
+        let end = (self.next + count).min(self.records.len());
+        let Self {
+            records,
+            skill_ids,
+            new_skills,
+            staged,
+            require_approval,
+            ..
+        } = self;
+        records[self.next..end].iter().for_each(|imported| {
+            let skill_id = *skill_ids
+                .entry(skill_key(&imported.skill))
+                .or_insert_with(|| {
+                    let id = Uuid::new_v4();
+                    let skill = Skill {
+                        name: imported.skill.trim().to_owned(),
+                        category: imported.category.trim().to_owned(),
+                        ..Default::default()
+                    };
+                    new_skills.insert(id, skill);
+                    id
+                });
+            staged.entry(skill_id).or_default().push(SheetActionRecord {
+                date: imported.date,
+                duration: imported.duration,
+                approved: !*require_approval,
+                ..Default::default()
+            });
+        });
+        self.next = end;
+        self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.records.len()
+    }
+
+    /// How many records have been got ready so far.
+    pub fn processed(&self) -> usize {
+        self.next
+    }
+
+    pub fn total(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Adds every record which is ready to the sheet in one go, creating any new skills they
+    /// need, and recalculates the skills involved. Records which weren't reached are dropped.
+    pub fn commit(&mut self, sheet: &mut Sheet) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+        let staged = std::mem::take(&mut self.staged);
+        staged.into_iter().for_each(|(skill_id, records)| {
+            if let Some(new_skill) = self.new_skills.get(&skill_id) {
+                // A brand new skill's history starts with its oldest imported record, not today.
+                let created_at = records
+                    .iter()
+                    .map(|r| r.date)
+                    .min()
+                    .map_or_else(chrono::Utc::now, skill::start_of_day);
+                sheet.skills_list.entry(skill_id).or_insert_with(|| {
+                    summary.skills_created += 1;
+                    Skill {
+                        created_at,
+                        ..new_skill.clone()
+                    }
+                });
+            }
+
+            if let Some(skill) = sheet.skills_list.get_mut(&skill_id) {
+                summary.records_added += records.len();
+                skill.records.extend(records);
+                sheet.recalculate_skill(&skill_id);
+            }
+        });
+        summary
+    }
+}
+
+/// Skill names are matched ignoring case and surrounding whitespace.
+fn skill_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Adds together records for the same skill on the same day, so each day ends up as one
+/// session. The result is in skill name order, then date order.
+pub fn merge_same_day(records: &[ImportedRecord]) -> Vec<ImportedRecord> {
+    let mut merged: BTreeMap<(String, NaiveDate), ImportedRecord> = BTreeMap::new();
+    records.iter().for_each(|record| {
+        merged
+            .entry((record.skill.clone(), record.date))
+            .and_modify(|merged| merged.duration += record.duration)
+            .or_insert_with(|| record.clone());
+    });
+    merged.into_values().collect()
+}
+
+/// Looks a skill up by name, ignoring case and surrounding whitespace.
+pub fn find_skill(sheet: &Sheet, name: &str) -> Option<Uuid> {
+    let name = skill_key(name);
+    sheet
+        .skills_list
+        .iter()
+        .find(|(_, skill)| skill_key(&skill.name) == name)
+        .map(|(id, _)| *id)
+}
+
+/// Reads a date in any of the common spreadsheet layouts, or the date part of a timestamp.
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%m/%d/%Y", "%d.%m.%Y"];
+    const DATE_TIME_FORMATS: &[&str] =
+        &["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"];
+
+    let text = text.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+        .or_else(|| {
+            DATE_TIME_FORMATS
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+                .map(|date_time| date_time.date())
+        })
+        .or_else(|| {
+            chrono::DateTime::parse_from_rfc3339(text)
+                .ok()
+                .map(|date_time| date_time.naive_local().date())
+        })
+}
+
+/// The longest a single record can be. Anything over a day is a typo or a stopwatch left
+/// running, and taking it would only throw every total off.
+pub const MAX_DURATION_MINUTES: u64 = 24 * 60;
+
+/// Reads a duration as a plain number of minutes, `h:mm[:ss]`, or with units, like `90m`,
+/// `1.5h` or `1h 30m`. Anything negative, with more than 59 minutes or seconds after a colon,
+/// or longer than [`MAX_DURATION_MINUTES`] isn't a duration.
+///
+/// ```
+/// use sheet_core::import::parse_duration_minutes;
+///
+/// assert_eq!(parse_duration_minutes("90"), Some(90));
+/// assert_eq!(parse_duration_minutes("1:30"), Some(90));
+/// assert_eq!(parse_duration_minutes("90m"), Some(90));
+/// assert_eq!(parse_duration_minutes("1.5h"), Some(90));
+/// assert_eq!(parse_duration_minutes("1h 30min"), Some(90));
+/// assert_eq!(parse_duration_minutes("1h30"), Some(90));
+/// assert_eq!(parse_duration_minutes("1:"), None);
+/// assert_eq!(parse_duration_minutes("inf"), None);
+/// assert_eq!(parse_duration_minutes("NaN"), None);
+/// assert_eq!(parse_duration_minutes("1e30"), None);
+/// assert_eq!(parse_duration_minutes("-5"), None);
+/// assert_eq!(parse_duration_minutes("1:75"), None);
+/// assert_eq!(parse_duration_minutes("307445734561825861:00"), None);
+/// assert_eq!(parse_duration_minutes("25h"), None);
+/// assert_eq!(parse_duration_minutes("24:00"), Some(1440));
+/// ```
+pub fn parse_duration_minutes(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let minutes = if let Ok(minutes) = text.parse::<f64>() {
+        minutes_from_f64(minutes)?
+    } else if text.starts_with(|c: char| c.is_ascii_digit() || c == '.') && !text.contains(':') {
+        parse_duration_with_units(text)?
+    } else {
+        let parts: Vec<u64> = text
+            .split(':')
+            .map(|part| part.trim().parse::<u64>())
+            .collect::<Result<_, _>>()
+            .ok()?;
+        match parts.as_slice() {
+            [hours, minutes] if *minutes < 60 => hours.checked_mul(60)?.checked_add(*minutes)?,
+            [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => hours
+                .checked_mul(60)?
+                .checked_add(*minutes)?
+                .checked_add(if *seconds >= 30 { 1 } else { 0 })?,
+            _ => return None,
+        }
+    };
+    (minutes <= MAX_DURATION_MINUTES).then_some(minutes)
+}
+
+/// `minutes` rounded to a whole number, as long as it's a real number which isn't negative or
+/// too big to be a duration, so it can't saturate on the way to a `u64`.
+fn minutes_from_f64(minutes: f64) -> Option<u64> {
+    let in_range =
+        minutes.is_finite() && minutes >= 0f64 && minutes.round() <= MAX_DURATION_MINUTES as f64;
+    in_range.then(|| minutes.round() as u64)
+}
+
+/// Reads amounts each followed by `h` or `m`, or a word starting with one, and adds them up.
+fn parse_duration_with_units(text: &str) -> Option<u64> {
+    let mut minutes = 0f64;
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = rest[number_end..].trim_start();
+        let unit_end = rest
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = rest[..unit_end].to_lowercase();
+        minutes += match unit.chars().next() {
+            Some('h') => number * 60f64,
+            Some('m') => number,
+            // Minutes can go without their unit after hours, as in `1h30`.
+            None if minutes > 0f64 => number,
+            _ => return None,
+        };
+        rest = rest[unit_end..].trim_start();
+    }
+    minutes_from_f64(minutes)
+}
+
+//====================================================
+// CSV
+//====================================================
+/// Splits CSV text into rows of fields, handling quoted fields with embedded delimiters, quotes
+/// and newlines.
+pub fn parse_csv(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+        } else if c == '"' {
+            in_quotes = true;
+        } else if c == delimiter {
+            row.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            row.push(std::mem::take(&mut field));
+            rows.push(std::mem::take(&mut row));
+        } else {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    // Blank lines aren't rows.
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    rows
+}
+
+/// Picks whichever of the usual delimiters appears most in the first line.
+pub fn guess_delimiter(text: &str) -> char {
+    let first_line = text.lines().next().unwrap_or_default();
+    [',', ';', '\t']
+        .iter()
+        .copied()
+        .max_by_key(|d| first_line.matches(*d).count())
+        .unwrap_or(',')
+}
+
+/// Which CSV columns hold which parts of a record.
+#[derive(Clone, PartialEq)]
+pub struct CsvMapping {
+    pub has_header: bool,
+    pub date_column: usize,
+    pub duration_column: usize,
+    /// The column naming each row's skill, or `None` to put every row into `default_skill`.
+    pub skill_column: Option<usize>,
+    pub default_skill: String,
+}
+
+impl Default for CsvMapping {
+    fn default() -> Self {
+        Self {
+            has_header: true,
+            date_column: 0,
+            duration_column: 1,
+            skill_column: None,
+            default_skill: "Imported".to_owned(),
+        }
+    }
+}
+
+impl CsvMapping {
+    /// Guesses a mapping from the names in a header row, where they look familiar.
+    pub fn guess(header: &[String]) -> Self {
+        let mut mapping = Self::default();
+        let find = |names: &[&str]| {
+            header
+                .iter()
+                .position(|h| names.contains(&h.trim().to_lowercase().as_str()))
+        };
+
+        match find(&["date", "day", "start date", "start"]) {
+            Some(column) => mapping.date_column = column,
+            None => mapping.has_header = false,
+        }
+        if let Some(column) = find(&["duration", "minutes", "mins", "time"]) {
+            mapping.duration_column = column;
+        }
+        mapping.skill_column = find(&["skill", "activity", "project", "task", "category"]);
+        mapping
+    }
+
+    /// Reads each data row as a record, or explains why it couldn't be read.
+    pub fn map_rows(&self, rows: &[Vec<String>]) -> Vec<Result<ImportedRecord, String>> {
+        let skip = if self.has_header { 1 } else { 0 };
+        rows.iter()
+            .skip(skip)
+            .map(|row| self.map_row(row))
+            .collect()
+    }
+
+    fn map_row(&self, row: &[String]) -> Result<ImportedRecord, String> {
+        let field = |column: usize| {
+            row.get(column)
+                .map(|f| f.as_str())
+                .ok_or_else(|| format!("missing column {}", column + 1))
+        };
+
+        let date_text = field(self.date_column)?;
+        let date = parse_date(date_text).ok_or_else(|| format!("bad date \"{}\"", date_text))?;
+        let duration_text = field(self.duration_column)?;
+        let duration = parse_duration_minutes(duration_text)
+            .ok_or_else(|| format!("bad duration \"{}\"", duration_text))?;
+        let skill = match self.skill_column {
+            Some(column) => field(column)?.trim().to_owned(),
+            None => self.default_skill.trim().to_owned(),
+        };
+        if skill.is_empty() {
+            return Err("no skill name".to_owned());
+        }
+
+        Ok(ImportedRecord {
+            skill,
+            date,
+            duration,
+            category: String::new(),
+        })
+    }
+}
+
+//====================================================
+// Toggl
+//====================================================
+/// What Toggl entries without a project are filed under.
+pub const TOGGL_NO_PROJECT: &str = "(No project)";
+
+/// Reads a Toggl Track export, either the CSV from the detailed report or JSON from the reports
+/// or time entries API. Each entry's Toggl project becomes its skill name.
+pub fn parse_toggl(text: &str) -> Result<Vec<ImportedRecord>, String> {
+    if text.trim_start().starts_with(['[', '{']) {
+        parse_toggl_json(text)
+    } else {
+        parse_toggl_csv(text)
+    }
+}
+
+fn parse_toggl_csv(text: &str) -> Result<Vec<ImportedRecord>, String> {
+    let rows = parse_csv(text, guess_delimiter(text));
+    let header = rows.first().ok_or("the file is empty")?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("no \"{}\" column; is this a Toggl detailed report?", name))
+    };
+    let project_column = column("Project")?;
+    let date_column = column("Start date")?;
+    let duration_column = column("Duration")?;
+
+    rows.iter()
+        .skip(1)
+        .map(|row| {
+            let field = |column: usize| row.get(column).map_or("", |f| f.as_str());
+            let date = parse_date(field(date_column))
+                .ok_or_else(|| format!("bad date \"{}\"", field(date_column)))?;
+            let duration = parse_duration_minutes(field(duration_column))
+                .ok_or_else(|| format!("bad duration \"{}\"", field(duration_column)))?;
+            Ok(ImportedRecord {
+                skill: toggl_project_name(field(project_column)),
+                date,
+                duration,
+                category: String::new(),
+            })
+        })
+        .collect()
+}
+
+fn parse_toggl_json(text: &str) -> Result<Vec<ImportedRecord>, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    // The reports API wraps its entries in `data`; the time entries API is a bare list.
+    let entries = value
+        .get("data")
+        .unwrap_or(&value)
+        .as_array()
+        .ok_or("expected a list of time entries")?;
+
+    Ok(entries.iter().filter_map(toggl_json_entry).collect())
+}
+
+/// Reads one JSON time entry, skipping any which are still running or too long to be a record.
+fn toggl_json_entry(entry: &Value) -> Option<ImportedRecord> {
+    let date = parse_date(entry.get("start")?.as_str()?)?;
+    // Reports give `dur` in milliseconds; time entries give `duration` in seconds, negative while
+    // the timer is still going.
+    let seconds = match entry.get("dur").and_then(Value::as_f64) {
+        Some(millis) => millis / 1000f64,
+        None => entry.get("duration")?.as_f64()?,
+    };
+    let duration = minutes_from_f64(seconds / 60f64)?;
+    let project = ["project", "project_name"]
+        .iter()
+        .find_map(|key| entry.get(*key).and_then(Value::as_str))
+        .unwrap_or_default();
+
+    Some(ImportedRecord {
+        skill: toggl_project_name(project),
+        date,
+        duration,
+        category: String::new(),
+    })
+}
+
+fn toggl_project_name(project: &str) -> String {
+    let project = project.trim();
+    if project.is_empty() {
+        TOGGL_NO_PROJECT.to_owned()
+    } else {
+        project.to_owned()
+    }
+}
+
+//====================================================
+// Quantified Self
+//====================================================
+/// Reads the one-event-per-row CSV most self-tracking tools can export: an ISO 8601 `start`
+/// timestamp, `duration_seconds`, the `activity` name and an optional `category`. This is also
+/// what [`crate::export::records_to_qs_csv`] writes.
+pub fn parse_qs_csv(text: &str) -> Result<Vec<ImportedRecord>, String> {
+    let rows = parse_csv(text, guess_delimiter(text));
+    let header = rows.first().ok_or("the file is empty")?;
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|h| names.contains(&h.trim().to_lowercase().as_str()))
+    };
+    let start_column =
+        column(&["start", "timestamp", "start_time"]).ok_or("no \"start\" column")?;
+    let duration_column = column(&["duration_seconds", "duration_s", "seconds"])
+        .ok_or("no \"duration_seconds\" column")?;
+    let activity_column =
+        column(&["activity", "name", "event", "title"]).ok_or("no \"activity\" column")?;
+    let category_column = column(&["category", "tag"]);
+
+    rows.iter()
+        .skip(1)
+        .map(|row| {
+            let field = |column: usize| row.get(column).map_or("", |f| f.trim());
+            let date = parse_date(field(start_column))
+                .ok_or_else(|| format!("bad timestamp \"{}\"", field(start_column)))?;
+            let duration = field(duration_column)
+                .parse::<f64>()
+                .ok()
+                .and_then(|seconds| minutes_from_f64(seconds / 60f64))
+                .ok_or_else(|| format!("bad duration \"{}\"", field(duration_column)))?;
+            let skill = field(activity_column);
+            if skill.is_empty() {
+                return Err("no activity name".to_owned());
+            }
+            Ok(ImportedRecord {
+                skill: skill.to_owned(),
+                date,
+                duration,
+                category: category_column.map_or("", field).to_owned(),
+            })
+        })
+        .collect()
+}
+
+//====================================================
+// iCalendar
+//====================================================
+/// Reads the timed events in an iCalendar (`.ics`) file, such as one exported from a calendar
+/// app, as records named after their titles. All-day and cancelled events aren't sessions, so
+/// they're skipped, and a repeating event only counts its first occurrence. This also reads
+/// what [`crate::export::records_to_ics`] writes.
+///
+/// ```
+/// use sheet_core::import;
+///
+/// let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Guitar\\, scales\r\n\
+///            DTSTART:20240501T090000\r\nDTEND:20240501T094500\r\nEND:VEVENT\r\n\
+///            BEGIN:VEVENT\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20240502\r\n\
+///            END:VEVENT\r\nEND:VCALENDAR\r\n";
+/// let records = import::parse_ics(ics)?;
+/// assert_eq!(records.len(), 1);
+/// assert_eq!(records[0].skill, "Guitar, scales");
+/// assert_eq!(records[0].duration, 45);
+/// # Ok::<(), String>(())
+/// ```
+pub fn parse_ics(text: &str) -> Result<Vec<ImportedRecord>, String> {
+    // Long lines are folded onto continuation lines which start with a space or a tab.
+    let mut lines: Vec<String> = Vec::new();
+    text.lines().for_each(|line| {
+        match (
+            line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')),
+            lines.last_mut(),
+        ) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_owned()),
+        }
+    });
+    if !lines.iter().any(|line| line.trim() == "BEGIN:VCALENDAR") {
+        return Err("it isn't an iCalendar file".to_owned());
+    }
+
+    let mut records = Vec::new();
+    let mut event: Option<Vec<(String, String, String)>> = None;
+    for line in &lines {
+        match line.trim() {
+            "BEGIN:VEVENT" => event = Some(Vec::new()),
+            "END:VEVENT" => {
+                if let Some(record) = event.take().and_then(|props| ics_event(&props)) {
+                    records.push(record);
+                }
+            }
+            line => {
+                if let (Some(props), Some((name, value))) = (&mut event, line.split_once(':')) {
+                    let (name, params) = name.split_once(';').unwrap_or((name, ""));
+                    let property = (name.to_uppercase(), params.to_uppercase(), value.to_owned());
+                    props.push(property);
+                }
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Turns one event's properties, as (name, parameters, value), into a record, if it's a timed
+/// event which went ahead.
+fn ics_event(props: &[(String, String, String)]) -> Option<ImportedRecord> {
+    let get = |name: &str| {
+        props
+            .iter()
+            .find(|(n, _, _)| n == name)
+            .map(|(_, params, value)| (params.as_str(), value.trim()))
+    };
+    if get("STATUS").map_or(false, |(_, status)| {
+        status.eq_ignore_ascii_case("CANCELLED")
+    }) {
+        return None;
+    }
+    let (start_params, start) = get("DTSTART")?;
+    if start_params.contains("VALUE=DATE") && !start_params.contains("VALUE=DATE-TIME") {
+        return None;
+    }
+    let start = parse_ics_time(start)?;
+    let minutes = match (get("DTEND"), get("DURATION")) {
+        (Some((_, end)), _) => (parse_ics_time(end)? - start).num_minutes(),
+        (None, Some((_, duration))) => parse_ics_duration(duration)?,
+        (None, None) => return None,
+    };
+    let skill = unescape_ics_text(get("SUMMARY")?.1);
+    if skill.trim().is_empty() || minutes <= 0 || minutes as u64 > MAX_DURATION_MINUTES {
+        return None;
+    }
+    let category = get("CATEGORIES")
+        .map(|(_, categories)| unescape_ics_text(categories.split(',').next().unwrap_or("")))
+        .unwrap_or_default();
+    Some(ImportedRecord {
+        skill: skill.trim().to_owned(),
+        date: start.date(),
+        duration: minutes as u64,
+        category,
+    })
+}
+
+/// Reads an iCalendar date-time as wall-clock time: UTC ones (ending in `Z`) in the local time
+/// zone, and ones with their own time zone, or none, as they're written.
+fn parse_ics_time(text: &str) -> Option<NaiveDateTime> {
+    match text.strip_suffix('Z') {
+        Some(utc) => {
+            let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            Some(
+                Utc.from_utc_datetime(&utc)
+                    .with_timezone(&Local)
+                    .naive_local(),
+            )
+        }
+        None => NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%S").ok(),
+    }
+}
+
+/// Reads an iCalendar duration, such as `PT1H30M` or `P1D`, in minutes.
+fn parse_ics_duration(text: &str) -> Option<i64> {
+    let text = text.strip_prefix('+').unwrap_or(text).strip_prefix('P')?;
+    let mut minutes = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                minutes += match unit {
+                    'W' => n * 7 * 24 * 60,
+                    'D' => n * 24 * 60,
+                    'H' => n * 60,
+                    'M' => n,
+                    'S' => (n + 30) / 60,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(minutes)
+}
+
+fn unescape_ics_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+/// Whether an event called `title` matches `pattern`, ignoring case. `*` stands for anything,
+/// and a pattern without one matches any title containing it.
+///
+/// ```
+/// use sheet_core::import::title_matches;
+///
+/// assert!(title_matches("guitar", "Guitar practice"));
+/// assert!(title_matches("Japanese*", "Japanese: kanji review"));
+/// assert!(!title_matches("Japanese*", "Review Japanese"));
+/// assert!(title_matches("*run*5k", "Long run, 5K"));
+/// ```
+pub fn title_matches(pattern: &str, title: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let title = title.trim().to_lowercase();
+    if pattern.is_empty() {
+        return false;
+    }
+    if !pattern.contains('*') {
+        return title.contains(&pattern);
+    }
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    // Both ends have to match before slicing between them, or the slice could land part way
+    // into a character.
+    if !title.starts_with(first) || !title.ends_with(last) || title.len() < first.len() + last.len()
+    {
+        return false;
+    }
+    let mut rest = &title[first.len()..title.len() - last.len()];
+    parts[1..parts.len() - 1]
+        .iter()
+        .all(|part| match rest.find(part) {
+            Some(at) => {
+                rest = &rest[at + part.len()..];
+                true
+            }
+            None => false,
+        })
+}
+
+//====================================================
+// Habitica
+//====================================================
+/// How long each Habitica check-in counts as. Habitica only keeps that a habit or daily was
+/// done, not for how long.
+pub const HABITICA_CHECK_IN_MINUTES: u64 = 15;
+
+/// Reads a Habitica data export (Settings > Export Data > User Data as JSON), or the list from
+/// its tasks API, turning every time a habit was scored up or a daily was checked off into a
+/// [`HABITICA_CHECK_IN_MINUTES`] record of a skill named after the task. To-dos and rewards
+/// aren't practice, so they're left out.
+///
+/// ```
+/// use sheet_core::import;
+///
+/// let export = r#"{"tasks": {
+///     "habits": [{"text": "Guitar", "history": [
+///         {"date": 1714550400000, "value": 1, "scoredUp": 2, "scoredDown": 0}]}],
+///     "dailys": [{"text": "Japanese", "history": [
+///         {"date": 1714550400000, "value": 1, "completed": true},
+///         {"date": 1714636800000, "value": 0.5, "completed": false}]}]
+/// }}"#;
+/// let records = import::parse_habitica(export)?;
+/// assert_eq!(records.len(), 3);
+/// assert_eq!(records.iter().filter(|r| r.skill == "Guitar").count(), 2);
+/// assert_eq!(records[2].category, "Dailies");
+/// # Ok::<(), String>(())
+/// ```
+pub fn parse_habitica(text: &str) -> Result<Vec<ImportedRecord>, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    // The API wraps its answer in `data`; the export keeps the tasks under `tasks`, by type.
+    let value = value.get("data").unwrap_or(&value);
+    let tasks: Vec<(&str, &Value)> = match value.get("tasks").unwrap_or(value) {
+        Value::Array(tasks) => tasks
+            .iter()
+            .filter_map(|task| Some((task.get("type")?.as_str()?, task)))
+            .collect(),
+        Value::Object(by_type) => ["habits", "dailys"]
+            .iter()
+            .filter_map(|kind| Some((*kind, by_type.get(*kind)?.as_array()?)))
+            .flat_map(|(kind, tasks)| tasks.iter().map(move |task| (kind, task)))
+            .collect(),
+        _ => return Err("expected Habitica tasks; is this a user data export?".to_owned()),
+    };
+
+    let mut records = Vec::new();
+    tasks.iter().for_each(|(kind, task)| {
+        let (daily, category) = match *kind {
+            "habit" | "habits" => (false, "Habits"),
+            "daily" | "dailys" => (true, "Dailies"),
+            _ => return,
+        };
+        let name = task
+            .get("text")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .trim();
+        let history = task.get("history").and_then(Value::as_array);
+        if name.is_empty() || history.is_none() {
+            return;
+        }
+        let mut previous_value = 0f64;
+        history.into_iter().flatten().for_each(|entry| {
+            let value = entry.get("value").and_then(Value::as_f64).unwrap_or(0f64);
+            // Older histories only have the task's value, which goes up when it's done.
+            let went_up = value > previous_value;
+            previous_value = value;
+            let check_ins = if daily {
+                let completed = entry.get("completed").and_then(Value::as_bool);
+                completed.unwrap_or(went_up) as u64
+            } else {
+                match entry.get("scoredUp").and_then(Value::as_u64) {
+                    Some(scored_up) => scored_up,
+                    None => went_up as u64,
+                }
+            };
+            let date = match entry.get("date").and_then(habitica_date) {
+                Some(date) => date,
+                None => return,
+            };
+            (0..check_ins).for_each(|_| {
+                records.push(ImportedRecord {
+                    skill: name.to_owned(),
+                    date,
+                    duration: HABITICA_CHECK_IN_MINUTES,
+                    category: category.to_owned(),
+                });
+            });
+        });
+    });
+    Ok(records)
+}
+
+/// Habitica dates are milliseconds since 1970, though some older ones are ISO 8601 strings.
+fn habitica_date(date: &Value) -> Option<NaiveDate> {
+    match date {
+        Value::Number(millis) => {
+            let millis = millis.as_f64()? as i64;
+            let time = Utc.timestamp_opt(millis / 1000, 0).single()?;
+            Some(clock::date_at(time))
+        }
+        Value::String(text) => parse_date(text),
+        _ => None,
+    }
+}
+
+//====================================================
+// Pasted rows
+//====================================================
+/// Reads rows copied out of a spreadsheet into one skill: a date, a duration, and optionally a
+/// note, separated by tabs. A header row at the top is skipped.
+pub fn parse_pasted_rows(text: &str) -> Vec<Result<SheetActionRecord, String>> {
+    let rows = parse_csv(text, '\t');
+    let has_header = rows
+        .first()
+        .and_then(|row| row.first())
+        .map_or(false, |first| parse_date(first).is_none());
+
+    rows.iter()
+        .skip(if has_header { 1 } else { 0 })
+        .map(|row| {
+            let field = |column: usize| row.get(column).map_or("", |f| f.trim());
+            let date = parse_date(field(0)).ok_or_else(|| format!("bad date \"{}\"", field(0)))?;
+            let duration = parse_duration_minutes(field(1))
+                .ok_or_else(|| format!("bad duration \"{}\"", field(1)))?;
+            Ok(SheetActionRecord {
+                date,
+                duration,
+                note: field(2).to_owned(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+//====================================================
+// Skill lists
+//====================================================
+/// A skill to add, read from one line of a typed list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewSkill {
+    pub name: String,
+    /// Empty leaves it uncategorized.
+    pub category: String,
+    /// Base EXP for an hour, or `None` for the sheet's usual formula.
+    pub exp_per_hour: Option<f64>,
+}
+
+/// Reads a list of skills to add, one per line: just a name, or `name, category, exp/hour`.
+/// Either extra can be left empty, and names with commas in can be quoted.
+///
+/// ```
+/// use sheet_core::import;
+///
+/// let skills = import::parse_skill_list("Guitar\nJapanese, Languages, 80\nChess,,40/h\n");
+/// assert_eq!(skills.len(), 3);
+/// let japanese = skills[1].as_ref().unwrap();
+/// assert_eq!((japanese.category.as_str(), japanese.exp_per_hour), ("Languages", Some(80.0)));
+/// assert_eq!(skills[2].as_ref().unwrap().exp_per_hour, Some(40.0));
+/// ```
+pub fn parse_skill_list(text: &str) -> Vec<Result<NewSkill, String>> {
+    parse_csv(text, ',')
+        .iter()
+        .map(|row| {
+            let field = |column: usize| row.get(column).map_or("", |f| f.trim());
+            if field(0).is_empty() {
+                return Err("no name".to_owned());
+            }
+            let rate = field(2)
+                .trim_end_matches(|c: char| c.is_alphabetic() || c == '/' || c.is_whitespace());
+            let exp_per_hour = match rate {
+                "" => None,
+                rate => match rate.parse::<f64>() {
+                    Ok(exp) if exp.is_finite() && exp >= 0f64 => Some(exp),
+                    _ => return Err(format!("bad EXP per hour \"{}\"", field(2))),
+                },
+            };
+            Ok(NewSkill {
+                name: field(0).to_owned(),
+                category: field(1).to_owned(),
+                exp_per_hour,
+            })
+        })
+        .collect()
+}
+
+impl Sheet {
+    /// Adds each of `skills` which isn't on the sheet yet, ignoring case, giving back the ids of
+    /// the ones added. Those with their own EXP per hour get a formula to earn it.
+    ///
+    /// ```
+    /// use sheet_core::import;
+    /// use sheet_core::Sheet;
+    ///
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// sheet.skill("Guitar");
+    /// let skills: Vec<_> = import::parse_skill_list("guitar\nPiano, Music, 70")
+    ///     .into_iter()
+    ///     .filter_map(Result::ok)
+    ///     .collect();
+    /// let added = sheet.add_skills(&skills);
+    /// assert_eq!(added.len(), 1);
+    /// assert_eq!(sheet.exp_formulas.text_for(&added[0]), Some("hours * 70"));
+    /// ```
+    pub fn add_skills(&mut self, skills: &[NewSkill]) -> Vec<Uuid> {
+        let mut added = Vec::new();
+        skills.iter().for_each(|new_skill| {
+            if find_skill(self, &new_skill.name).is_some() {
+                return;
+            }
+            let id = Uuid::new_v4();
+            let skill = Skill {
+                name: new_skill.name.trim().to_owned(),
+                category: new_skill.category.trim().to_owned(),
+                ..Default::default()
+            };
+            self.skills_list.insert(id, skill);
+            if let Some(exp) = new_skill.exp_per_hour {
+                self.exp_formulas
+                    .skills
+                    .insert(id, format!("hours * {}", exp));
+            }
+            added.push(id);
+        });
+        if !added.is_empty() {
+            self.dirty = true;
+        }
+        added
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_match_titles_which_arent_ascii() {
+        assert!(!title_matches("a*b", "aé"));
+        assert!(!title_matches("é*b", "éa"));
+        assert!(title_matches("日本語*", "日本語の勉強"));
+        assert!(title_matches("*練習", "ギター練習"));
+        assert!(title_matches("café*crème", "Café au crème"));
+        assert!(!title_matches("café*crème", "Café"));
+    }
+
+    #[test]
+    fn csv_rows_with_bad_durations_are_skipped() {
+        let text = "Date,Minutes,Skill\n\
+                    2024-03-01,45,Guitar\n\
+                    2024-03-02,inf,Guitar\n\
+                    2024-03-03,307445734561825861:00,Guitar\n\
+                    2024-03-04,1e30,Piano\n\
+                    2024-03-05,-5,Piano\n\
+                    2024-03-06,1:75,Piano\n\
+                    2024-03-07,1:30,Piano\n\
+                    2024-03-08,18446744073709551615,Piano\n";
+        let rows = parse_csv(text, guess_delimiter(text));
+        let mapping = CsvMapping::guess(&rows[0]);
+        let results = mapping.map_rows(&rows);
+        assert_eq!(results.len(), 8);
+        let bad: Vec<&String> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+        assert_eq!(bad.len(), 6);
+        assert!(bad.iter().all(|e| e.starts_with("bad duration")));
+
+        let records: Vec<ImportedRecord> = results.into_iter().flatten().collect();
+        let mut sheet = Sheet::new("myself.sht".as_ref());
+        let mut job = ImportJob::new(&sheet, records);
+        assert!(job.step(job.total()));
+        let summary = job.commit(&mut sheet);
+        assert_eq!(summary.records_added, 2);
+        assert_eq!(summary.skills_created, 2);
+        assert_eq!(sheet.stats().total_minutes(), 45 + 90);
+    }
+}
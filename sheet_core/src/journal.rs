@@ -0,0 +1,96 @@
+use crate::clock;
+use crate::sheet::Sheet;
+use crate::skill::level_for_exp;
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+//====================================================
+// JournalEntry
+//====================================================
+/// A milestone in a skill's journal, written when it was reached, so the journal builds up into
+/// a diary of how practice went over time.
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct JournalEntry {
+    pub date: NaiveDate,
+    /// The level which was reached.
+    pub level: u32,
+    /// What happened, e.g. "Reached level 7 on 2024-06-02 after 112 hours".
+    pub text: String,
+    /// A line written about it afterwards, if there is one.
+    #[serde(default)]
+    pub reflection: String,
+}
+
+/// e.g. "112 hours" or "1.5 hours".
+fn hours_text(minutes: u64) -> String {
+    let hours = (minutes as f64 / 6.0).round() / 10.0;
+    if (hours - 1.0).abs() < f64::EPSILON {
+        "1 hour".to_owned()
+    } else {
+        format!("{} hours", hours)
+    }
+}
+
+impl Sheet {
+    /// Adds an entry to `skill_id`'s journal for it reaching `level`, dated by the record which
+    /// took it there and counting the practice it took up to then. Skills logged in another unit
+    /// count that instead of hours. Levels already in the journal, such as ones lost to an edit
+    /// and then reached again, aren't written twice. Gives back where the entry went, if one was
+    /// written.
+    ///
+    /// ```
+    /// use chrono::{Duration, NaiveDate};
+    /// use sheet_core::Sheet;
+    ///
+    /// let day = NaiveDate::from_ymd(2024, 6, 2);
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// let id = sheet.skill("Guitar").log_on(day, Duration::hours(2)).id();
+    /// assert_eq!(sheet.journal_level_up(&id, 1), Some(0));
+    /// assert_eq!(sheet.journal_level_up(&id, 1), None);
+    /// assert_eq!(sheet.journals[&id][0].text, "Reached level 1 on 2024-06-02 after 2 hours");
+    /// ```
+    pub fn journal_level_up(&mut self, skill_id: &Uuid, level: u32) -> Option<usize> {
+        let skill = self.skills_list.get(skill_id)?;
+        if self
+            .journals
+            .get(skill_id)
+            .map_or(false, |journal| journal.iter().any(|e| e.level == level))
+        {
+            return None;
+        }
+
+        let mut exp = 0.0;
+        let mut amount = 0;
+        let reached = skill
+            .records
+            .iter()
+            .filter(|record| record.approved)
+            .find(|record| {
+                exp += record.base_exp + record.bonus_exp;
+                amount += record.duration;
+                level_for_exp(exp) >= level
+            })
+            .map(|record| record.date);
+        // The level came from somewhere other than the records, so it's dated by whatever the
+        // latest one is.
+        let date = reached
+            .or_else(|| skill.records.last().map(|record| record.date))
+            .unwrap_or_else(clock::today);
+        let after = match self.units.get(skill_id) {
+            Some(unit) => format!("{} {}", amount, unit.name),
+            None => hours_text(amount),
+        };
+
+        let journal = self.journals.entry(*skill_id).or_default();
+        journal.push(JournalEntry {
+            date,
+            level,
+            text: format!("Reached level {} on {} after {}", level, date, after),
+            reflection: String::new(),
+        });
+        self.dirty = true;
+        Some(journal.len() - 1)
+    }
+}
@@ -0,0 +1,116 @@
+//! The sheet model behind Sheet Myself, with no GUI attached: skills and their records, the EXP
+//! calculation, and reading and writing sheets. The app is one front-end; anything else which
+//! wants to read or change a sheet can use this crate the same way.
+//!
+//! ```no_run
+//! use chrono::Duration;
+//! use sheet_core::Sheet;
+//!
+//! let mut sheet = Sheet::open("myself.sht".as_ref())?;
+//! sheet.skill("Guitar").log(Duration::minutes(45));
+//! sheet.save(1)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Everything re-exported from the crate root follows semver. The modules are public so the app
+//! can get at everything, but anything only reachable through them may change between releases.
+
+#![forbid(unsafe_code)]
+#![cfg_attr(not(debug_assertions), deny(warnings))] // Forbid warnings in release builds
+#![warn(clippy::all, rust_2018_idioms)]
+
+/// Achievements unlocked by milestones across a sheet's skills.
+pub mod achievements;
+pub mod api;
+/// Attributes, which add up the EXP of a group of skills.
+pub mod attributes;
+/// The bonus rule pipeline which works out each record's bonus EXP.
+pub mod bonus;
+/// Showing as busy in a calendar, or turning on do not disturb, while a timer runs.
+pub mod busy;
+/// The weekly boss, rolled from the sheet's goals.
+pub mod challenges;
+/// What day it is, by the configured time zone and day boundary.
+pub mod clock;
+/// Skills which have been seen through to the end, for the Hall of Fame.
+pub mod completion;
+/// Notes about whole days, such as mood and sleep, and how they line up with practice.
+pub mod daily_notes;
+/// Rankings and summaries for an overview of a sheet.
+pub mod dashboard;
+/// Editing a record's note in an external program.
+pub mod editor;
+/// Encrypting a sheet's file with a password.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod encryption;
+/// Writing sheets out as CSV and other formats.
+pub mod export;
+pub mod file_format;
+/// How EXP amounts and durations are named and written out.
+pub mod format;
+pub mod formula;
+/// Goals to reach a number of hours or EXP by a date.
+pub mod goals;
+/// Undo and redo of edits to a sheet's skills.
+pub mod history;
+/// The HTTP agent webhooks, publishing and update checks are sent with.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod http;
+pub mod identity;
+/// Reading records in from CSV, spreadsheets and other apps.
+pub mod import;
+/// Each skill's diary of the milestones it reached.
+pub mod journal;
+/// Links from records to what their sessions produced.
+pub mod links;
+/// Rewards which drop as skills are practiced.
+pub mod loot;
+/// Live figures about a sheet to pin to the status bar.
+pub mod metrics;
+/// Upgrading sheets saved by older versions.
+pub mod migration;
+/// How hard the app nudges to keep practicing, and calm mode.
+pub mod nudging;
+/// Blank logging sheets to print and fill in by hand.
+pub mod paper;
+/// Sessions planned ahead of time.
+pub mod planner;
+/// Pomodoro work and break intervals.
+pub mod pomodoro;
+/// Skills which have to reach a level before others are unlocked, for the skill tree.
+pub mod prerequisites;
+/// Starting a skill's level over once it's maxed out, for a lasting EXP bonus.
+pub mod prestige;
+/// Publishing a read-only progress page to a website.
+pub mod publish;
+/// Fixed commitments which log themselves.
+pub mod recurring;
+/// Progress reports written as Markdown, HTML or JSON.
+pub mod report;
+/// Seasons, which track EXP over a stretch of time.
+pub mod season;
+/// [`Sheet`], which holds everything and is what gets saved.
+pub mod sheet;
+/// [`Skill`]s, their records, and the EXP calculation.
+pub mod skill;
+/// Save points to compare progress against.
+pub mod snapshot;
+/// Statistics worked out from a skill's records.
+pub mod stats;
+pub mod storage;
+/// Suggestions for what to practice next.
+pub mod suggest;
+pub mod sync;
+/// Bulk edits and data health checks.
+pub mod tools;
+/// Units other than minutes for skills to be logged in.
+pub mod units;
+pub mod versions;
+/// Telling other services about level ups, streaks and completed goals.
+pub mod webhooks;
+/// Daily minute zones, from maintenance to stretch.
+pub mod zones;
+
+pub use api::{SheetStats, SkillHandle};
+pub use sheet::{LoadError, Sheet};
+pub use skill::{Effort, SheetActionRecord, Skill};
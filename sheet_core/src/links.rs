@@ -0,0 +1,142 @@
+use crate::sheet::Sheet;
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+//====================================================
+// LinkKind
+//====================================================
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, JsonSchema, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Url,
+    /// A git commit, by its hash.
+    Commit,
+    File,
+}
+
+impl LinkKind {
+    pub const ALL: [LinkKind; 3] = [LinkKind::Url, LinkKind::Commit, LinkKind::File];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LinkKind::Url => "Link",
+            LinkKind::Commit => "Commit",
+            LinkKind::File => "File",
+        }
+    }
+}
+
+//====================================================
+// Link
+//====================================================
+/// Something a session produced or worked on, kept with its record: a page, a commit or a file.
+#[derive(Clone, Debug, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct Link {
+    pub kind: LinkKind,
+    /// The URL, commit hash or path.
+    pub target: String,
+    /// What to show instead of the target, if anything.
+    #[serde(default)]
+    pub label: String,
+}
+
+impl Link {
+    /// A link to `text`, guessing what kind it is: web addresses are URLs, runs of 7 to 40
+    /// hexadecimal digits are commits, and anything else is a file path.
+    ///
+    /// ```
+    /// use sheet_core::links::{Link, LinkKind};
+    ///
+    /// assert_eq!(Link::guess("https://example.com/pr/12").kind, LinkKind::Url);
+    /// assert_eq!(Link::guess("3f9c2ab").kind, LinkKind::Commit);
+    /// assert_eq!(Link::guess("~/music/etude.pdf").kind, LinkKind::File);
+    /// ```
+    pub fn guess(text: &str) -> Self {
+        let target = text.trim();
+        let is_url = ["http://", "https://", "mailto:"]
+            .iter()
+            .any(|scheme| target.starts_with(scheme));
+        let is_commit =
+            (7..=40).contains(&target.len()) && target.chars().all(|c| c.is_ascii_hexdigit());
+        Self {
+            kind: if is_url {
+                LinkKind::Url
+            } else if is_commit {
+                LinkKind::Commit
+            } else {
+                LinkKind::File
+            },
+            target: target.to_owned(),
+            label: String::new(),
+        }
+    }
+
+    /// What to show for the link: its label, or else its target, with commits cut down to the
+    /// short hash.
+    pub fn display(&self) -> String {
+        if !self.label.trim().is_empty() {
+            return self.label.trim().to_owned();
+        }
+        match self.kind {
+            LinkKind::Commit => self.target.chars().take(7).collect(),
+            LinkKind::File => Path::new(&self.target).file_name().map_or_else(
+                || self.target.clone(),
+                |name| name.to_string_lossy().into_owned(),
+            ),
+            LinkKind::Url => self.target.clone(),
+        }
+    }
+
+    /// Somewhere a browser or the desktop can open the link, if there is one. Relative file
+    /// paths are taken to be from `sheet_folder`. Commits don't have anywhere of their own to go.
+    pub fn url(&self, sheet_folder: &Path) -> Option<String> {
+        let path = match self.kind {
+            LinkKind::Url => return Some(self.target.clone()),
+            LinkKind::Commit => return None,
+            LinkKind::File => match self.target.strip_prefix("~/") {
+                Some(rest) => std::env::var_os("HOME").map_or_else(
+                    || PathBuf::from(&self.target),
+                    |home| Path::new(&home).join(rest),
+                ),
+                None => sheet_folder.join(&self.target),
+            },
+        };
+        let path = path.to_string_lossy().replace('\\', "/");
+        // Windows paths start with a drive letter rather than a slash.
+        let slash = if path.starts_with('/') { "" } else { "/" };
+        Some(format!("file://{}{}", slash, path))
+    }
+}
+
+impl Sheet {
+    /// Adds `link` to the latest of `skill_id`'s records on `date`, such as the one a timer was
+    /// just stopped into. Gives back whether there was a record to add it to.
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use sheet_core::links::Link;
+    /// use sheet_core::{clock, Sheet};
+    ///
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// let id = sheet.skill("Rust").log(Duration::minutes(50)).id();
+    /// assert!(sheet.link_session(&id, clock::today(), Link::guess("3f9c2ab")));
+    /// assert_eq!(sheet.skills_list[&id].records[0].links[0].display(), "3f9c2ab");
+    /// ```
+    pub fn link_session(&mut self, skill_id: &Uuid, date: NaiveDate, link: Link) -> bool {
+        let record = self
+            .skills_list
+            .get_mut(skill_id)
+            .and_then(|skill| skill.records.iter_mut().rev().find(|r| r.date == date));
+        match record {
+            Some(record) => {
+                record.links.push(link);
+                self.dirty = true;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::skill::Skill;
+use chrono::{Duration, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+//====================================================
+// LootItem
+//====================================================
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, JsonSchema, Serialize)]
+pub enum LootKind {
+    #[default]
+    Title,
+    Theme,
+    AvatarFrame,
+}
+
+impl LootKind {
+    pub const ALL: [LootKind; 3] = [LootKind::Title, LootKind::Theme, LootKind::AvatarFrame];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LootKind::Title => "Title",
+            LootKind::Theme => "Sheet theme",
+            LootKind::AvatarFrame => "Avatar frame",
+        }
+    }
+}
+
+/// A purely cosmetic reward.
+#[derive(Clone, PartialEq, Default, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct LootItem {
+    pub kind: LootKind,
+    pub name: String,
+}
+
+/// One line of a loot table. An entry's chance of being picked is its weight out of the total.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct LootEntry {
+    pub item: LootItem,
+    pub weight: u32,
+}
+
+impl Default for LootEntry {
+    fn default() -> Self {
+        Self {
+            item: LootItem::default(),
+            weight: 10,
+        }
+    }
+}
+
+/// Something which has dropped, and what for.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+pub struct FoundItem {
+    pub item: LootItem,
+    pub date: NaiveDate,
+    pub skill_id: Uuid,
+}
+
+//====================================================
+// Loot
+//====================================================
+/// A sheet's loot settings, table and inventory.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Loot {
+    pub enabled: bool,
+    /// The chance each skill's first session of the day drops something.
+    pub drop_chance: f64,
+    pub table: Vec<LootEntry>,
+    pub inventory: Vec<FoundItem>,
+    /// What's currently equipped, one item per kind.
+    pub equipped: HashMap<LootKind, String>,
+    /// State of the random number generator, saved so a reload can't be used to re-roll.
+    #[serde(with = "bits_as_i64")]
+    #[schemars(with = "i64")]
+    pub rng_state: u64,
+    /// Skills and days which have already had their roll, so deleting and re-adding a session
+    /// doesn't give another go. Only recent days are kept.
+    pub rolled: HashSet<(Uuid, NaiveDate)>,
+}
+
+impl Default for Loot {
+    fn default() -> Self {
+        let entry = |kind, name: &str, weight| LootEntry {
+            item: LootItem {
+                kind,
+                name: name.to_owned(),
+            },
+            weight,
+        };
+        Self {
+            enabled: false,
+            drop_chance: 0.1,
+            table: vec![
+                entry(LootKind::Title, "the Diligent", 30),
+                entry(LootKind::Title, "Early Riser", 20),
+                entry(LootKind::Title, "Grandmaster", 2),
+                entry(LootKind::Theme, "Parchment", 15),
+                entry(LootKind::Theme, "Midnight", 10),
+                entry(LootKind::AvatarFrame, "Bronze Frame", 15),
+                entry(LootKind::AvatarFrame, "Golden Frame", 5),
+            ],
+            inventory: Vec::new(),
+            equipped: HashMap::new(),
+            rng_state: seed(),
+            rolled: HashSet::new(),
+        }
+    }
+}
+
+impl Loot {
+    /// Sessions older than this don't roll, so back-filling or importing history can't be used
+    /// to farm loot.
+    const ROLL_WINDOW_DAYS: i64 = 1;
+
+    /// Gives every skill which has gained a recent session since `before` its roll for the day,
+    /// and returns anything which dropped.
+    pub fn roll_for_new_sessions(
+        &mut self,
+        before: &HashMap<Uuid, Skill>,
+        after: &HashMap<Uuid, Skill>,
+        today: NaiveDate,
+    ) -> Vec<FoundItem> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let oldest = today - Duration::days(Self::ROLL_WINDOW_DAYS);
+        self.rolled.retain(|(_, date)| *date >= oldest);
+
+        let sessions = |skill: &Skill, date: NaiveDate| {
+            skill
+                .records
+                .iter()
+                .filter(|r| r.approved && r.date == date)
+                .count()
+        };
+        let mut found = Vec::new();
+        after.iter().for_each(|(skill_id, skill)| {
+            let mut dates: Vec<NaiveDate> = skill
+                .records
+                .iter()
+                .filter(|r| r.approved && oldest <= r.date && r.date <= today)
+                .map(|r| r.date)
+                .collect();
+            dates.dedup();
+            dates.into_iter().for_each(|date| {
+                let had = before.get(skill_id).map_or(0, |s| sessions(s, date));
+                if sessions(skill, date) > had && self.rolled.insert((*skill_id, date)) {
+                    if let Some(item) = self.roll() {
+                        found.push(FoundItem {
+                            item,
+                            date,
+                            skill_id: *skill_id,
+                        });
+                    }
+                }
+            });
+        });
+
+        self.inventory.extend(found.iter().cloned());
+        found
+    }
+
+    /// One roll of the dice: maybe nothing, maybe something from the table.
+    fn roll(&mut self) -> Option<LootItem> {
+        if self.next_f64() >= self.drop_chance {
+            return None;
+        }
+        let total: u64 = self.table.iter().map(|e| e.weight as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut pick = (self.next_f64() * total as f64) as u64;
+        self.table
+            .iter()
+            .find(|e| {
+                if pick < e.weight as u64 {
+                    true
+                } else {
+                    pick -= e.weight as u64;
+                    false
+                }
+            })
+            .map(|e| e.item.clone())
+    }
+
+    /// SplitMix64, which is plenty random enough for handing out cosmetics.
+    fn next_u64(&mut self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0.0..1.0`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Saves a `u64` as the `i64` with the same bits, as TOML can't hold integers past `i64::MAX`.
+/// Either is read back, so sheets which saved it as a `u64` still load.
+mod bits_as_i64 {
+    use serde::de::{self, Deserializer, Visitor};
+    use serde::Serializer;
+    use std::fmt;
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(*value as i64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        struct Bits;
+
+        impl Visitor<'_> for Bits {
+            type Value = u64;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a 64-bit integer")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+                Ok(value)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+                Ok(value as u64)
+            }
+        }
+
+        deserializer.deserialize_any(Bits)
+    }
+}
+
+/// A fresh seed for a new sheet's loot rolls.
+fn seed() -> u64 {
+    Utc::now().timestamp_nanos() as u64 ^ Uuid::new_v4().as_u128() as u64
+}
@@ -0,0 +1,176 @@
+use crate::format::ExpFormat;
+use crate::goals::GoalState;
+use crate::nudging::NudgePolicy;
+use crate::sheet::Sheet;
+use crate::stats;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+//====================================================
+// Metric
+//====================================================
+/// A live figure about a sheet which can be pinned somewhere small, like the app's status bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Metric {
+    /// Minutes logged today, on skills which are logged in minutes.
+    TodayMinutes,
+    WeekMinutes,
+    /// How far through the goals which are still running are, on average.
+    GoalProgress,
+    /// How far through this week's boss is, on average across its parts.
+    WeeklyBoss,
+    /// How many skills have a streak going.
+    ActiveStreaks,
+    LongestStreak,
+    TotalExp,
+    /// Records waiting to be approved.
+    Pending,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 8] = [
+        Metric::TodayMinutes,
+        Metric::WeekMinutes,
+        Metric::GoalProgress,
+        Metric::WeeklyBoss,
+        Metric::ActiveStreaks,
+        Metric::LongestStreak,
+        Metric::TotalExp,
+        Metric::Pending,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Metric::TodayMinutes => "Minutes today",
+            Metric::WeekMinutes => "Minutes this week",
+            Metric::GoalProgress => "Goal progress",
+            Metric::WeeklyBoss => "Weekly boss",
+            Metric::ActiveStreaks => "Active streaks",
+            Metric::LongestStreak => "Longest streak",
+            Metric::TotalExp => "Total EXP",
+            Metric::Pending => "Pending records",
+        }
+    }
+
+    /// The metric as of `today`, written out short with what it is, e.g. "Today: 45 min". Streaks
+    /// are named by `nudging`.
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use sheet_core::format::ExpFormat;
+    /// use sheet_core::metrics::Metric;
+    /// use sheet_core::nudging::NudgePolicy;
+    /// use sheet_core::{clock, Sheet};
+    ///
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// sheet.skill("Guitar").log(Duration::minutes(45));
+    /// let today = clock::today();
+    /// let nudging = NudgePolicy::default();
+    /// let show = |metric: Metric| metric.show(&sheet, &ExpFormat::default(), &nudging, today);
+    /// assert_eq!(show(Metric::TodayMinutes), "Today: 45 min");
+    /// assert_eq!(show(Metric::ActiveStreaks), "Streaks: 1");
+    /// assert_eq!(show(Metric::GoalProgress), "Goals: none running");
+    /// ```
+    pub fn show(
+        &self,
+        sheet: &Sheet,
+        exp_format: &ExpFormat,
+        nudging: &NudgePolicy,
+        today: NaiveDate,
+    ) -> String {
+        match self {
+            Metric::TodayMinutes => {
+                let minutes = minutes_per_day(sheet).get(&today).copied().unwrap_or(0);
+                format!("Today: {} min", minutes)
+            }
+            Metric::WeekMinutes => {
+                let week_start = stats::week_start(today);
+                let minutes: u64 = minutes_per_day(sheet)
+                    .range(week_start..=today)
+                    .map(|(_, minutes)| minutes)
+                    .sum();
+                format!("Week: {} min", minutes)
+            }
+            Metric::GoalProgress => {
+                let running: Vec<f64> = sheet
+                    .goals
+                    .iter()
+                    .map(|goal| goal.progress(sheet.skills_list.get(&goal.skill_id), today))
+                    .filter(|progress| progress.state == GoalState::InProgress)
+                    .map(|progress| progress.fraction)
+                    .collect();
+                match average(&running) {
+                    Some(fraction) => format!("Goals: {:.0}%", fraction * 100.0),
+                    None => "Goals: none running".to_owned(),
+                }
+            }
+            Metric::WeeklyBoss => match &sheet.challenges.current {
+                Some(boss) if boss.defeated.is_some() => format!("{}: beaten", boss.name),
+                Some(boss) => {
+                    let parts: Vec<f64> = boss
+                        .progress(&sheet.skills_list, &sheet.units)
+                        .iter()
+                        .map(|part| part.fraction)
+                        .collect();
+                    let fraction = average(&parts).unwrap_or(0.0);
+                    format!("{}: {:.0}%", boss.name, fraction * 100.0)
+                }
+                None => "Boss: not rolled yet".to_owned(),
+            },
+            Metric::ActiveStreaks => {
+                let streaks = sheet
+                    .skills_list
+                    .values()
+                    .filter(|skill| stats::current_streak(skill, today) > 0)
+                    .count();
+                format!("{}s: {}", nudging.streak_name(), streaks)
+            }
+            Metric::LongestStreak => {
+                let longest = sheet
+                    .skills_list
+                    .values()
+                    .map(|skill| stats::current_streak(skill, today))
+                    .max()
+                    .unwrap_or(0);
+                format!(
+                    "Longest {}: {} days",
+                    nudging.streak_name().to_lowercase(),
+                    longest
+                )
+            }
+            Metric::TotalExp => {
+                let exp: f64 = sheet.skills_list.values().map(|s| s.total_exp).sum();
+                format!("{}: {}", exp_format.name, exp_format.number(exp))
+            }
+            Metric::Pending => {
+                let pending = sheet
+                    .skills_list
+                    .values()
+                    .flat_map(|skill| skill.records.iter())
+                    .filter(|record| !record.approved)
+                    .count();
+                format!("Pending: {}", pending)
+            }
+        }
+    }
+}
+
+/// Approved minutes per day, leaving out skills which are logged in other units.
+fn minutes_per_day(sheet: &Sheet) -> BTreeMap<NaiveDate, u64> {
+    stats::minutes_per_day(
+        sheet
+            .skills_list
+            .iter()
+            .filter(|(id, _)| !sheet.units.contains_key(id))
+            .map(|(_, skill)| skill),
+    )
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
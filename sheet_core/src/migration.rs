@@ -0,0 +1,157 @@
+use crate::sheet::Sheet;
+use crate::skill;
+use chrono::{NaiveDate, Utc};
+use serde_json::Value;
+use std::fmt;
+
+/// The save format version written by this build. Bump it whenever the shape of `Sheet`,
+/// `Skill` or `SheetActionRecord` changes in a way `#[serde(default)]` can't paper over, and add
+/// a migration for it below.
+pub const CURRENT_FORMAT_VERSION: u32 = 3;
+
+/// Files saved before versioning was added have no `format_version` field at all.
+const UNVERSIONED: u32 = 1;
+
+/// Upgrades a sheet's JSON from one version to the next.
+type Migration = fn(&mut Value);
+
+/// `MIGRATIONS[n]` upgrades a sheet from version `n + 1` to version `n + 2`.
+const MIGRATIONS: &[Migration] = &[v1_to_v2, v2_to_v3];
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The file was written by a newer build, which we can't know how to read.
+    TooNew(u32),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::TooNew(version) => write!(
+                f,
+                "saved with format version {}, but this build only understands up to {}",
+                version, CURRENT_FORMAT_VERSION
+            ),
+        }
+    }
+}
+
+/// A JSON Schema describing sheets as this build saves them, so other tools can read and write
+/// them safely. Fields the app works out for itself on load aren't included.
+pub fn schema() -> String {
+    let mut schema = schemars::schema_for!(Sheet);
+    let metadata = schema.schema.metadata();
+    metadata.title = Some("Sheet Myself sheet".to_owned());
+    metadata.description = Some(format!(
+        "Save format version {}. Older versions are upgraded when opened, so files written by \
+        other tools should set `format_version` to the version they were written against.",
+        CURRENT_FORMAT_VERSION
+    ));
+    serde_json::to_string_pretty(&schema).unwrap_or_default()
+}
+
+/// The format version a sheet's JSON claims to be.
+pub fn format_version(value: &Value) -> u32 {
+    value
+        .get("format_version")
+        .and_then(Value::as_u64)
+        .map_or(UNVERSIONED, |v| v as u32)
+}
+
+/// Brings a sheet's JSON up to `CURRENT_FORMAT_VERSION`, one version at a time.
+pub fn migrate(value: &mut Value) -> Result<(), MigrationError> {
+    let version = format_version(value);
+    if version > CURRENT_FORMAT_VERSION {
+        return Err(MigrationError::TooNew(version));
+    }
+
+    MIGRATIONS
+        .iter()
+        .skip((version - UNVERSIONED) as usize)
+        .for_each(|migration| migration(value));
+
+    if let Some(object) = value.as_object_mut() {
+        object.insert("format_version".to_owned(), CURRENT_FORMAT_VERSION.into());
+    }
+    Ok(())
+}
+
+/// Version 2 only introduced `format_version` itself. Everything else added since the
+/// unversioned format has a sensible default, so there's nothing to restructure.
+fn v1_to_v2(_value: &mut Value) {}
+
+/// Version 3 added `created_at` to skills. Rather than have every existing skill look brand new,
+/// it's backfilled from the skill's earliest record.
+fn v2_to_v3(value: &mut Value) {
+    let skills = match value.get_mut("skills_list").and_then(Value::as_object_mut) {
+        Some(skills) => skills,
+        None => return,
+    };
+
+    skills
+        .values_mut()
+        .filter_map(Value::as_object_mut)
+        .for_each(|skill| {
+            let earliest = skill
+                .get("records")
+                .and_then(Value::as_array)
+                .and_then(|records| {
+                    records
+                        .iter()
+                        .filter_map(|r| r.get("date")?.as_str()?.parse::<NaiveDate>().ok())
+                        .min()
+                });
+            let created_at = earliest.map_or_else(Utc::now, skill::start_of_day);
+            skill
+                .entry("created_at")
+                .or_insert_with(|| created_at.to_rfc3339().into());
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unversioned_sheets_are_brought_up_to_date() {
+        let mut value = json!({ "skills_list": {} });
+        assert_eq!(format_version(&value), UNVERSIONED);
+        migrate(&mut value).unwrap();
+        assert_eq!(format_version(&value), CURRENT_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn current_sheets_are_left_as_they_are() {
+        let mut value = json!({ "format_version": CURRENT_FORMAT_VERSION, "skills_list": {} });
+        let before = value.clone();
+        migrate(&mut value).unwrap();
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn sheets_from_newer_builds_are_refused() {
+        let mut value = json!({ "format_version": CURRENT_FORMAT_VERSION + 1 });
+        let error = migrate(&mut value).unwrap_err();
+        assert!(matches!(error, MigrationError::TooNew(v) if v == CURRENT_FORMAT_VERSION + 1));
+        assert_eq!(format_version(&value), CURRENT_FORMAT_VERSION + 1);
+    }
+
+    #[test]
+    fn skills_are_backfilled_as_created_on_their_first_record() {
+        let mut value = json!({
+            "format_version": 2,
+            "skills_list": {
+                "a": { "records": [{ "date": "2024-03-05" }, { "date": "2024-03-01" }] },
+                "b": { "created_at": "2020-01-01T00:00:00+00:00", "records": [] },
+                "c": { "records": [] },
+            },
+        });
+        migrate(&mut value).unwrap();
+        let created_at = |id: &str| value["skills_list"][id]["created_at"].as_str().unwrap();
+        let first = skill::start_of_day(NaiveDate::from_ymd(2024, 3, 1));
+        assert_eq!(created_at("a"), first.to_rfc3339());
+        assert_eq!(created_at("b"), "2020-01-01T00:00:00+00:00");
+        assert!(created_at("c").parse::<chrono::DateTime<Utc>>().is_ok());
+    }
+}
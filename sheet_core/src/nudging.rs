@@ -0,0 +1,91 @@
+use crate::goals::GoalState;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+//====================================================
+// NudgePolicy
+//====================================================
+/// How hard the app pushes to keep practicing. Everything which nudges, from streak labels to
+/// reminders and suggestions, asks this how to go about it, so calm mode changes it everywhere.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use sheet_core::nudging::NudgePolicy;
+///
+/// let calm = NudgePolicy { calm: true, ..Default::default() };
+/// assert_eq!(calm.streak_name(), "Momentum");
+/// assert!(!calm.warns_of_risk());
+/// let day = NaiveDate::from_ymd(2024, 5, 1);
+/// assert!(!calm.reminder_due(day.succ(), Some(day)));
+/// ```
+#[derive(Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NudgePolicy {
+    /// Takes the pressure off: streaks are called momentum, nothing is ever at risk, and missed
+    /// goals or sessions aren't shown as losses.
+    pub calm: bool,
+    /// In calm mode, the fewest days between gentle reminders. 0 turns them off altogether.
+    pub calm_reminder_days: u32,
+}
+
+impl Default for NudgePolicy {
+    fn default() -> Self {
+        Self {
+            calm: false,
+            calm_reminder_days: 3,
+        }
+    }
+}
+
+impl NudgePolicy {
+    /// What days practiced in a row are called, e.g. in "Streak: 12 days".
+    pub fn streak_name(&self) -> &'static str {
+        if self.calm {
+            "Momentum"
+        } else {
+            "Streak"
+        }
+    }
+
+    /// e.g. "12 days in a row" or, in calm mode, "12 days of momentum".
+    pub fn streak_days(&self, days: u32) -> String {
+        if self.calm {
+            format!("{} days of momentum", days)
+        } else {
+            format!("{} days in a row", days)
+        }
+    }
+
+    /// Whether to warn about streaks which will run out, and to sound urgent about it.
+    pub fn warns_of_risk(&self) -> bool {
+        !self.calm
+    }
+
+    /// Whether misses, like failed goals and overdue sessions, are shown as bad news.
+    pub fn shows_losses(&self) -> bool {
+        !self.calm
+    }
+
+    /// What a goal's state is called. In calm mode, a goal whose deadline passed has just ended.
+    pub fn goal_state_label(&self, state: GoalState) -> &'static str {
+        match state {
+            GoalState::Failed if self.calm => "Ended",
+            state => state.label(),
+        }
+    }
+
+    /// Whether a reminder may go off `today`, given the day the last one did. Reminders are
+    /// daily, except in calm mode, where they're spaced out by
+    /// [`NudgePolicy::calm_reminder_days`].
+    pub fn reminder_due(&self, today: NaiveDate, last_reminded: Option<NaiveDate>) -> bool {
+        if !self.calm {
+            return last_reminded.map_or(true, |last| last < today);
+        }
+        if self.calm_reminder_days == 0 {
+            return false;
+        }
+        last_reminded.map_or(true, |last| {
+            today.signed_duration_since(last).num_days() >= self.calm_reminder_days as i64
+        })
+    }
+}
@@ -0,0 +1,224 @@
+//! Blank logging sheets to print out, so practice can be noted down on paper and typed in later
+//! with Paste Rows. All they need is lines and some Helvetica text, so the PDF is written out by
+//! hand.
+
+use crate::sheet::Sheet;
+use crate::skill::Skill;
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::fmt::Write;
+
+// A4, on its side, in points.
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const MARGIN: f32 = 36.0;
+const NAME_WIDTH: f32 = 150.0;
+const TITLE_HEIGHT: f32 = 30.0;
+const HEADER_HEIGHT: f32 = 26.0;
+const ROW_HEIGHT: f32 = 24.0;
+const FOOTER_HEIGHT: f32 = 20.0;
+const TITLE_SIZE: f32 = 14.0;
+const TEXT_SIZE: f32 = 9.0;
+/// How light weekend columns are shaded, from 0 (black) to 1 (white).
+const WEEKEND_GRAY: f32 = 0.9;
+
+/// The first and last days of the month `date` is in.
+pub fn month_of(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first = NaiveDate::from_ymd(date.year(), date.month(), 1);
+    let next = if date.month() == 12 {
+        NaiveDate::from_ymd(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd(date.year(), date.month() + 1, 1)
+    };
+    (first, next.pred())
+}
+
+/// A PDF with a row for each of the sheet's skills, in name order, and a column for each day of
+/// the month `month` is in. Skills which don't fit on one page carry on onto the next.
+pub fn blank_log_pdf(sheet: &Sheet, month: NaiveDate) -> Vec<u8> {
+    let (first, last) = month_of(month);
+    let days: Vec<NaiveDate> = first.iter_days().take_while(|day| *day <= last).collect();
+
+    let mut skills: Vec<&Skill> = sheet.skills_list.values().collect();
+    skills.sort_by(|a, b| a.name.cmp(&b.name));
+    let names: Vec<&str> = skills.iter().map(|skill| skill.name.as_str()).collect();
+
+    let title = format!("{} - {}", sheet.display_name(), first.format("%B %Y"));
+    let rows_per_page =
+        ((PAGE_HEIGHT - 2.0 * MARGIN - TITLE_HEIGHT - HEADER_HEIGHT - FOOTER_HEIGHT) / ROW_HEIGHT)
+            as usize;
+    // An empty sheet still gets a page, so there's something to print.
+    let pages: Vec<String> = if names.is_empty() {
+        vec![page_content(&title, &days, &[], rows_per_page)]
+    } else {
+        names
+            .chunks(rows_per_page)
+            .map(|chunk| page_content(&title, &days, chunk, rows_per_page))
+            .collect()
+    };
+    write_pdf(&pages)
+}
+
+/// The drawing commands for one page of the grid. Every page has `rows` rows, so any left over
+/// after the skills' names are there for writing in ones which aren't on the sheet yet.
+fn page_content(title: &str, days: &[NaiveDate], names: &[&str], rows: usize) -> String {
+    let mut out = String::new();
+    let left = MARGIN;
+    let right = PAGE_WIDTH - MARGIN;
+    let day_width = (right - left - NAME_WIDTH) / days.len() as f32;
+    let grid_top = PAGE_HEIGHT - MARGIN - TITLE_HEIGHT;
+    let grid_bottom = grid_top - HEADER_HEIGHT - rows as f32 * ROW_HEIGHT;
+    let day_x = |i: usize| left + NAME_WIDTH + i as f32 * day_width;
+
+    text(
+        &mut out,
+        left,
+        PAGE_HEIGHT - MARGIN - TITLE_SIZE,
+        TITLE_SIZE,
+        title,
+    );
+
+    // Weekends are shaded first so the lines go over the top of them.
+    let _ = writeln!(out, "{} g", WEEKEND_GRAY);
+    days.iter().enumerate().for_each(|(i, day)| {
+        if matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            let _ = writeln!(
+                out,
+                "{} {} {} {} re f",
+                day_x(i),
+                grid_bottom,
+                day_width,
+                grid_top - grid_bottom
+            );
+        }
+    });
+    out.push_str("0 g\n0.5 w\n");
+
+    (0..=rows).for_each(|row| {
+        let y = grid_top - HEADER_HEIGHT - row as f32 * ROW_HEIGHT;
+        line(&mut out, left, y, right, y);
+    });
+    line(&mut out, left, grid_top, right, grid_top);
+    line(&mut out, left, grid_top, left, grid_bottom);
+    (0..=days.len()).for_each(|i| line(&mut out, day_x(i), grid_top, day_x(i), grid_bottom));
+
+    days.iter().enumerate().for_each(|(i, day)| {
+        let x = day_x(i) + 3.0;
+        text(
+            &mut out,
+            x,
+            grid_top - 11.0,
+            TEXT_SIZE,
+            &day.day().to_string(),
+        );
+        let weekday = &day.weekday().to_string()[..2];
+        text(&mut out, x, grid_top - 21.0, TEXT_SIZE - 2.0, weekday);
+    });
+    text(&mut out, left + 4.0, grid_top - 17.0, TEXT_SIZE, "Skill");
+
+    // Helvetica's letters average a little over half the font size across.
+    let max_chars = ((NAME_WIDTH - 8.0) / (TEXT_SIZE * 0.55)) as usize;
+    names.iter().enumerate().for_each(|(row, name)| {
+        let y = grid_top - HEADER_HEIGHT - (row + 1) as f32 * ROW_HEIGHT + 8.0;
+        let name: String = if name.chars().count() > max_chars {
+            name.chars()
+                .take(max_chars - 3)
+                .chain("...".chars())
+                .collect()
+        } else {
+            (*name).to_owned()
+        };
+        text(&mut out, left + 4.0, y, TEXT_SIZE, &name);
+    });
+
+    text(
+        &mut out,
+        left,
+        MARGIN,
+        TEXT_SIZE - 1.0,
+        "Write the minutes practiced in each box. Type them in later with Paste Rows: \
+         date, minutes, note.",
+    );
+    out
+}
+
+fn line(out: &mut String, x1: f32, y1: f32, x2: f32, y2: f32) {
+    let _ = writeln!(out, "{} {} m {} {} l S", x1, y1, x2, y2);
+}
+
+fn text(out: &mut String, x: f32, y: f32, size: f32, text: &str) {
+    let _ = writeln!(
+        out,
+        "BT /F1 {} Tf {} {} Td ({}) Tj ET",
+        size,
+        x,
+        y,
+        pdf_string(text)
+    );
+}
+
+/// Escapes `text` for a PDF string literal. The font uses WinAnsiEncoding, which has Latin-1's
+/// letters in the same places; anything outside of that is written as "?".
+fn pdf_string(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '(' | ')' | '\\' => format!("\\{}", c),
+            ' '..='~' => c.to_string(),
+            '\u{a0}'..='\u{ff}' => format!("\\{:03o}", c as u32),
+            _ => "?".to_owned(),
+        })
+        .collect()
+}
+
+/// Puts the pages' content streams together into a PDF file, with its cross-reference table.
+fn write_pdf(pages: &[String]) -> Vec<u8> {
+    // Objects 1 to 3 are the catalog, the page tree and the font; each page then has its page
+    // object followed by its content stream.
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + 2 * i).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids
+                .iter()
+                .map(|id| format!("{} 0 R", id))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+            .to_owned(),
+    ];
+    pages.iter().zip(&page_ids).for_each(|(content, id)| {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            id + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    });
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    objects.iter().enumerate().for_each(|(i, object)| {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+    });
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    offsets.iter().for_each(|offset| {
+        let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+    });
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    pdf.into_bytes()
+}
@@ -0,0 +1,34 @@
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+//====================================================
+// PlannedSession
+//====================================================
+/// A session planned ahead of time, shown as a quest on its day until it's done.
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct PlannedSession {
+    pub skill_id: Uuid,
+    /// The day it's planned for, or `None` while it's waiting to be put on a day.
+    pub date: Option<NaiveDate>,
+    pub minutes: u64,
+}
+
+impl Default for PlannedSession {
+    fn default() -> Self {
+        Self {
+            skill_id: Uuid::nil(),
+            date: None,
+            minutes: 30,
+        }
+    }
+}
+
+impl PlannedSession {
+    /// Whether it was planned for a day which has already gone by.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        self.date.map_or(false, |date| date < today)
+    }
+}
@@ -0,0 +1,85 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+//====================================================
+// Pomodoro
+//====================================================
+/// How long the work and break intervals last.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PomodoroSettings {
+    pub work_minutes: u32,
+    pub break_minutes: u32,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        Self {
+            work_minutes: 25,
+            break_minutes: 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Phase {
+    Work,
+    Break,
+}
+
+impl Phase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Phase::Work => "Work",
+            Phase::Break => "Break",
+        }
+    }
+}
+
+/// A run of work intervals on one skill, each followed by a break, until it's stopped.
+#[derive(Clone)]
+pub struct Pomodoro {
+    pub skill_id: Uuid,
+    pub phase: Phase,
+    pub phase_started: DateTime<Utc>,
+}
+
+impl Pomodoro {
+    pub fn start(skill_id: Uuid, now: DateTime<Utc>) -> Self {
+        Self {
+            skill_id,
+            phase: Phase::Work,
+            phase_started: now,
+        }
+    }
+
+    fn phase_length(&self, settings: &PomodoroSettings) -> Duration {
+        let minutes = match self.phase {
+            Phase::Work => settings.work_minutes,
+            Phase::Break => settings.break_minutes,
+        };
+        Duration::minutes(minutes as i64)
+    }
+
+    /// How long is left of the current interval.
+    pub fn remaining(&self, now: DateTime<Utc>, settings: &PomodoroSettings) -> Duration {
+        (self.phase_started + self.phase_length(settings) - now).max(Duration::zero())
+    }
+
+    /// Moves on to the next interval if this one is over. Returns whether a work interval was
+    /// just completed, which is when it should be logged.
+    pub fn advance(&mut self, now: DateTime<Utc>, settings: &PomodoroSettings) -> bool {
+        let ends = self.phase_started + self.phase_length(settings);
+        if now < ends {
+            return false;
+        }
+        let finished = self.phase;
+        self.phase = match finished {
+            Phase::Work => Phase::Break,
+            Phase::Break => Phase::Work,
+        };
+        self.phase_started = ends;
+        finished == Phase::Work
+    }
+}
@@ -0,0 +1,107 @@
+use crate::sheet::Sheet;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+//====================================================
+// Prerequisite
+//====================================================
+/// A skill which has to reach a level before another one is unlocked, e.g. Music Theory 5 for
+/// Composition.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct Prerequisite {
+    pub skill_id: Uuid,
+    pub level: u32,
+}
+
+impl Sheet {
+    /// The prerequisites of `skill_id` which haven't been met yet, each with the level its skill
+    /// is at now. Ones whose skill has been deleted don't hold anything back.
+    pub fn unmet_prerequisites(&self, skill_id: &Uuid) -> Vec<(Prerequisite, u32)> {
+        self.prerequisites
+            .get(skill_id)
+            .map_or(&[][..], Vec::as_slice)
+            .iter()
+            .filter_map(|prerequisite| {
+                let level = self.skills_list.get(&prerequisite.skill_id)?.level();
+                (level < prerequisite.level).then_some((*prerequisite, level))
+            })
+            .collect()
+    }
+
+    /// Whether every one of `skill_id`'s prerequisites has been met.
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use sheet_core::prerequisites::Prerequisite;
+    /// use sheet_core::Sheet;
+    ///
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// let theory = sheet.skill("Music Theory").log(Duration::hours(10)).id();
+    /// let composition = sheet.skill("Composition").id();
+    /// let needs = Prerequisite { skill_id: theory, level: 5 };
+    /// sheet.prerequisites.insert(composition, vec![needs]);
+    /// assert!(!sheet.is_unlocked(&composition));
+    ///
+    /// sheet.skill("Music Theory").log(Duration::hours(18));
+    /// assert!(sheet.is_unlocked(&composition));
+    /// assert!(sheet.would_loop(&theory, &composition));
+    /// ```
+    pub fn is_unlocked(&self, skill_id: &Uuid) -> bool {
+        self.unmet_prerequisites(skill_id).is_empty()
+    }
+
+    /// Whether making `prerequisite_id` a prerequisite of `skill_id` would have a skill end up
+    /// needing itself, which could never be unlocked.
+    pub fn would_loop(&self, skill_id: &Uuid, prerequisite_id: &Uuid) -> bool {
+        let mut to_visit = vec![*prerequisite_id];
+        let mut seen = Vec::new();
+        while let Some(id) = to_visit.pop() {
+            if id == *skill_id {
+                return true;
+            }
+            if seen.contains(&id) {
+                continue;
+            }
+            seen.push(id);
+            if let Some(prerequisites) = self.prerequisites.get(&id) {
+                to_visit.extend(prerequisites.iter().map(|p| p.skill_id));
+            }
+        }
+        false
+    }
+
+    /// How far down the skill tree each skill is: 0 for skills which don't need anything, and
+    /// otherwise one more than the furthest down of their prerequisites.
+    pub fn skill_tiers(&self) -> HashMap<Uuid, usize> {
+        let mut tiers = HashMap::new();
+        self.skills_list.keys().for_each(|id| {
+            self.tier_of(id, &mut tiers, &mut Vec::new());
+        });
+        tiers
+    }
+
+    fn tier_of(&self, id: &Uuid, tiers: &mut HashMap<Uuid, usize>, path: &mut Vec<Uuid>) -> usize {
+        if let Some(tier) = tiers.get(id) {
+            return *tier;
+        }
+        // A loop from a hand-edited file is cut where it's found rather than followed forever.
+        if path.contains(id) {
+            return 0;
+        }
+        path.push(*id);
+        let tier = self
+            .prerequisites
+            .get(id)
+            .map_or(&[][..], Vec::as_slice)
+            .iter()
+            .filter(|p| self.skills_list.contains_key(&p.skill_id))
+            .map(|p| self.tier_of(&p.skill_id, tiers, path) + 1)
+            .max()
+            .unwrap_or(0);
+        path.pop();
+        tiers.insert(*id, tier);
+        tier
+    }
+}
@@ -0,0 +1,79 @@
+use crate::skill::{level_for_exp, Skill};
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The level a skill has to reach, counting from its last prestige, before it can prestige.
+pub const MAX_LEVEL: u32 = 50;
+/// How much more base EXP each prestige earns from then on, on top of the rest.
+pub const EXP_BONUS_PER_RANK: f64 = 0.1;
+
+//====================================================
+// PrestigeRank
+//====================================================
+/// One time a skill was prestiged: its level started over from there, with an EXP bonus kept
+/// for good. Its records are left as they are.
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct PrestigeRank {
+    pub date: NaiveDate,
+    /// The skill's total EXP at the time, which its level counts from afterwards.
+    pub exp: f64,
+}
+
+/// What base EXP is multiplied by for a record on `date`, given the prestiges in `ranks`. Only
+/// records from after the day of a prestige get its bonus, so the EXP which reached it doesn't
+/// change.
+pub fn exp_factor(ranks: &[PrestigeRank], date: NaiveDate) -> f64 {
+    let earned = ranks.iter().filter(|rank| rank.date < date).count();
+    1.0 + earned as f64 * EXP_BONUS_PER_RANK
+}
+
+impl Skill {
+    /// How many times the skill has been prestiged.
+    pub fn prestige_rank(&self) -> u32 {
+        self.prestige.len() as u32
+    }
+
+    /// The level to show for the skill, counted from its last prestige. [`Skill::level`] is
+    /// still the level across its whole history.
+    pub fn prestige_level(&self) -> u32 {
+        let since = self.prestige.last().map_or(0.0, |rank| rank.exp);
+        level_for_exp(self.total_exp - since)
+    }
+
+    pub fn can_prestige(&self) -> bool {
+        self.prestige_level() >= MAX_LEVEL
+    }
+
+    /// Starts the skill's shown level over on `date`, once it's reached [`MAX_LEVEL`], in return
+    /// for [`EXP_BONUS_PER_RANK`] more base EXP on every session after that day. It needs
+    /// recalculating afterwards if it has records from after `date`. Gives back whether it could
+    /// be prestiged.
+    ///
+    /// ```
+    /// use chrono::{Duration, NaiveDate};
+    /// use sheet_core::Sheet;
+    ///
+    /// let day = NaiveDate::from_ymd(2024, 6, 2);
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// let id = sheet.skill("Piano").log_on(day, Duration::hours(2400)).id();
+    /// let piano = sheet.skills_list.get_mut(&id).unwrap();
+    /// assert!(piano.prestige(day));
+    /// assert_eq!(piano.prestige_level(), 0);
+    ///
+    /// sheet.skill("Piano").log_on(day.succ(), Duration::hours(1));
+    /// let piano = &sheet.skills_list[&id];
+    /// assert!((piano.records[1].base_exp - 55.0 * 1.1).abs() < 1e-9);
+    /// assert_eq!(piano.prestige_rank(), 1);
+    /// ```
+    pub fn prestige(&mut self, date: NaiveDate) -> bool {
+        if !self.can_prestige() {
+            return false;
+        }
+        self.prestige.push(PrestigeRank {
+            date,
+            exp: self.total_exp,
+        });
+        true
+    }
+}
@@ -0,0 +1,64 @@
+use crate::clock;
+use crate::skill::SheetActionRecord;
+use chrono::{Datelike, Duration, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+//====================================================
+// Recurring
+//====================================================
+/// A fixed commitment, like a lesson every Tuesday, which is logged automatically. The records it
+/// makes start out pending, so each one still has to be confirmed as having happened.
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Recurring {
+    pub name: String,
+    pub skill_id: Uuid,
+    pub minutes: u64,
+    /// Which days of the week it happens on, Monday first.
+    pub weekdays: [bool; 7],
+    /// The last day records have been made for. Nothing on or before it is made again, so
+    /// rejecting a record doesn't bring it back.
+    pub logged_until: NaiveDate,
+}
+
+impl Default for Recurring {
+    fn default() -> Self {
+        let yesterday = clock::today() - Duration::days(1);
+        Self {
+            name: "Lesson".to_owned(),
+            skill_id: Uuid::nil(),
+            minutes: 60,
+            weekdays: [false; 7],
+            logged_until: yesterday,
+        }
+    }
+}
+
+impl Recurring {
+    pub fn happens_on(&self, date: NaiveDate) -> bool {
+        self.weekdays[date.weekday().num_days_from_monday() as usize]
+    }
+
+    /// Pending records for every day it happened on since it was last logged, up to and
+    /// including `today`.
+    pub fn catch_up(&mut self, today: NaiveDate) -> Vec<SheetActionRecord> {
+        let mut records = Vec::new();
+        let mut day = self.logged_until.succ();
+        while day <= today {
+            if self.happens_on(day) {
+                records.push(SheetActionRecord {
+                    date: day,
+                    duration: self.minutes,
+                    approved: false,
+                    note: self.name.clone(),
+                    ..Default::default()
+                });
+            }
+            day = day.succ();
+        }
+        self.logged_until = self.logged_until.max(today);
+        records
+    }
+}
@@ -0,0 +1,209 @@
+use crate::format::{DurationStyle, ExpFormat};
+use crate::sheet::Sheet;
+use crate::skill::Skill;
+use crate::stats;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::fmt::Write;
+
+//====================================================
+// Report
+//====================================================
+/// A summary of one skill's progress, as of the day the report was made.
+#[derive(Serialize)]
+pub struct SkillSummary {
+    pub name: String,
+    pub category: String,
+    pub level: u32,
+    pub total_exp: f64,
+    pub total_minutes: u64,
+    pub month_minutes: u64,
+    pub streak_days: u32,
+}
+
+impl SkillSummary {
+    fn new(skill: &Skill, today: NaiveDate) -> Self {
+        let approved = || skill.records.iter().filter(|r| r.approved);
+        Self {
+            name: skill.name.clone(),
+            category: skill.category.clone(),
+            level: skill.level(),
+            total_exp: skill.total_exp,
+            total_minutes: approved().map(|r| r.duration).sum(),
+            month_minutes: approved()
+                .filter(|r| r.date.year() == today.year() && r.date.month() == today.month())
+                .map(|r| r.duration)
+                .sum(),
+            streak_days: stats::current_streak(skill, today),
+        }
+    }
+}
+
+/// A progress report for a whole sheet. Build one with [`Report::new`] and then write it out in
+/// whichever format is needed.
+#[derive(Serialize)]
+pub struct Report {
+    pub player_name: String,
+    pub date: NaiveDate,
+    /// In category order, then name order.
+    pub skills: Vec<SkillSummary>,
+    /// How the sheet writes its durations. The JSON has them in minutes whatever this is.
+    #[serde(skip)]
+    pub duration_style: DurationStyle,
+}
+
+impl Report {
+    pub fn new(sheet: &Sheet, today: NaiveDate) -> Self {
+        let mut skills: Vec<SkillSummary> = sheet
+            .skills_list
+            .values()
+            .map(|skill| SkillSummary::new(skill, today))
+            .collect();
+        skills.sort_by(|a, b| (&a.category, &a.name).cmp(&(&b.category, &b.name)));
+
+        Self {
+            player_name: sheet.player_name.clone(),
+            date: today,
+            skills,
+            duration_style: sheet.duration_style,
+        }
+    }
+
+    pub fn month_minutes(&self) -> u64 {
+        self.skills.iter().map(|s| s.month_minutes).sum()
+    }
+
+    /// Skills with a streak going, longest first.
+    pub fn streaks(&self) -> Vec<&SkillSummary> {
+        let mut streaks: Vec<&SkillSummary> =
+            self.skills.iter().filter(|s| s.streak_days > 1).collect();
+        streaks.sort_by_key(|s| std::cmp::Reverse(s.streak_days));
+        streaks
+    }
+
+    pub fn to_markdown(&self, exp_format: &ExpFormat) -> String {
+        let mut lines = vec![
+            format!("# Progress report: {}", self.player_name),
+            String::new(),
+            format!("_{}_", self.date),
+            String::new(),
+            format!(
+                "**{}** logged so far in {}.",
+                self.duration_style.total(self.month_minutes()),
+                self.date.format("%B %Y")
+            ),
+            String::new(),
+            "## Skills".to_owned(),
+            String::new(),
+            format!(
+                "| Skill | Category | Level | {} | Total | This month |",
+                escape_markdown_cell(&exp_format.name)
+            ),
+            "| --- | --- | ---: | ---: | ---: | ---: |".to_owned(),
+        ];
+        self.skills.iter().for_each(|s| {
+            lines.push(format!(
+                "| {} | {} | {} | {} | {} | {} |",
+                escape_markdown_cell(&s.name),
+                escape_markdown_cell(&s.category),
+                s.level,
+                escape_markdown_cell(&exp_format.number(s.total_exp)),
+                self.duration_style.total(s.total_minutes),
+                self.duration_style.total(s.month_minutes),
+            ));
+        });
+
+        let streaks = self.streaks();
+        if !streaks.is_empty() {
+            lines.push(String::new());
+            lines.push("## Streaks".to_owned());
+            lines.push(String::new());
+            streaks.iter().for_each(|s| {
+                lines.push(format!("- {}: {} days in a row", s.name, s.streak_days));
+            });
+        }
+
+        let mut markdown = lines.join("\n");
+        markdown.push('\n');
+        markdown
+    }
+
+    /// A page to put up on a website. It has the same figures as the Markdown, and no notes.
+    pub fn to_html(&self, exp_format: &ExpFormat) -> String {
+        let title = format!("Progress: {}", escape_html(&self.player_name));
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>{title}</title>\n<style>\n\
+             body {{ font-family: sans-serif; max-width: 48em; margin: 2em auto; \
+             padding: 0 1em; }}\n\
+             table {{ border-collapse: collapse; width: 100%; }}\n\
+             th, td {{ padding: 0.3em 0.6em; border-bottom: 1px solid #ddd; text-align: left; }}\n\
+             td.number, th.number {{ text-align: right; }}\n\
+             </style>\n</head>\n<body>\n<h1>{title}</h1>\n<p><em>{date}</em></p>\n\
+             <p><strong>{hours}</strong> logged so far in {month}.</p>\n",
+            title = title,
+            date = self.date,
+            hours = self.duration_style.total(self.month_minutes()),
+            month = self.date.format("%B %Y"),
+        );
+
+        let _ = writeln!(
+            html,
+            "<h2>Skills</h2>\n<table>\n<tr><th>Skill</th><th>Category</th>\
+             <th class=\"number\">Level</th><th class=\"number\">{}</th>\
+             <th class=\"number\">Total</th><th class=\"number\">This month</th></tr>",
+            escape_html(&exp_format.name)
+        );
+        self.skills.iter().for_each(|s| {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td class=\"number\">{}</td>\
+                 <td class=\"number\">{}</td><td class=\"number\">{}</td>\
+                 <td class=\"number\">{}</td></tr>",
+                escape_html(&s.name),
+                escape_html(&s.category),
+                s.level,
+                escape_html(&exp_format.number(s.total_exp)),
+                self.duration_style.total(s.total_minutes),
+                self.duration_style.total(s.month_minutes),
+            );
+        });
+        html.push_str("</table>\n");
+
+        let streaks = self.streaks();
+        if !streaks.is_empty() {
+            html.push_str("<h2>Streaks</h2>\n<ul>\n");
+            streaks.iter().for_each(|s| {
+                let _ = writeln!(
+                    html,
+                    "<li>{}: {} days in a row</li>",
+                    escape_html(&s.name),
+                    s.streak_days
+                );
+            });
+            html.push_str("</ul>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// The report's figures as JSON, for a page which lays them out itself.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Keeps a pipe in a skill name from splitting its table cell in two.
+fn escape_markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
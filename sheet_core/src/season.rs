@@ -0,0 +1,263 @@
+use crate::sheet::Sheet;
+use crate::skill::{self, Skill};
+use chrono::{Datelike, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+//====================================================
+// Season
+//====================================================
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub enum SeasonLength {
+    #[default]
+    Quarter,
+    Year,
+}
+
+impl SeasonLength {
+    pub const ALL: [SeasonLength; 2] = [SeasonLength::Quarter, SeasonLength::Year];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SeasonLength::Quarter => "Quarterly",
+            SeasonLength::Year => "Yearly",
+        }
+    }
+}
+
+/// One quarter or year, running from `start` up to (but not including) the next season's start.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Season {
+    pub start: NaiveDate,
+    pub length: SeasonLength,
+}
+
+impl Season {
+    pub fn containing(date: NaiveDate, length: SeasonLength) -> Self {
+        let start = match length {
+            SeasonLength::Quarter => NaiveDate::from_ymd(date.year(), date.month0() / 3 * 3 + 1, 1),
+            SeasonLength::Year => NaiveDate::from_ymd(date.year(), 1, 1),
+        };
+        Self { start, length }
+    }
+
+    /// The first day of the following season.
+    pub fn end(&self) -> NaiveDate {
+        match self.length {
+            SeasonLength::Quarter if self.start.month() > 9 => {
+                NaiveDate::from_ymd(self.start.year() + 1, 1, 1)
+            }
+            SeasonLength::Quarter => {
+                NaiveDate::from_ymd(self.start.year(), self.start.month() + 3, 1)
+            }
+            SeasonLength::Year => NaiveDate::from_ymd(self.start.year() + 1, 1, 1),
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        Self {
+            start: self.end(),
+            length: self.length,
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        Season::containing(self.start.pred(), self.length)
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date < self.end()
+    }
+
+    /// e.g. "2022 Q1", or just "2022" for yearly seasons.
+    pub fn label(&self) -> String {
+        match self.length {
+            SeasonLength::Quarter => {
+                format!("{} Q{}", self.start.year(), self.start.month0() / 3 + 1)
+            }
+            SeasonLength::Year => self.start.year().to_string(),
+        }
+    }
+
+    /// The EXP `skill` earned during this season.
+    pub fn exp(&self, skill: &Skill) -> f64 {
+        skill
+            .records
+            .iter()
+            .filter(|r| r.approved && self.contains(r.date))
+            .map(|r| r.base_exp + r.bonus_exp)
+            .sum()
+    }
+
+    /// The level `skill` has reached this season, as if it had started again from nothing.
+    pub fn level(&self, skill: &Skill) -> u32 {
+        skill::level_for_exp(self.exp(skill))
+    }
+}
+
+//====================================================
+// Seasons
+//====================================================
+/// A sheet's seasonal mode settings. While it's on, levels only count the EXP earned in the
+/// current season. Nothing is thrown away at the end of a season, so lifetime totals can still
+/// be looked back on.
+#[derive(Clone, Default, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Seasons {
+    pub enabled: bool,
+    pub length: SeasonLength,
+}
+
+impl Seasons {
+    /// The season `today` falls in, if seasonal mode is on.
+    pub fn current(&self, today: NaiveDate) -> Option<Season> {
+        if self.enabled {
+            Some(Season::containing(today, self.length))
+        } else {
+            None
+        }
+    }
+}
+
+/// What was achieved across the whole sheet in one season.
+pub struct SeasonSummary {
+    pub season: Season,
+    pub exp: f64,
+    pub minutes: u64,
+    pub sessions: usize,
+    /// The skill which earned the most EXP, if anything was practiced.
+    pub top_skill: Option<String>,
+}
+
+impl SeasonSummary {
+    pub fn new(sheet: &Sheet, season: Season) -> Self {
+        let mut skill_exp: Vec<(&Skill, f64)> = sheet
+            .skills_list
+            .values()
+            .map(|skill| (skill, season.exp(skill)))
+            .filter(|(_, exp)| *exp > 0f64)
+            .collect();
+        skill_exp.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+        let records = || {
+            sheet
+                .skills_list
+                .values()
+                .flat_map(|skill| skill.records.iter())
+                .filter(|r| r.approved && season.contains(r.date))
+        };
+        Self {
+            season,
+            exp: skill_exp.iter().map(|(_, exp)| exp).sum(),
+            minutes: records().map(|r| r.duration).sum(),
+            sessions: records().count(),
+            top_skill: skill_exp.first().map(|(skill, _)| skill.name.clone()),
+        }
+    }
+}
+
+/// Every season from the first one anything was recorded in up to the one `today` is in, newest
+/// first.
+pub fn history(sheet: &Sheet, length: SeasonLength, today: NaiveDate) -> Vec<SeasonSummary> {
+    let first = sheet
+        .skills_list
+        .values()
+        .flat_map(|skill| skill.records.iter())
+        .filter(|r| r.approved)
+        .map(|r| r.date)
+        .min()
+        .unwrap_or(today);
+
+    let mut season = Season::containing(first.min(today), length);
+    let mut seasons = Vec::new();
+    while season.start <= today {
+        seasons.push(SeasonSummary::new(sheet, season));
+        season = season.next();
+    }
+    seasons.reverse();
+    seasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skill::SheetActionRecord;
+    use uuid::Uuid;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd(y, m, d)
+    }
+
+    fn skill(name: &str, days: &[NaiveDate]) -> Skill {
+        Skill {
+            name: name.to_owned(),
+            records: days
+                .iter()
+                .map(|date| SheetActionRecord {
+                    date: *date,
+                    duration: 60,
+                    base_exp: 60.0,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn quarters_run_into_the_next_year() {
+        let autumn = Season::containing(date(2023, 11, 15), SeasonLength::Quarter);
+        assert!(autumn.start == date(2023, 10, 1));
+        assert!(autumn.end() == date(2024, 1, 1));
+        assert_eq!(autumn.label(), "2023 Q4");
+        assert_eq!(autumn.next().label(), "2024 Q1");
+        assert!(autumn.next().previous() == autumn);
+        assert!(autumn.contains(date(2023, 12, 31)));
+        assert!(!autumn.contains(date(2024, 1, 1)));
+    }
+
+    #[test]
+    fn years_are_one_season() {
+        let year = Season::containing(date(2024, 6, 1), SeasonLength::Year);
+        assert!(year.start == date(2024, 1, 1));
+        assert!(year.end() == date(2025, 1, 1));
+        assert_eq!(year.label(), "2024");
+    }
+
+    #[test]
+    fn seasons_only_count_their_own_exp() {
+        let mut guitar = skill("Guitar", &[date(2024, 3, 31), date(2024, 4, 1)]);
+        guitar.records[0].bonus_exp = 10.0;
+        let q1 = Season::containing(date(2024, 1, 1), SeasonLength::Quarter);
+        assert!((q1.exp(&guitar) - 70.0).abs() < 1e-9);
+        guitar.records[0].approved = false;
+        assert!(q1.exp(&guitar) == 0.0);
+    }
+
+    #[test]
+    fn seasonal_mode_can_be_off() {
+        let mut seasons = Seasons::default();
+        assert!(seasons.current(date(2024, 6, 1)).is_none());
+        seasons.enabled = true;
+        let current = seasons.current(date(2024, 6, 1)).unwrap();
+        assert_eq!(current.label(), "2024 Q2");
+    }
+
+    #[test]
+    fn history_goes_back_to_the_first_record_newest_first() {
+        let mut sheet = Sheet::new("myself.sht".as_ref());
+        let guitar = skill("Guitar", &[date(2023, 12, 1), date(2024, 5, 1)]);
+        let piano = skill("Piano", &[date(2024, 5, 2), date(2024, 5, 3)]);
+        sheet.skills_list.insert(Uuid::new_v4(), guitar);
+        sheet.skills_list.insert(Uuid::new_v4(), piano);
+
+        let history = history(&sheet, SeasonLength::Quarter, date(2024, 6, 1));
+        let labels: Vec<String> = history.iter().map(|s| s.season.label()).collect();
+        assert_eq!(labels, ["2024 Q2", "2024 Q1", "2023 Q4"]);
+        assert_eq!(history[0].sessions, 3);
+        assert_eq!(history[0].minutes, 180);
+        assert_eq!(history[0].top_skill.as_deref(), Some("Piano"));
+        assert_eq!(history[1].sessions, 0);
+        assert!(history[1].top_skill.is_none());
+    }
+}
@@ -0,0 +1,632 @@
+use crate::achievements::Achievements;
+use crate::attributes::{self, Attribute};
+use crate::bonus::{self, BonusRule, PracticeDays};
+use crate::challenges::Challenges;
+use crate::clock;
+use crate::completion::Completion;
+use crate::daily_notes::DailyNote;
+use crate::editor::NoteEdit;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::encryption::Encryption;
+use crate::file_format::{FileFormat, FormatError};
+use crate::format::DurationStyle;
+use crate::formula::ExpFormulas;
+use crate::goals::Goal;
+use crate::history::{Edit, History};
+use crate::journal::JournalEntry;
+use crate::loot::Loot;
+use crate::migration::{self, MigrationError};
+use crate::planner::PlannedSession;
+use crate::pomodoro::Pomodoro;
+use crate::prerequisites::Prerequisite;
+use crate::recurring::Recurring;
+use crate::season::Seasons;
+use crate::skill::{RecordSort, SheetActionRecord, Skill};
+use crate::snapshot::Snapshot;
+use crate::stats::RecentDays;
+use crate::storage::{self, PendingSave};
+use crate::sync::DiskStamp;
+use crate::units::Unit;
+use crate::zones::Zones;
+use chrono::{DateTime, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+//====================================================
+// LoadError
+//====================================================
+/// Why a sheet couldn't be opened.
+#[derive(Debug)]
+pub enum LoadError {
+    /// There's no file there.
+    NotFound,
+    /// The file is there but couldn't be read.
+    Io(io::Error),
+    /// The file isn't a sheet, or is damaged.
+    Parse(FormatError),
+    Migration(MigrationError),
+    /// The file is encrypted, and has to be opened with its password. See
+    /// [`Encryption::open`](crate::encryption::Encryption::open).
+    NeedsPassword,
+    /// The file is encrypted with a different password, or its encrypted contents are damaged.
+    WrongPassword,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::NotFound => write!(f, "there's no file there"),
+            LoadError::Io(e) => write!(f, "couldn't read the file: {}", e),
+            LoadError::Parse(e) => write!(f, "not a valid sheet: {}", e),
+            LoadError::Migration(e) => e.fmt(f),
+            LoadError::NeedsPassword => write!(f, "it's encrypted with a password"),
+            LoadError::WrongPassword => write!(f, "the password is wrong, or the file is damaged"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+//====================================================
+// Sheet
+//====================================================
+/// A single character sheet, backed by its own file on disk.
+#[derive(Deserialize, JsonSchema, Serialize)]
+#[serde(default)] // if we add new fields, give them default values when deserializing old state
+pub struct Sheet {
+    /// Which version of the save format this was written with. See `migration`.
+    pub format_version: u32,
+    pub player_name: String,
+    pub skills_list: HashMap<Uuid, Skill>,
+    /// When set, newly added records stay pending until they're approved from the pending queue.
+    pub require_approval: bool,
+    /// The bonus pipeline every skill on this sheet is calculated with, in order.
+    pub bonus_rules: Vec<BonusRule>,
+    /// Custom formulas for base EXP, for skills which aren't worked out the built-in way.
+    pub exp_formulas: ExpFormulas,
+    /// How lengths of time are shown on this sheet and its reports.
+    pub duration_style: DurationStyle,
+    /// Named save points to compare progress against, oldest first.
+    pub snapshots: Vec<Snapshot>,
+    pub loot: Loot,
+    pub seasons: Seasons,
+    pub achievements: Achievements,
+    pub challenges: Challenges,
+    pub goals: Vec<Goal>,
+    /// RPG-style stats which the skills feed into.
+    pub attributes: Vec<Attribute>,
+    /// Skills with a session timer running, and when it was started.
+    pub timers: HashMap<Uuid, DateTime<Utc>>,
+    /// When each interruption was logged, for skills with a timer running.
+    pub distractions: HashMap<Uuid, Vec<DateTime<Utc>>>,
+    /// How many pomodoro work intervals were completed each day.
+    pub pomodoros: BTreeMap<NaiveDate, u32>,
+    /// Daily minute zones for skills which don't use the default ones.
+    pub zones: HashMap<Uuid, Zones>,
+    /// How each skill's records are listed, for skills which aren't just in date order.
+    pub record_sorts: HashMap<Uuid, RecordSort>,
+    /// Skills whose records on the same day are merged into one whenever they're recalculated.
+    /// That way a day of practice feeds the streak bonus once, rather than later sessions getting
+    /// a bonus from earlier ones the same day.
+    pub merge_same_day: HashSet<Uuid>,
+    /// Skills whose records count something other than minutes, and what they count.
+    pub units: HashMap<Uuid, Unit>,
+    /// Sessions planned on the weekly planner which haven't been done yet.
+    pub planned: Vec<PlannedSession>,
+    pub recurring: Vec<Recurring>,
+    /// Skills which have been completed, and where they stood when they were. They stay on the
+    /// sheet but aren't practiced any more.
+    pub completed: HashMap<Uuid, Completion>,
+    /// Each skill's journal of the levels it reached, oldest first.
+    pub journals: HashMap<Uuid, Vec<JournalEntry>>,
+    /// The skills each skill needs to reach a level in before it's unlocked.
+    pub prerequisites: HashMap<Uuid, Vec<Prerequisite>>,
+    /// Notes about each day as a whole, such as mood and sleep, rather than any one skill.
+    pub daily_notes: BTreeMap<NaiveDate, DailyNote>,
+
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// What the sheet is saved as when its path's extension doesn't say. Sheets which are opened
+    /// keep the format they were in.
+    #[serde(skip)]
+    pub file_format: FileFormat,
+    /// What the file was like when the sheet was opened or last saved, if there was one, to
+    /// tell whether something else has changed it since. See [`Sheet::changed_on_disk`].
+    #[serde(skip)]
+    pub on_disk: Option<DiskStamp>,
+    /// Whether there are edits which haven't been saved yet.
+    #[serde(skip)]
+    pub dirty: bool,
+    /// When the last edit happened, in `egui` input time.
+    #[serde(skip)]
+    pub last_edit_time: f64,
+    #[serde(skip)]
+    pub history: History,
+    #[serde(skip)]
+    pub pomodoro: Option<Pomodoro>,
+    /// Notes open in an external editor.
+    #[serde(skip)]
+    pub note_edits: Vec<NoteEdit>,
+    /// Each skill's running EXP totals, so an edit only has to recalculate the records from the
+    /// one it touched onward. See [`Skill::calculate_exp_from`].
+    #[serde(skip)]
+    pub exp_totals: HashMap<Uuid, Vec<f64>>,
+    /// Which of the last two weeks each skill was practiced on, as of its last recalculation.
+    #[serde(skip)]
+    pub recent_days: HashMap<Uuid, RecentDays>,
+    /// The password the sheet's file is encrypted with, if it has one.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub encryption: Option<Encryption>,
+}
+
+impl Default for Sheet {
+    fn default() -> Self {
+        Self {
+            format_version: migration::CURRENT_FORMAT_VERSION,
+            player_name: "New Player Name".to_owned(),
+            skills_list: HashMap::<Uuid, Skill>::new(),
+            require_approval: false,
+            bonus_rules: bonus::default_rules(),
+            exp_formulas: ExpFormulas::default(),
+            duration_style: DurationStyle::default(),
+            snapshots: Vec::new(),
+            loot: Loot::default(),
+            seasons: Seasons::default(),
+            achievements: Achievements::default(),
+            challenges: Challenges::default(),
+            goals: Vec::new(),
+            attributes: attributes::default_attributes(),
+            timers: HashMap::new(),
+            distractions: HashMap::new(),
+            pomodoros: BTreeMap::new(),
+            zones: HashMap::new(),
+            record_sorts: HashMap::new(),
+            merge_same_day: HashSet::new(),
+            units: HashMap::new(),
+            planned: Vec::new(),
+            recurring: Vec::new(),
+            completed: HashMap::new(),
+            journals: HashMap::new(),
+            prerequisites: HashMap::new(),
+            daily_notes: BTreeMap::new(),
+            path: PathBuf::new(),
+            file_format: FileFormat::default(),
+            on_disk: None,
+            dirty: false,
+            last_edit_time: 0.0,
+            history: History::default(),
+            pomodoro: None,
+            note_edits: Vec::new(),
+            exp_totals: HashMap::new(),
+            recent_days: HashMap::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            encryption: None,
+        }
+    }
+}
+
+impl Sheet {
+    /// Creates an empty sheet which will be saved to `path`.
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    /// Loads the sheet stored at `path`, upgrading it from an older save format if need be. The
+    /// file can be in any of the [`FileFormat`]s.
+    pub fn open(path: &Path) -> Result<Self, LoadError> {
+        storage::for_path(path).load(path)
+    }
+
+    /// Makes a sheet at `path` out of its fields as they were saved, migrating them first if
+    /// they're from an older version. For [`Storage`](storage::Storage)s to load with.
+    pub fn from_saved(mut value: Value, path: &Path) -> Result<Self, LoadError> {
+        migration::migrate(&mut value).map_err(LoadError::Migration)?;
+        let mut sheet: Sheet =
+            serde_json::from_value(value).map_err(|e| LoadError::Parse(FormatError::Json(e)))?;
+        sheet.recalculate_all();
+        sheet.path = path.to_path_buf();
+        Ok(sheet)
+    }
+
+    /// Opens `path`, a copy of this sheet such as a backup or its own file after it's changed on
+    /// disk, the way this sheet was opened. That way an encrypted sheet's copies are read with
+    /// its password.
+    pub fn open_copy(&self, path: &Path) -> Result<Self, LoadError> {
+        storage::for_sheet(self).load(path)
+    }
+
+    /// Like [`Sheet::open`], but starts an empty sheet at `path` if there's no file there yet.
+    /// A file which is there but can't be read is still an error, so it never gets replaced.
+    pub fn open_or_new(path: &Path) -> Result<Self, LoadError> {
+        match Self::open(path) {
+            Err(LoadError::NotFound) => Ok(Self::new(path)),
+            result => result,
+        }
+    }
+
+    /// Saves the sheet to its path, keeping up to `backup_count` previous versions alongside it.
+    /// Files are written in the format the path's extension asks for, or else `file_format`.
+    pub fn save(&mut self, backup_count: usize) -> io::Result<()> {
+        storage::for_sheet(self).save(self, backup_count)?;
+        self.dirty = false;
+        self.on_disk = DiskStamp::read(&self.path).unwrap_or(None);
+        Ok(())
+    }
+
+    /// Works out what saving the sheet would write, without writing it yet. The write doesn't
+    /// need the sheet, so it can be left to another thread while the sheet goes on being edited;
+    /// [`Sheet::saved_as_of`] marks it saved once it's done.
+    pub fn prepare_save(&self) -> io::Result<PendingSave> {
+        storage::for_sheet(self).prepare_save(self)
+    }
+
+    /// Marks the sheet saved once a [`PendingSave`] prepared when it was last edited at
+    /// `edit_time` has been written, leaving it dirty if it's been edited since.
+    pub fn saved_as_of(&mut self, edit_time: f64, on_disk: Option<DiskStamp>) {
+        if self.last_edit_time == edit_time {
+            self.dirty = false;
+        }
+        self.on_disk = on_disk;
+    }
+
+    /// Where the `n`th most recent backup lives, counting from 1.
+    pub fn backup_path(&self, n: usize) -> PathBuf {
+        backup_path(&self.path, n)
+    }
+
+    /// Every backup of this sheet which currently exists on disk, newest first.
+    pub fn existing_backups(&self) -> Vec<PathBuf> {
+        (1..)
+            .map(|n| self.backup_path(n))
+            .take_while(|path| path.exists())
+            .collect()
+    }
+
+    /// Replaces the contents of this sheet with those of `backup`. The sheet keeps its own path,
+    /// and is left dirty so the restored version can be looked over before it's saved. If the
+    /// backup can't be opened the sheet is left as it was.
+    pub fn restore_backup(&mut self, backup: &Path, time: f64) -> Result<(), LoadError> {
+        let restored = self.open_copy(backup)?;
+        self.replace_with(restored, time);
+        Ok(())
+    }
+
+    /// Replaces the contents of this sheet with `restored`'s, such as an older version of it,
+    /// keeping its own path. It's left dirty, like [`Sheet::restore_backup`].
+    pub fn replace_with(&mut self, mut restored: Sheet, time: f64) {
+        restored.path = self.path.clone();
+        restored.on_disk = self.on_disk;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            restored.encryption = self.encryption.take();
+        }
+        *self = restored;
+        self.mark_dirty(time);
+    }
+
+    pub fn mark_dirty(&mut self, time: f64) {
+        self.dirty = true;
+        self.last_edit_time = time;
+    }
+
+    /// Has the undo history keep track of the skills, if it isn't already, so later changes can
+    /// be recorded. This copies them the first time, e.g. just after the sheet is opened.
+    pub fn track_changes(&mut self) {
+        self.history.track(&self.skills_list);
+    }
+
+    /// Adds whatever has changed since the last change recorded to the undo history.
+    pub fn record_changes(&mut self, time: f64) {
+        self.history.record(&self.skills_list, time);
+    }
+
+    pub fn undo(&mut self, time: f64) {
+        if let Some(edit) = self.history.undo() {
+            edit.apply(&mut self.skills_list, true);
+            self.after_history_change(&edit, time);
+        }
+    }
+
+    pub fn redo(&mut self, time: f64) {
+        if let Some(edit) = self.history.redo() {
+            edit.apply(&mut self.skills_list, false);
+            self.after_history_change(&edit, time);
+        }
+    }
+
+    fn after_history_change(&mut self, edit: &Edit, time: f64) {
+        edit.skill_ids()
+            .iter()
+            .for_each(|skill_id| self.recalculate_skill(skill_id));
+        self.mark_dirty(time);
+    }
+
+    /// The daily minute zones for a skill, which are the defaults unless they've been changed.
+    pub fn zones_for(&self, skill_id: &Uuid) -> Zones {
+        self.zones.get(skill_id).copied().unwrap_or_default()
+    }
+
+    pub fn start_timer(&mut self, skill_id: Uuid, now: DateTime<Utc>) {
+        self.timers.insert(skill_id, now);
+    }
+
+    /// Notes an interruption to the skill's timer session, if it has one running.
+    pub fn log_distraction(&mut self, skill_id: &Uuid, now: DateTime<Utc>) {
+        if self.timers.contains_key(skill_id) {
+            self.distractions.entry(*skill_id).or_default().push(now);
+        }
+    }
+
+    /// Stops the skill's timer and adds the time to today's record, starting one if there isn't
+    /// one yet, along with how many distractions were logged. Returns how many minutes were
+    /// logged.
+    pub fn stop_timer(&mut self, skill_id: &Uuid, now: DateTime<Utc>) -> Option<u64> {
+        let started = self.timers.remove(skill_id)?;
+        let distractions = self.distractions.remove(skill_id).unwrap_or_default();
+        let minutes = ((now - started).num_seconds().max(0) as u64 + 30) / 60;
+        let today = clock::date_at(now);
+        self.log_today(skill_id, minutes, today);
+        let record = self
+            .skills_list
+            .get_mut(skill_id)
+            .and_then(|skill| skill.records.iter_mut().rev().find(|r| r.date == today));
+        if let Some(record) = record.filter(|_| minutes > 0) {
+            record.timed_minutes += minutes;
+            record.distractions += distractions.len() as u32;
+        }
+        Some(minutes)
+    }
+
+    /// Counts a finished pomodoro work interval, logging it to the skill it was for.
+    pub fn complete_pomodoro(&mut self, skill_id: &Uuid, minutes: u64, today: NaiveDate) {
+        self.log_today(skill_id, minutes, today);
+        *self.pomodoros.entry(today).or_insert(0) += 1;
+    }
+
+    /// Turns a planned session into a record. A session planned for a day that's gone by is
+    /// logged on that day, as it's being ticked off late; anything else is logged today.
+    pub fn complete_planned(&mut self, idx: usize, today: NaiveDate) {
+        if idx >= self.planned.len() {
+            return;
+        }
+        let session = self.planned.remove(idx);
+        let date = session.date.filter(|date| *date < today).unwrap_or(today);
+        self.log_today(&session.skill_id, session.minutes, date);
+    }
+
+    /// Adds pending records for any recurring commitments which have come round since they were
+    /// last logged. Returns whether any were added.
+    pub fn log_recurring(&mut self, today: NaiveDate) -> bool {
+        let mut logged = Vec::new();
+        let Sheet {
+            recurring,
+            skills_list,
+            completed,
+            ..
+        } = self;
+        recurring
+            .iter_mut()
+            .filter(|recurring| recurring.logged_until < today)
+            .for_each(|recurring| {
+                let records = recurring.catch_up(today);
+                // Completed skills let their commitments lapse rather than saving them up.
+                if completed.contains_key(&recurring.skill_id) {
+                    return;
+                }
+                if let Some(skill) = skills_list.get_mut(&recurring.skill_id) {
+                    if !records.is_empty() {
+                        skill.records.extend(records);
+                        logged.push(recurring.skill_id);
+                    }
+                }
+            });
+        logged
+            .iter()
+            .for_each(|skill_id| self.recalculate_skill(skill_id));
+        !logged.is_empty()
+    }
+
+    /// Adds `minutes` to the skill's record for `today`, starting one if there isn't one yet.
+    fn log_today(&mut self, skill_id: &Uuid, minutes: u64, today: NaiveDate) {
+        let skill = match self.skills_list.get_mut(skill_id) {
+            Some(skill) if minutes > 0 => skill,
+            _ => return,
+        };
+        match skill.records.iter_mut().rev().find(|r| r.date == today) {
+            Some(record) => record.duration += minutes,
+            None => skill.records.push(SheetActionRecord {
+                date: today,
+                duration: minutes,
+                approved: !self.require_approval,
+                ..Default::default()
+            }),
+        }
+        self.recalculate_skill(skill_id);
+    }
+
+    /// Throws away any unsaved changes by reading the sheet's file again. If it can't be read
+    /// the sheet is left as it was.
+    pub fn reload_from_json(&mut self) -> Result<(), LoadError> {
+        *self = self.open_copy(&self.path)?;
+        Ok(())
+    }
+
+    /// Which days each skill was practiced on, for bonus rules which look across skills.
+    pub fn practice_days(&self) -> PracticeDays {
+        self.skills_list
+            .iter()
+            .map(|(id, skill)| {
+                let days = skill
+                    .records
+                    .iter()
+                    .filter(|r| r.approved)
+                    .map(|r| r.date)
+                    .collect();
+                (*id, days)
+            })
+            .collect()
+    }
+
+    /// Re-sorts and recalculates one skill after it's been edited. If any bonus rule looks across
+    /// skills then the edit could affect the others too, so they all get recalculated.
+    pub fn recalculate_skill(&mut self, skill_id: &Uuid) {
+        if self.bonus_rules.iter().any(|r| r.looks_across_skills()) {
+            self.recalculate_all();
+            return;
+        }
+
+        let practice_days = self.practice_days();
+        if let Some(skill) = self.skills_list.get_mut(skill_id) {
+            skill.sort_actions();
+            if self.merge_same_day.contains(skill_id) {
+                skill.merge_same_day_records(true);
+            }
+            let totals = self.exp_totals.entry(*skill_id).or_default();
+            let formula = self.exp_formulas.for_skill(skill_id);
+            let rules = &self.bonus_rules;
+            skill.calculate_exp_from(skill_id, 0, totals, rules, &practice_days, formula.as_ref());
+            let recent_days = RecentDays::of(skill, clock::today());
+            self.recent_days.insert(*skill_id, recent_days);
+        }
+        self.evaluate_achievements();
+    }
+
+    /// Recalculates one skill after an edit which only touched records dated `since` or later.
+    /// The records before then keep the EXP they already have, which keeps edits quick on skills
+    /// with a long history.
+    pub fn recalculate_skill_since(&mut self, skill_id: &Uuid, since: NaiveDate) {
+        if self.bonus_rules.iter().any(|r| r.looks_across_skills()) {
+            self.recalculate_all();
+            return;
+        }
+
+        let practice_days = self.practice_days();
+        if let Some(skill) = self.skills_list.get_mut(skill_id) {
+            skill.sort_actions();
+            if self.merge_same_day.contains(skill_id) {
+                skill.merge_same_day_records(true);
+            }
+            let from = skill.records.partition_point(|r| r.date < since);
+            let totals = self.exp_totals.entry(*skill_id).or_default();
+            let formula = self.exp_formulas.for_skill(skill_id);
+            let rules = &self.bonus_rules;
+            let formula = formula.as_ref();
+            skill.calculate_exp_from(skill_id, from, totals, rules, &practice_days, formula);
+            let recent_days = RecentDays::of(skill, clock::today());
+            self.recent_days.insert(*skill_id, recent_days);
+        }
+        self.evaluate_achievements();
+    }
+
+    pub fn recalculate_all(&mut self) {
+        let practice_days = self.practice_days();
+        let rules = &self.bonus_rules;
+        let formulas = &self.exp_formulas;
+        let merge_same_day = &self.merge_same_day;
+        let exp_totals = &mut self.exp_totals;
+        exp_totals.retain(|skill_id, _| self.skills_list.contains_key(skill_id));
+        let recent_days = &mut self.recent_days;
+        recent_days.clear();
+        let today = clock::today();
+        self.skills_list.iter_mut().for_each(|(skill_id, skill)| {
+            skill.sort_actions();
+            if merge_same_day.contains(skill_id) {
+                skill.merge_same_day_records(true);
+            }
+            let totals = exp_totals.entry(*skill_id).or_default();
+            let formula = formulas.for_skill(skill_id);
+            skill.calculate_exp_from(skill_id, 0, totals, rules, &practice_days, formula.as_ref());
+            recent_days.insert(*skill_id, RecentDays::of(skill, today));
+        });
+        self.evaluate_achievements();
+    }
+
+    fn evaluate_achievements(&mut self) {
+        let today = clock::today();
+        self.achievements.evaluate(&self.skills_list, today);
+        self.challenges
+            .update(&self.skills_list, &self.units, &self.goals, today);
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.skills_list.values().map(|s| s.pending_count()).sum()
+    }
+
+    /// Whether the sheet has a password to encrypt its file with.
+    pub fn is_encrypted(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.encryption.is_some();
+        #[cfg(target_arch = "wasm32")]
+        false
+    }
+
+    /// Short name used to label this sheet in the sheet switcher.
+    pub fn display_name(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".to_owned())
+    }
+}
+
+/// `myself.sht` + `bak1` gives `myself.sht.bak1`.
+pub(crate) fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+/// Writes `data` to a temporary file next to `path` first and then swaps it into place, so a
+/// crash part way through can never leave a half-written sheet behind.
+pub(crate) fn write_atomically(path: &Path, data: &[u8], backup_count: usize) -> io::Result<()> {
+    let tmp_path = append_extension(path, "tmp");
+    if let Err(e) = write_synced(&tmp_path, data) {
+        // Don't leave half of it lying around, e.g. when the disk is full.
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    if backup_count > 0 && path.exists() {
+        rotate_backups(path, backup_count)?;
+    }
+    fs::rename(&tmp_path, path)
+}
+
+/// Shuffles `.bak1` to `.bak2` and so on, dropping the oldest, then copies the file at `path`
+/// to `.bak1`.
+fn rotate_backups(path: &Path, backup_count: usize) -> io::Result<()> {
+    let oldest = backup_path(path, backup_count);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for n in (1..backup_count).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, n + 1))?;
+        }
+    }
+    fs::copy(path, backup_path(path, 1)).map(|_| ())
+}
+
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    append_extension(path, &format!("bak{}", n))
+}
+
+/// Writes `data` to a new file at `path` and waits for it to reach the disk.
+pub(crate) fn write_synced(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
@@ -0,0 +1,430 @@
+use crate::bonus::{self, BonusRule, PracticeDays, RuleContext};
+use crate::clock;
+use crate::formula::{Formula, FormulaHistory};
+use crate::links::Link;
+use crate::prestige::{self, PrestigeRank};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::ops::Add;
+use uuid::Uuid;
+
+//====================================================
+// Effort
+//====================================================
+/// How hard a session was, used to estimate how much recovery a skill needs.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub enum Effort {
+    Light,
+    #[default]
+    Moderate,
+    Heavy,
+}
+
+impl Effort {
+    pub const ALL: [Effort; 3] = [Effort::Light, Effort::Moderate, Effort::Heavy];
+
+    /// How much an hour at this effort level counts toward fatigue.
+    pub fn load_factor(&self) -> f64 {
+        match self {
+            Effort::Light => 0.5,
+            Effort::Moderate => 1.0,
+            Effort::Heavy => 2.0,
+        }
+    }
+
+    /// Effort as a number from 1 to 3, for averaging.
+    pub fn score(&self) -> f64 {
+        match self {
+            Effort::Light => 1.0,
+            Effort::Moderate => 2.0,
+            Effort::Heavy => 3.0,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Effort::Light => "Light",
+            Effort::Moderate => "Moderate",
+            Effort::Heavy => "Heavy",
+        }
+    }
+}
+
+//====================================================
+// SheetActionRecord
+//====================================================
+/// EXP set by hand for a record, in place of what the bonus rules work out.
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct ExpOverride {
+    pub exp: f64,
+    /// Why it was set. One has to be given, so the change can be looked back on later.
+    pub reason: String,
+}
+
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct SheetActionRecord {
+    pub date: NaiveDate,
+    pub duration: u64,
+    pub base_exp: f64,
+    pub bonus_exp: f64,
+    /// Records on sheets which require approval don't count until someone signs off on them.
+    pub approved: bool,
+    pub effort: Effort,
+    /// Anything worth remembering about the session.
+    pub note: String,
+    /// EXP set by hand, which recalculating leaves alone.
+    pub exp_override: Option<ExpOverride>,
+    /// Minutes logged with the session timer, which is when distractions can be logged.
+    pub timed_minutes: u64,
+    /// How many times timer sessions were interrupted.
+    pub distractions: u32,
+    /// What the session produced or worked on, such as a commit or a document.
+    pub links: Vec<Link>,
+    /// How much harder or easier the session was than usual, from 0.5 to 2.0, which its base
+    /// EXP is scaled by. See [`SheetActionRecord::intensity_factor`].
+    pub intensity: f64,
+}
+
+impl Default for SheetActionRecord {
+    fn default() -> Self {
+        Self {
+            date: clock::today(),
+            duration: 0,
+            base_exp: 0.0,
+            bonus_exp: 0.0,
+            approved: true,
+            effort: Effort::default(),
+            note: String::new(),
+            exp_override: None,
+            timed_minutes: 0,
+            distractions: 0,
+            links: Vec::new(),
+            intensity: 1.0,
+        }
+    }
+}
+
+impl SheetActionRecord {
+    pub const MIN_INTENSITY: f64 = 0.5;
+    pub const MAX_INTENSITY: f64 = 2.0;
+
+    /// What the record's base EXP is multiplied by: its intensity, kept within
+    /// [`SheetActionRecord::MIN_INTENSITY`] and [`SheetActionRecord::MAX_INTENSITY`], or 1 if it
+    /// isn't a number.
+    ///
+    /// ```
+    /// use sheet_core::skill::SheetActionRecord;
+    ///
+    /// let record = SheetActionRecord { intensity: 5.0, ..Default::default() };
+    /// assert_eq!(record.intensity_factor(), 2.0);
+    /// let record = SheetActionRecord { intensity: f64::NAN, ..Default::default() };
+    /// assert_eq!(record.intensity_factor(), 1.0);
+    /// ```
+    pub fn intensity_factor(&self) -> f64 {
+        if self.intensity.is_finite() {
+            self.intensity
+                .clamp(Self::MIN_INTENSITY, Self::MAX_INTENSITY)
+        } else {
+            1.0
+        }
+    }
+}
+
+//====================================================
+// RecordSort
+//====================================================
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub enum SortColumn {
+    #[default]
+    Date,
+    Duration,
+    Exp,
+}
+
+/// How a skill's records are listed. This only changes the order they're shown in; the records
+/// themselves are always kept in date order, which the EXP calculation needs.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct RecordSort {
+    pub column: SortColumn,
+    pub descending: bool,
+}
+
+impl RecordSort {
+    /// Sorts by `column`, or flips the direction if it's already sorted by it.
+    pub fn click(&mut self, column: SortColumn) {
+        if self.column == column {
+            self.descending = !self.descending;
+        } else {
+            self.column = column;
+            self.descending = false;
+        }
+    }
+
+    /// The order to show `records` in, as indices into it. Ties stay in date order.
+    pub fn order(&self, records: &[SheetActionRecord]) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..records.len()).collect();
+        order.sort_by(|a, b| {
+            let (a, b) = (&records[*a], &records[*b]);
+            let ordering = match self.column {
+                SortColumn::Date => a.date.cmp(&b.date),
+                SortColumn::Duration => a.duration.cmp(&b.duration),
+                SortColumn::Exp => (a.base_exp + a.bonus_exp)
+                    .partial_cmp(&(b.base_exp + b.bonus_exp))
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            };
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        order
+    }
+}
+
+//====================================================
+// Skill
+//====================================================
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct Skill {
+    pub name: String,
+    pub records: Vec<SheetActionRecord>,
+    /// Name of the group this skill is listed under. Empty means uncategorized.
+    #[serde(default)]
+    pub category: String,
+    /// When the skill was added. Sheets from before this was tracked get the date of the skill's
+    /// earliest record instead.
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    /// Each time its level was started over, oldest first. See [`Skill::prestige`].
+    #[serde(default)]
+    pub prestige: Vec<PrestigeRank>,
+
+    #[serde(skip)]
+    pub potential_bonus: f64,
+    #[serde(skip)]
+    pub total_exp: f64,
+}
+
+impl Default for Skill {
+    fn default() -> Self {
+        Self {
+            name: "new skill".to_string(),
+            records: Vec::new(),
+            category: String::new(),
+            created_at: Utc::now(),
+            prestige: Vec::new(),
+            potential_bonus: 0f64,
+            total_exp: 0f64,
+        }
+    }
+}
+
+/// The level a skill with `total_exp` has reached. Each level costs 100 EXP more than the one
+/// before it, so level 1 takes 100 EXP, level 2 another 200, and so on.
+pub fn level_for_exp(total_exp: f64) -> u32 {
+    const EXP_PER_LEVEL_STEP: f64 = 100.0;
+
+    // Solves total_exp = step * n * (n + 1) / 2 for n.
+    let steps = total_exp.max(0f64) / EXP_PER_LEVEL_STEP;
+    (((1f64 + 8f64 * steps).sqrt() - 1f64) / 2f64).floor() as u32
+}
+
+fn merge_record(into: &mut SheetActionRecord, record: SheetActionRecord) {
+    if into.exp_override.is_some() || record.exp_override.is_some() {
+        let exp = |r: &SheetActionRecord| {
+            r.exp_override
+                .as_ref()
+                .map_or(r.base_exp + r.bonus_exp, |o| o.exp)
+        };
+        let reasons: Vec<String> = [&into.exp_override, &record.exp_override]
+            .iter()
+            .filter_map(|o| o.as_ref().map(|o| o.reason.clone()))
+            .collect();
+        into.exp_override = Some(ExpOverride {
+            exp: exp(into) + exp(&record),
+            reason: reasons.join("; "),
+        });
+    }
+    // Weighted by how long each part went on, so the merged record earns the same base EXP.
+    let total = into.duration + record.duration;
+    if total > 0 {
+        into.intensity = (into.intensity_factor() * into.duration as f64
+            + record.intensity_factor() * record.duration as f64)
+            / total as f64;
+    }
+    into.duration += record.duration;
+    into.timed_minutes += record.timed_minutes;
+    into.distractions += record.distractions;
+    into.links.extend(record.links);
+    into.approved = into.approved && record.approved;
+    if record.effort.load_factor() > into.effort.load_factor() {
+        into.effort = record.effort;
+    }
+    if into.note.is_empty() {
+        into.note = record.note;
+    } else if !record.note.is_empty() {
+        into.note = format!("{}; {}", into.note, record.note);
+    }
+}
+
+/// The very start of `date`, for treating a day as a timestamp.
+pub fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    clock::start_of(date)
+}
+
+impl Skill {
+    pub fn level(&self) -> u32 {
+        level_for_exp(self.total_exp)
+    }
+
+    pub fn sort_actions(&mut self) {
+        self.records.sort_by_key(|a| a.date);
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.records.iter().filter(|r| !r.approved).count()
+    }
+
+    /// Folds each day's records into the first one of that day, adding up their durations and
+    /// joining their notes. A merged record keeps the hardest effort, and only counts as approved
+    /// if everything merged into it was. With `keep_pending_apart`, pending records are only
+    /// merged with each other, so they can still be approved on their own.
+    ///
+    /// If any of the records had its EXP set by hand, the merged one gets the EXP they add up to,
+    /// set by hand. Returns how many records were merged away.
+    pub fn merge_same_day_records(&mut self, keep_pending_apart: bool) -> usize {
+        self.sort_actions();
+        let before = self.records.len();
+        let mut merged: Vec<SheetActionRecord> = Vec::with_capacity(before);
+        self.records.drain(..).for_each(|record| {
+            let into = merged
+                .iter_mut()
+                .rev()
+                .take_while(|r| r.date == record.date)
+                .find(|r| !keep_pending_apart || r.approved == record.approved);
+            match into {
+                Some(into) => merge_record(into, record),
+                None => merged.push(record),
+            }
+        });
+        self.records = merged;
+        before - self.records.len()
+    }
+
+    /// Works out the EXP for every record by running each one through the bonus `rules`, with
+    /// the built-in formula for base EXP.
+    pub fn calculate_exp(
+        &mut self,
+        skill_id: &Uuid,
+        rules: &[BonusRule],
+        practice_days: &PracticeDays,
+    ) {
+        // Streak bonuses depend on seeing the records in date order, however they're being shown.
+        self.sort_actions();
+        self.calculate_exp_from(skill_id, 0, &mut Vec::new(), rules, practice_days, None);
+    }
+
+    /// Works out the EXP for the records from index `from` on, which must already be in date
+    /// order. Rules only look back at earlier records, so the ones before `from` are left alone.
+    ///
+    /// `running_totals[i]` is the EXP earned by the records before `i`. The totals up to `from` are
+    /// reused and the rest are rebuilt; if there aren't enough of them, everything is worked out
+    /// from the start.
+    ///
+    /// Base EXP comes from `formula` where it gives a number of EXP, and from the built-in 55 an
+    /// hour otherwise, scaled by the record's intensity and the skill's prestige bonus. Streak
+    /// bonuses are worked out from base EXP, so those count toward them too. EXP set by hand is
+    /// left as it is.
+    pub fn calculate_exp_from(
+        &mut self,
+        skill_id: &Uuid,
+        from: usize,
+        running_totals: &mut Vec<f64>,
+        rules: &[BonusRule],
+        practice_days: &PracticeDays,
+        formula: Option<&Formula>,
+    ) {
+        let exp_per_hour: f64 = 55.0;
+
+        let from = if from < running_totals.len() && from <= self.records.len() {
+            from
+        } else {
+            running_totals.clear();
+            running_totals.push(0f64);
+            0
+        };
+        running_totals.truncate(from + 1);
+        let mut exp_total = running_totals[from];
+        // Built up once, rather than searched for every record, as it's needed by each of them.
+        let mut formula_history = formula.map(|_| FormulaHistory::of(&self.records[..from]));
+        for idx in from..self.records.len() {
+            let (history, rest) = self.records.split_at_mut(idx);
+            let r = &mut rest[0];
+            if !r.approved {
+                // Pending records are kept out of both the totals and the streak window.
+                r.base_exp = 0f64;
+                r.bonus_exp = 0f64;
+                running_totals.push(exp_total);
+                continue;
+            }
+
+            if let Some(exp_override) = &r.exp_override {
+                // All of it counts as base EXP, so no rule adds anything on top.
+                r.base_exp = exp_override.exp;
+                r.bonus_exp = 0f64;
+            } else {
+                let built_in = (r.duration as f64 / 60f64) * exp_per_hour;
+                r.base_exp = formula
+                    .zip(formula_history.as_ref())
+                    .and_then(|(formula, formula_history)| {
+                        let inputs = formula_history.inputs_for(r, built_in, exp_total);
+                        formula.base_exp(&inputs)
+                    })
+                    .unwrap_or(built_in)
+                    * r.intensity_factor()
+                    * prestige::exp_factor(&self.prestige, r.date);
+                let ctx = RuleContext {
+                    skill_id,
+                    history,
+                    practice_days,
+                };
+                r.bonus_exp = bonus::apply_rules(rules, &ctx, r);
+            }
+
+            if let Some(formula_history) = &mut formula_history {
+                formula_history.add(r);
+            }
+            exp_total += r.base_exp + r.bonus_exp;
+            running_totals.push(exp_total);
+        }
+        self.total_exp = exp_total;
+
+        // Try to calculate how much bonus to expect if you do the thing today (or tomorrow if
+        // you already did it today)
+        let today = clock::today();
+        let last = self.records.iter().rev().find(|r| r.approved);
+        let next_day = if let Some(last) = last {
+            if today.signed_duration_since(last.date).is_zero() {
+                today.add(Duration::days(1))
+            } else {
+                today
+            }
+        } else {
+            today
+        };
+        let next_record = SheetActionRecord {
+            date: next_day,
+            ..Default::default()
+        };
+        let ctx = RuleContext {
+            skill_id,
+            history: &self.records,
+            practice_days,
+        };
+        self.potential_bonus = bonus::apply_rules(rules, &ctx, &next_record);
+    }
+}
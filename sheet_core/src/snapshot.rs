@@ -0,0 +1,129 @@
+use crate::sheet::Sheet;
+use crate::skill::Skill;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+//====================================================
+// Snapshot
+//====================================================
+/// One skill's totals at the moment a snapshot was taken.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct SkillTotals {
+    pub name: String,
+    pub level: u32,
+    pub total_exp: f64,
+    pub total_minutes: u64,
+    pub sessions: usize,
+}
+
+impl Default for SkillTotals {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            level: 0,
+            total_exp: 0f64,
+            total_minutes: 0,
+            sessions: 0,
+        }
+    }
+}
+
+impl SkillTotals {
+    pub fn new(skill: &Skill) -> Self {
+        let approved = || skill.records.iter().filter(|r| r.approved);
+        Self {
+            name: skill.name.clone(),
+            level: skill.level(),
+            total_exp: skill.total_exp,
+            total_minutes: approved().map(|r| r.duration).sum(),
+            sessions: approved().count(),
+        }
+    }
+}
+
+/// A named "save point" of every skill's totals, to compare against later. Only the totals are
+/// kept, not the records themselves, so snapshots stay small however long the history gets.
+#[derive(Clone, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Snapshot {
+    pub name: String,
+    pub taken_at: DateTime<Utc>,
+    pub skills: HashMap<Uuid, SkillTotals>,
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            taken_at: Utc::now(),
+            skills: HashMap::new(),
+        }
+    }
+}
+
+impl Snapshot {
+    pub fn take(name: &str, sheet: &Sheet) -> Self {
+        Self {
+            name: name.to_owned(),
+            taken_at: Utc::now(),
+            skills: sheet
+                .skills_list
+                .iter()
+                .map(|(id, skill)| (*id, SkillTotals::new(skill)))
+                .collect(),
+        }
+    }
+}
+
+/// How one skill has moved on since a snapshot.
+pub struct Comparison {
+    /// The skill's current name, or its name in the snapshot if it's since been deleted.
+    pub name: String,
+    /// `None` if the skill didn't exist yet when the snapshot was taken.
+    pub then: Option<SkillTotals>,
+    /// `None` if the skill has been deleted since.
+    pub now: Option<SkillTotals>,
+}
+
+impl Comparison {
+    pub fn exp_gained(&self) -> f64 {
+        self.now.as_ref().map_or(0f64, |t| t.total_exp)
+            - self.then.as_ref().map_or(0f64, |t| t.total_exp)
+    }
+
+    pub fn minutes_gained(&self) -> i64 {
+        self.now.as_ref().map_or(0, |t| t.total_minutes as i64)
+            - self.then.as_ref().map_or(0, |t| t.total_minutes as i64)
+    }
+}
+
+/// Lines up every skill in either the snapshot or the sheet, in name order.
+pub fn compare(sheet: &Sheet, snapshot: &Snapshot) -> Vec<Comparison> {
+    let mut ids: Vec<&Uuid> = sheet.skills_list.keys().collect();
+    ids.extend(
+        snapshot
+            .skills
+            .keys()
+            .filter(|id| !sheet.skills_list.contains_key(id)),
+    );
+
+    let mut comparisons: Vec<Comparison> = ids
+        .into_iter()
+        .map(|id| {
+            let then = snapshot.skills.get(id).cloned();
+            let now = sheet.skills_list.get(id).map(SkillTotals::new);
+            let name = now
+                .as_ref()
+                .or(then.as_ref())
+                .map(|t| t.name.clone())
+                .unwrap_or_default();
+            Comparison { name, then, now }
+        })
+        .collect();
+    comparisons.sort_by(|a, b| a.name.cmp(&b.name));
+    comparisons
+}
@@ -0,0 +1,580 @@
+use crate::clock;
+use crate::skill::{Effort, SheetActionRecord, Skill};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use uuid::Uuid;
+
+/// The Monday of the week containing `date`.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Total minutes logged in each week, keyed by the Monday the week starts on.
+pub fn minutes_per_week<'a>(
+    skills: impl IntoIterator<Item = &'a Skill>,
+) -> BTreeMap<NaiveDate, u64> {
+    let mut weeks = BTreeMap::new();
+    skills.into_iter().for_each(|skill| {
+        skill.records.iter().for_each(|r| {
+            *weeks.entry(week_start(r.date)).or_insert(0) += r.duration;
+        });
+    });
+    weeks
+}
+
+/// How many days in a row the skill has been practiced, counting back from `today`. A streak
+/// which ended yesterday still counts, since there's time left to keep it going today.
+pub fn current_streak(skill: &Skill, today: NaiveDate) -> u32 {
+    let days: BTreeSet<NaiveDate> = skill
+        .records
+        .iter()
+        .filter(|r| r.approved)
+        .map(|r| r.date)
+        .collect();
+
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    let mut streak = 0;
+    while days.contains(&day) {
+        streak += 1;
+        day -= Duration::days(1);
+    }
+    streak
+}
+
+/// The most days in a row the skill has ever been practiced.
+pub fn best_streak(skill: &Skill) -> u32 {
+    let days: BTreeSet<NaiveDate> = skill
+        .records
+        .iter()
+        .filter(|r| r.approved)
+        .map(|r| r.date)
+        .collect();
+
+    let mut best = 0;
+    let mut streak = 0;
+    let mut previous: Option<NaiveDate> = None;
+    days.iter().for_each(|day| {
+        streak = match previous {
+            Some(previous) if *day - previous == Duration::days(1) => streak + 1,
+            _ => 1,
+        };
+        best = best.max(streak);
+        previous = Some(*day);
+    });
+    best
+}
+
+/// Total approved minutes logged on each day.
+pub fn minutes_per_day<'a>(
+    skills: impl IntoIterator<Item = &'a Skill>,
+) -> BTreeMap<NaiveDate, u64> {
+    let mut days = BTreeMap::new();
+    skills.into_iter().for_each(|skill| {
+        skill.records.iter().filter(|r| r.approved).for_each(|r| {
+            *days.entry(r.date).or_insert(0) += r.duration;
+        });
+    });
+    days
+}
+
+//====================================================
+// ExpHistory
+//====================================================
+/// EXP earned on one day.
+#[derive(Clone)]
+pub struct ExpDay {
+    pub date: NaiveDate,
+    pub exp: f64,
+    /// Everything earned up to and including this day.
+    pub cumulative_exp: f64,
+}
+
+/// Day-by-day EXP for one skill, oldest first, for charting. Only days with approved records are
+/// included.
+pub fn exp_history(skill: &Skill) -> Vec<ExpDay> {
+    let mut by_day: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    skill.records.iter().filter(|r| r.approved).for_each(|r| {
+        *by_day.entry(r.date).or_insert(0f64) += r.base_exp + r.bonus_exp;
+    });
+    history_of(&by_day)
+}
+
+fn history_of(by_day: &BTreeMap<NaiveDate, f64>) -> Vec<ExpDay> {
+    let mut running_total = 0f64;
+    by_day
+        .iter()
+        .map(|(date, exp)| {
+            running_total += exp;
+            ExpDay {
+                date: *date,
+                exp: *exp,
+                cumulative_exp: running_total,
+            }
+        })
+        .collect()
+}
+
+//====================================================
+// Lifetime
+//====================================================
+/// How a skill has gone over its whole life so far.
+pub struct Lifetime {
+    pub age_days: i64,
+    pub total_minutes: u64,
+    /// Average hours per week since the skill was created.
+    pub weekly_hours: f64,
+    /// Running total of hours at the end of each week, as (weeks since creation, hours).
+    pub cumulative_hours: Vec<(f64, f64)>,
+}
+
+pub fn lifetime(skill: &Skill, today: NaiveDate) -> Lifetime {
+    let created = clock::date_at(skill.created_at);
+    let age_days = today.signed_duration_since(created).num_days().max(0);
+    let approved = || skill.records.iter().filter(|r| r.approved);
+    let total_minutes: u64 = approved().map(|r| r.duration).sum();
+    // A skill less than a week old has still had one week's worth of chances.
+    let age_weeks = (age_days as f64 / 7f64).max(1f64);
+
+    let mut weekly_minutes: BTreeMap<i64, u64> = BTreeMap::new();
+    approved().for_each(|r| {
+        let week = r.date.signed_duration_since(created).num_days().max(0) / 7;
+        *weekly_minutes.entry(week).or_insert(0) += r.duration;
+    });
+    let mut running_total = 0u64;
+    let cumulative_hours = (0..=age_days / 7)
+        .map(|week| {
+            running_total += weekly_minutes.get(&week).copied().unwrap_or(0);
+            (week as f64, running_total as f64 / 60f64)
+        })
+        .collect();
+
+    Lifetime {
+        age_days,
+        total_minutes,
+        weekly_hours: total_minutes as f64 / 60f64 / age_weeks,
+        cumulative_hours,
+    }
+}
+
+//====================================================
+// Quality
+//====================================================
+/// How much a skill was practiced in one week, and how hard.
+#[derive(Clone)]
+pub struct WeekQuality {
+    /// The Monday the week starts on.
+    pub week: NaiveDate,
+    pub hours: f64,
+    /// Effort score from 1 (light) to 3 (heavy), weighted by how long each session was.
+    pub average_effort: f64,
+}
+
+/// Every week the skill was practiced in, oldest first. Only approved records with a duration
+/// are counted.
+pub fn weekly_quality(skill: &Skill) -> Vec<WeekQuality> {
+    let mut weeks: BTreeMap<NaiveDate, (u64, f64)> = BTreeMap::new();
+    skill
+        .records
+        .iter()
+        .filter(|r| r.approved && r.duration > 0)
+        .for_each(|r| {
+            let week = weeks.entry(week_start(r.date)).or_insert((0, 0f64));
+            week.0 += r.duration;
+            week.1 += r.duration as f64 * r.effort.score();
+        });
+    quality_of(&weeks)
+}
+
+fn quality_of(weeks: &BTreeMap<NaiveDate, (u64, f64)>) -> Vec<WeekQuality> {
+    weeks
+        .iter()
+        .map(|(week, (minutes, weighted_effort))| WeekQuality {
+            week: *week,
+            hours: *minutes as f64 / 60f64,
+            average_effort: weighted_effort / *minutes as f64,
+        })
+        .collect()
+}
+
+/// How the last four weeks compare with the four before them.
+pub struct QualityTrend {
+    pub hours_change: f64,
+    pub effort_change: f64,
+}
+
+impl QualityTrend {
+    /// More time is going in, but the sessions are getting lighter.
+    pub fn is_slipping(&self) -> bool {
+        self.hours_change > 0f64 && self.effort_change < 0f64
+    }
+}
+
+/// Returns `None` unless the skill was practiced in both four week spans.
+pub fn quality_trend(skill: &Skill, today: NaiveDate) -> Option<QualityTrend> {
+    const SPAN_WEEKS: i64 = 4;
+
+    let this_week = week_start(today);
+    let recent_start = this_week - Duration::weeks(SPAN_WEEKS - 1);
+    let earlier_start = recent_start - Duration::weeks(SPAN_WEEKS);
+    let weeks = weekly_quality(skill);
+    let span = |from: NaiveDate, to: NaiveDate| {
+        let weeks: Vec<&WeekQuality> = weeks
+            .iter()
+            .filter(|w| from <= w.week && w.week < to)
+            .collect();
+        let hours: f64 = weeks.iter().map(|w| w.hours).sum();
+        let effort = weeks
+            .iter()
+            .map(|w| w.average_effort * w.hours)
+            .sum::<f64>()
+            / hours;
+        (hours > 0f64).then_some((hours, effort))
+    };
+
+    let (recent_hours, recent_effort) = span(recent_start, this_week + Duration::weeks(1))?;
+    let (earlier_hours, earlier_effort) = span(earlier_start, recent_start)?;
+    Some(QualityTrend {
+        hours_change: recent_hours - earlier_hours,
+        effort_change: recent_effort - earlier_effort,
+    })
+}
+
+//====================================================
+// Recent days
+//====================================================
+const RECENT_DAYS: usize = 14;
+
+/// Which of the last two weeks a skill was practiced on, for showing the state of its streak at a
+/// glance.
+#[derive(Clone, Copy)]
+pub struct RecentDays {
+    /// The last day covered, normally today.
+    pub until: NaiveDate,
+    /// Oldest first, ending with `until`.
+    pub logged: [bool; RECENT_DAYS],
+}
+
+impl RecentDays {
+    pub fn of(skill: &Skill, until: NaiveDate) -> Self {
+        let mut logged = [false; RECENT_DAYS];
+        // Records are kept in date order, so only the last few need looking at.
+        skill
+            .records
+            .iter()
+            .rev()
+            .skip_while(|r| r.date > until)
+            .map(|r| (r, until.signed_duration_since(r.date).num_days() as usize))
+            .take_while(|(_, days_ago)| *days_ago < RECENT_DAYS)
+            .filter(|(r, _)| r.approved && r.duration > 0)
+            .for_each(|(_, days_ago)| logged[RECENT_DAYS - 1 - days_ago] = true);
+        Self { until, logged }
+    }
+}
+
+//====================================================
+// Focus
+//====================================================
+/// How often timer sessions were interrupted in one week.
+#[derive(Clone)]
+pub struct WeekFocus {
+    /// The Monday the week starts on.
+    pub week: NaiveDate,
+    pub timed_hours: f64,
+    pub distractions: u32,
+}
+
+impl WeekFocus {
+    pub fn distractions_per_hour(&self) -> f64 {
+        self.distractions as f64 / self.timed_hours
+    }
+}
+
+/// Every week with time logged by the session timer, oldest first. Only approved records are
+/// counted.
+pub fn weekly_focus(skill: &Skill) -> Vec<WeekFocus> {
+    let mut weeks: BTreeMap<NaiveDate, (u64, u32)> = BTreeMap::new();
+    skill
+        .records
+        .iter()
+        .filter(|r| r.approved && r.timed_minutes > 0)
+        .for_each(|r| {
+            let week = weeks.entry(week_start(r.date)).or_insert((0, 0));
+            week.0 += r.timed_minutes;
+            week.1 += r.distractions;
+        });
+    focus_of(&weeks)
+}
+
+fn focus_of(weeks: &BTreeMap<NaiveDate, (u64, u32)>) -> Vec<WeekFocus> {
+    weeks
+        .iter()
+        .map(|(week, (minutes, distractions))| WeekFocus {
+            week: *week,
+            timed_hours: *minutes as f64 / 60f64,
+            distractions: *distractions,
+        })
+        .collect()
+}
+
+//====================================================
+// Charts
+//====================================================
+/// Everything the charts page plots for a skill.
+#[derive(Clone, Default)]
+pub struct ChartStats {
+    pub history: Vec<ExpDay>,
+    pub weeks: Vec<WeekQuality>,
+    pub focus: Vec<WeekFocus>,
+}
+
+/// Works out a skill's [`ChartStats`] a slice of records at a time, oldest first, so a long
+/// history can be charted as it's gone through instead of all at once. It counts the same
+/// records as [`exp_history`], [`weekly_quality`] and [`weekly_focus`].
+///
+/// ```
+/// use chrono::{Duration, NaiveDate};
+/// use sheet_core::stats::{self, ChartJob};
+/// use sheet_core::Sheet;
+///
+/// let day = NaiveDate::from_ymd(2024, 6, 3);
+/// let mut sheet = Sheet::new("myself.sht".as_ref());
+/// sheet.skill("Guitar").log_on(day.succ(), Duration::minutes(30));
+/// let id = sheet.skill("Guitar").log_on(day, Duration::minutes(45)).id();
+///
+/// let mut job = ChartJob::new(&sheet.skills_list[&id]);
+/// assert!(!job.step(1));
+/// assert_eq!(job.stats().history[0].date, day);
+/// assert!(job.step(100));
+/// let all = stats::exp_history(&sheet.skills_list[&id]);
+/// assert_eq!(job.stats().history.len(), all.len());
+/// assert_eq!(job.stats().weeks[0].hours, 1.25);
+/// ```
+pub struct ChartJob {
+    records: Vec<SheetActionRecord>,
+    next: usize,
+    days: BTreeMap<NaiveDate, f64>,
+    weeks: BTreeMap<NaiveDate, (u64, f64)>,
+    focus: BTreeMap<NaiveDate, (u64, u32)>,
+}
+
+impl ChartJob {
+    pub fn new(skill: &Skill) -> Self {
+        let mut records: Vec<SheetActionRecord> = skill
+            .records
+            .iter()
+            .filter(|r| r.approved)
+            .cloned()
+            .collect();
+        records.sort_by_key(|r| r.date);
+        Self {
+            records,
+            next: 0,
+            days: BTreeMap::new(),
+            weeks: BTreeMap::new(),
+            focus: BTreeMap::new(),
+        }
+    }
+
+    /// Counts up to `count` more records. Returns whether every record has now been counted.
+    pub fn step(&mut self, count: usize) -> bool {
+        let end = self.next.saturating_add(count).min(self.records.len());
+        self.records[self.next..end].iter().for_each(|r| {
+            *self.days.entry(r.date).or_insert(0f64) += r.base_exp + r.bonus_exp;
+            let week = week_start(r.date);
+            if r.duration > 0 {
+                let quality = self.weeks.entry(week).or_insert((0, 0f64));
+                quality.0 += r.duration;
+                quality.1 += r.duration as f64 * r.effort.score();
+            }
+            if r.timed_minutes > 0 {
+                let focus = self.focus.entry(week).or_insert((0, 0));
+                focus.0 += r.timed_minutes;
+                focus.1 += r.distractions;
+            }
+        });
+        self.next = end;
+        self.is_finished()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.records.len()
+    }
+
+    /// How many records have been counted so far.
+    pub fn processed(&self) -> usize {
+        self.next
+    }
+
+    pub fn total(&self) -> usize {
+        self.records.len()
+    }
+
+    /// The charts as far as the records counted so far go.
+    pub fn stats(&self) -> ChartStats {
+        ChartStats {
+            history: history_of(&self.days),
+            weeks: quality_of(&self.weeks),
+            focus: focus_of(&self.focus),
+        }
+    }
+}
+
+//====================================================
+// Recovery
+//====================================================
+/// How worn out a skill's recent sessions have left you, and what to do about it today.
+pub struct Recovery {
+    /// Effort-weighted daily load over the last week relative to the last four weeks. Around 1.0
+    /// is business as usual; well above it means you've been pushing harder than you're used to.
+    pub fatigue: f64,
+    pub suggested_effort: Effort,
+    pub reason: &'static str,
+}
+
+/// Effort-weighted hours logged on a skill over the `days` days up to and including `today`.
+fn load_over_days(skill: &Skill, today: NaiveDate, days: i64) -> f64 {
+    skill
+        .records
+        .iter()
+        .filter(|r| {
+            let days_ago = today.signed_duration_since(r.date).num_days();
+            (0..days).contains(&days_ago)
+        })
+        .map(|r| (r.duration as f64 / 60f64) * r.effort.load_factor())
+        .sum()
+}
+
+/// Compares the last week's load against the last four weeks' to suggest how hard to go today.
+/// Returns `None` if there's nothing recent enough to go on.
+pub fn recovery(skill: &Skill, today: NaiveDate) -> Option<Recovery> {
+    const HIGH_FATIGUE: f64 = 1.3;
+    const LOW_FATIGUE: f64 = 0.8;
+
+    let chronic = load_over_days(skill, today, 28) / 28f64;
+    if chronic <= 0f64 {
+        return None;
+    }
+    let acute = load_over_days(skill, today, 7) / 7f64;
+    let fatigue = acute / chronic;
+
+    let yesterday = today - Duration::days(1);
+    let heavy_yesterday = skill
+        .records
+        .iter()
+        .any(|r| r.date == yesterday && r.effort == Effort::Heavy);
+
+    let (suggested_effort, reason) = if heavy_yesterday {
+        (Effort::Light, "Heavy session yesterday, take it easy")
+    } else if fatigue >= HIGH_FATIGUE {
+        (Effort::Light, "Busier week than usual, keep it light")
+    } else if fatigue <= LOW_FATIGUE {
+        (Effort::Heavy, "Well rested, a good day to push")
+    } else {
+        (Effort::Moderate, "Keep up the usual pace")
+    };
+
+    Some(Recovery {
+        fatigue,
+        suggested_effort,
+        reason,
+    })
+}
+
+//====================================================
+// Correlation
+//====================================================
+/// How closely two skills' weekly minutes move together, from -1 to 1. `None` means one of the
+/// series didn't vary enough to say.
+pub struct PairCorrelation {
+    pub first: Uuid,
+    pub second: Uuid,
+    pub same_week: Option<f64>,
+    /// The first skill's weeks against the second's the week after.
+    pub first_leads: Option<f64>,
+    /// The second skill's weeks against the first's the week after.
+    pub second_leads: Option<f64>,
+}
+
+impl PairCorrelation {
+    /// The strongest of the three relationships, for ranking pairs.
+    pub fn strongest(&self) -> f64 {
+        [self.same_week, self.first_leads, self.second_leads]
+            .iter()
+            .flatten()
+            .map(|r| r.abs())
+            .fold(0f64, f64::max)
+    }
+}
+
+/// Pearson's correlation coefficient of two series of the same length.
+pub fn correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    let n = a.len().min(b.len());
+    if n < 3 {
+        return None;
+    }
+    let mean = |xs: &[f64]| xs[..n].iter().sum::<f64>() / n as f64;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let mut covariance = 0f64;
+    let mut variance_a = 0f64;
+    let mut variance_b = 0f64;
+    a.iter().zip(b).take(n).for_each(|(a, b)| {
+        covariance += (a - mean_a) * (b - mean_b);
+        variance_a += (a - mean_a).powi(2);
+        variance_b += (b - mean_b).powi(2);
+    });
+    let spread = (variance_a * variance_b).sqrt();
+    (spread > f64::EPSILON).then(|| covariance / spread)
+}
+
+/// Correlations between every pair of skills over the last `weeks` weeks, strongest first.
+/// Skills practiced in fewer than `min_active_weeks` of those weeks are left out, as a handful of
+/// sessions can look like a strong relationship by chance.
+pub fn correlations(
+    skills: &HashMap<Uuid, Skill>,
+    today: NaiveDate,
+    weeks: usize,
+    min_active_weeks: usize,
+) -> Vec<PairCorrelation> {
+    if weeks < 2 {
+        return Vec::new();
+    }
+    let first_week = week_start(today) - Duration::weeks(weeks as i64 - 1);
+    let mut series: Vec<(Uuid, Vec<f64>)> = skills
+        .iter()
+        .map(|(id, skill)| {
+            let mut minutes = vec![0f64; weeks];
+            skill.records.iter().filter(|r| r.approved).for_each(|r| {
+                let week = (week_start(r.date) - first_week).num_weeks();
+                if (0..weeks as i64).contains(&week) {
+                    minutes[week as usize] += r.duration as f64;
+                }
+            });
+            (*id, minutes)
+        })
+        .filter(|(_, minutes)| minutes.iter().filter(|m| **m > 0f64).count() >= min_active_weeks)
+        .collect();
+    series.sort_by_key(|(id, _)| *id);
+
+    let mut pairs = Vec::new();
+    series.iter().enumerate().for_each(|(idx, (first, a))| {
+        series[idx + 1..].iter().for_each(|(second, b)| {
+            pairs.push(PairCorrelation {
+                first: *first,
+                second: *second,
+                same_week: correlation(a, b),
+                first_leads: correlation(&a[..weeks - 1], &b[1..]),
+                second_leads: correlation(&b[..weeks - 1], &a[1..]),
+            });
+        });
+    });
+    pairs.sort_by(|a, b| {
+        b.strongest()
+            .partial_cmp(&a.strongest())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    pairs
+}
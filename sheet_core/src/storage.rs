@@ -0,0 +1,549 @@
+//! Where sheets are kept between runs. [`Sheet::open`] and [`Sheet::save`] go through the
+//! [`Storage`] that [`for_path`] picks, so another way of keeping sheets only has to implement
+//! the trait and be picked there. Most sheets are a file in one of the [`FileFormat`]s, but big
+//! ones can be kept in a folder instead, a file per skill, or in a SQLite database, so that
+//! saving only rewrites the parts which changed.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::encryption;
+use crate::file_format::{FileFormat, FormatError};
+use crate::sheet::{self, LoadError, Sheet};
+use crate::sync::DiskStamp;
+#[cfg(not(target_arch = "wasm32"))]
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde_json::Value;
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What saving a sheet is going to write, worked out from the sheet but not written yet. It's
+/// called with how many previous versions to keep, and only touches the disk, so it can be run
+/// on another thread.
+pub type PendingSave = Box<dyn FnOnce(usize) -> io::Result<()> + Send>;
+
+/// A way of keeping sheets.
+pub trait Storage {
+    /// Reads the sheet kept at `path`, migrated and recalculated.
+    fn load(&self, path: &Path) -> Result<Sheet, LoadError>;
+    /// Works out what writing all of `sheet` to its path would write.
+    fn prepare_save(&self, sheet: &Sheet) -> io::Result<PendingSave>;
+    /// Writes all of `sheet` to its path, keeping up to `backup_count` previous versions.
+    fn save(&self, sheet: &Sheet, backup_count: usize) -> io::Result<()> {
+        self.prepare_save(sheet)?(backup_count)
+    }
+}
+
+/// The storage a sheet at `path` is kept in: a [`DirectoryStorage`] for folders, and for the
+/// index file inside one, a [`SqliteStorage`] for databases, or else a [`FileStorage`].
+pub fn for_path(path: &Path) -> &'static dyn Storage {
+    if DirectoryStorage::folder_of(path).is_some() {
+        return &DirectoryStorage;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if SqliteStorage::is_database(path) {
+        return &SqliteStorage;
+    }
+    &FileStorage
+}
+
+/// The storage `sheet` is kept in: its [`Encryption`](encryption::Encryption) if it has a
+/// password and is kept in a file, or else whatever [`for_path`] picks for its path.
+pub fn for_sheet(sheet: &Sheet) -> &dyn Storage {
+    let storage = for_path(&sheet.path);
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(encryption) = &sheet.encryption {
+        let in_file = DirectoryStorage::folder_of(&sheet.path).is_none()
+            && !SqliteStorage::is_database(&sheet.path);
+        if in_file {
+            return encryption;
+        }
+    }
+    storage
+}
+
+//====================================================
+// FileStorage
+//====================================================
+/// A sheet in a file of its own, written in the format its extension asks for. `.sht` files can
+/// be in any of them, which is worked out from the text when they're opened.
+pub struct FileStorage;
+
+impl FileStorage {
+    /// Reads `text`, the contents of a sheet file which is, or was, at `path`.
+    pub fn parse(text: &str, path: &Path) -> Result<Sheet, LoadError> {
+        let file_format = FileFormat::for_path(path).unwrap_or_else(|| FileFormat::detect(text));
+        let value = file_format.read(text).map_err(LoadError::Parse)?;
+        let mut sheet = Sheet::from_saved(value, path)?;
+        sheet.file_format = file_format;
+        Ok(sheet)
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self, path: &Path) -> Result<Sheet, LoadError> {
+        let data = fs::read(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => LoadError::NotFound,
+            _ => LoadError::Io(e),
+        })?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if encryption::is_encrypted(&data) {
+            return Err(LoadError::NeedsPassword);
+        }
+        let text = String::from_utf8(data)
+            .map_err(|e| LoadError::Io(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        let mut sheet = Self::parse(&text, path)?;
+        sheet.on_disk = Some(DiskStamp::of(text.as_bytes()));
+        Ok(sheet)
+    }
+
+    fn prepare_save(&self, sheet: &Sheet) -> io::Result<PendingSave> {
+        let file_format = FileFormat::for_path(&sheet.path).unwrap_or(sheet.file_format);
+        let data = file_format
+            .write(sheet)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let path = sheet.path.clone();
+        Ok(Box::new(move |backup_count| {
+            sheet::write_atomically(&path, data.as_bytes(), backup_count)
+        }))
+    }
+}
+
+//====================================================
+// DirectoryStorage
+//====================================================
+/// A sheet kept in a folder, usually named `<sheet>.shtd`: each skill in `skills/<id>.json`, and
+/// everything else in `sheet.json`. Saving only rewrites the files whose contents changed, and
+/// removes those of skills which are gone. Each file is swapped into place whole, but no backups
+/// are kept, since there's no one file to keep them of.
+///
+/// ```
+/// use chrono::Duration;
+/// use sheet_core::storage::{self, DirectoryStorage};
+/// use sheet_core::Sheet;
+///
+/// let folder = std::env::temp_dir().join(format!("{}.shtd", uuid::Uuid::new_v4()));
+/// let mut sheet = Sheet::new(&folder);
+/// sheet.skill("Guitar").log(Duration::minutes(45));
+/// let piano = sheet.skill("Piano").log(Duration::minutes(30)).id();
+/// assert_eq!(DirectoryStorage::save_changes(&sheet).unwrap(), 3);
+///
+/// sheet.skills_list.get_mut(&piano).unwrap().records[0].note = "scales".to_owned();
+/// assert_eq!(DirectoryStorage::save_changes(&sheet).unwrap(), 1);
+/// assert_eq!(Sheet::open(&folder).unwrap().skills_list.len(), 2);
+/// # std::fs::remove_dir_all(&folder).unwrap();
+/// ```
+pub struct DirectoryStorage;
+
+impl DirectoryStorage {
+    pub const EXTENSION: &'static str = "shtd";
+    /// The file in the folder with everything but the skills.
+    pub const INDEX: &'static str = "sheet.json";
+    const SKILLS: &'static str = "skills";
+
+    /// The folder a sheet at `path` is kept in, if it's kept in one: `path` itself if it's a
+    /// folder or has the folder extension, or the folder holding it if it's the index.
+    pub fn folder_of(path: &Path) -> Option<&Path> {
+        let is_folder =
+            |path: &Path| path.is_dir() || path.extension().map_or(false, |e| e == Self::EXTENSION);
+        if is_folder(path) {
+            return Some(path);
+        }
+        let parent = path.parent()?;
+        (path.file_name()? == Self::INDEX && is_folder(parent)).then_some(parent)
+    }
+
+    /// Where a sheet kept in a file at `path` goes when it's moved into a folder.
+    pub fn path_for(path: &Path) -> PathBuf {
+        path.with_extension(Self::EXTENSION)
+    }
+
+    /// Writes the files of `sheet` which are different from what's in its folder, giving back
+    /// how many there were.
+    pub fn save_changes(sheet: &Sheet) -> io::Result<usize> {
+        let folder = Self::folder_of(&sheet.path).unwrap_or(&sheet.path);
+        Self::write_changes(folder, serde_json::to_value(sheet).map_err(invalid)?)
+    }
+
+    /// Writes the files of the sheet saved as `index` which are different from what's in
+    /// `folder`.
+    fn write_changes(folder: &Path, mut index: Value) -> io::Result<usize> {
+        let skills_folder = folder.join(Self::SKILLS);
+        fs::create_dir_all(&skills_folder)?;
+
+        let skills = match index.as_object_mut().and_then(|o| o.remove("skills_list")) {
+            Some(Value::Object(skills)) => skills,
+            _ => serde_json::Map::new(),
+        };
+        let mut written = 0;
+        for (id, skill) in &skills {
+            let data = serde_json::to_string_pretty(skill).map_err(invalid)?;
+            written += write_if_changed(&skills_folder.join(format!("{}.json", id)), &data)?;
+        }
+        for (id, path) in Self::skill_files(&skills_folder)? {
+            if !skills.contains_key(&id) {
+                fs::remove_file(path)?;
+            }
+        }
+        let data = serde_json::to_string_pretty(&index).map_err(invalid)?;
+        written += write_if_changed(&folder.join(Self::INDEX), &data)?;
+        Ok(written)
+    }
+
+    /// The skill files in `skills_folder`, by the skill id they're named for.
+    fn skill_files(skills_folder: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(skills_folder)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |e| e == "json") {
+                if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    files.push((id.to_owned(), path.clone()));
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// A stamp of everything in `folder`, to tell when anything in it has changed.
+    pub fn stamp(folder: &Path) -> io::Result<DiskStamp> {
+        let mut data = fs::read(folder.join(Self::INDEX))?;
+        let skills_folder = folder.join(Self::SKILLS);
+        if skills_folder.is_dir() {
+            for (id, path) in Self::skill_files(&skills_folder)? {
+                data.extend(id.as_bytes());
+                data.extend(fs::read(path)?);
+            }
+        }
+        Ok(DiskStamp::of(&data))
+    }
+}
+
+impl Storage for DirectoryStorage {
+    fn load(&self, path: &Path) -> Result<Sheet, LoadError> {
+        let folder = Self::folder_of(path).unwrap_or(path);
+        let read = |path: &Path| {
+            fs::read_to_string(path).map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => LoadError::NotFound,
+                _ => LoadError::Io(e),
+            })
+        };
+        let parse = |text: &str| -> Result<Value, LoadError> {
+            serde_json::from_str(text).map_err(|e| LoadError::Parse(FormatError::Json(e)))
+        };
+
+        let mut value = parse(&read(&folder.join(Self::INDEX))?)?;
+        let skills_folder = folder.join(Self::SKILLS);
+        let mut skills = serde_json::Map::new();
+        if skills_folder.is_dir() {
+            for (id, path) in Self::skill_files(&skills_folder).map_err(LoadError::Io)? {
+                skills.insert(id, parse(&read(&path)?)?);
+            }
+        }
+        if let Some(object) = value.as_object_mut() {
+            object.insert("skills_list".to_owned(), Value::Object(skills));
+        }
+        let mut sheet = Sheet::from_saved(value, folder)?;
+        sheet.on_disk = Self::stamp(folder).ok();
+        Ok(sheet)
+    }
+
+    fn prepare_save(&self, sheet: &Sheet) -> io::Result<PendingSave> {
+        let folder = Self::folder_of(&sheet.path)
+            .unwrap_or(&sheet.path)
+            .to_owned();
+        let index = serde_json::to_value(sheet).map_err(invalid)?;
+        Ok(Box::new(move |_| {
+            Self::write_changes(&folder, index).map(|_| ())
+        }))
+    }
+}
+
+//====================================================
+// SqliteStorage
+//====================================================
+/// A sheet kept in a SQLite database, for big histories and for other programs reading it at the
+/// same time. Everything but the skills is a row in `sheet`, each skill a row in `skills`, and
+/// each of its records a row in `records`, in order by `position`. Rows are kept as the same JSON
+/// the files use, and `records` also has each one's `date` and `minutes` so they can be queried.
+/// Saving is one transaction which only rewrites the rows which changed. No backups are kept,
+/// since a save which doesn't finish leaves the database as it was.
+///
+/// ```
+/// use chrono::Duration;
+/// use sheet_core::storage::SqliteStorage;
+/// use sheet_core::Sheet;
+///
+/// let file = std::env::temp_dir().join(format!("{}.sht", uuid::Uuid::new_v4()));
+/// let mut sheet = Sheet::new(&file);
+/// sheet.skill("Guitar").log(Duration::minutes(45));
+/// let piano = sheet.skill("Piano").log(Duration::minutes(30)).id();
+/// sheet.save(0)?;
+///
+/// let mut sheet = SqliteStorage::import(&file)?;
+/// assert_eq!(sheet.path, SqliteStorage::path_for(&file));
+/// sheet.skills_list.get_mut(&piano).unwrap().records[0].note = "scales".to_owned();
+/// assert_eq!(SqliteStorage::save_changes(&sheet)?, 1);
+///
+/// let reopened = Sheet::open(&sheet.path).unwrap();
+/// assert_eq!(reopened.skills_list[&piano].records[0].note, "scales");
+/// assert_eq!(reopened.skills_list.len(), 2);
+/// # std::fs::remove_file(&file)?;
+/// # std::fs::remove_file(&sheet.path)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct SqliteStorage;
+
+impl SqliteStorage {
+    pub const EXTENSION: &'static str = "sqlite";
+    /// Extensions which are taken to mean a database when opening one.
+    const EXTENSIONS: [&'static str; 3] = [Self::EXTENSION, "sqlite3", "db"];
+
+    pub fn is_database(path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map_or(false, |e| {
+                Self::EXTENSIONS.contains(&e.to_ascii_lowercase().as_str())
+            })
+    }
+
+    /// Where a sheet kept in a file at `path` goes when it's moved into a database.
+    pub fn path_for(path: &Path) -> PathBuf {
+        path.with_extension(Self::EXTENSION)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SqliteStorage {
+    const SCHEMA: &'static str = "
+        CREATE TABLE IF NOT EXISTS sheet (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS skills (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            data TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS records (
+            skill_id TEXT NOT NULL REFERENCES skills (id) ON DELETE CASCADE,
+            position INTEGER NOT NULL,
+            date TEXT NOT NULL,
+            minutes INTEGER NOT NULL,
+            data TEXT NOT NULL,
+            PRIMARY KEY (skill_id, position)
+        );";
+
+    /// Copies the sheet in the file at `from` into a new database at [`Self::path_for`] it, and
+    /// gives back the sheet as it's kept there. The file is left as it was, and nothing already
+    /// at the database's path is replaced.
+    pub fn import(from: &Path) -> io::Result<Sheet> {
+        let to = Self::path_for(from);
+        if to.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("there's already something at {}", to.display()),
+            ));
+        }
+        let mut sheet = Sheet::open(from).map_err(|e| match e {
+            LoadError::Io(e) => e,
+            LoadError::NotFound => io::Error::new(io::ErrorKind::NotFound, e.to_string()),
+            e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+        })?;
+        sheet.path = to;
+        sheet.save(0)?;
+        Ok(sheet)
+    }
+
+    fn connect(path: &Path, flags: OpenFlags) -> rusqlite::Result<Connection> {
+        let connection = Connection::open_with_flags(path, flags)?;
+        // Wait a while for anything else which has it open, rather than failing straight away.
+        connection.busy_timeout(std::time::Duration::from_secs(5))?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        Ok(connection)
+    }
+
+    /// Writes the rows of `sheet` which are different from what's in its database, creating it
+    /// if need be, and gives back how many there were.
+    pub fn save_changes(sheet: &Sheet) -> io::Result<usize> {
+        Self::write_changes(&sheet.path, serde_json::to_value(sheet).map_err(invalid)?)
+    }
+
+    /// Writes the rows of the sheet saved as `index` which are different from what's in the
+    /// database at `path`.
+    fn write_changes(path: &Path, mut index: Value) -> io::Result<usize> {
+        let skills = match index.as_object_mut().and_then(|o| o.remove("skills_list")) {
+            Some(Value::Object(skills)) => skills,
+            _ => serde_json::Map::new(),
+        };
+        let mut connection = Self::connect(path, OpenFlags::default()).map_err(db_error)?;
+        connection.execute_batch(Self::SCHEMA).map_err(db_error)?;
+        let transaction = connection
+            .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+            .map_err(db_error)?;
+
+        let mut written = transaction
+            .execute(
+                "INSERT INTO sheet (id, data) VALUES (1, ?1) ON CONFLICT (id) DO UPDATE
+                 SET data = excluded.data WHERE data != excluded.data",
+                [index.to_string()],
+            )
+            .map_err(db_error)?;
+        let old_skills: HashSet<String> = transaction
+            .prepare("SELECT id FROM skills")
+            .and_then(|mut select| select.query_map([], |row| row.get(0))?.collect())
+            .map_err(db_error)?;
+        for id in old_skills.iter().filter(|id| !skills.contains_key(*id)) {
+            written += transaction
+                .execute("DELETE FROM skills WHERE id = ?1", [id])
+                .map_err(db_error)?;
+        }
+        for (id, skill) in skills {
+            written += Self::save_skill(&transaction, &id, skill).map_err(db_error)?;
+        }
+        transaction.commit().map_err(db_error)?;
+        Ok(written)
+    }
+
+    /// Writes one skill's row and those of its records which changed.
+    fn save_skill(connection: &Connection, id: &str, mut skill: Value) -> rusqlite::Result<usize> {
+        let records = match skill.as_object_mut().and_then(|o| o.remove("records")) {
+            Some(Value::Array(records)) => records,
+            _ => Vec::new(),
+        };
+        let name = skill["name"].as_str().unwrap_or_default().to_owned();
+        let mut written = connection.execute(
+            "INSERT INTO skills (id, name, data) VALUES (?1, ?2, ?3) ON CONFLICT (id) DO UPDATE
+             SET name = excluded.name, data = excluded.data WHERE data != excluded.data",
+            [id, &name, &skill.to_string()],
+        )?;
+
+        let old_records: HashMap<usize, String> = connection
+            .prepare("SELECT position, data FROM records WHERE skill_id = ?1")?
+            .query_map([id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        for (position, record) in records.iter().enumerate() {
+            let data = record.to_string();
+            if old_records.get(&position) == Some(&data) {
+                continue;
+            }
+            written += connection.execute(
+                "INSERT OR REPLACE INTO records (skill_id, position, date, minutes, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![
+                    id,
+                    position,
+                    record["date"].as_str().unwrap_or_default(),
+                    record["duration"].as_u64().unwrap_or_default(),
+                    data,
+                ],
+            )?;
+        }
+        written += connection.execute(
+            "DELETE FROM records WHERE skill_id = ?1 AND position >= ?2",
+            rusqlite::params![id, records.len()],
+        )?;
+        Ok(written)
+    }
+
+    fn read(path: &Path) -> Result<Value, LoadError> {
+        let db = |e| LoadError::Io(db_error(e));
+        let parse = |text: &str| -> Result<Value, LoadError> {
+            serde_json::from_str(text).map_err(|e| LoadError::Parse(FormatError::Json(e)))
+        };
+        let mut connection = Self::connect(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(db)?;
+        // Read it all as of one moment, even if something else is saving to it.
+        let transaction = connection.transaction().map_err(db)?;
+        let index: Option<String> = transaction
+            .query_row("SELECT data FROM sheet WHERE id = 1", [], |row| row.get(0))
+            .optional()
+            .map_err(db)?;
+        let mut index = parse(&index.ok_or_else(|| {
+            LoadError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the database doesn't hold a sheet",
+            ))
+        })?)?;
+
+        let mut skills = serde_json::Map::new();
+        let rows: Vec<(String, String)> = transaction
+            .prepare("SELECT id, data FROM skills")
+            .and_then(|mut select| {
+                select
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect()
+            })
+            .map_err(db)?;
+        for (id, data) in rows {
+            let mut skill = parse(&data)?;
+            skill["records"] = Value::Array(Vec::new());
+            skills.insert(id, skill);
+        }
+        let rows: Vec<(String, String)> = transaction
+            .prepare("SELECT skill_id, data FROM records ORDER BY skill_id, position")
+            .and_then(|mut select| {
+                select
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect()
+            })
+            .map_err(db)?;
+        for (skill_id, data) in rows {
+            let record = parse(&data)?;
+            if let Some(Value::Array(records)) =
+                skills.get_mut(&skill_id).map(|s| &mut s["records"])
+            {
+                records.push(record);
+            }
+        }
+        if let Some(object) = index.as_object_mut() {
+            object.insert("skills_list".to_owned(), Value::Object(skills));
+        }
+        Ok(index)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for SqliteStorage {
+    fn load(&self, path: &Path) -> Result<Sheet, LoadError> {
+        // Opening it would otherwise make an empty database.
+        if !path.exists() {
+            return Err(LoadError::NotFound);
+        }
+        let mut sheet = Sheet::from_saved(Self::read(path)?, path)?;
+        sheet.on_disk = DiskStamp::read(path).unwrap_or(None);
+        Ok(sheet)
+    }
+
+    fn prepare_save(&self, sheet: &Sheet) -> io::Result<PendingSave> {
+        let path = sheet.path.clone();
+        let index = serde_json::to_value(sheet).map_err(invalid)?;
+        Ok(Box::new(move |_| {
+            Self::write_changes(&path, index).map(|_| ())
+        }))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn db_error(e: rusqlite::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+fn invalid(e: serde_json::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Replaces the file at `path` with `data` unless it already holds exactly that, giving back how
+/// many files were written.
+fn write_if_changed(path: &Path, data: &str) -> io::Result<usize> {
+    if fs::read(path).map_or(false, |old| old == data.as_bytes()) {
+        return Ok(0);
+    }
+    let tmp_path = sheet::append_extension(path, "tmp");
+    if let Err(e) = sheet::write_synced(&tmp_path, data.as_bytes()) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    fs::rename(&tmp_path, path)?;
+    Ok(1)
+}
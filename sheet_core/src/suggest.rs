@@ -0,0 +1,129 @@
+use crate::format::ExpFormat;
+use crate::nudging::NudgePolicy;
+use crate::sheet::Sheet;
+use crate::skill::{Effort, Skill};
+use crate::stats;
+use chrono::{Duration, NaiveDate};
+use uuid::Uuid;
+
+//====================================================
+// Suggestion
+//====================================================
+/// A skill worth doing next, along with why.
+pub struct Suggestion {
+    pub skill_id: Uuid,
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+impl Suggestion {
+    fn add(&mut self, score: f64, reason: String) {
+        self.score += score;
+        self.reasons.push(reason);
+    }
+}
+
+/// Ranks every skill on the sheet by how much it would benefit from a session today, best first.
+/// Completed skills are left out, and the reasons are worded as `nudging` says.
+///
+/// Each factor below adds to (or takes away from) a skill's score and explains itself, so the
+/// UI can show why something was suggested.
+pub fn rank(
+    sheet: &Sheet,
+    today: NaiveDate,
+    exp_format: &ExpFormat,
+    nudging: &NudgePolicy,
+) -> Vec<Suggestion> {
+    let mut suggestions: Vec<Suggestion> = sheet
+        .active_skills()
+        .map(|(skill_id, skill)| {
+            let mut suggestion = Suggestion {
+                skill_id: *skill_id,
+                score: 0f64,
+                reasons: Vec::new(),
+            };
+            neglect(skill, today, &mut suggestion);
+            streak_risk(skill, today, exp_format, nudging, &mut suggestion);
+            recovery(skill, today, &mut suggestion);
+            suggestion
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    suggestions
+}
+
+/// The most recent day anything counted toward a skill.
+fn last_practiced(skill: &Skill) -> Option<NaiveDate> {
+    skill
+        .records
+        .iter()
+        .filter(|r| r.approved)
+        .map(|r| r.date)
+        .max()
+}
+
+/// The longer a skill has gone untouched, the more it's worth coming back to.
+fn neglect(skill: &Skill, today: NaiveDate, suggestion: &mut Suggestion) {
+    const MAX_NEGLECT_DAYS: i64 = 30;
+
+    match last_practiced(skill) {
+        Some(last) => {
+            let days = today.signed_duration_since(last).num_days();
+            if days > 1 {
+                let score = days.min(MAX_NEGLECT_DAYS) as f64 / MAX_NEGLECT_DAYS as f64;
+                suggestion.add(score, format!("Not practiced in {} days", days));
+            }
+        }
+        None => suggestion.add(0.5, "Not practiced yet".to_owned()),
+    }
+}
+
+/// Skills with a streak bonus waiting are worth doing before the bonus runs out, while skills
+/// already done today can wait.
+fn streak_risk(
+    skill: &Skill,
+    today: NaiveDate,
+    exp_format: &ExpFormat,
+    nudging: &NudgePolicy,
+    suggestion: &mut Suggestion,
+) {
+    const BONUS_FOR_FULL_SCORE: f64 = 50.0;
+
+    let last = match last_practiced(skill) {
+        Some(last) => last,
+        None => return,
+    };
+
+    if last == today {
+        suggestion.add(-1f64, "Already practiced today".to_owned());
+        return;
+    }
+    if last == today - Duration::days(1) {
+        let reason = if nudging.warns_of_risk() {
+            "Practiced yesterday, keep the streak going"
+        } else {
+            "Practiced yesterday, some momentum going"
+        };
+        suggestion.add(0.5, reason.to_owned());
+    }
+    if skill.potential_bonus > 0f64 {
+        let score = (skill.potential_bonus / BONUS_FOR_FULL_SCORE).min(1f64) * 0.5;
+        suggestion.add(
+            score,
+            format!(
+                "Next session earns {} bonus",
+                exp_format.amount(skill.potential_bonus)
+            ),
+        );
+    }
+}
+
+/// Skills you've been pushing hard lately get nudged down so they can recover.
+fn recovery(skill: &Skill, today: NaiveDate, suggestion: &mut Suggestion) {
+    if let Some(recovery) = stats::recovery(skill, today) {
+        if recovery.suggested_effort == Effort::Light {
+            suggestion.add(-0.3, recovery.reason.to_owned());
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Noticing when a sheet's file has been changed by something else since it was opened, such as
+//! a sync client bringing in a save from another machine, so it isn't overwritten unseen.
+
+use crate::sheet::Sheet;
+use crate::skill::SheetActionRecord;
+use crate::storage::DirectoryStorage;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::Path;
+
+//====================================================
+// DiskStamp
+//====================================================
+/// Enough about a file's contents to tell whether they've changed. It's only compared within
+/// one run of the app, so the hash doesn't need to be stable between versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiskStamp {
+    len: usize,
+    hash: u64,
+}
+
+impl DiskStamp {
+    pub fn of(data: &[u8]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        Self {
+            len: data.len(),
+            hash: hasher.finish(),
+        }
+    }
+
+    /// The stamp of the file at `path` as it is now, or `None` if there's no file there. A sheet
+    /// kept in a folder is stamped with everything in it.
+    pub fn read(path: &Path) -> io::Result<Option<Self>> {
+        if let Some(folder) = DirectoryStorage::folder_of(path) {
+            return match DirectoryStorage::stamp(folder) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                stamp => stamp.map(Some),
+            };
+        }
+        match fs::read(path) {
+            Ok(data) => Ok(Some(Self::of(&data))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// What [`Sheet::merge_from`] or [`Sheet::bring_in`] brought in.
+#[derive(Default)]
+pub struct MergeSummary {
+    pub skills_added: usize,
+    pub records_added: usize,
+}
+
+impl Sheet {
+    /// Whether the file at the sheet's path is different from when it was opened or last saved.
+    /// A file which has gone missing isn't counted, as saving just puts it back; one which can't
+    /// be read is left for saving to report.
+    pub fn changed_on_disk(&self) -> bool {
+        match DiskStamp::read(&self.path) {
+            Ok(Some(now)) => Some(now) != self.on_disk,
+            Ok(None) | Err(_) => false,
+        }
+    }
+
+    /// Brings in what `theirs` has and this sheet doesn't: skills only it has, with their unit,
+    /// zones and record order, records only it has on skills both have, and achievements only it
+    /// has unlocked. Everything else stays as this sheet has it. There's no telling a deletion
+    /// from something that was never there, so anything deleted on only one side comes back.
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use sheet_core::links::Link;
+    /// use sheet_core::{Sheet, SheetActionRecord};
+    ///
+    /// let record = SheetActionRecord {
+    ///     date: NaiveDate::from_ymd(2024, 6, 1),
+    ///     duration: 45,
+    ///     ..Default::default()
+    /// };
+    /// let mut mine = Sheet::new("myself.sht".as_ref());
+    /// let guitar = mine.skill("Guitar").add_record(record).id();
+    /// let mut theirs = Sheet::new("myself.sht".as_ref());
+    /// theirs.skills_list = mine.skills_list.clone();
+    /// let linked = theirs.skills_list.get_mut(&guitar).unwrap();
+    /// linked.records[0].links.push(Link::guess("https://example.com/take-2"));
+    ///
+    /// // Only the links are different, but that still makes it a session this sheet hasn't got.
+    /// assert_eq!(mine.merge_from(theirs).records_added, 1);
+    /// assert_eq!(mine.skills_list[&guitar].records.len(), 2);
+    /// ```
+    pub fn merge_from(&mut self, theirs: Sheet) -> MergeSummary {
+        let mut summary = MergeSummary::default();
+        theirs
+            .skills_list
+            .into_iter()
+            .for_each(|(skill_id, their_skill)| {
+                let skill = match self.skills_list.get_mut(&skill_id) {
+                    Some(skill) => skill,
+                    None => {
+                        if let Some(unit) = theirs.units.get(&skill_id) {
+                            self.units.insert(skill_id, unit.clone());
+                        }
+                        if let Some(zones) = theirs.zones.get(&skill_id) {
+                            self.zones.insert(skill_id, *zones);
+                        }
+                        if let Some(sort) = theirs.record_sorts.get(&skill_id) {
+                            self.record_sorts.insert(skill_id, *sort);
+                        }
+                        summary.skills_added += 1;
+                        summary.records_added += their_skill.records.len();
+                        self.skills_list.insert(skill_id, their_skill);
+                        return;
+                    }
+                };
+                their_skill.records.into_iter().for_each(|record| {
+                    if !skill.records.iter().any(|r| same_session(r, &record)) {
+                        skill.records.push(record);
+                        summary.records_added += 1;
+                    }
+                });
+            });
+        theirs
+            .achievements
+            .unlocked
+            .into_iter()
+            .for_each(|(achievement, date)| {
+                let unlocked = self
+                    .achievements
+                    .unlocked
+                    .entry(achievement)
+                    .or_insert(date);
+                *unlocked = (*unlocked).min(date);
+            });
+        self.recalculate_all();
+        summary
+    }
+}
+
+/// Whether two records are the same session, ignoring the EXP, which is worked out again anyway.
+pub(crate) fn same_session(a: &SheetActionRecord, b: &SheetActionRecord) -> bool {
+    a.date == b.date
+        && a.duration == b.duration
+        && a.note == b.note
+        && a.effort == b.effort
+        && a.approved == b.approved
+        && a.exp_override == b.exp_override
+        && a.timed_minutes == b.timed_minutes
+        && a.distractions == b.distractions
+        && a.intensity == b.intensity
+        && a.links == b.links
+}
@@ -0,0 +1,214 @@
+use crate::sheet::Sheet;
+use crate::skill::{SheetActionRecord, Skill};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use uuid::Uuid;
+
+//====================================================
+// DateShift
+//====================================================
+/// A bulk correction to record dates, e.g. for fixing up imported data.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DateShift {
+    /// Moves every date by a number of days (negative moves them earlier).
+    Days(i64),
+    /// Re-dates records which were written down in one timezone as if they'd been written down
+    /// in another. Records only store a date, so `hour` says what time of day the sessions
+    /// usually happened.
+    Timezone {
+        from_utc_offset: i32,
+        to_utc_offset: i32,
+        hour: u32,
+    },
+}
+
+impl Default for DateShift {
+    fn default() -> Self {
+        DateShift::Days(0)
+    }
+}
+
+impl DateShift {
+    pub fn apply(&self, date: NaiveDate) -> NaiveDate {
+        match *self {
+            DateShift::Days(days) => date + Duration::days(days),
+            DateShift::Timezone {
+                from_utc_offset,
+                to_utc_offset,
+                hour,
+            } => {
+                let time = NaiveTime::from_hms(hour.min(23), 0, 0);
+                let moved =
+                    date.and_time(time) + Duration::hours((to_utc_offset - from_utc_offset) as i64);
+                moved.date()
+            }
+        }
+    }
+}
+
+/// Which records a bulk tool should touch: all of them, or just those dated within a range.
+#[derive(Clone, Copy, Default)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from.map_or(true, |from| from <= date) && self.to.map_or(true, |to| date <= to)
+    }
+}
+
+/// Applies `shift` to every record of `skill` within `range`, returning how many were changed.
+/// The records will need re-sorting and recalculating afterwards.
+pub fn shift_dates(skill: &mut Skill, shift: &DateShift, range: &DateRange) -> usize {
+    let mut count = 0;
+    skill
+        .records
+        .iter_mut()
+        .filter(|r| range.contains(r.date))
+        .for_each(|r| {
+            let shifted = shift.apply(r.date);
+            if shifted != r.date {
+                r.date = shifted;
+                count += 1;
+            }
+        });
+    count
+}
+
+//====================================================
+// Data health
+//====================================================
+/// Something about a record which looks like a mistake.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Anomaly {
+    /// The record has no duration, so it earns nothing.
+    ZeroDuration,
+    /// The record is longer than anyone could plausibly practice in a day.
+    TooLong,
+    /// The skill has more than one record on the same day.
+    DuplicateDay,
+    /// The record's EXP was set by hand rather than worked out.
+    Overridden,
+}
+
+impl Anomaly {
+    pub const ALL: [Anomaly; 4] = [
+        Anomaly::ZeroDuration,
+        Anomaly::TooLong,
+        Anomaly::DuplicateDay,
+        Anomaly::Overridden,
+    ];
+
+    /// Sessions longer than this are flagged as `TooLong`.
+    pub const MAX_PLAUSIBLE_MINUTES: u64 = 16 * 60;
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Anomaly::ZeroDuration => "Records with no duration",
+            Anomaly::TooLong => "Sessions longer than 16 hours",
+            Anomaly::DuplicateDay => "More than one record on the same day",
+            Anomaly::Overridden => "Records with EXP set by hand",
+        }
+    }
+
+    /// What `fix_anomalies` does about this kind of anomaly.
+    pub fn fix_label(&self) -> &'static str {
+        match self {
+            Anomaly::ZeroDuration => "Delete them",
+            Anomaly::TooLong => "Cut them down to 16 hours",
+            Anomaly::DuplicateDay => "Merge each day into one record",
+            Anomaly::Overridden => "Go back to the worked out EXP",
+        }
+    }
+}
+
+/// One suspicious record found by `find_anomalies`.
+pub struct Finding {
+    pub skill_id: Uuid,
+    pub date: NaiveDate,
+    pub duration: u64,
+    pub anomaly: Anomaly,
+    /// Anything else worth showing, like why a record's EXP was overridden.
+    pub detail: String,
+}
+
+/// Looks through every record on `sheet` for likely mistakes. Findings are in skill name order,
+/// then date order.
+pub fn find_anomalies(sheet: &Sheet) -> Vec<Finding> {
+    let mut skills: Vec<(&Uuid, &Skill)> = sheet.skills_list.iter().collect();
+    skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+
+    let mut findings = Vec::new();
+    skills.iter().for_each(|(skill_id, skill)| {
+        let finding = |record: &SheetActionRecord, anomaly| Finding {
+            skill_id: **skill_id,
+            date: record.date,
+            duration: record.duration,
+            anomaly,
+            detail: String::new(),
+        };
+        skill.records.iter().enumerate().for_each(|(idx, record)| {
+            if record.duration == 0 {
+                findings.push(finding(record, Anomaly::ZeroDuration));
+            }
+            if record.duration > Anomaly::MAX_PLAUSIBLE_MINUTES {
+                findings.push(finding(record, Anomaly::TooLong));
+            }
+            // Records are kept in date order, so a repeated day is always next to its twin.
+            if idx > 0 && skill.records[idx - 1].date == record.date {
+                findings.push(finding(record, Anomaly::DuplicateDay));
+            }
+            if let Some(exp_override) = &record.exp_override {
+                findings.push(Finding {
+                    detail: format!("{} EXP: {}", exp_override.exp.round(), exp_override.reason),
+                    ..finding(record, Anomaly::Overridden)
+                });
+            }
+        });
+    });
+    findings
+}
+
+/// Fixes every `anomaly` on the sheet in one go, returning how many records were changed or
+/// removed. Changed skills are recalculated.
+pub fn fix_anomalies(sheet: &mut Sheet, anomaly: Anomaly) -> usize {
+    let mut count = 0;
+    let mut touched = Vec::new();
+    sheet.skills_list.iter_mut().for_each(|(skill_id, skill)| {
+        let fixed = match anomaly {
+            Anomaly::ZeroDuration => {
+                let before = skill.records.len();
+                skill.records.retain(|r| r.duration > 0);
+                before - skill.records.len()
+            }
+            Anomaly::TooLong => {
+                let mut fixed = 0;
+                skill
+                    .records
+                    .iter_mut()
+                    .filter(|r| r.duration > Anomaly::MAX_PLAUSIBLE_MINUTES)
+                    .for_each(|r| {
+                        r.duration = Anomaly::MAX_PLAUSIBLE_MINUTES;
+                        fixed += 1;
+                    });
+                fixed
+            }
+            Anomaly::DuplicateDay => skill.merge_same_day_records(false),
+            Anomaly::Overridden => skill
+                .records
+                .iter_mut()
+                .filter_map(|r| r.exp_override.take())
+                .count(),
+        };
+        if fixed > 0 {
+            count += fixed;
+            touched.push(*skill_id);
+        }
+    });
+
+    touched
+        .iter()
+        .for_each(|skill_id| sheet.recalculate_skill(skill_id));
+    count
+}
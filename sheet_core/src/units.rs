@@ -0,0 +1,145 @@
+use crate::sheet::Sheet;
+use crate::skill::Skill;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+//====================================================
+// Unit
+//====================================================
+/// What a skill's records count when it isn't minutes, such as pages read or problems solved.
+/// Records keep the count in their `duration`.
+#[derive(Clone, PartialEq, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Unit {
+    /// What they're called, e.g. "pages".
+    pub name: String,
+    /// Roughly how long they take, so the skill can be counted in totals of time across skills.
+    /// Skills without one are left out of those.
+    pub estimate: Option<UnitEstimate>,
+}
+
+impl Default for Unit {
+    fn default() -> Self {
+        Self {
+            name: "pages".to_owned(),
+            estimate: None,
+        }
+    }
+}
+
+impl Unit {
+    /// Roughly how many minutes `amount` of these is worth, if there's an estimate to go by.
+    pub fn minutes_for(&self, amount: u64) -> Option<f64> {
+        self.estimate.map(|estimate| estimate.minutes_for(amount))
+    }
+}
+
+/// An exchange rate between a unit and time: `amount` of them take about `minutes`, e.g. 10
+/// pages in 30 minutes. It's only ever an estimate, and is shown as one.
+#[derive(Clone, Copy, PartialEq, Deserialize, JsonSchema, Serialize)]
+pub struct UnitEstimate {
+    pub amount: f64,
+    pub minutes: f64,
+}
+
+impl Default for UnitEstimate {
+    fn default() -> Self {
+        Self {
+            amount: 10.0,
+            minutes: 30.0,
+        }
+    }
+}
+
+impl UnitEstimate {
+    pub fn minutes_for(&self, amount: u64) -> f64 {
+        if self.amount > 0.0 {
+            amount as f64 * self.minutes / self.amount
+        } else {
+            0.0
+        }
+    }
+
+    /// The other way around: roughly how many of the unit take `minutes`.
+    pub fn amount_for(&self, minutes: u64) -> f64 {
+        if self.minutes > 0.0 {
+            minutes as f64 * self.amount / self.minutes
+        } else {
+            0.0
+        }
+    }
+
+    /// e.g. "10 pages ≈ 30 min".
+    pub fn describe(&self, unit_name: &str) -> String {
+        format!("{} {} ≈ {} min", self.amount, unit_name, self.minutes)
+    }
+}
+
+//====================================================
+// UnitMigration
+//====================================================
+/// What happens to the records a skill already has when it changes from minutes to another unit,
+/// or back.
+pub enum UnitMigration {
+    /// Each record's amount is converted at `rate` and rounded, as if it had been logged in the
+    /// new unit all along.
+    Convert { rate: UnitEstimate },
+    /// The records move onto a new skill called `archive_name`, which stays in the old unit, and
+    /// the skill starts again from nothing.
+    Split { archive_name: String },
+}
+
+impl Sheet {
+    /// Changes what `skill_id` is logged in to `unit`, with `None` being minutes, and deals with
+    /// its records by `migration`. Gives back the id of the skill the old records were split off
+    /// onto, if they were.
+    pub fn change_unit(
+        &mut self,
+        skill_id: &Uuid,
+        unit: Option<Unit>,
+        migration: UnitMigration,
+    ) -> Option<Uuid> {
+        let skill = self.skills_list.get_mut(skill_id)?;
+        let archive_id = match migration {
+            UnitMigration::Convert { rate } => {
+                let to_minutes = unit.is_none();
+                skill.records.iter_mut().for_each(|record| {
+                    let converted = if to_minutes {
+                        rate.minutes_for(record.duration)
+                    } else {
+                        rate.amount_for(record.duration)
+                    };
+                    // Nothing that was logged is rounded away to nothing.
+                    let least = record.duration.min(1);
+                    record.duration = (converted.round() as u64).max(least);
+                });
+                None
+            }
+            UnitMigration::Split { archive_name } => {
+                let archive = Skill {
+                    name: archive_name,
+                    records: std::mem::take(&mut skill.records),
+                    category: skill.category.clone(),
+                    created_at: skill.created_at,
+                    ..Default::default()
+                };
+                let archive_id = Uuid::new_v4();
+                if let Some(old_unit) = self.units.get(skill_id).cloned() {
+                    self.units.insert(archive_id, old_unit);
+                }
+                if self.merge_same_day.contains(skill_id) {
+                    self.merge_same_day.insert(archive_id);
+                }
+                self.skills_list.insert(archive_id, archive);
+                Some(archive_id)
+            }
+        };
+        match unit {
+            Some(unit) => self.units.insert(*skill_id, unit),
+            None => self.units.remove(skill_id),
+        };
+        self.recalculate_all();
+        archive_id
+    }
+}
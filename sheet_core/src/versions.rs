@@ -0,0 +1,258 @@
+//! A history of every save of a sheet, kept in a git repository of its own so old versions can
+//! be looked through and brought back. It's run through the `git` command, which has to be
+//! installed. The repository lives next to the sheet in `<sheet>.history` and only ever tracks
+//! the sheet's own file, so it can share a folder with anything else, even another repository.
+
+use crate::sheet::{LoadError, Sheet};
+use crate::storage::{DirectoryStorage, FileStorage, SqliteStorage};
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use uuid::Uuid;
+
+/// The most skills named in a version's summary before the rest are just counted.
+const SUMMARY_SKILLS: usize = 3;
+
+//====================================================
+// Version
+//====================================================
+/// One saved version of a sheet.
+pub struct Version {
+    /// The commit it was saved as.
+    pub id: String,
+    pub time: DateTime<Local>,
+    /// What changed since the version before, e.g. "+45min Guitar".
+    pub summary: String,
+}
+
+//====================================================
+// VersionHistory
+//====================================================
+pub struct VersionHistory {
+    /// The folder the sheet is in, which git runs from.
+    folder: PathBuf,
+    /// The sheet's file name, which is all git is ever told about.
+    file: OsString,
+    /// Where the sheet was opened from, so old versions are read in its format.
+    path: PathBuf,
+}
+
+impl VersionHistory {
+    pub fn for_sheet(path: &Path) -> Self {
+        let folder = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_owned(),
+            _ => PathBuf::from("."),
+        };
+        Self {
+            folder,
+            file: path.file_name().unwrap_or_default().to_owned(),
+            path: path.to_owned(),
+        }
+    }
+
+    fn git_dir(&self) -> OsString {
+        let mut git_dir = self.file.clone();
+        git_dir.push(".history");
+        git_dir
+    }
+
+    /// Whether any versions have been kept yet.
+    pub fn exists(&self) -> bool {
+        self.folder.join(self.git_dir()).exists()
+    }
+
+    /// Commits the sheet's file as it is on disk now, with a summary of what changed since the
+    /// last version. Nothing happens if it hasn't changed, if the sheet is kept in a folder,
+    /// which only tracking one file can't cover, or if it's kept in a database or encrypted,
+    /// which aren't text.
+    pub fn commit(&self) -> io::Result<()> {
+        if DirectoryStorage::folder_of(&self.path).is_some()
+            || SqliteStorage::is_database(&self.path)
+        {
+            return Ok(());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if crate::encryption::is_encrypted(&fs::read(&self.path)?) {
+            return Ok(());
+        }
+        if !self.exists() {
+            self.git(&["init", "--quiet"])?;
+        }
+        let before = self.head().ok();
+        let after = fs::read_to_string(&self.path)?;
+        let after = FileStorage::parse(&after, &self.path).map_err(into_io)?;
+
+        let file = self.file.to_string_lossy().into_owned();
+        self.git(&["add", "--", &file])?;
+        if self
+            .command(&["diff", "--cached", "--quiet"])?
+            .status
+            .success()
+        {
+            return Ok(());
+        }
+        let message = match before {
+            Some(before) => summarize(&before, &after),
+            None => "First version".to_owned(),
+        };
+        self.git(&["commit", "--quiet", "--no-verify", "-m", &message])?;
+        Ok(())
+    }
+
+    /// Every version kept, newest first.
+    pub fn versions(&self) -> io::Result<Vec<Version>> {
+        let head = ["rev-parse", "--quiet", "--verify", "HEAD"];
+        if !self.exists() || !self.command(&head)?.status.success() {
+            return Ok(Vec::new());
+        }
+        let log = self.git(&["log", "--format=%H%x09%aI%x09%s"])?;
+        Ok(log
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let id = fields.next()?.to_owned();
+                let time = DateTime::parse_from_rfc3339(fields.next()?).ok()?;
+                Some(Version {
+                    id,
+                    time: time.with_timezone(&Local),
+                    summary: fields.next().unwrap_or_default().to_owned(),
+                })
+            })
+            .collect())
+    }
+
+    /// The sheet as it was saved in `version`.
+    pub fn load(&self, version: &Version) -> Result<Sheet, LoadError> {
+        self.sheet_at(&version.id)
+    }
+
+    fn head(&self) -> Result<Sheet, LoadError> {
+        self.sheet_at("HEAD")
+    }
+
+    fn sheet_at(&self, commit: &str) -> Result<Sheet, LoadError> {
+        let object = format!("{}:{}", commit, self.file.to_string_lossy());
+        let text = self.git(&["show", &object]).map_err(LoadError::Io)?;
+        FileStorage::parse(&text, &self.path)
+    }
+
+    /// Runs git on the sheet's repository, giving back what it printed, or what it complained
+    /// about if it failed.
+    fn git(&self, args: &[&str]) -> io::Result<String> {
+        let output = self.command(args)?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("git {} failed: {}", args[0], stderr.trim()),
+            ))
+        }
+    }
+
+    fn command(&self, args: &[&str]) -> io::Result<Output> {
+        let mut git_dir = OsString::from("--git-dir=");
+        git_dir.push(self.git_dir());
+        Command::new("git")
+            .arg(git_dir)
+            .arg("--work-tree=.")
+            // Whoever's using the app may not have set up git themselves, and their own
+            // settings, such as signing commits, shouldn't get in the way of these ones.
+            .args([
+                "-c",
+                "user.name=Sheet Myself",
+                "-c",
+                "user.email=sheet-myself@localhost",
+            ])
+            .args(["-c", "commit.gpgsign=false", "-c", "core.autocrlf=false"])
+            .args(args)
+            .current_dir(&self.folder)
+            .output()
+    }
+}
+
+fn into_io(e: LoadError) -> io::Error {
+    match e {
+        LoadError::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+    }
+}
+
+/// A short description of how `after` differs from `before`, going by what was logged: e.g.
+/// "+45min Guitar, -10min Piano, removed Drawing". Changes to anything other than records are
+/// just "Saved".
+///
+/// ```
+/// use chrono::Duration;
+/// use sheet_core::versions::summarize;
+/// use sheet_core::Sheet;
+///
+/// let mut before = Sheet::new("myself.sht".as_ref());
+/// before.skill("Guitar");
+/// before.skill("Drawing");
+/// let mut after: Sheet = serde_json::from_value(serde_json::to_value(&before)?)?;
+/// after.skill("Guitar").log(Duration::minutes(45));
+/// after.skill("Piano");
+/// after.skills_list.retain(|_, skill| skill.name != "Drawing");
+/// assert_eq!(summarize(&before, &after), "+45min Guitar, added Piano, removed Drawing");
+/// assert_eq!(summarize(&after, &before), "added Drawing, -45min Guitar, removed Piano");
+/// assert_eq!(summarize(&before, &before), "Saved");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn summarize(before: &Sheet, after: &Sheet) -> String {
+    let logged = |sheet: &Sheet| -> HashMap<Uuid, u64> {
+        sheet
+            .skills_list
+            .iter()
+            .map(|(id, skill)| (*id, skill.records.iter().map(|r| r.duration).sum()))
+            .collect()
+    };
+    let (logged_before, logged_after) = (logged(before), logged(after));
+    let unit_name = |id: &Uuid| {
+        let units = after.units.get(id).or_else(|| before.units.get(id));
+        units.map_or_else(|| "min".to_owned(), |unit| format!(" {}", unit.name))
+    };
+
+    let mut changes: Vec<(&str, String)> = after
+        .skills_list
+        .iter()
+        .filter_map(|(id, skill)| {
+            let now = logged_after[id];
+            let change = match logged_before.get(id) {
+                None if now == 0 => "added".to_owned(),
+                None => format!("+{}{}", now, unit_name(id)),
+                Some(&was) if now > was => format!("+{}{}", now - was, unit_name(id)),
+                Some(&was) if now < was => format!("-{}{}", was - now, unit_name(id)),
+                Some(_) => return None,
+            };
+            Some((skill.name.as_str(), change))
+        })
+        .collect();
+    changes.extend(
+        before
+            .skills_list
+            .iter()
+            .filter(|(id, _)| !after.skills_list.contains_key(id))
+            .map(|(_, skill)| (skill.name.as_str(), "removed".to_owned())),
+    );
+    if changes.is_empty() {
+        return "Saved".to_owned();
+    }
+    // Removals go last, and the rest stay in name order so the same save reads the same way.
+    changes.sort_by(|a, b| (a.1 == "removed", a.0).cmp(&(b.1 == "removed", b.0)));
+
+    let mut parts: Vec<String> = changes
+        .iter()
+        .take(SUMMARY_SKILLS)
+        .map(|(name, change)| format!("{} {}", change, name))
+        .collect();
+    if changes.len() > SUMMARY_SKILLS {
+        parts.push(format!("and {} more", changes.len() - SUMMARY_SKILLS));
+    }
+    parts.join(", ")
+}
@@ -0,0 +1,246 @@
+//! Telling other services, such as IFTTT or a home automation hub, when something worth
+//! celebrating happens on a sheet: a skill levels up, a streak reaches a milestone, or a goal is
+//! completed. Each event is POSTed to the webhook's URL as JSON, along with a sentence describing
+//! it for services which only pass text along. Only `http://` and `https://` URLs are called.
+
+use crate::goals::GoalState;
+use crate::sheet::Sheet;
+use crate::stats;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// How many days in a row count as a streak milestone.
+pub const STREAK_MILESTONES: [u32; 8] = [3, 7, 14, 30, 50, 100, 200, 365];
+
+//====================================================
+// Webhook
+//====================================================
+/// A URL to tell about events, and which ones it wants.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Webhook {
+    pub url: String,
+    pub level_up: bool,
+    pub streak: bool,
+    pub goal_completed: bool,
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            level_up: true,
+            streak: true,
+            goal_completed: true,
+        }
+    }
+}
+
+impl Webhook {
+    pub fn wants(&self, event: &Event) -> bool {
+        !self.url.trim().is_empty()
+            && match event.kind {
+                EventKind::LevelUp => self.level_up,
+                EventKind::Streak => self.streak,
+                EventKind::GoalCompleted => self.goal_completed,
+            }
+    }
+
+    /// Sends `event` from the sheet called `sheet_name`. This waits for the network, so it
+    /// shouldn't be called on the UI thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn send(&self, event: &Event, sheet_name: &str) -> Result<(), String> {
+        let url = crate::http::checked_url(&self.url)?;
+        crate::http::agent()
+            .post(url)
+            .set("Content-Type", "application/json")
+            .send_string(&event.payload(sheet_name).to_string())
+            .map(|_| ())
+            .map_err(|e| format!("{}: {}", url, e))
+    }
+}
+
+//====================================================
+// Event
+//====================================================
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    LevelUp,
+    Streak,
+    GoalCompleted,
+}
+
+impl EventKind {
+    /// What the event is called in its payload.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::LevelUp => "level_up",
+            EventKind::Streak => "streak",
+            EventKind::GoalCompleted => "goal_completed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub kind: EventKind,
+    pub skill: String,
+    /// The level reached, the days in a row, or the goal's target.
+    pub value: f64,
+    /// e.g. "Guitar reached level 5".
+    pub text: String,
+}
+
+impl Event {
+    pub fn payload(&self, sheet_name: &str) -> Value {
+        let value_name = match self.kind {
+            EventKind::LevelUp => "level",
+            EventKind::Streak => "days",
+            EventKind::GoalCompleted => "target",
+        };
+        let mut payload = json!({
+            "event": self.kind.name(),
+            "sheet": sheet_name,
+            "skill": self.skill,
+            "text": self.text,
+            // What IFTTT's Webhooks service hands on to an applet.
+            "value1": self.text,
+        });
+        payload[value_name] = json!(self.value);
+        payload
+    }
+}
+
+//====================================================
+// Milestones
+//====================================================
+/// Where a sheet's skills and goals stood at one point, to tell which events happened between
+/// then and later.
+pub struct Milestones {
+    /// Each skill's name, level and current streak.
+    skills: HashMap<Uuid, (String, u32, u32)>,
+    /// The goals which were complete, by what they are.
+    completed_goals: HashSet<(Uuid, u64, NaiveDate, NaiveDate)>,
+}
+
+impl Milestones {
+    pub fn of(sheet: &Sheet, today: NaiveDate) -> Self {
+        Self {
+            skills: sheet
+                .skills_list
+                .iter()
+                .map(|(id, skill)| {
+                    let streak = stats::current_streak(skill, today);
+                    (*id, (skill.name.clone(), skill.level(), streak))
+                })
+                .collect(),
+            completed_goals: sheet
+                .goals
+                .iter()
+                .filter(|goal| {
+                    let skill = sheet.skills_list.get(&goal.skill_id);
+                    goal.progress(skill, today).state == GoalState::Completed
+                })
+                .map(|goal| {
+                    (
+                        goal.skill_id,
+                        goal.target.to_bits(),
+                        goal.start,
+                        goal.deadline,
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// The levels each skill has reached since `before`, one at a time, so jumping from level 5
+    /// to 7 gives both 6 and 7.
+    pub fn level_ups_since(&self, before: &Milestones) -> Vec<(Uuid, u32)> {
+        let mut level_ups: Vec<(Uuid, u32)> = self
+            .skills
+            .iter()
+            .flat_map(|(id, (_, level, _))| {
+                let was_level = before.skills.get(id).map_or(0, |(_, level, _)| *level);
+                (was_level + 1..=*level).map(move |level| (*id, level))
+            })
+            .collect();
+        level_ups.sort_by_key(|(_, level)| *level);
+        level_ups
+    }
+
+    /// What's been reached since `before`. Skills which are new since then level up and start
+    /// streaks from nothing. `exp_name` is whatever EXP has been renamed to, for goals counting
+    /// it.
+    ///
+    /// ```
+    /// use chrono::Duration;
+    /// use sheet_core::webhooks::Milestones;
+    /// use sheet_core::{clock, Sheet};
+    ///
+    /// let today = clock::today();
+    /// let mut sheet = Sheet::new("myself.sht".as_ref());
+    /// let before = Milestones::of(&sheet, today);
+    /// sheet.skill("Guitar").log(Duration::hours(3));
+    /// let events = Milestones::of(&sheet, today).events_since(&before, &sheet, "EXP");
+    /// assert_eq!(events.len(), 1);
+    /// assert_eq!(events[0].text, "Guitar reached level 1");
+    /// ```
+    pub fn events_since(&self, before: &Milestones, sheet: &Sheet, exp_name: &str) -> Vec<Event> {
+        let mut events = Vec::new();
+        let mut skills: Vec<_> = self.skills.iter().collect();
+        skills.sort_by(|a, b| (a.1).0.cmp(&(b.1).0));
+        skills.iter().for_each(|(id, (name, level, streak))| {
+            let (was_level, was_streak) = before
+                .skills
+                .get(id)
+                .map_or((0, 0), |(_, level, streak)| (*level, *streak));
+            if level > &was_level {
+                events.push(Event {
+                    kind: EventKind::LevelUp,
+                    skill: name.clone(),
+                    value: *level as f64,
+                    text: format!("{} reached level {}", name, level),
+                });
+            }
+            let milestone = STREAK_MILESTONES
+                .iter()
+                .rev()
+                .find(|days| was_streak < **days && streak >= *days);
+            if let Some(days) = milestone {
+                events.push(Event {
+                    kind: EventKind::Streak,
+                    skill: name.clone(),
+                    value: *days as f64,
+                    text: format!("{} has been practiced {} days in a row", name, days),
+                });
+            }
+        });
+
+        sheet.goals.iter().for_each(|goal| {
+            let key = (
+                goal.skill_id,
+                goal.target.to_bits(),
+                goal.start,
+                goal.deadline,
+            );
+            if !self.completed_goals.contains(&key) || before.completed_goals.contains(&key) {
+                return;
+            }
+            let skill = self
+                .skills
+                .get(&goal.skill_id)
+                .map_or("", |(name, ..)| name);
+            let target = format!("{} {}", goal.target, goal.measure.label(exp_name));
+            events.push(Event {
+                kind: EventKind::GoalCompleted,
+                skill: skill.to_owned(),
+                value: goal.target,
+                text: format!("Completed the goal of {} of {}", target, skill),
+            });
+        });
+        events
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::NaiveDate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+//====================================================
+// Zones
+//====================================================
+/// How much of a day's practice a skill got, like heart-rate zones for time.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Zone {
+    /// Enough to keep from going rusty.
+    Maintenance,
+    /// Enough to keep improving.
+    Growth,
+    /// A big day.
+    Stretch,
+}
+
+impl Zone {
+    pub const ALL: [Zone; 3] = [Zone::Maintenance, Zone::Growth, Zone::Stretch];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Zone::Maintenance => "Maintenance",
+            Zone::Growth => "Growth",
+            Zone::Stretch => "Stretch",
+        }
+    }
+}
+
+/// The daily minutes a skill needs to reach each zone.
+#[derive(Clone, Copy, PartialEq, Deserialize, JsonSchema, Serialize)]
+#[serde(default)]
+pub struct Zones {
+    pub maintenance: u64,
+    pub growth: u64,
+    pub stretch: u64,
+}
+
+impl Default for Zones {
+    fn default() -> Self {
+        Self {
+            maintenance: 15,
+            growth: 30,
+            stretch: 60,
+        }
+    }
+}
+
+impl Zones {
+    pub fn minutes(&self, zone: Zone) -> u64 {
+        match zone {
+            Zone::Maintenance => self.maintenance,
+            Zone::Growth => self.growth,
+            Zone::Stretch => self.stretch,
+        }
+    }
+
+    pub fn minutes_mut(&mut self, zone: Zone) -> &mut u64 {
+        match zone {
+            Zone::Maintenance => &mut self.maintenance,
+            Zone::Growth => &mut self.growth,
+            Zone::Stretch => &mut self.stretch,
+        }
+    }
+
+    /// The highest zone `minutes` reaches, if any.
+    pub fn zone(&self, minutes: u64) -> Option<Zone> {
+        Zone::ALL
+            .iter()
+            .rev()
+            .find(|zone| minutes > 0 && minutes >= self.minutes(**zone))
+            .copied()
+    }
+
+    /// How many days from `from` to `to` (inclusive) reached each zone. Days which reached none
+    /// of them, including days with nothing logged, are counted in `missed`.
+    pub fn distribution(
+        &self,
+        minutes_per_day: &BTreeMap<NaiveDate, u64>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> ZoneDistribution {
+        let mut distribution = ZoneDistribution {
+            days: HashMap::new(),
+            missed: 0,
+        };
+        let mut day = from;
+        while day <= to {
+            let minutes = minutes_per_day.get(&day).copied().unwrap_or(0);
+            match self.zone(minutes) {
+                Some(zone) => *distribution.days.entry(zone).or_insert(0) += 1,
+                None => distribution.missed += 1,
+            }
+            day = day.succ();
+        }
+        distribution
+    }
+}
+
+pub struct ZoneDistribution {
+    pub days: HashMap<Zone, u32>,
+    pub missed: u32,
+}
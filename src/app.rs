@@ -1,303 +1,9122 @@
-use crate::skill::{SheetActionRecord, Skill};
-use chrono::{Datelike, Utc};
-use eframe::{egui, epi};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::achievements::Achievement;
+use crate::appearance::{Appearance, Colors, Palette, Theme};
+use crate::attributes::Attribute;
+use crate::bonus::{self, BonusRule, PracticeDays, RuleContext};
+use crate::busy::BusyState;
+use crate::challenges::BossPart;
+use crate::clock::{self, ClockZone};
+use crate::completion::Completion;
+use crate::daily_notes::{self, DailyNote, Mood};
+use crate::dashboard::{Dashboard, Ranked};
+use crate::date_picker::date_picker_ui;
+use crate::dialogs;
+use crate::editor::NoteEdit;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::encryption::Encryption;
+use crate::export::{self, CsvColumn, CsvOptions};
+use crate::file_format::FileFormat;
+use crate::format::{DurationStyle, ExpFormat, NumberStyle};
+use crate::formula::{Formula, FormulaInputs};
+use crate::goals::{Goal, GoalMeasure, GoalState};
+use crate::identity::{self, Identity, IncomingSkill, Resolution};
+use crate::import::{self, CsvMapping, ImportJob, ImportSummary, ImportedRecord, NewSkill};
+use crate::journal::JournalEntry;
+use crate::links::{Link, LinkKind};
+use crate::loot::{FoundItem, LootEntry, LootKind};
+use crate::metrics::Metric;
+use crate::migration;
+use crate::nudging::NudgePolicy;
+use crate::paper;
+use crate::planner::PlannedSession;
+use crate::pomodoro::Pomodoro;
+use crate::prerequisites::Prerequisite;
+use crate::prestige;
+use crate::publish::{self, Upload};
+use crate::recurring::Recurring;
+use crate::reminders;
+use crate::report::Report;
+use crate::season::{self, Season, SeasonLength, SeasonSummary};
+use crate::server::{self, ApiToken, TokenScope};
+use crate::settings::Settings;
+use crate::sheet::{LoadError, Sheet};
+use crate::shortcuts::{self, Action, Shortcut};
+use crate::skill::{self, Effort, ExpOverride, RecordSort, SheetActionRecord, Skill, SortColumn};
+use crate::snapshot::{self, Snapshot};
+use crate::stats::{self, ChartJob, ChartStats, RecentDays};
+use crate::storage::{DirectoryStorage, PendingSave, SqliteStorage};
+use crate::suggest;
+use crate::sync::DiskStamp;
+use crate::tools::{self, Anomaly, DateRange, DateShift, Finding};
+use crate::units::{Unit, UnitEstimate, UnitMigration};
+use crate::updates::{self, Release};
+use crate::versions::{Version, VersionHistory};
+use crate::webhooks::{self, Milestones, Webhook};
+use crate::worker::{Update, Worker};
+use crate::zones::{Zone, Zones};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use eframe::egui::{self, plot};
+use eframe::epi;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufReader, Write};
-use std::path::Path;
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
 use uuid::Uuid;
 
 fn get_default_file_path() -> Box<Path> {
     Path::new("myself.sht").into()
 }
 
+fn get_config_file_path() -> Box<Path> {
+    Path::new("sheet_myself.cfg").into()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn request_repaint_after(frame: &epi::Frame, secs: f64) {
+    let frame = frame.clone();
+    let delay = std::time::Duration::from_secs_f64(secs.max(0.0));
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        frame.request_repaint();
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+fn request_repaint_after(_frame: &epi::Frame, _secs: f64) {
+    // There's nowhere to save to on the web, so there's nothing to wake up for either.
+}
+
+/// Writes a sheet's pending save to `path`, then keeps a version of it if `git_history` is on.
+fn write_sheet(pending: PendingSave, path: &Path, backup_count: usize, git_history: bool) -> Saved {
+    let result = pending(backup_count);
+    let on_disk = DiskStamp::read(path).unwrap_or(None);
+    let version_error = match result {
+        Ok(()) if git_history => VersionHistory::for_sheet(path)
+            .commit()
+            .err()
+            .map(|e| e.to_string()),
+        _ => None,
+    };
+    Saved {
+        result,
+        on_disk,
+        version_error,
+    }
+}
+
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)] // if we add new fields, give them default values when deserializing old state
 pub struct SheetMyselfApp {
-    player_name: String,
-    skills_list: HashMap<Uuid, Skill>,
+    /// The set of sheets that were open last time, reopened on startup.
+    open_sheet_paths: Vec<PathBuf>,
+    active_sheet: usize,
+
     // this how you opt-out of serialization of a member
-    // #[cfg_attr(feature = "persistence", serde(skip))]
+    settings: Settings,
+
+    #[serde(skip)]
+    sheets: Vec<Sheet>,
+    #[serde(skip)]
+    page: Page,
+    /// When the next autosave wake-up has been scheduled for, in `egui` input time.
+    #[serde(skip)]
+    autosave_wakeup: Option<f64>,
+    #[serde(skip)]
+    csv_export: Option<CsvExportWindow>,
+    #[serde(skip)]
+    date_shift: Option<DateShiftWindow>,
+    #[serde(skip)]
+    csv_import: Option<CsvImportWindow>,
+    #[serde(skip)]
+    data_health_open: bool,
+    #[serde(skip)]
+    activity_import: Option<ActivityImportWindow>,
+    #[serde(skip)]
+    text_export: Option<TextExportWindow>,
+    #[serde(skip)]
+    paper_log: Option<PaperLogWindow>,
+    /// Sheets which couldn't be opened, to ask what to do about one at a time. They stay in the
+    /// config until dealt with, so they're tried again next time rather than forgotten.
+    #[serde(skip)]
+    load_failures: Vec<LoadFailure>,
+    #[serde(skip)]
+    month_grid: Option<MonthGridWindow>,
+    #[serde(skip)]
+    paste_rows: Option<PasteRowsWindow>,
+    #[serde(skip)]
+    skill_list: Option<SkillListWindow>,
+    #[serde(skip)]
+    skill_import: Option<SkillImportWindow>,
+    #[serde(skip)]
+    exp_override: Option<ExpOverrideWindow>,
+    #[serde(skip)]
+    record_editor: Option<RecordEditorWindow>,
+    #[serde(skip)]
+    unit_change: Option<UnitChangeWindow>,
+    #[serde(skip)]
+    complete_skill: Option<CompleteSkillWindow>,
+    #[serde(skip)]
+    session_links: Option<SessionLinksWindow>,
+    #[serde(skip)]
+    reflection: Option<ReflectionWindow>,
+    #[serde(skip)]
+    versions: Option<VersionsWindow>,
+    #[serde(skip)]
+    import_progress: Option<ImportProgressWindow>,
+    #[serde(skip)]
+    charts: Option<ChartsInProgress>,
+    /// Loot which has dropped and not been looked at yet.
+    #[serde(skip)]
+    new_loot: Vec<FoundItem>,
+    /// The last day the streak reminder went off, so it only goes off once a day.
+    #[serde(skip)]
+    reminded_on: Option<NaiveDate>,
+    /// When the next reminder wake-up has been scheduled for, in local time.
+    #[serde(skip)]
+    reminder_wakeup: Option<chrono::NaiveDateTime>,
+    #[serde(skip)]
+    reminder_banner: bool,
+    /// The last day a reminder actually went off, kept between runs so calm mode can space them
+    /// out.
+    nudged_on: Option<NaiveDate>,
+    /// The last save which didn't work, shown until it's retried or dismissed.
+    #[serde(skip)]
+    save_failure: Option<SaveFailure>,
+    /// Sheets being saved, or waiting to be.
+    #[serde(skip)]
+    saves: Vec<SheetSave>,
+    /// The sheet whose progress page should be published next, once the last one is done.
+    #[serde(skip)]
+    publish_waiting: Option<PathBuf>,
+    /// Hears back from the progress page being published in the background.
+    #[serde(skip)]
+    publishing: Option<Worker<(), io::Result<()>>>,
+    /// Something which didn't work but needn't stop anything, such as publishing after a save,
+    /// shown in a banner until it's dismissed.
+    #[serde(skip)]
+    notice: Option<String>,
+    #[serde(skip)]
+    update_check: Option<UpdateCheck>,
+    /// The port to serve the API on, from `--serve`.
+    #[serde(skip)]
+    serve_port: Option<u16>,
+    /// Requests to the API, waiting to be answered.
+    #[serde(skip)]
+    api_calls: Option<mpsc::Receiver<server::Call>>,
+    /// The sheet the API answers from, which is the one that was active when it started, so
+    /// switching tabs doesn't change where posted records go.
+    #[serde(skip)]
+    api_sheet: Option<PathBuf>,
+    /// Where each sheet's skills and goals stood after its last edit, to tell when one reaches a
+    /// milestone the webhooks should hear about.
+    #[serde(skip)]
+    milestones: HashMap<PathBuf, (f64, Milestones)>,
+    /// What the busy marker was last set to show, or `None` if it's clear.
+    #[serde(skip)]
+    busy: Option<BusyState>,
+    /// Webhook calls still being made, each giving back the ones that failed.
+    #[serde(skip)]
+    webhook_calls: Vec<mpsc::Receiver<Vec<String>>>,
+    /// Sheets changed on disk by something else, waiting to be dealt with one at a time.
+    #[serde(skip)]
+    sync_conflicts: Vec<SyncConflict>,
+    /// A sheet with unsaved changes which was asked to close, waiting on whether to save it.
+    #[serde(skip)]
+    closing_sheet: Option<PathBuf>,
+    /// File > Quit was picked while some sheets had unsaved changes, and is waiting on whether
+    /// to save them.
+    #[serde(skip)]
+    quitting: bool,
+    /// Sheets with unsaved changes are left unsaved on exit, as asked.
+    #[serde(skip)]
+    discard_on_quit: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    password_window: Option<PasswordWindow>,
+    /// When the next check for notes saved in an external editor is due, in `egui` input time.
+    #[serde(skip)]
+    note_poll_wakeup: Option<f64>,
+    /// The appearance egui was last set up with, and the system theme at the time.
+    #[serde(skip)]
+    applied_appearance: Option<(Appearance, Option<bool>)>,
+}
+
+/// State of the File > Import > CSV wizard while it's open.
+struct CsvImportWindow {
+    path: PathBuf,
+    text: String,
+    delimiter: char,
+    rows: Vec<Vec<String>>,
+    mapping: CsvMapping,
+    /// `rows` read with `results_mapping`, kept until the mapping changes so large files aren't
+    /// re-read every frame.
+    results: Vec<Result<ImportedRecord, String>>,
+    results_mapping: Option<CsvMapping>,
+}
+
+impl CsvImportWindow {
+    fn open(path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        let delimiter = import::guess_delimiter(&text);
+        let rows = import::parse_csv(&text, delimiter);
+        let mapping = rows
+            .first()
+            .map(|header| CsvMapping::guess(header))
+            .unwrap_or_default();
+        Some(Self {
+            path: path.to_path_buf(),
+            text,
+            delimiter,
+            rows,
+            mapping,
+            results: Vec::new(),
+            results_mapping: None,
+        })
+    }
+
+    /// What to call each column in the mapping drop-downs.
+    fn column_names(&self) -> Vec<String> {
+        let column_count = self.rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        (0..column_count)
+            .map(|column| {
+                let header = self
+                    .rows
+                    .first()
+                    .filter(|_| self.mapping.has_header)
+                    .and_then(|header| header.get(column))
+                    .filter(|name| !name.trim().is_empty());
+                match header {
+                    Some(name) => name.clone(),
+                    None => format!("Column {}", column + 1),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Shows how far along an import is, and what it added once it's done. The records are got
+/// ready on a worker, and only added to the sheet once it hands the job back.
+struct ImportProgressWindow {
+    /// The job before it's been handed to the worker, and once it's back.
+    job: Option<ImportJob>,
+    worker: Option<Worker<usize, ImportJob>>,
+    processed: usize,
+    total: usize,
+    /// Set once the worker has been asked to stop and keep what it's done.
+    stopping: bool,
+    /// Set once the records have been added to the sheet.
+    result: Option<ImportSummary>,
+}
+
+impl ImportProgressWindow {
+    fn start(sheet: &Sheet, records: Vec<ImportedRecord>) -> Self {
+        let job = ImportJob::new(sheet, records);
+        Self {
+            total: job.total(),
+            job: Some(job),
+            worker: None,
+            processed: 0,
+            stopping: false,
+            result: None,
+        }
+    }
+}
+
+/// The charts page's figures for one skill, worked out on a worker and filled in as they come
+/// back.
+struct ChartsInProgress {
+    skill_id: Uuid,
+    /// When the sheet had last been edited when they were started, so an edit starts them over.
+    edited: f64,
+    stats: ChartStats,
+    processed: usize,
+    total: usize,
+    worker: Option<Worker<(usize, ChartStats), ChartStats>>,
+}
+
+impl ChartsInProgress {
+    fn start(frame: &epi::Frame, skill_id: Uuid, skill: &Skill, edited: f64) -> Self {
+        // Often enough for the charts to fill in smoothly, without copying them out too often.
+        const RECORDS_PER_UPDATE: usize = 5000;
+
+        let mut job = ChartJob::new(skill);
+        let total = job.total();
+        let worker = Worker::spawn(frame, move |reporter| {
+            while !job.step(RECORDS_PER_UPDATE) {
+                if !reporter.progress((job.processed(), job.stats())) {
+                    break;
+                }
+            }
+            job.stats()
+        });
+        Self {
+            skill_id,
+            edited,
+            stats: ChartStats::default(),
+            processed: 0,
+            total,
+            worker: Some(worker),
+        }
+    }
+
+    /// Picks up whatever the worker has sent back since the last frame.
+    fn update(&mut self) {
+        match self.worker.as_ref().and_then(Worker::poll) {
+            Some(Update::Progress((processed, stats))) => {
+                self.processed = processed;
+                self.stats = stats;
+            }
+            Some(Update::Done(stats)) => {
+                self.processed = self.total;
+                self.stats = stats;
+                self.worker = None;
+            }
+            Some(Update::Stopped) => self.worker = None,
+            None => {}
+        }
+    }
+}
+
+/// Where the entries for one project or activity should go.
+#[derive(Clone, Copy, PartialEq)]
+enum ProjectTarget {
+    Skip,
+    /// A new skill named after the project.
+    NewSkill,
+    Existing(Uuid),
+}
+
+/// Where an [`ActivityImportWindow`] is importing from.
+struct ActivitySource {
+    name: &'static str,
+    /// What the source calls the things which become skills.
+    group_name: &'static str,
+    kind: dialogs::FileKind,
+    parse: fn(&str) -> Result<Vec<ImportedRecord>, String>,
+    /// How long each entry is, for sources which only keep that something was done, not for how
+    /// long. The import window lets it be changed.
+    check_in_minutes: Option<u64>,
+}
+
+impl ActivitySource {
+    /// How long `record` is once each check-in is taken to be `check_in_minutes`.
+    fn duration(&self, record: &ImportedRecord, check_in_minutes: u64) -> u64 {
+        match self.check_in_minutes {
+            Some(each) if each > 0 => record.duration / each * check_in_minutes,
+            _ => record.duration,
+        }
+    }
+}
+
+const TOGGL_SOURCE: ActivitySource = ActivitySource {
+    name: "Toggl Track",
+    group_name: "Project",
+    kind: dialogs::TOGGL,
+    parse: import::parse_toggl,
+    check_in_minutes: None,
+};
+
+const QS_SOURCE: ActivitySource = ActivitySource {
+    name: "Quantified Self CSV",
+    group_name: "Activity",
+    kind: dialogs::CSV,
+    parse: import::parse_qs_csv,
+    check_in_minutes: None,
+};
+
+const ICS_SOURCE: ActivitySource = ActivitySource {
+    name: "Calendar (iCal)",
+    group_name: "Event",
+    kind: dialogs::ICALENDAR,
+    parse: import::parse_ics,
+    check_in_minutes: None,
+};
+
+const HABITICA_SOURCE: ActivitySource = ActivitySource {
+    name: "Habitica",
+    group_name: "Task",
+    kind: dialogs::HABITICA,
+    parse: import::parse_habitica,
+    check_in_minutes: Some(import::HABITICA_CHECK_IN_MINUTES),
+};
+
+const ACTIVITY_SOURCES: [&ActivitySource; 4] =
+    [&TOGGL_SOURCE, &QS_SOURCE, &ICS_SOURCE, &HABITICA_SOURCE];
+
+/// State of the window for importing from another time tracker, such as File > Import > Toggl,
+/// while it's open. Each of the source's projects can be sent to any skill.
+struct ActivityImportWindow {
+    source: &'static ActivitySource,
+    path: PathBuf,
+    records: Vec<ImportedRecord>,
+    /// Project name to where its entries go.
+    targets: BTreeMap<String, ProjectTarget>,
+    /// Title patterns, as [`import::title_matches`] reads them, and where the entries they match
+    /// go. The first one matching a project wins over its own target.
+    patterns: Vec<(String, ProjectTarget)>,
+    /// How long each check-in counts as, for sources which only have check-ins.
+    check_in_minutes: u64,
+    merge_same_day: bool,
+    error: Option<String>,
+}
+
+impl ActivityImportWindow {
+    fn open(source: &'static ActivitySource, path: &Path, sheet: &Sheet) -> Self {
+        let parsed = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| (source.parse)(&text));
+        let (records, error) = match parsed {
+            Ok(records) => (records, None),
+            Err(error) => (Vec::new(), Some(error)),
+        };
+        let targets = records
+            .iter()
+            .map(|record| {
+                let target = match import::find_skill(sheet, &record.skill) {
+                    Some(id) => ProjectTarget::Existing(id),
+                    None => ProjectTarget::NewSkill,
+                };
+                (record.skill.clone(), target)
+            })
+            .collect();
+        Self {
+            source,
+            path: path.to_path_buf(),
+            records,
+            targets,
+            patterns: Vec::new(),
+            check_in_minutes: source.check_in_minutes.unwrap_or(0),
+            merge_same_day: true,
+            error,
+        }
+    }
+
+    /// The entries as they'll be added to `sheet`, renamed to the skills they're going to.
+    fn mapped_records(&self, sheet: &Sheet) -> Vec<ImportedRecord> {
+        let records: Vec<ImportedRecord> = self
+            .records
+            .iter()
+            .filter_map(|record| {
+                let pattern = self
+                    .patterns
+                    .iter()
+                    .find(|(pattern, _)| import::title_matches(pattern, &record.skill));
+                let target = match pattern {
+                    Some((_, target)) => target,
+                    None => self.targets.get(&record.skill)?,
+                };
+                let skill = match target {
+                    ProjectTarget::Skip => return None,
+                    ProjectTarget::NewSkill => record.skill.clone(),
+                    ProjectTarget::Existing(id) => sheet.skills_list.get(id)?.name.clone(),
+                };
+                Some(ImportedRecord {
+                    skill,
+                    duration: self.source.duration(record, self.check_in_minutes),
+                    ..record.clone()
+                })
+            })
+            .collect();
+        if self.merge_same_day {
+            import::merge_same_day(&records)
+        } else {
+            records
+        }
+    }
+}
+
+/// A drop-down for picking one of the CSV columns.
+fn column_combo_ui(ui: &mut egui::Ui, label: &str, column: &mut usize, names: &[String]) {
+    egui::ComboBox::from_label(label)
+        .selected_text(names.get(*column).cloned().unwrap_or_default())
+        .show_ui(ui, |ui| {
+            names.iter().enumerate().for_each(|(idx, name)| {
+                ui.selectable_value(column, idx, name);
+            });
+        });
+}
+
+/// State of the Tools > Shift Dates window while it's open.
+struct DateShiftWindow {
+    /// Only shift this skill, or every skill on the sheet if `None`.
+    skill: Option<Uuid>,
+    shift: DateShift,
+    limit_to_range: bool,
+    from: NaiveDate,
+    to: NaiveDate,
+}
+
+impl Default for DateShiftWindow {
+    fn default() -> Self {
+        let today = clock::today();
+        Self {
+            skill: None,
+            shift: DateShift::default(),
+            limit_to_range: false,
+            from: today,
+            to: today,
+        }
+    }
+}
+
+impl DateShiftWindow {
+    fn range(&self) -> DateRange {
+        if self.limit_to_range {
+            DateRange {
+                from: Some(self.from),
+                to: Some(self.to),
+            }
+        } else {
+            DateRange::default()
+        }
+    }
+}
+
+/// State of the File > Export > CSV window while it's open.
+#[derive(Default)]
+struct CsvExportWindow {
+    options: CsvOptions,
+    error: Option<String>,
+}
+
+/// State of a window previewing some generated text before it's copied or saved, such as the
+/// Markdown report.
+struct TextExportWindow {
+    title: &'static str,
+    text: String,
+    kind: &'static dialogs::FileKind,
+    /// What the save dialog suggests calling the file.
+    file_name: String,
+    error: Option<String>,
+}
+
+/// Asking GitHub for the latest release, from Help > Check for Updates or when the app starts.
+struct UpdateCheck {
+    /// Whether it was asked for. The check at startup only says anything if there's a new
+    /// version.
+    asked: bool,
+    /// Hears back from the check while it's still going.
+    pending: Option<mpsc::Receiver<Result<Release, String>>>,
+    result: Option<Result<Release, String>>,
+}
+
+/// A file which couldn't be written.
+struct SaveFailure {
+    path: PathBuf,
+    error: io::Error,
+}
+
+/// A sheet being saved in the background. Its file is only written by one save at a time, so
+/// one asked for while it's being saved waits for this one and then saves it again.
+struct SheetSave {
+    path: PathBuf,
+    /// The sheet's `last_edit_time` when it was saved, to tell whether it's changed since.
+    as_of: f64,
+    /// What's to be written, until it's handed to the worker.
+    pending: Option<PendingSave>,
+    worker: Option<Worker<(), Saved>>,
+    /// Whether it's been asked to be saved again once this is done.
+    again: bool,
+    /// Whether to close the sheet once it's saved.
+    close_after: bool,
+    /// What to say once it's saved.
+    notice: Option<String>,
+}
+
+/// How a save in the background went.
+struct Saved {
+    result: io::Result<()>,
+    on_disk: Option<DiskStamp>,
+    /// Why a version of the sheet couldn't be kept, if it couldn't.
+    version_error: Option<String>,
+}
+
+/// A sheet whose file was changed by something else since it was opened or last saved, which
+/// isn't saved again until it's been decided what to do about it.
+struct SyncConflict {
+    path: PathBuf,
+    /// Why taking their version or merging it didn't work, if it didn't.
+    error: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum SyncChoice {
+    KeepMine,
+    TakeTheirs,
+    Merge,
+    Later,
+}
+
+#[derive(Clone, Copy)]
+enum CloseChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+/// State of the File > Set Password window while it's open.
+#[cfg(not(target_arch = "wasm32"))]
+struct PasswordWindow {
+    /// The sheet whose password is being set.
+    path: PathBuf,
+    password: String,
+    confirm: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+enum PasswordChoice {
+    Set,
+    Remove,
+    Cancel,
+}
+
+/// A sheet which couldn't be opened, or a backup which couldn't be restored.
+struct LoadFailure {
+    path: PathBuf,
+    error: LoadError,
+    /// The open sheet `path` was being restored into, if it's a backup.
+    restoring_into: Option<PathBuf>,
+    /// What's been typed in to unlock it, if it's encrypted.
+    password: String,
+}
+
+impl LoadFailure {
+    fn new(path: &Path, error: LoadError) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            error,
+            restoring_into: None,
+            password: String::new(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let path = self.path.display();
+        match &self.error {
+            LoadError::NotFound => format!(
+                "There's no file at {}. It may have been moved or renamed, or be on a drive \
+                 which isn't connected.",
+                path
+            ),
+            LoadError::Io(e) => format!("{} couldn't be read: {}", path, e),
+            LoadError::Parse(e) => format!(
+                "{} isn't a sheet, or has been damaged ({}). It's been left as it is.",
+                path, e
+            ),
+            LoadError::Migration(e) => format!(
+                "{} was {}. It needs a newer version of Sheet Myself to open it.",
+                path, e
+            ),
+            LoadError::NeedsPassword => format!("{} is encrypted. What's its password?", path),
+            LoadError::WrongPassword => format!(
+                "That isn't the password for {}, or the file has been damaged.",
+                path
+            ),
+        }
+    }
+}
+
+/// What's been picked in the window about a sheet which couldn't be opened.
+enum LoadFailureChoice {
+    Retry,
+    #[cfg(not(target_arch = "wasm32"))]
+    Unlock(String),
+    OpenOther(PathBuf),
+    StartNew,
+    Close,
+}
+
+/// State of the File > Export > Blank Paper Log window while it's open.
+struct PaperLogWindow {
+    /// Any day in the month to print.
+    month: NaiveDate,
+    error: Option<String>,
+}
+
+/// State of the Tools > Month Grid window while it's open.
+struct MonthGridWindow {
+    /// Any day in the month being shown.
+    month: NaiveDate,
+    /// What's been typed into each cell, by skill and day. Cells from other months are kept, so
+    /// several months can be filled in before adding them all.
+    cells: HashMap<(Uuid, NaiveDate), String>,
+}
+
+impl MonthGridWindow {
+    fn new(month: NaiveDate) -> Self {
+        Self {
+            month,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Every filled-in cell's minutes, and how many cells couldn't be read.
+    fn entries(&self) -> (Vec<(Uuid, NaiveDate, u64)>, usize) {
+        let mut entries = Vec::new();
+        let mut unreadable = 0;
+        self.cells
+            .iter()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .for_each(
+                |((id, date), text)| match import::parse_duration_minutes(text) {
+                    Some(minutes) if minutes > 0 => entries.push((*id, *date, minutes)),
+                    Some(_) => {}
+                    None => unreadable += 1,
+                },
+            );
+        (entries, unreadable)
+    }
+}
+
+/// State of the window for pasting rows copied from a spreadsheet into one skill.
+struct PasteRowsWindow {
+    skill_id: Uuid,
+    text: String,
+    rows: Vec<Result<SheetActionRecord, String>>,
+}
+
+impl PasteRowsWindow {
+    fn new(skill_id: Uuid, text: String) -> Self {
+        Self {
+            skill_id,
+            rows: import::parse_pasted_rows(&text),
+            text,
+        }
+    }
+}
+
+/// State of the window for typing or pasting a list of skills to add all at once.
+#[derive(Default)]
+struct SkillListWindow {
+    text: String,
+    skills: Vec<Result<NewSkill, String>>,
+}
+
+/// Bringing in the skills from another sheet, such as a shared template, with what to do about
+/// each one the sheet already has.
+struct SkillImportWindow {
+    path: PathBuf,
+    skills: Vec<IncomingSkill>,
+}
+
+/// Setting a record's EXP by hand.
+struct ExpOverrideWindow {
+    skill_id: Uuid,
+    record: usize,
+    /// The record's date, to make sure it's still the same record when the EXP is set.
+    date: NaiveDate,
+    exp: f64,
+    reason: String,
+}
+
+impl ExpOverrideWindow {
+    fn new(skill_id: Uuid, record: usize, rec: &SheetActionRecord) -> Self {
+        Self {
+            skill_id,
+            record,
+            date: rec.date,
+            exp: (rec.base_exp + rec.bonus_exp).round(),
+            reason: rec
+                .exp_override
+                .as_ref()
+                .map_or(String::new(), |o| o.reason.clone()),
+        }
+    }
+}
+
+/// Editing one of a skill's records, or a new one, away from the records grid. Nothing on the
+/// sheet changes until it's accepted, and then the skill is re-sorted and recalculated in one go.
+struct RecordEditorWindow {
+    skill_id: Uuid,
+    /// Which record is being edited, and its date when the window opened to make sure it's
+    /// still the same one, or `None` for a new record.
+    editing: Option<(usize, NaiveDate)>,
+    record: SheetActionRecord,
+    /// What's typed in for the amount, which is only read into the record once it's accepted.
+    amount: String,
+    /// Whether the skill counts something other than minutes.
+    counted: bool,
+}
+
+impl RecordEditorWindow {
+    fn new(
+        skill_id: Uuid,
+        editing: Option<usize>,
+        record: SheetActionRecord,
+        counted: bool,
+        duration_style: DurationStyle,
+    ) -> Self {
+        let amount = match (editing, counted) {
+            (None, _) => String::new(),
+            (Some(_), true) => record.duration.to_string(),
+            (Some(_), false) => duration_style.editable(record.duration),
+        };
+        Self {
+            skill_id,
+            editing: editing.map(|idx| (idx, record.date)),
+            record,
+            amount,
+            counted,
+        }
+    }
+
+    /// The amount typed in, or what's wrong with it.
+    fn amount(&self) -> Result<u64, &'static str> {
+        // Other units are counted, so they don't have hours or minutes.
+        let (amount, expected) = if self.counted {
+            (
+                self.amount.trim().parse::<u64>().ok(),
+                "Enter a whole number",
+            )
+        } else {
+            let amount = import::parse_duration_minutes(&self.amount);
+            (
+                amount,
+                "Enter up to 24 hours as minutes, h:mm or hours, like 90, 1:30 or 1.5h",
+            )
+        };
+        match amount {
+            Some(0) => Err("It needs to be more than 0"),
+            Some(amount) => Ok(amount),
+            None => Err(expected),
+        }
+    }
+}
+
+/// Switching a skill which already has records between minutes and another unit.
+struct UnitChangeWindow {
+    skill_id: Uuid,
+    /// What the skill will be logged in; `None` is minutes.
+    to: Option<Unit>,
+    /// What its records are in now, for the explanation.
+    from_name: String,
+    /// How they convert, when they're converted.
+    rate: UnitEstimate,
+    /// Whether converting to another unit also gives it this estimate, so the skill carries on
+    /// counting toward time totals.
+    keep_in_totals: bool,
+    /// Whether the old records are moved onto a skill of their own instead of being converted.
+    split: bool,
+    archive_name: String,
+}
+
+impl UnitChangeWindow {
+    fn new(skill_id: Uuid, skill: &Skill, current: Option<&Unit>) -> Self {
+        let from_name = current.map_or("minutes".to_owned(), |unit| unit.name.clone());
+        Self {
+            skill_id,
+            to: match current {
+                Some(_) => None,
+                None => Some(Unit::default()),
+            },
+            rate: current.and_then(|unit| unit.estimate).unwrap_or_default(),
+            keep_in_totals: true,
+            split: false,
+            archive_name: format!("{} ({})", skill.name, from_name),
+            from_name,
+        }
+    }
+}
+
+/// Marking a skill as completed, then celebrating it once it is.
+struct CompleteSkillWindow {
+    skill_id: Uuid,
+    achievement: String,
+    date: NaiveDate,
+    /// What it was completed with, once it has been.
+    completed: Option<Completion>,
+}
+
+impl CompleteSkillWindow {
+    fn new(skill_id: Uuid) -> Self {
+        Self {
+            skill_id,
+            achievement: String::new(),
+            date: clock::today(),
+            completed: None,
+        }
+    }
+}
+
+/// Offered when a timer is stopped, to link the session it logged to what it produced.
+struct SessionLinksWindow {
+    skill_id: Uuid,
+    date: NaiveDate,
+    text: String,
+}
+
+/// Offered when a skill levels up, to write a line about it in the skill's journal.
+struct ReflectionWindow {
+    skill_id: Uuid,
+    /// Which of the skill's journal entries it's for.
+    entry: usize,
+    text: String,
+}
+
+/// The versions kept in a sheet's history, newest first, to pick one to restore.
+struct VersionsWindow {
+    path: PathBuf,
+    versions: Result<Vec<Version>, String>,
+    error: Option<String>,
+}
+
+impl VersionsWindow {
+    fn open(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            versions: VersionHistory::for_sheet(path)
+                .versions()
+                .map_err(|e| e.to_string()),
+            error: None,
+        }
+    }
+}
+
+/// The views which can be picked from the left side panel.
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Page {
+    Dashboard,
+    Today,
+    #[default]
+    Skills,
+    Goals,
+    Boss,
+    Planner,
+    Pending,
+    TimeAudit,
+    BonusRules,
+    Snapshots,
+    Charts,
+    Correlations,
+    Activity,
+    Loot,
+    Legacy,
+    Achievements,
+    HallOfFame,
+    SkillTree,
+    Settings,
+}
+
+impl SheetMyselfApp {
+    /// Writes the list of open sheets out so that the same set is reopened next time.
+    fn save_config(&mut self) {
+        self.open_sheet_paths = self
+            .sheets
+            .iter()
+            .map(|s| s.path.clone())
+            .chain(
+                self.load_failures
+                    .iter()
+                    .filter(|f| f.restoring_into.is_none())
+                    .map(|f| f.path.clone()),
+            )
+            .collect();
+        let path = get_config_file_path();
+        let written = serde_json::to_string(&self)
+            .map_err(io::Error::from)
+            .and_then(|json_data| File::create(&path)?.write_all(json_data.as_bytes()));
+        self.note_save_result(&path, written);
+    }
+
+    /// Starts saving the sheet at `idx` in the background, and gives back whether it could be.
+    /// If the save doesn't work, the sheet keeps its changes and the banner says what went wrong.
+    fn save_sheet(&mut self, idx: usize) -> bool {
+        let sheet = &self.sheets[idx];
+        if let Some(save) = self.saves.iter_mut().find(|s| s.path == sheet.path) {
+            save.again = true;
+            return true;
+        }
+        // Saving over a file that something else changed would lose those changes unseen.
+        if self.sync_conflicts.iter().any(|c| c.path == sheet.path) {
+            return false;
+        }
+        if sheet.changed_on_disk() {
+            self.sync_conflicts.push(SyncConflict {
+                path: sheet.path.clone(),
+                error: None,
+            });
+            return false;
+        }
+        let path = sheet.path.clone();
+        let as_of = sheet.last_edit_time;
+        match sheet.prepare_save() {
+            Ok(pending) => {
+                self.saves.push(SheetSave {
+                    path,
+                    as_of,
+                    pending: Some(pending),
+                    worker: None,
+                    again: false,
+                    close_after: false,
+                    notice: None,
+                });
+                true
+            }
+            Err(e) => self.note_save_result(&path, Err(e)),
+        }
+    }
+
+    /// The save of the sheet at `path` which is going on, if there is one.
+    fn save_of(&mut self, path: &Path) -> Option<&mut SheetSave> {
+        self.saves.iter_mut().find(|save| save.path == path)
+    }
+
+    /// Picks up how earlier saves went, then starts writing the sheets waiting to be saved.
+    /// Writing happens on another thread, as a big sheet with backups and a version to keep can
+    /// take long enough to be felt.
+    fn save_in_background(&mut self, ctx: &egui::Context, frame: &epi::Frame) {
+        let mut idx = 0;
+        while idx < self.saves.len() {
+            let saved = match self.saves[idx].worker.as_ref().and_then(Worker::poll) {
+                Some(Update::Done(saved)) => saved,
+                Some(Update::Stopped) => Saved {
+                    result: Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "saving stopped part way",
+                    )),
+                    on_disk: None,
+                    version_error: None,
+                },
+                Some(Update::Progress(())) | None => {
+                    idx += 1;
+                    continue;
+                }
+            };
+            let save = self.saves.remove(idx);
+            self.finish_save(save, saved, ctx.input().time);
+        }
+
+        let backup_count = self.settings.backup_count;
+        let git_history = self.settings.git_history;
+        self.saves.iter_mut().for_each(|save| {
+            if let Some(pending) = save.pending.take() {
+                let path = save.path.clone();
+                save.worker = Some(Worker::spawn(frame, move |_| {
+                    write_sheet(pending, &path, backup_count, git_history)
+                }));
+            }
+        });
+    }
+
+    fn finish_save(&mut self, save: SheetSave, saved: Saved, time: f64) {
+        let idx = self.sheets.iter().position(|s| s.path == save.path);
+        if let Err(e) = saved.result {
+            self.note_save_result(&save.path, Err(e));
+            // Autosave waits a whole interval before trying again, rather than every frame.
+            if let Some(idx) = idx {
+                self.sheets[idx].last_edit_time = time;
+            }
+            return;
+        }
+        self.note_save_result(&save.path, Ok(()));
+        if let Some(e) = saved.version_error {
+            self.notice = Some(format!("Couldn't keep a version of the sheet: {}", e));
+        }
+        if let Some(notice) = save.notice {
+            self.notice = Some(notice);
+        }
+        if self.settings.publish.after_save {
+            self.publish_waiting = Some(save.path.clone());
+        }
+        let idx = match idx {
+            Some(idx) => idx,
+            None => return,
+        };
+        self.sheets[idx].saved_as_of(save.as_of, saved.on_disk);
+        if save.again {
+            if self.save_sheet(idx) && save.close_after {
+                if let Some(again) = self.save_of(&save.path) {
+                    again.close_after = true;
+                }
+            }
+        } else if save.close_after && self.sheets.len() > 1 {
+            self.close_sheet(idx);
+        }
+    }
+
+    /// Picks up how the last publish went, then starts on the next one if a sheet is waiting.
+    /// Writing and uploading happen on another thread, as the upload could take a while.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn publish_in_background(&mut self, frame: &epi::Frame) {
+        if let Some(publishing) = &self.publishing {
+            match publishing.poll() {
+                Some(Update::Done(result)) => {
+                    self.notice = result
+                        .err()
+                        .map(|e| format!("Couldn't publish the progress page: {}", e));
+                }
+                Some(Update::Stopped) => {}
+                Some(Update::Progress(())) | None => return,
+            }
+            self.publishing = None;
+        }
+
+        let path = match self.publish_waiting.take() {
+            Some(path) => path,
+            None => return,
+        };
+        let sheet = match self.sheets.iter().find(|sheet| sheet.path == path) {
+            Some(sheet) => sheet,
+            None => return,
+        };
+        let page = publish::Page::render(
+            sheet,
+            &self.settings.publish,
+            &self.settings.exp_format,
+            clock::today(),
+        );
+        self.publishing = Some(Worker::spawn(frame, move |_| page.publish()));
+    }
+
+    /// Works out whether the active sheet has reached any milestones since it was last edited.
+    /// Levels reached go in the skills' journals, asking for a line about the latest if that's
+    /// turned on, and the webhooks which want to know are told.
+    fn check_milestones(&mut self, ctx: &egui::Context, frame: &epi::Frame) {
+        let sheet = &mut self.sheets[self.active_sheet];
+        let seen = self.milestones.get(&sheet.path);
+        if seen.map_or(false, |(time, _)| *time == sheet.last_edit_time) {
+            self.call_webhooks(frame, &[]);
+            return;
+        }
+        let now = Milestones::of(sheet, clock::today());
+        // The first look at a sheet is only something to compare later ones with.
+        let (events, level_ups) = seen.map_or_else(Default::default, |(_, before)| {
+            let events = now.events_since(before, sheet, &self.settings.exp_format.name);
+            (events, now.level_ups_since(before))
+        });
+
+        let mut latest = None;
+        level_ups.iter().for_each(|(skill_id, level)| {
+            if let Some(entry) = sheet.journal_level_up(skill_id, *level) {
+                latest = Some((*skill_id, entry));
+            }
+        });
+        if latest.is_some() {
+            sheet.mark_dirty(ctx.input().time);
+        }
+        if let (Some((skill_id, entry)), true) = (latest, self.settings.reflect_on_level_up) {
+            self.reflection = Some(ReflectionWindow {
+                skill_id,
+                entry,
+                text: String::new(),
+            });
+        }
+        // Journaling is an edit too, which shouldn't count as reaching anything new.
+        self.milestones
+            .insert(sheet.path.clone(), (sheet.last_edit_time, now));
+        self.call_webhooks(frame, &events);
+    }
+
+    /// Tells the webhooks which want to know about `events`, on another thread, and shows any
+    /// calls made earlier which failed.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn call_webhooks(&mut self, frame: &epi::Frame, events: &[webhooks::Event]) {
+        let mut failures = Vec::new();
+        self.webhook_calls.retain(|calls| match calls.try_recv() {
+            Ok(failed) => {
+                failures.extend(failed);
+                false
+            }
+            Err(mpsc::TryRecvError::Empty) => true,
+            Err(mpsc::TryRecvError::Disconnected) => false,
+        });
+        if !failures.is_empty() {
+            self.notice = Some(format!("Couldn't call a webhook: {}", failures.join("; ")));
+        }
+
+        let calls: Vec<(Webhook, webhooks::Event)> = events
+            .iter()
+            .flat_map(|event| {
+                self.settings
+                    .webhooks
+                    .iter()
+                    .filter(move |hook| hook.wants(event))
+                    .map(move |hook| (hook.clone(), event.clone()))
+            })
+            .collect();
+        if calls.is_empty() {
+            return;
+        }
+        let sheet_name = self.sheets[self.active_sheet].display_name();
+        let (sender, receiver) = mpsc::channel();
+        let frame = frame.clone();
+        std::thread::spawn(move || {
+            let failed = calls
+                .iter()
+                .filter_map(|(hook, event)| hook.send(event, &sheet_name).err())
+                .collect();
+            let _ = sender.send(failed);
+            frame.request_repaint();
+        });
+        self.webhook_calls.push(receiver);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn call_webhooks(&mut self, _frame: &epi::Frame, _events: &[webhooks::Event]) {
+        // A page can't POST to other sites without them allowing it, which webhooks don't.
+    }
+
+    /// Keeps the busy marker in step with the timers: set while any is running, pushed on while
+    /// they keep going, and cleared once they've all stopped or it's been turned off.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update_busy_status(&mut self, frame: &epi::Frame) {
+        let settings = &self.settings.busy;
+        let now = Utc::now();
+        let state = if settings.enabled {
+            settings.state(&self.sheets, now)
+        } else {
+            None
+        };
+        if state == self.busy {
+            return;
+        }
+        if let Err(e) = settings.apply(state.as_ref(), self.busy.is_some()) {
+            self.notice = Some(format!("Couldn't update the busy status: {}", e));
+        }
+        if let Some(state) = &state {
+            let secs = (state.until - now).num_milliseconds() as f64 / 1000.0;
+            request_repaint_after(frame, secs / 2.0);
+        }
+        self.busy = state;
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn update_busy_status(&mut self, _frame: &epi::Frame) {
+        // There's no calendar file or shell to mark anything busy with on the web.
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn publish_in_background(&mut self, _frame: &epi::Frame) {
+        // There's no file system or shell to publish with on the web.
+        self.publish_waiting = None;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_update_check(&mut self, asked: bool, frame: &epi::Frame) {
+        let (sender, receiver) = mpsc::channel();
+        let frame = frame.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(updates::latest_release());
+            frame.request_repaint();
+        });
+        self.update_check = Some(UpdateCheck {
+            asked,
+            pending: Some(receiver),
+            result: None,
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn start_update_check(&mut self, asked: bool, _frame: &epi::Frame) {
+        // The web version is always the one that's deployed.
+        self.update_check = Some(UpdateCheck {
+            asked,
+            pending: None,
+            result: Some(Err("there's nothing to update on the web".to_owned())),
+        });
+    }
+
+    /// What the latest release is, and its notes. A check nobody asked for stays out of sight
+    /// unless it found a newer version.
+    fn update_check_window_ui(&mut self, ctx: &egui::Context) {
+        let mut check = match self.update_check.take() {
+            Some(check) => check,
+            None => return,
+        };
+        if let Some(pending) = &check.pending {
+            match pending.try_recv() {
+                Ok(result) => check.result = Some(result),
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    check.result = Some(Err("the check stopped part way through".to_owned()));
+                }
+            }
+            if check.result.is_some() {
+                check.pending = None;
+            }
+        }
+        let newer = matches!(&check.result, Some(Ok(release)) if release.is_newer());
+        if !check.asked && !newer {
+            if check.pending.is_some() {
+                self.update_check = Some(check);
+            }
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new("Updates")
+            .id(egui::Id::new("update_check_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| match &check.result {
+                None => {
+                    ui.label("Checking for updates...");
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(
+                        Colors::of(ui.ctx()).error,
+                        format!("Couldn't check for updates: {}", e),
+                    );
+                }
+                Some(Ok(release)) => {
+                    if newer {
+                        ui.heading(format!("{} is out", release.title()));
+                        ui.label(format!("This is version {}.", updates::CURRENT_VERSION));
+                        ui.hyperlink_to("Download it", &release.html_url);
+                    } else {
+                        ui.label(format!(
+                            "Version {} is the latest.",
+                            updates::CURRENT_VERSION
+                        ));
+                    }
+                    if !release.notes().trim().is_empty() {
+                        ui.separator();
+                        ui.strong(format!("What's in {}", release.title()));
+                        egui::ScrollArea::vertical()
+                            .max_height(300.0)
+                            .show(ui, |ui| ui.label(release.notes()));
+                    }
+                }
+            });
+        if open {
+            self.update_check = Some(check);
+        }
+    }
+
+    fn notice_banner_ui(&mut self, ctx: &egui::Context) {
+        let notice = match &self.notice {
+            Some(notice) => notice,
+            None => return,
+        };
+        let mut dismiss = false;
+        egui::TopBottomPanel::top("notice_banner").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.colored_label(Colors::of(ui.ctx()).warning, notice);
+                dismiss = ui.button("Dismiss").clicked();
+            });
+        });
+        if dismiss {
+            self.notice = None;
+        }
+    }
+
+    fn note_save_result(&mut self, path: &Path, result: io::Result<()>) -> bool {
+        match result {
+            Ok(()) => {
+                if self.save_failure.as_ref().map_or(false, |f| f.path == path) {
+                    self.save_failure = None;
+                }
+                true
+            }
+            Err(error) => {
+                self.save_failure = Some(SaveFailure {
+                    path: path.to_path_buf(),
+                    error,
+                });
+                false
+            }
+        }
+    }
+
+    /// Says what went wrong with the last save until it's dealt with, as the changes are only
+    /// in memory until then.
+    fn save_failure_banner_ui(&mut self, ctx: &egui::Context) {
+        let failure = match &self.save_failure {
+            Some(failure) => failure,
+            None => return,
+        };
+        let is_config = failure.path == *get_config_file_path();
+        let message = if is_config {
+            format!(
+                "Couldn't save the list of open sheets and settings to {}: {}",
+                failure.path.display(),
+                failure.error
+            )
+        } else {
+            format!(
+                "Couldn't save {}: {}. The changes are still here, but only until the app is \
+                 closed.",
+                failure.path.display(),
+                failure.error
+            )
+        };
+        let failed_sheet = self.sheets.iter().position(|s| s.path == failure.path);
+
+        let mut retry = false;
+        let mut save_as = false;
+        let mut dismiss = false;
+        egui::TopBottomPanel::top("save_failure_banner").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                ui.colored_label(Colors::of(ui.ctx()).error, message);
+                retry = ui.button("Try Again").clicked();
+                if failed_sheet.is_some() {
+                    save_as = ui.button("Save As...").clicked();
+                }
+                dismiss = ui.button("Dismiss").clicked();
+            });
+        });
+
+        if retry {
+            match failed_sheet {
+                Some(idx) => {
+                    self.save_sheet(idx);
+                }
+                None if is_config => self.save_config(),
+                None => self.save_failure = None,
+            }
+        } else if save_as {
+            if let Some(idx) = failed_sheet {
+                let current = self.sheets[idx].path.clone();
+                if let Some(path) = dialogs::pick_file_to_save(&dialogs::SHEET, &current) {
+                    self.active_sheet = idx;
+                    self.save_active_sheet_as(&path);
+                }
+            }
+        } else if dismiss {
+            self.save_failure = None;
+        }
+    }
+
+    /// (Re)loads every sheet listed in `open_sheet_paths`, falling back to the default sheet.
+    /// Any which can't be opened are left for [`Self::load_failure_window_ui`] to ask about.
+    fn load_sheets(&mut self) {
+        let default_path = get_default_file_path().to_path_buf();
+        if self.open_sheet_paths.is_empty() {
+            self.open_sheet_paths.push(default_path.clone());
+        }
+        self.sheets.clear();
+        self.load_failures.clear();
+        let sheet_format = self.settings.sheet_format;
+        let blank = |path: &Path| Sheet {
+            file_format: sheet_format,
+            ..Sheet::new(path)
+        };
+        self.open_sheet_paths.iter().for_each(|path| {
+            let opened = match Sheet::open(path) {
+                // The default sheet not being there yet is just a first run.
+                Err(LoadError::NotFound) if *path == default_path => Ok(blank(path)),
+                opened => opened,
+            };
+            match opened {
+                Ok(sheet) => self.sheets.push(sheet),
+                Err(error) => self.load_failures.push(LoadFailure::new(path, error)),
+            }
+        });
+        if self.sheets.is_empty() {
+            let near = self.open_sheet_paths.first().unwrap_or(&default_path);
+            self.sheets.push(blank(&untitled_path(near)));
+        }
+        self.active_sheet = self.active_sheet.min(self.sheets.len() - 1);
+    }
+
+    /// Switches to the sheet at `path`, opening it first if it isn't open already.
+    fn open_sheet(&mut self, path: &Path) {
+        if let Some(idx) = self.sheets.iter().position(|s| s.path == path) {
+            self.active_sheet = idx;
+            self.save_config();
+        } else {
+            self.load_sheet(path);
+        }
+    }
+
+    /// Reads the sheet at `path` and switches to it, replacing it if it's open already. If it
+    /// can't be read, whatever was open is left alone and the problem is shown instead.
+    fn load_sheet(&mut self, path: &Path) {
+        self.load_failures.retain(|f| f.path != path);
+        match Sheet::open(path) {
+            Ok(sheet) => self.show_opened_sheet(sheet),
+            Err(error) => self.load_failures.push(LoadFailure::new(path, error)),
+        }
+        self.save_config();
+    }
+
+    /// Switches to `sheet`, which has just been opened, in place of any open at the same path.
+    fn show_opened_sheet(&mut self, sheet: Sheet) {
+        match self.sheets.iter().position(|s| s.path == sheet.path) {
+            Some(idx) => {
+                self.sheets[idx] = sheet;
+                self.active_sheet = idx;
+            }
+            None => {
+                self.sheets.push(sheet);
+                self.active_sheet = self.sheets.len() - 1;
+            }
+        }
+    }
+
+    /// Restores the active sheet from `backup`, showing the problem if the backup can't be read.
+    fn restore_backup(&mut self, backup: &Path, time: f64) {
+        let sheet = self.active_sheet_mut();
+        if let Err(error) = sheet.restore_backup(backup, time) {
+            let restoring_into = Some(sheet.path.clone());
+            self.load_failures.retain(|f| f.path != backup);
+            self.load_failures.push(LoadFailure {
+                restoring_into,
+                ..LoadFailure::new(backup, error)
+            });
+        }
+    }
+
+    /// Asks what to do about the first sheet which couldn't be opened: try again, open a
+    /// different file, or leave it closed. Nothing is ever saved over it from here.
+    fn load_failure_window_ui(&mut self, ctx: &egui::Context) {
+        let failure = match self.load_failures.first_mut() {
+            Some(failure) => failure,
+            None => return,
+        };
+        let title = match failure.restoring_into {
+            Some(_) => "Couldn't Restore Backup",
+            None => "Couldn't Open Sheet",
+        };
+
+        let mut choice = None;
+        egui::Window::new(title)
+            .id(egui::Id::new("load_failure_window"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(failure.describe());
+                #[cfg(not(target_arch = "wasm32"))]
+                if matches!(
+                    failure.error,
+                    LoadError::NeedsPassword | LoadError::WrongPassword
+                ) {
+                    ui.horizontal(|ui| {
+                        ui.label("Password:");
+                        let field = ui
+                            .add(egui::TextEdit::singleline(&mut failure.password).password(true));
+                        let entered =
+                            field.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                        if ui.button("Unlock").clicked() || entered {
+                            choice = Some(LoadFailureChoice::Unlock(failure.password.clone()));
+                        }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Try Again").clicked() {
+                        choice = Some(LoadFailureChoice::Retry);
+                    }
+                    if failure.restoring_into.is_none() {
+                        if ui.button("Open Another File...").clicked() {
+                            choice = dialogs::pick_file_to_open(&dialogs::SHEET)
+                                .map(LoadFailureChoice::OpenOther);
+                        }
+                        // Only offered when there's nothing there which could be lost.
+                        if matches!(failure.error, LoadError::NotFound)
+                            && ui.button("Start a New Sheet There").clicked()
+                        {
+                            choice = Some(LoadFailureChoice::StartNew);
+                        }
+                    }
+                    if ui.button("Leave It Closed").clicked() {
+                        choice = Some(LoadFailureChoice::Close);
+                    }
+                });
+            });
+
+        let choice = match choice {
+            Some(choice) => choice,
+            None => return,
+        };
+        let failure = self.load_failures.remove(0);
+        match choice {
+            LoadFailureChoice::Retry => match &failure.restoring_into {
+                Some(target) => {
+                    if let Some(idx) = self.sheets.iter().position(|s| s.path == *target) {
+                        self.active_sheet = idx;
+                        self.restore_backup(&failure.path, ctx.input().time);
+                    }
+                }
+                None => self.load_sheet(&failure.path),
+            },
+            #[cfg(not(target_arch = "wasm32"))]
+            LoadFailureChoice::Unlock(password) => {
+                match Encryption::open(&failure.path, &password) {
+                    Ok(opened) => match &failure.restoring_into {
+                        Some(target) => {
+                            if let Some(sheet) = self.sheets.iter_mut().find(|s| s.path == *target)
+                            {
+                                sheet.replace_with(opened, ctx.input().time);
+                            }
+                        }
+                        None => {
+                            self.show_opened_sheet(opened);
+                            self.save_config();
+                        }
+                    },
+                    Err(error) => self.load_failures.insert(
+                        0,
+                        LoadFailure {
+                            error,
+                            password: String::new(),
+                            ..failure
+                        },
+                    ),
+                }
+            }
+            LoadFailureChoice::OpenOther(path) => self.open_sheet(&path),
+            LoadFailureChoice::StartNew => self.new_sheet(&failure.path),
+            LoadFailureChoice::Close => self.save_config(),
+        }
+    }
+
+    /// Asks what to do about the first sheet whose file was changed somewhere else.
+    fn sync_conflict_window_ui(&mut self, ctx: &egui::Context) {
+        let conflict = match self.sync_conflicts.first() {
+            Some(conflict) => conflict,
+            None => return,
+        };
+        let idx = match self.sheets.iter().position(|s| s.path == conflict.path) {
+            Some(idx) => idx,
+            None => {
+                self.sync_conflicts.remove(0);
+                return;
+            }
+        };
+
+        let mut choice = None;
+        egui::Window::new("Sheet Changed Somewhere Else")
+            .id(egui::Id::new("sync_conflict_window"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} has been changed since it was opened here, perhaps by a sync from \
+                     another computer. Saving now would overwrite those changes.",
+                    conflict.path.display()
+                ));
+                if let Some(error) = &conflict.error {
+                    ui.colored_label(Colors::of(ui.ctx()).error, error);
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Keep Mine")
+                        .on_hover_text("Save this version over the file")
+                        .clicked()
+                    {
+                        choice = Some(SyncChoice::KeepMine);
+                    }
+                    if ui
+                        .button("Take Theirs")
+                        .on_hover_text("Throw away the changes made here and load the file")
+                        .clicked()
+                    {
+                        choice = Some(SyncChoice::TakeTheirs);
+                    }
+                    if ui
+                        .button("Merge")
+                        .on_hover_text(
+                            "Add the skills and records only the file has to this version, then \
+                             save. Anything deleted on just one side comes back.",
+                        )
+                        .clicked()
+                    {
+                        choice = Some(SyncChoice::Merge);
+                    }
+                    if ui
+                        .button("Decide Later")
+                        .on_hover_text("Don't save it for now; this is asked again next time")
+                        .clicked()
+                    {
+                        choice = Some(SyncChoice::Later);
+                    }
+                });
+            });
+
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[idx];
+        let result = match choice {
+            None => return,
+            Some(SyncChoice::KeepMine) => {
+                sheet.on_disk = DiskStamp::read(&sheet.path).unwrap_or(None);
+                Ok(true)
+            }
+            Some(SyncChoice::TakeTheirs) => sheet.reload_from_json().map(|()| false),
+            Some(SyncChoice::Merge) => sheet.open_copy(&sheet.path).map(|theirs| {
+                let on_disk = theirs.on_disk;
+                sheet.merge_from(theirs);
+                sheet.on_disk = on_disk;
+                sheet.mark_dirty(time);
+                true
+            }),
+            Some(SyncChoice::Later) => Ok(false),
+        };
+        match result {
+            Ok(save) => {
+                self.sync_conflicts.remove(0);
+                if save {
+                    self.save_sheet(idx);
+                }
+            }
+            Err(e) => {
+                self.sync_conflicts[0].error = Some(format!("Couldn't read the file: {}", e));
+            }
+        }
+    }
+
+    /// Starts a fresh, empty sheet which will be saved to `path`.
+    fn new_sheet(&mut self, path: &Path) {
+        if let Some(idx) = self.sheets.iter().position(|s| s.path == path) {
+            self.sheets.remove(idx);
+        }
+        self.sheets.push(Sheet {
+            file_format: self.settings.sheet_format,
+            ..Sheet::new(path)
+        });
+        self.active_sheet = self.sheets.len() - 1;
+        self.save_config();
+    }
+
+    /// Moves the active sheet to `path` and saves it there; later saves go to the new path too.
+    fn save_active_sheet_as(&mut self, path: &Path) {
+        let failed_path = self.active_sheet_mut().path.clone();
+        if self.api_sheet.as_ref() == Some(&failed_path) {
+            self.api_sheet = Some(path.to_path_buf());
+        }
+        let sheet = self.active_sheet_mut();
+        sheet.path = path.to_path_buf();
+        // Whatever's there already was chosen to be replaced.
+        sheet.on_disk = DiskStamp::read(path).unwrap_or(None);
+        if self.save_sheet(self.active_sheet)
+            && self
+                .save_failure
+                .as_ref()
+                .map_or(false, |f| f.path == failed_path)
+        {
+            self.save_failure = None;
+        }
+        self.save_config();
+    }
+
+    /// Saves the active sheet at `path`, such as a folder or database next to its file, and
+    /// keeps it there from then on. The file is left where it is, in case anything else still
+    /// reads it.
+    fn move_active_sheet_to(&mut self, path: PathBuf) {
+        let old_path = self.active_sheet_mut().path.clone();
+        if path.exists() {
+            self.notice = Some(format!("There's already something at {}", path.display()));
+            return;
+        }
+        self.save_active_sheet_as(&path);
+        let notice = format!(
+            "The sheet is kept in {} now. {} was left as it was.",
+            path.display(),
+            old_path.display()
+        );
+        if let Some(save) = self.save_of(&path) {
+            save.notice = Some(notice);
+        }
+    }
+
+    /// Keeps every sheet's pomodoro ticking over, and shows the active sheet's.
+    fn pomodoro_window_ui(&mut self, ctx: &egui::Context) {
+        let now = Utc::now();
+        let time = ctx.input().time;
+        let settings = self.settings.pomodoro;
+        self.sheets.iter_mut().for_each(|sheet| {
+            let finished = sheet.pomodoro.as_mut().and_then(|pomodoro| {
+                pomodoro
+                    .advance(now, &settings)
+                    .then_some(pomodoro.skill_id)
+            });
+            if let Some(skill_id) = finished {
+                let today = clock::date_at(now);
+                sheet.complete_pomodoro(&skill_id, settings.work_minutes as u64, today);
+                sheet.mark_dirty(time);
+            }
+        });
+
+        let sheet = &mut self.sheets[self.active_sheet];
+        let pomodoro = match &sheet.pomodoro {
+            Some(pomodoro) => pomodoro,
+            None => return,
+        };
+        let skill_name = sheet
+            .skills_list
+            .get(&pomodoro.skill_id)
+            .map_or("", |skill| skill.name.as_str());
+        let remaining = pomodoro.remaining(now, &settings).num_seconds();
+        let today = clock::date_at(now);
+
+        let mut open = true;
+        egui::Window::new("Pomodoro")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.strong(skill_name);
+                ui.heading(format!(
+                    "{} {}:{:02}",
+                    pomodoro.phase.label(),
+                    remaining / 60,
+                    remaining % 60
+                ));
+                let done_today = sheet.pomodoros.get(&today).copied().unwrap_or(0);
+                ui.label(format!("{} done today", done_today));
+            });
+        ctx.request_repaint();
+
+        if !open {
+            sheet.pomodoro = None;
+        }
+    }
+
+    fn paste_rows_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.paste_rows.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[self.active_sheet];
+        let skill_name = match sheet.skills_list.get(&window.skill_id) {
+            Some(skill) => skill.name.clone(),
+            None => return,
+        };
+
+        let mut open = true;
+        let mut add = false;
+        egui::Window::new(format!("Paste Rows into {}", skill_name))
+            .id(egui::Id::new("paste_rows_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Paste rows copied from a spreadsheet: a date, a duration in minutes or \
+                     h:mm, and optionally a note.",
+                );
+                let edited = ui
+                    .add(
+                        egui::TextEdit::multiline(&mut window.text)
+                            .desired_rows(6)
+                            .code_editor(),
+                    )
+                    .changed();
+                if edited {
+                    window.rows = import::parse_pasted_rows(&window.text);
+                }
+
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("paste_rows_preview")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Date");
+                                ui.strong("Duration");
+                                ui.strong("Note");
+                                ui.end_row();
+                                window.rows.iter().enumerate().for_each(|(idx, row)| {
+                                    match row {
+                                        Ok(rec) => {
+                                            ui.label(rec.date.to_string());
+                                            ui.label(format!("{} min", rec.duration));
+                                            ui.label(rec.note.as_str());
+                                        }
+                                        Err(e) => {
+                                            ui.colored_label(
+                                                Colors::of(ui.ctx()).error,
+                                                format!("Row {}: {}", idx + 1, e),
+                                            );
+                                        }
+                                    }
+                                    ui.end_row();
+                                });
+                            });
+                    });
+
+                let good_rows = window.rows.iter().filter(|r| r.is_ok()).count();
+                let skipped = window.rows.len() - good_rows;
+                ui.horizontal(|ui| {
+                    add = ui
+                        .add_enabled(
+                            good_rows > 0,
+                            egui::Button::new(format!("Add {} records", good_rows)),
+                        )
+                        .clicked();
+                    if skipped > 0 {
+                        ui.label(format!(
+                            "{} rows can't be read and will be skipped",
+                            skipped
+                        ));
+                    }
+                });
+            });
+
+        if add {
+            let approved = !sheet.require_approval;
+            if let Some(skill) = sheet.skills_list.get_mut(&window.skill_id) {
+                skill.records.extend(
+                    window
+                        .rows
+                        .drain(..)
+                        .filter_map(Result::ok)
+                        .map(|rec| SheetActionRecord { approved, ..rec }),
+                );
+                skill.sort_actions();
+            }
+            sheet.recalculate_skill(&window.skill_id);
+            sheet.mark_dirty(time);
+        } else if open {
+            self.paste_rows = Some(window);
+        }
+    }
+
+    fn skill_list_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.skill_list.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[self.active_sheet];
+
+        let mut open = true;
+        let mut add = false;
+        egui::Window::new("New Skills from List")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "One skill per line: a name, or a name, a category and the EXP an hour \
+                     earns, separated by commas.",
+                );
+                let edited = ui
+                    .add(
+                        egui::TextEdit::multiline(&mut window.text)
+                            .desired_rows(8)
+                            .hint_text("Guitar, Music, 60\nJapanese, Languages\nChess")
+                            .code_editor(),
+                    )
+                    .changed();
+                if edited {
+                    window.skills = import::parse_skill_list(&window.text);
+                }
+
+                let mut new_count = 0;
+                let mut listed = HashSet::new();
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("skill_list_preview")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Name");
+                                ui.strong("Category");
+                                ui.strong("EXP/hour");
+                                ui.end_row();
+                                window.skills.iter().enumerate().for_each(|(idx, line)| {
+                                    match line {
+                                        Ok(skill) => {
+                                            ui.label(&skill.name);
+                                            ui.label(&skill.category);
+                                            let rate = skill
+                                                .exp_per_hour
+                                                .map_or("usual".to_owned(), |exp| exp.to_string());
+                                            ui.label(rate);
+                                            let warning = Colors::of(ui.ctx()).warning;
+                                            if import::find_skill(sheet, &skill.name).is_some() {
+                                                ui.colored_label(warning, "Already on the sheet");
+                                            } else if !listed.insert(skill.name.to_lowercase()) {
+                                                ui.colored_label(warning, "Listed already");
+                                            } else {
+                                                new_count += 1;
+                                            }
+                                        }
+                                        Err(e) => {
+                                            ui.colored_label(
+                                                Colors::of(ui.ctx()).error,
+                                                format!("Line {}: {}", idx + 1, e),
+                                            );
+                                        }
+                                    }
+                                    ui.end_row();
+                                });
+                            });
+                    });
+
+                add = ui
+                    .add_enabled(
+                        new_count > 0,
+                        egui::Button::new(format!("Add {} skills", new_count)),
+                    )
+                    .clicked();
+            });
+
+        if add {
+            let skills: Vec<NewSkill> = window.skills.drain(..).filter_map(Result::ok).collect();
+            sheet.add_skills(&skills);
+            sheet.mark_dirty(time);
+        } else if open {
+            self.skill_list = Some(window);
+        }
+    }
+
+    /// Reads the skills from the sheet at `path`, for the import window to line up with the
+    /// active sheet's.
+    fn open_skill_import(&mut self, path: &Path) {
+        match Sheet::open(path) {
+            Ok(theirs) if theirs.skills_list.is_empty() => {
+                self.notice = Some(format!("{} doesn't have any skills", path.display()));
+            }
+            Ok(theirs) => {
+                let sheet = &self.sheets[self.active_sheet];
+                self.skill_import = Some(SkillImportWindow {
+                    path: path.to_owned(),
+                    skills: identity::incoming(sheet, &theirs),
+                });
+            }
+            Err(e) => self.notice = Some(format!("Couldn't open {}: {}", path.display(), e)),
+        }
+    }
+
+    fn skill_import_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.skill_import.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[self.active_sheet];
+
+        let mut open = true;
+        let mut bring_in = false;
+        let mut close = false;
+        let file_name = window
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        egui::Window::new(format!("Import Skills from {}", file_name))
+            .id(egui::Id::new("skill_import_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Skills the sheet already has, copied from the same place or just by the same \
+                     name, can be merged into it, added under another name, or put in its place.",
+                );
+                let mut names_taken = false;
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("skill_import_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Skill");
+                                ui.strong("Records");
+                                ui.strong("On this sheet");
+                                ui.strong("What to do");
+                                ui.end_row();
+                                window
+                                    .skills
+                                    .iter_mut()
+                                    .enumerate()
+                                    .for_each(|(idx, incoming)| {
+                                        names_taken |=
+                                            skill_import_row_ui(ui, idx, incoming, sheet);
+                                        ui.end_row();
+                                    });
+                            });
+                    });
+                if names_taken {
+                    ui.colored_label(
+                        Colors::of(ui.ctx()).warning,
+                        "Each renamed skill needs a name which isn't on the sheet yet.",
+                    );
+                }
+                ui.horizontal(|ui| {
+                    bring_in = ui
+                        .add_enabled(!names_taken, egui::Button::new("Import"))
+                        .clicked();
+                    close = ui.button("Cancel").clicked();
+                });
+            });
+
+        if bring_in {
+            let summary = sheet.bring_in(window.skills);
+            sheet.mark_dirty(time);
+            self.notice = Some(format!(
+                "Added {} skills and {} records from {}",
+                summary.skills_added,
+                summary.records_added,
+                window.path.display()
+            ));
+        } else if open && !close {
+            self.skill_import = Some(window);
+        }
+    }
+
+    fn exp_override_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.exp_override.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[self.active_sheet];
+        let skill = match sheet.skills_list.get_mut(&window.skill_id) {
+            Some(skill) => skill,
+            None => return,
+        };
+        let skill_name = skill.name.clone();
+        // The record could have been deleted, or moved by a re-sort, since the window opened.
+        let record = match skill.records.get_mut(window.record) {
+            Some(record) if record.date == window.date => record,
+            _ => return,
+        };
+
+        let mut open = true;
+        let mut apply = None;
+        egui::Window::new(format!("Set EXP for {} on {}", skill_name, window.date))
+            .id(egui::Id::new("exp_override_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "EXP set by hand is kept when the sheet is recalculated, and is listed under \
+                     Data Health.",
+                );
+                egui::Grid::new("exp_override_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("EXP");
+                        ui.add(egui::DragValue::new(&mut window.exp).clamp_range(0.0..=f64::MAX));
+                        ui.end_row();
+                        ui.label("Reason");
+                        ui.text_edit_singleline(&mut window.reason);
+                        ui.end_row();
+                    });
+
+                let has_reason = !window.reason.trim().is_empty();
+                ui.horizontal(|ui| {
+                    let set = ui
+                        .add_enabled(has_reason, egui::Button::new("Set EXP"))
+                        .on_disabled_hover_text("Give a reason for setting it by hand");
+                    if set.clicked() {
+                        apply = Some(Some(ExpOverride {
+                            exp: window.exp,
+                            reason: window.reason.trim().to_owned(),
+                        }));
+                    }
+                    if record.exp_override.is_some()
+                        && ui.button("Go back to the worked out EXP").clicked()
+                    {
+                        apply = Some(None);
+                    }
+                });
+            });
+
+        match apply {
+            Some(exp_override) => {
+                record.exp_override = exp_override;
+                sheet.recalculate_skill_since(&window.skill_id, window.date);
+                sheet.mark_dirty(time);
+            }
+            None if open => self.exp_override = Some(window),
+            None => {}
+        }
+    }
+
+    /// The record editor. The record is only put back on the skill once it's accepted.
+    fn record_editor_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.record_editor.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[self.active_sheet];
+        let skill = match sheet.skills_list.get(&window.skill_id) {
+            Some(skill) => skill,
+            None => return,
+        };
+        // The record could have been deleted, or moved by a re-sort, since the window opened.
+        if let Some((idx, date)) = window.editing {
+            if skill.records.get(idx).map(|rec| rec.date) != Some(date) {
+                return;
+            }
+        }
+        let title = match window.editing {
+            Some(_) => format!("Edit {} Record", skill.name),
+            None => format!("New {} Record", skill.name),
+        };
+        let amount_label = sheet
+            .units
+            .get(&window.skill_id)
+            .map_or("Duration".to_owned(), |unit| unit.name.clone());
+        let sheet_folder = sheet.path.parent().unwrap_or_else(|| Path::new(""));
+        let require_approval = sheet.require_approval;
+
+        let mut open = true;
+        let mut accept = false;
+        let mut cancel = false;
+        egui::Window::new(title)
+            .id(egui::Id::new("record_editor_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let record = &mut window.record;
+                egui::Grid::new("record_editor_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Date");
+                        date_picker_ui(ui, egui::Id::new("record_editor_date"), &mut record.date);
+                        ui.end_row();
+
+                        ui.label(amount_label);
+                        let hint = if window.counted { "" } else { "90, 1:30, 1.5h" };
+                        ui.add(egui::TextEdit::singleline(&mut window.amount).hint_text(hint));
+                        ui.end_row();
+
+                        ui.label("Effort");
+                        egui::ComboBox::from_id_source("record_editor_effort")
+                            .selected_text(record.effort.label())
+                            .show_ui(ui, |ui| {
+                                Effort::ALL.iter().for_each(|effort| {
+                                    ui.selectable_value(
+                                        &mut record.effort,
+                                        *effort,
+                                        effort.label(),
+                                    );
+                                });
+                            });
+                        ui.end_row();
+
+                        ui.label("Intensity");
+                        ui.add(
+                            egui::DragValue::new(&mut record.intensity)
+                                .clamp_range(
+                                    SheetActionRecord::MIN_INTENSITY
+                                        ..=SheetActionRecord::MAX_INTENSITY,
+                                )
+                                .speed(0.05)
+                                .fixed_decimals(2)
+                                .suffix("x"),
+                        )
+                        .on_hover_text("How much harder or easier than usual it was");
+                        ui.end_row();
+
+                        ui.label("Note");
+                        ui.add(egui::TextEdit::multiline(&mut record.note).desired_rows(3));
+                        ui.end_row();
+
+                        ui.label("Links");
+                        let links_id = egui::Id::new("record_editor_links");
+                        links_ui(ui, links_id, &mut record.links, sheet_folder);
+                        ui.end_row();
+
+                        if require_approval {
+                            ui.label("Approved");
+                            ui.checkbox(&mut record.approved, "");
+                            ui.end_row();
+                        }
+                    });
+
+                let amount = window.amount();
+                if let Err(problem) = amount {
+                    ui.colored_label(Colors::of(ui.ctx()).error, problem);
+                }
+                ui.horizontal(|ui| {
+                    accept = ui
+                        .add_enabled(amount.is_ok(), egui::Button::new("Accept"))
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+        let amount = match (accept, window.amount()) {
+            (true, Ok(amount)) => amount,
+            _ => {
+                if open && !cancel {
+                    self.record_editor = Some(window);
+                }
+                return;
+            }
+        };
+        let mut record = window.record;
+        record.duration = amount;
+        let skill = match sheet.skills_list.get_mut(&window.skill_id) {
+            Some(skill) => skill,
+            None => return,
+        };
+        let since = match window.editing {
+            Some((idx, _)) => match skill.records.get_mut(idx) {
+                Some(existing) => {
+                    let since = existing.date.min(record.date);
+                    *existing = record;
+                    since
+                }
+                None => return,
+            },
+            None => {
+                let since = record.date;
+                skill.records.push(record);
+                since
+            }
+        };
+        sheet.recalculate_skill_since(&window.skill_id, since);
+        sheet.mark_dirty(time);
+    }
+
+    /// Answers whatever has come in through the API, from the active sheet.
+    fn answer_api_calls(&mut self, time: f64) {
+        let calls = match &self.api_calls {
+            Some(calls) => calls,
+            None => return,
+        };
+        let api_sheet = self.api_sheet.as_deref();
+        let sheet = self
+            .sheets
+            .iter_mut()
+            .find(|s| Some(s.path.as_path()) == api_sheet);
+        let sheet = match sheet {
+            Some(sheet) => sheet,
+            None => {
+                calls.try_iter().for_each(|call| {
+                    call.refuse(503, "the sheet the API logs into has been closed")
+                });
+                return;
+            }
+        };
+        calls.try_iter().for_each(|call| {
+            call.answer(
+                sheet,
+                &self.settings.exp_format,
+                &mut self.settings.api_tokens,
+                time,
+            );
+        });
+    }
+
+    /// Shows where files can be dropped while they're dragged over the window, and opens the
+    /// import preview for one once it's dropped.
+    fn dropped_files_ui(&mut self, ctx: &egui::Context) {
+        if !ctx.input().raw.hovered_files.is_empty() {
+            let painter = ctx.layer_painter(egui::LayerId::new(
+                egui::Order::Foreground,
+                egui::Id::new("file_drop_target"),
+            ));
+            let screen = ctx.input().screen_rect();
+            painter.rect_filled(screen, 0.0, egui::Color32::from_black_alpha(192));
+            painter.text(
+                screen.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop a CSV or Toggl Track export to import it",
+                egui::TextStyle::Heading.resolve(&ctx.style()),
+                egui::Color32::WHITE,
+            );
+        }
+
+        // Only one import can be previewed at a time, so anything past the first is left.
+        let dropped = ctx.input().raw.dropped_files.first().cloned();
+        match dropped {
+            Some(egui::DroppedFile {
+                path: Some(path), ..
+            }) => self.import_dropped_file(&path),
+            Some(file) => {
+                self.notice = Some(format!(
+                    "Couldn't import {}: dropped files can only be read in the desktop app",
+                    file.name
+                ));
+            }
+            None => {}
+        }
+    }
+
+    /// Picks the importer for `path` from what's in it: Toggl Track and Quantified Self exports
+    /// get their own, and any other CSV has its columns mapped by hand.
+    fn import_dropped_file(&mut self, path: &Path) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let extension = path
+            .extension()
+            .map_or(String::new(), |e| e.to_string_lossy().to_lowercase());
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.notice = Some(format!("Couldn't read {}: {}", name, e));
+                return;
+            }
+        };
+
+        let source = ACTIVITY_SOURCES.iter().find(|source| {
+            // Some sources read each other's files as having nothing in them.
+            source.kind.extensions.contains(&extension.as_str())
+                && (source.parse)(&text).map_or(false, |records| !records.is_empty())
+        });
+        if let Some(source) = source {
+            let sheet = &self.sheets[self.active_sheet];
+            self.activity_import = Some(ActivityImportWindow::open(source, path, sheet));
+        } else if dialogs::CSV.extensions.contains(&extension.as_str()) {
+            self.csv_import = CsvImportWindow::open(path);
+        } else {
+            self.notice = Some(format!(
+                "{} can't be imported. CSV files, calendars, and Toggl Track and Habitica \
+                 exports can.",
+                name
+            ));
+        }
+    }
+
+    /// Lists the versions in a sheet's history to restore one. The sheet is saved first if it
+    /// has changes, so they're kept as a version too, and it's left dirty afterwards so the
+    /// restored one is saved as the newest.
+    fn versions_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.versions.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let idx = match self.sheets.iter().position(|s| s.path == window.path) {
+            Some(idx) => idx,
+            None => return,
+        };
+        let keeping = self.settings.git_history;
+
+        let mut open = true;
+        let mut restore = None;
+        egui::Window::new(format!("Versions of {}", self.sheets[idx].display_name()))
+            .id(egui::Id::new("versions_window"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                match &window.versions {
+                    Err(error) => {
+                        ui.colored_label(
+                            Colors::of(ui.ctx()).error,
+                            format!("Couldn't read the history: {}", error),
+                        );
+                    }
+                    Ok(versions) if versions.is_empty() => {
+                        ui.label(if keeping {
+                            "No versions yet. One is kept every time the sheet is saved."
+                        } else {
+                            "No versions yet. Turn on Settings > Saving > Keep a version \
+                             history to keep one every time the sheet is saved."
+                        });
+                    }
+                    Ok(versions) => {
+                        egui::ScrollArea::vertical()
+                            .max_height(400.0)
+                            .show(ui, |ui| {
+                                egui::Grid::new("versions_grid")
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        versions.iter().enumerate().for_each(|(i, version)| {
+                                            ui.label(
+                                                version.time.format("%Y-%m-%d %H:%M").to_string(),
+                                            );
+                                            ui.label(&version.summary);
+                                            if ui.button("Restore").clicked() {
+                                                restore = Some(i);
+                                            }
+                                            ui.end_row();
+                                        });
+                                    });
+                            });
+                    }
+                }
+                if let Some(error) = &window.error {
+                    ui.colored_label(Colors::of(ui.ctx()).error, error);
+                }
+            });
+
+        if let (Some(i), Ok(versions)) = (restore, &window.versions) {
+            let history = VersionHistory::for_sheet(&window.path);
+            match history.load(&versions[i]) {
+                Ok(restored) => {
+                    if self.sheets[idx].dirty {
+                        self.save_sheet(idx);
+                    }
+                    self.sheets[idx].replace_with(restored, ctx.input().time);
+                    open = false;
+                }
+                Err(e) => window.error = Some(format!("Couldn't restore that version: {}", e)),
+            }
+        }
+        if open {
+            self.versions = Some(window);
+        }
+    }
+
+    fn unit_change_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.unit_change.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[self.active_sheet];
+        let skill = match sheet.skills_list.get(&window.skill_id) {
+            Some(skill) => skill,
+            None => return,
+        };
+        let to_name = window
+            .to
+            .as_ref()
+            .map_or("minutes".to_owned(), |unit| unit.name.clone());
+
+        let mut open = true;
+        let mut apply = false;
+        let mut cancel = false;
+        egui::Window::new(format!("Change what {} is logged in", skill.name))
+            .id(egui::Id::new("unit_change_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} has {} records in {}. They can be converted, or kept apart as they are.",
+                    skill.name,
+                    skill.records.len(),
+                    window.from_name
+                ));
+                if let Some(unit) = &mut window.to {
+                    ui.horizontal(|ui| {
+                        ui.label("Log it in:");
+                        ui.add(egui::TextEdit::singleline(&mut unit.name).desired_width(100.0));
+                    });
+                }
+                ui.separator();
+
+                ui.radio_value(&mut window.split, false, "Convert the records");
+                ui.add_enabled_ui(!window.split, |ui| {
+                    let unit_name = window
+                        .to
+                        .as_ref()
+                        .map_or(&window.from_name, |unit| &unit.name)
+                        .clone();
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut window.rate.amount)
+                                .clamp_range(0.1..=f64::MAX)
+                                .speed(1.0),
+                        );
+                        ui.label(format!("{} ≈", unit_name));
+                        ui.add(
+                            egui::DragValue::new(&mut window.rate.minutes)
+                                .clamp_range(0.1..=f64::MAX)
+                                .speed(1.0)
+                                .suffix(" min"),
+                        );
+                    });
+                    if let Some(record) = skill.records.last() {
+                        let converted = match window.to {
+                            Some(_) => window.rate.amount_for(record.duration),
+                            None => window.rate.minutes_for(record.duration),
+                        };
+                        ui.label(format!(
+                            "The last record, {} {}, would become {} {}.",
+                            record.duration,
+                            window.from_name,
+                            (converted.round() as u64).max(record.duration.min(1)),
+                            to_name
+                        ));
+                    }
+                    if window.to.is_some() {
+                        ui.checkbox(
+                            &mut window.keep_in_totals,
+                            "Keep counting it toward time totals at this rate",
+                        );
+                    }
+                    ui.label("EXP is worked out again from the converted amounts.");
+                });
+                ui.separator();
+
+                ui.radio_value(
+                    &mut window.split,
+                    true,
+                    "Keep them in their own skill, and start this one again",
+                );
+                ui.add_enabled_ui(window.split, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Called:");
+                        ui.text_edit_singleline(&mut window.archive_name);
+                    });
+                    ui.label(format!(
+                        "It keeps the {} era's EXP and level, and its own stats. {} starts again \
+                         from level 1.",
+                        window.from_name, skill.name
+                    ));
+                });
+                ui.separator();
+
+                let named = !window.split || !window.archive_name.trim().is_empty();
+                ui.horizontal(|ui| {
+                    apply = ui
+                        .add_enabled(named, egui::Button::new("Change"))
+                        .on_disabled_hover_text("Give the old records' skill a name")
+                        .clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+
+        if apply {
+            let migration = if window.split {
+                UnitMigration::Split {
+                    archive_name: window.archive_name.trim().to_owned(),
+                }
+            } else {
+                UnitMigration::Convert { rate: window.rate }
+            };
+            let mut to = window.to;
+            if let Some(unit) = &mut to {
+                if !window.split && window.keep_in_totals {
+                    unit.estimate = Some(window.rate);
+                }
+            }
+            sheet.change_unit(&window.skill_id, to, migration);
+            sheet.mark_dirty(time);
+        } else if open && !cancel {
+            self.unit_change = Some(window);
+        }
+    }
+
+    fn complete_skill_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.complete_skill.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let exp_format = &self.settings.exp_format;
+        let sheet = &mut self.sheets[self.active_sheet];
+        let name = match sheet.skills_list.get(&window.skill_id) {
+            Some(skill) => skill.name.clone(),
+            None => return,
+        };
+        let unit = sheet.units.get(&window.skill_id).cloned();
+
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(format!("Complete {}", name))
+            .id(egui::Id::new("complete_skill_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| match &window.completed {
+                None => {
+                    ui.label(format!(
+                        "Completing {} moves it to the Hall of Fame with its level and hours as \
+                         they are now. It won't be suggested or reminded about, and it can't be \
+                         logged to unless it's reopened.",
+                        name
+                    ));
+                    ui.horizontal(|ui| {
+                        ui.label("What did you achieve?");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut window.achievement)
+                                .hint_text("Passed JLPT N2"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("On:");
+                        let id = egui::Id::new("complete_skill_date");
+                        date_picker_ui(ui, id, &mut window.date);
+                    });
+                    ui.horizontal(|ui| {
+                        let named = !window.achievement.trim().is_empty();
+                        if ui
+                            .add_enabled(named, egui::Button::new("Complete"))
+                            .clicked()
+                        {
+                            let id = window.skill_id;
+                            sheet.complete_skill(&id, &window.achievement, window.date);
+                            sheet.mark_dirty(time);
+                            window.completed = sheet.completed.get(&id).cloned();
+                        }
+                        close = ui.button("Cancel").clicked();
+                    });
+                }
+                Some(completed) => {
+                    ui.vertical_centered(|ui| {
+                        ui.heading(format!("{} is complete!", name));
+                        ui.strong(completed.achievement.as_str());
+                        ui.label(completed_summary(completed, unit.as_ref(), exp_format));
+                        close = ui.button("Well done").clicked();
+                    });
+                }
+            });
+
+        if open && !close {
+            self.complete_skill = Some(window);
+        }
+    }
+
+    fn session_links_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.session_links.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[self.active_sheet];
+        let name = match sheet.skills_list.get(&window.skill_id) {
+            Some(skill) => skill.name.clone(),
+            None => return,
+        };
+
+        let mut open = true;
+        let mut done = false;
+        egui::Window::new(format!("Link {} Session", name))
+            .id(egui::Id::new("session_links_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Did the session produce anything to link to it? A URL, a commit hash or a \
+                     file path, one per line.",
+                );
+                ui.add(
+                    egui::TextEdit::multiline(&mut window.text)
+                        .hint_text("https://github.com/me/project/pull/12")
+                        .desired_rows(3),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Add").clicked() {
+                        window
+                            .text
+                            .lines()
+                            .filter(|line| !line.trim().is_empty())
+                            .for_each(|line| {
+                                sheet.link_session(
+                                    &window.skill_id,
+                                    window.date,
+                                    Link::guess(line),
+                                );
+                            });
+                        sheet.mark_dirty(time);
+                        done = true;
+                    }
+                    done |= ui.button("Skip").clicked();
+                });
+            });
+
+        if open && !done {
+            self.session_links = Some(window);
+        }
+    }
+
+    fn reflection_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.reflection.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[self.active_sheet];
+        let name = match sheet.skills_list.get(&window.skill_id) {
+            Some(skill) => skill.name.clone(),
+            None => return,
+        };
+        let entry = match sheet
+            .journals
+            .get_mut(&window.skill_id)
+            .and_then(|journal| journal.get_mut(window.entry))
+        {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        let mut open = true;
+        let mut done = false;
+        let mut saved = false;
+        egui::Window::new(format!("{} Level {}", name, entry.level))
+            .id(egui::Id::new("reflection_window"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(entry.text.as_str());
+                ui.label("Anything to remember about getting here?");
+                ui.add(
+                    egui::TextEdit::singleline(&mut window.text)
+                        .hint_text("Finally got barre chords clean")
+                        .desired_width(280.0),
+                );
+                ui.horizontal(|ui| {
+                    let written = !window.text.trim().is_empty();
+                    if ui.add_enabled(written, egui::Button::new("Save")).clicked() {
+                        entry.reflection = window.text.trim().to_owned();
+                        saved = true;
+                    }
+                    done |= ui.button("Skip").clicked();
+                });
+            });
+
+        if saved {
+            sheet.mark_dirty(time);
+        } else if open && !done {
+            self.reflection = Some(window);
+        }
+    }
+
+    /// Pulls in notes saved in an external editor, and lists the active sheet's open notes so
+    /// they can be closed.
+    fn note_edits_window_ui(&mut self, ctx: &egui::Context, frame: &epi::Frame) {
+        const POLL_SECS: f64 = 1.0;
+
+        let time = ctx.input().time;
+        self.sheets.iter_mut().for_each(|sheet| {
+            let Sheet {
+                note_edits,
+                skills_list,
+                ..
+            } = sheet;
+            let mut changed = false;
+            note_edits.iter_mut().for_each(|edit| {
+                if let Some(text) = edit.poll() {
+                    if let Some(skill) = skills_list.get_mut(&edit.skill_id) {
+                        changed |= edit.apply(&mut skill.records, text);
+                    }
+                }
+            });
+            if changed {
+                sheet.mark_dirty(time);
+            }
+        });
+
+        let sheet = &mut self.sheets[self.active_sheet];
+        if sheet.note_edits.is_empty() {
+            self.note_poll_wakeup = None;
+            return;
+        }
+        // Saving in another program doesn't wake egui up, so check back regularly.
+        if self.note_poll_wakeup.map_or(true, |wakeup| wakeup <= time) {
+            self.note_poll_wakeup = Some(time + POLL_SECS);
+            request_repaint_after(frame, POLL_SECS);
+        }
+
+        let mut done = None;
+        egui::Window::new("Notes Being Edited")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("Save in your editor to update the note here.");
+                sheet.note_edits.iter().enumerate().for_each(|(idx, edit)| {
+                    let skill_name = sheet
+                        .skills_list
+                        .get(&edit.skill_id)
+                        .map_or("", |skill| skill.name.as_str());
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}, {}", skill_name, edit.date));
+                        if ui.button("Done").clicked() {
+                            done = Some(idx);
+                        }
+                    });
+                });
+            });
+        if let Some(idx) = done {
+            sheet.note_edits.remove(idx);
+        }
+    }
+
+    /// Gives any sessions added to the sheet this frame their chance at some loot. They're found
+    /// against the skills as the undo history last recorded them, so this has to happen before
+    /// this frame's edit is recorded.
+    fn roll_loot(&mut self, ctx: &egui::Context, edited_sheet: usize) {
+        let time = ctx.input().time;
+        if self.active_sheet != edited_sheet {
+            return;
+        }
+        let sheet = &mut self.sheets[edited_sheet];
+        let skills_before = match sheet.history.tracked() {
+            Some(skills) if sheet.last_edit_time == time => skills,
+            _ => return,
+        };
+
+        let today = clock::today();
+        let found = sheet
+            .loot
+            .roll_for_new_sessions(skills_before, &sheet.skills_list, today);
+        self.new_loot.extend(found);
+    }
+
+    fn new_loot_window_ui(&mut self, ctx: &egui::Context) {
+        if self.new_loot.is_empty() {
+            return;
+        }
+        let sheet = &self.sheets[self.active_sheet];
+
+        let mut open = true;
+        let mut done = false;
+        egui::Window::new("Loot!")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                self.new_loot.iter().for_each(|found| {
+                    let skill_name = sheet
+                        .skills_list
+                        .get(&found.skill_id)
+                        .map_or("", |skill| skill.name.as_str());
+                    ui.label(format!(
+                        "{} dropped a {}: {}",
+                        skill_name,
+                        found.item.kind.label().to_lowercase(),
+                        found.item.name
+                    ));
+                });
+                done = ui.button("Nice").clicked();
+            });
+
+        if done || !open {
+            self.new_loot.clear();
+        }
+    }
+
+    /// Records this frame's edits to the sheet which was active at the start of it, then carries
+    /// out any undo or redo which was asked for.
+    fn update_history(
+        &mut self,
+        ctx: &egui::Context,
+        edited_sheet: usize,
+        mut undo: bool,
+        mut redo: bool,
+    ) {
+        let time = ctx.input().time;
+        // Every sheet changed this frame has it recorded, even ones changed in the background,
+        // so nothing is left over to be mixed into a later edit.
+        self.sheets
+            .iter_mut()
+            .filter(|sheet| sheet.last_edit_time == time)
+            .for_each(|sheet| sheet.record_changes(time));
+        if self.active_sheet != edited_sheet {
+            return;
+        }
+        let sheet = &mut self.sheets[edited_sheet];
+
+        // Text fields have their own undo, so leave the shortcuts to them while one is focused.
+        if ctx.memory().focus().is_none() {
+            let input = ctx.input();
+            let command = input.modifiers.command;
+            if command && input.key_pressed(egui::Key::Z) {
+                if input.modifiers.shift {
+                    redo = true;
+                } else {
+                    undo = true;
+                }
+            }
+            redo |= command && input.key_pressed(egui::Key::Y);
+        }
+
+        if undo {
+            sheet.undo(time);
+        } else if redo {
+            sheet.redo(time);
+        }
+    }
+
+    fn csv_import_window_ui(&mut self, ctx: &egui::Context) {
+        const PREVIEW_ROWS: usize = 10;
+
+        let mut window = match self.csv_import.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let sheet = &mut self.sheets[self.active_sheet];
+
+        let mut open = true;
+        let mut import = false;
+        egui::Window::new("Import CSV")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(window.path.display().to_string());
+
+                ui.horizontal(|ui| {
+                    ui.label("Delimiter:");
+                    let old_delimiter = window.delimiter;
+                    ui.radio_value(&mut window.delimiter, ',', "Comma");
+                    ui.radio_value(&mut window.delimiter, ';', "Semicolon");
+                    ui.radio_value(&mut window.delimiter, '\t', "Tab");
+                    if window.delimiter != old_delimiter {
+                        window.rows = import::parse_csv(&window.text, window.delimiter);
+                        window.results_mapping = None;
+                    }
+                });
+                ui.checkbox(&mut window.mapping.has_header, "First row is a header");
+
+                let names = window.column_names();
+                let mapping = &mut window.mapping;
+                column_combo_ui(ui, "Date", &mut mapping.date_column, &names);
+                column_combo_ui(
+                    ui,
+                    "Duration (minutes or h:mm)",
+                    &mut mapping.duration_column,
+                    &names,
+                );
+                ui.horizontal(|ui| {
+                    let selected_text = match mapping.skill_column {
+                        Some(column) => names.get(column).cloned().unwrap_or_default(),
+                        None => "One skill for every row".to_owned(),
+                    };
+                    egui::ComboBox::from_label("Skill")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut mapping.skill_column,
+                                None,
+                                "One skill for every row",
+                            );
+                            names.iter().enumerate().for_each(|(idx, name)| {
+                                ui.selectable_value(&mut mapping.skill_column, Some(idx), name);
+                            });
+                        });
+                    if mapping.skill_column.is_none() {
+                        ui.text_edit_singleline(&mut mapping.default_skill);
+                    }
+                });
+                ui.separator();
+
+                if window.results_mapping.as_ref() != Some(&window.mapping) {
+                    window.results = window.mapping.map_rows(&window.rows);
+                    window.results_mapping = Some(window.mapping.clone());
+                }
+                let results = &window.results;
+                egui::Grid::new("csv_import_preview").show(ui, |ui| {
+                    ui.label("Skill");
+                    ui.label("Date");
+                    ui.label("Duration");
+                    ui.end_row();
+                    results.iter().take(PREVIEW_ROWS).for_each(|result| {
+                        match result {
+                            Ok(record) => {
+                                let skill_text =
+                                    if import::find_skill(sheet, &record.skill).is_some() {
+                                        record.skill.clone()
+                                    } else {
+                                        format!("{} (new)", record.skill)
+                                    };
+                                ui.label(skill_text);
+                                ui.label(record.date.to_string());
+                                ui.label(format!("{} min", record.duration));
+                            }
+                            Err(error) => {
+                                ui.colored_label(Colors::of(ui.ctx()).error, error);
+                                ui.label("");
+                                ui.label("");
+                            }
+                        }
+                        ui.end_row();
+                    });
+                });
+                if results.len() > PREVIEW_ROWS {
+                    ui.label(format!("...and {} more rows", results.len() - PREVIEW_ROWS));
+                }
+
+                let ready = results.iter().filter(|r| r.is_ok()).count();
+                ui.label(format!(
+                    "{} records ready to import, {} rows will be skipped",
+                    ready,
+                    results.len() - ready
+                ));
+                import = ui
+                    .add_enabled(ready > 0, egui::Button::new("Import"))
+                    .clicked();
+            });
+
+        if import {
+            let records = window.results.into_iter().filter_map(Result::ok).collect();
+            self.import_progress = Some(ImportProgressWindow::start(sheet, records));
+        } else if open {
+            self.csv_import = Some(window);
+        }
+    }
+
+    fn import_progress_window_ui(&mut self, ctx: &egui::Context, frame: &epi::Frame) {
+        // Often enough for the bar to move along, without waking the UI for every record.
+        const RECORDS_PER_UPDATE: usize = 2000;
+
+        let mut window = match self.import_progress.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let sheet = &mut self.sheets[self.active_sheet];
+
+        if window.result.is_none() && window.worker.is_none() {
+            if let Some(mut job) = window.job.take() {
+                window.worker = Some(Worker::spawn(frame, move |reporter| {
+                    while !job.step(RECORDS_PER_UPDATE) && reporter.progress(job.processed()) {}
+                    job
+                }));
+            }
+        }
+        let mut commit = false;
+        match window.worker.as_ref().and_then(Worker::poll) {
+            Some(Update::Progress(processed)) => window.processed = processed,
+            Some(Update::Done(job)) => {
+                window.processed = job.processed();
+                window.job = Some(job);
+                window.worker = None;
+                commit = true;
+            }
+            Some(Update::Stopped) => {
+                self.notice =
+                    Some("The import stopped part way through, so nothing was added.".to_owned());
+                return;
+            }
+            None => {}
+        }
+
+        let mut close = false;
+        let mut stop = false;
+
+        egui::Window::new("Importing")
+            .collapsible(false)
+            .show(ctx, |ui| match &window.result {
+                Some(summary) => {
+                    ui.label(format!(
+                        "Added {} of {} records, creating {} new skills.",
+                        summary.records_added, window.total, summary.skills_created
+                    ));
+                    close = ui.button("Close").clicked();
+                }
+                None => {
+                    let fraction = window.processed as f32 / window.total.max(1) as f32;
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .text(format!("{} / {}", window.processed, window.total)),
+                    );
+                    if window.stopping {
+                        ui.label("Stopping...");
+                        return;
+                    }
+                    ui.horizontal(|ui| {
+                        close = ui
+                            .button("Cancel")
+                            .on_hover_text("Stop without adding anything")
+                            .clicked();
+                        stop = ui
+                            .button("Stop and Keep")
+                            .on_hover_text("Stop here, keeping the records imported so far")
+                            .clicked();
+                    });
+                }
+            });
+
+        if let (true, Some(worker)) = (stop, &window.worker) {
+            worker.stop();
+            window.stopping = true;
+        }
+        if let (true, false, Some(job)) = (commit, close, &mut window.job) {
+            window.result = Some(job.commit(sheet));
+            sheet.mark_dirty(ctx.input().time);
+        }
+        if !close {
+            self.import_progress = Some(window);
+        }
+    }
+
+    fn text_export_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.text_export.take() {
+            Some(window) => window,
+            None => return,
+        };
+
+        let mut open = true;
+        let mut done = false;
+        egui::Window::new(window.title)
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(400f32)
+                    .show(ui, |ui| {
+                        // Read-only, but still selectable for copying bits of it.
+                        ui.add(
+                            egui::TextEdit::multiline(&mut window.text.as_str())
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+
+                if let Some(error) = &window.error {
+                    ui.colored_label(Colors::of(ui.ctx()).error, error);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Copy to Clipboard").clicked() {
+                        ui.output().copied_text = window.text.clone();
+                    }
+                    if ui.button("Save...").clicked() {
+                        if let Some(path) =
+                            dialogs::pick_file_to_save(window.kind, Path::new(&window.file_name))
+                        {
+                            match std::fs::write(&path, &window.text) {
+                                Ok(()) => done = true,
+                                Err(e) => {
+                                    window.error =
+                                        Some(format!("Couldn't write {}: {}", path.display(), e))
+                                }
+                            }
+                        }
+                    }
+                });
+            });
+
+        if open && !done {
+            self.text_export = Some(window);
+        }
+    }
+
+    fn activity_import_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.activity_import.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let sheet = &mut self.sheets[self.active_sheet];
+
+        let mut open = true;
+        let mut import = false;
+        egui::Window::new(format!("Import from {}", window.source.name))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(window.path.display().to_string());
+                if let Some(error) = &window.error {
+                    ui.colored_label(Colors::of(ui.ctx()).error, error);
+                    return;
+                }
+
+                let mut skills: Vec<(&Uuid, &Skill)> = sheet.skills_list.iter().collect();
+                skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+                ui.collapsing(
+                    format!("{} title patterns", window.source.group_name),
+                    |ui| {
+                        ui.label(
+                            "Send everything whose title matches a pattern to the same place. \
+                         * stands for anything.",
+                        );
+                        let mut remove = None;
+                        egui::Grid::new("import_patterns").show(ui, |ui| {
+                            window
+                                .patterns
+                                .iter_mut()
+                                .enumerate()
+                                .for_each(|(idx, pattern)| {
+                                    let (text, target) = pattern;
+                                    ui.add(egui::TextEdit::singleline(text).desired_width(140f32));
+                                    let id = ui.id().with("pattern").with(idx);
+                                    project_target_ui(
+                                        ui,
+                                        id,
+                                        target,
+                                        "named after the title",
+                                        &skills,
+                                    );
+                                    if ui.button(" - ").clicked() {
+                                        remove = Some(idx);
+                                    }
+                                    ui.end_row();
+                                });
+                        });
+                        if let Some(idx) = remove {
+                            window.patterns.remove(idx);
+                        }
+                        if ui.button("Add pattern").clicked() {
+                            window
+                                .patterns
+                                .push((String::new(), ProjectTarget::NewSkill));
+                        }
+                    },
+                );
+
+                if window.source.check_in_minutes.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.label("Count each check-in as");
+                        ui.add(
+                            egui::DragValue::new(&mut window.check_in_minutes).clamp_range(1..=600),
+                        );
+                        ui.label("minutes");
+                    });
+                }
+
+                let records = &window.records;
+                let patterns = &window.patterns;
+                let (source, check_in_minutes) = (window.source, window.check_in_minutes);
+                egui::Grid::new("import_projects").show(ui, |ui| {
+                    ui.label(window.source.group_name);
+                    ui.label("Entries");
+                    ui.label("Hours");
+                    ui.label("Import into");
+                    ui.end_row();
+                    window.targets.iter_mut().for_each(|(project, target)| {
+                        let entries: Vec<&ImportedRecord> =
+                            records.iter().filter(|r| &r.skill == project).collect();
+                        let minutes: u64 = entries
+                            .iter()
+                            .map(|r| source.duration(r, check_in_minutes))
+                            .sum();
+                        ui.label(project);
+                        ui.label(entries.len().to_string());
+                        ui.label(format!("{:.1}", minutes as f64 / 60f64));
+
+                        let pattern = patterns
+                            .iter()
+                            .find(|(pattern, _)| import::title_matches(pattern, project));
+                        match pattern {
+                            Some((pattern, _)) => {
+                                ui.label(format!("Matches \"{}\"", pattern.trim()));
+                            }
+                            None => {
+                                let new_name = format!("\"{}\"", project);
+                                let id = ui.id().with(project);
+                                project_target_ui(ui, id, target, &new_name, &skills);
+                            }
+                        }
+                        ui.end_row();
+                    });
+                });
+                ui.checkbox(
+                    &mut window.merge_same_day,
+                    "Merge entries on the same day into one record",
+                );
+                ui.separator();
+
+                let count = window.mapped_records(sheet).len();
+                import = ui
+                    .add_enabled(
+                        count > 0,
+                        egui::Button::new(format!("Import {} records", count)),
+                    )
+                    .clicked();
+            });
+
+        if import {
+            let records = window.mapped_records(sheet);
+            self.import_progress = Some(ImportProgressWindow::start(sheet, records));
+        } else if open {
+            self.activity_import = Some(window);
+        }
+    }
+
+    fn data_health_window_ui(&mut self, ctx: &egui::Context) {
+        if !self.data_health_open {
+            return;
+        }
+        let sheet = &mut self.sheets[self.active_sheet];
+
+        let mut fix = None;
+        egui::Window::new("Data Health")
+            .open(&mut self.data_health_open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let findings = tools::find_anomalies(sheet);
+                if findings.is_empty() {
+                    ui.label("No problems found");
+                    return;
+                }
+
+                Anomaly::ALL.iter().for_each(|anomaly| {
+                    let found: Vec<&Finding> =
+                        findings.iter().filter(|f| f.anomaly == *anomaly).collect();
+                    if found.is_empty() {
+                        return;
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}: {}", anomaly.label(), found.len()));
+                        if ui.button(anomaly.fix_label()).clicked() {
+                            fix = Some(*anomaly);
+                        }
+                    });
+                    egui::CollapsingHeader::new("Show records")
+                        .id_source(anomaly.label())
+                        .show(ui, |ui| {
+                            egui::Grid::new(anomaly.label()).show(ui, |ui| {
+                                found.iter().for_each(|finding| {
+                                    let skill_name = sheet
+                                        .skills_list
+                                        .get(&finding.skill_id)
+                                        .map_or("", |skill| skill.name.as_str());
+                                    ui.label(skill_name);
+                                    ui.label(finding.date.to_string());
+                                    ui.label(format!("{} min", finding.duration));
+                                    ui.label(finding.detail.as_str());
+                                    ui.end_row();
+                                });
+                            });
+                        });
+                    ui.separator();
+                });
+            });
+
+        if let Some(anomaly) = fix {
+            if tools::fix_anomalies(sheet, anomaly) > 0 {
+                sheet.mark_dirty(ctx.input().time);
+            }
+        }
+    }
+
+    fn date_shift_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.date_shift.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let sheet = &mut self.sheets[self.active_sheet];
+
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new("Shift Dates")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let mut skills: Vec<(&Uuid, &Skill)> = sheet.skills_list.iter().collect();
+                skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+                let selected_text = window
+                    .skill
+                    .and_then(|id| sheet.skills_list.get(&id))
+                    .map_or("All skills", |skill| skill.name.as_str());
+                egui::ComboBox::from_label("Records from")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut window.skill, None, "All skills");
+                        skills.iter().for_each(|(id, skill)| {
+                            ui.selectable_value(&mut window.skill, Some(**id), &skill.name);
+                        });
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut window.limit_to_range, "Only records from");
+                    ui.add_enabled_ui(window.limit_to_range, |ui| {
+                        date_picker_ui(ui, ui.make_persistent_id("shift_from"), &mut window.from);
+                        ui.label("to");
+                        date_picker_ui(ui, ui.make_persistent_id("shift_to"), &mut window.to);
+                    });
+                });
+                ui.separator();
+
+                let is_days = matches!(window.shift, DateShift::Days(_));
+                ui.horizontal(|ui| {
+                    if ui.radio(is_days, "Move by days").clicked() && !is_days {
+                        window.shift = DateShift::Days(0);
+                    }
+                    if ui.radio(!is_days, "Convert timezone").clicked() && is_days {
+                        window.shift = DateShift::Timezone {
+                            from_utc_offset: 0,
+                            to_utc_offset: 0,
+                            hour: 20,
+                        };
+                    }
+                });
+                match &mut window.shift {
+                    DateShift::Days(days) => {
+                        ui.horizontal(|ui| {
+                            ui.label("Days:");
+                            ui.add(egui::DragValue::new(days).clamp_range(-3650..=3650));
+                        });
+                    }
+                    DateShift::Timezone {
+                        from_utc_offset,
+                        to_utc_offset,
+                        hour,
+                    } => {
+                        ui.horizontal(|ui| {
+                            ui.label("Recorded in UTC");
+                            ui.add(
+                                egui::DragValue::new(from_utc_offset)
+                                    .clamp_range(-12..=14)
+                                    .suffix("h"),
+                            );
+                            ui.label("but should be UTC");
+                            ui.add(
+                                egui::DragValue::new(to_utc_offset)
+                                    .clamp_range(-12..=14)
+                                    .suffix("h"),
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Sessions usually happen around");
+                            ui.add(egui::DragValue::new(hour).clamp_range(0..=23).suffix(":00"));
+                        });
+                    }
+                }
+                ui.separator();
+
+                let range = window.range();
+                let affected: Vec<NaiveDate> = sheet
+                    .skills_list
+                    .iter()
+                    .filter(|(id, _)| window.skill.map_or(true, |skill| skill == **id))
+                    .flat_map(|(_, skill)| skill.records.iter().map(|r| r.date))
+                    .filter(|date| range.contains(*date))
+                    .collect();
+                match affected.first() {
+                    Some(date) => ui.label(format!(
+                        "{} records, e.g. {} becomes {}",
+                        affected.len(),
+                        date,
+                        window.shift.apply(*date)
+                    )),
+                    None => ui.label("No records to shift"),
+                };
+
+                apply = ui
+                    .add_enabled(!affected.is_empty(), egui::Button::new("Apply"))
+                    .clicked();
+            });
+
+        if apply {
+            let range = window.range();
+            let mut shifted = Vec::new();
+            sheet
+                .skills_list
+                .iter_mut()
+                .filter(|(id, _)| window.skill.map_or(true, |skill| skill == **id))
+                .for_each(|(id, skill)| {
+                    if tools::shift_dates(skill, &window.shift, &range) > 0 {
+                        shifted.push(*id);
+                    }
+                });
+            shifted
+                .iter()
+                .for_each(|skill_id| sheet.recalculate_skill(skill_id));
+            if !shifted.is_empty() {
+                sheet.mark_dirty(ctx.input().time);
+            }
+        } else if open {
+            self.date_shift = Some(window);
+        }
+    }
+
+    fn csv_export_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.csv_export.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let sheet = &self.sheets[self.active_sheet];
+
+        let mut open = true;
+        let mut done = false;
+        egui::Window::new("Export CSV")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let options = &mut window.options;
+
+                let mut skills: Vec<(&Uuid, &Skill)> = sheet.skills_list.iter().collect();
+                skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+                let selected_text = options
+                    .skill
+                    .and_then(|id| sheet.skills_list.get(&id))
+                    .map_or("Whole sheet", |skill| skill.name.as_str());
+                egui::ComboBox::from_label("Records from")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut options.skill, None, "Whole sheet");
+                        skills.iter().for_each(|(id, skill)| {
+                            ui.selectable_value(&mut options.skill, Some(**id), &skill.name);
+                        });
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.label("Columns:");
+                    CsvColumn::ALL.iter().for_each(|column| {
+                        let mut included = options.columns.contains(column);
+                        if ui.checkbox(&mut included, column.header()).changed() {
+                            // Keep the columns in their usual order however they're toggled.
+                            options.columns = CsvColumn::ALL
+                                .iter()
+                                .copied()
+                                .filter(|c| {
+                                    if c == column {
+                                        included
+                                    } else {
+                                        options.columns.contains(c)
+                                    }
+                                })
+                                .collect();
+                        }
+                    });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Delimiter:");
+                    ui.radio_value(&mut options.delimiter, ',', "Comma");
+                    ui.radio_value(&mut options.delimiter, ';', "Semicolon");
+                    ui.radio_value(&mut options.delimiter, '\t', "Tab");
+                });
+
+                if let Some(error) = &window.error {
+                    ui.colored_label(Colors::of(ui.ctx()).error, error);
+                }
+
+                let can_export = !window.options.columns.is_empty();
+                if ui
+                    .add_enabled(can_export, egui::Button::new("Export..."))
+                    .clicked()
+                {
+                    let default_name = format!("{}.csv", sheet.display_name());
+                    if let Some(path) =
+                        dialogs::pick_file_to_save(&dialogs::CSV, Path::new(&default_name))
+                    {
+                        let csv = export::records_to_csv(sheet, &window.options);
+                        match std::fs::write(&path, csv) {
+                            Ok(()) => done = true,
+                            Err(e) => {
+                                window.error =
+                                    Some(format!("Couldn't write {}: {}", path.display(), e))
+                            }
+                        }
+                    }
+                }
+            });
+
+        if open && !done {
+            self.csv_export = Some(window);
+        }
+    }
+
+    fn paper_log_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.paper_log.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let sheet = &self.sheets[self.active_sheet];
+
+        let mut open = true;
+        let mut done = false;
+        egui::Window::new("Blank Paper Log")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "A grid of this sheet's skills and the days of a month, to print out and \
+                     write in by hand. Type it in afterwards with Paste Rows.",
+                );
+                let (first, last) = paper::month_of(window.month);
+                ui.horizontal(|ui| {
+                    if ui.small_button("<").clicked() {
+                        window.month = first.pred();
+                    }
+                    ui.label(first.format("%B %Y").to_string());
+                    if ui.small_button(">").clicked() {
+                        window.month = last.succ();
+                    }
+                });
+
+                if let Some(error) = &window.error {
+                    ui.colored_label(Colors::of(ui.ctx()).error, error);
+                }
+
+                if ui.button("Save PDF...").clicked() {
+                    let default_name =
+                        format!("{} {}.pdf", sheet.display_name(), first.format("%Y-%m"));
+                    if let Some(path) =
+                        dialogs::pick_file_to_save(&dialogs::PDF, Path::new(&default_name))
+                    {
+                        match std::fs::write(&path, paper::blank_log_pdf(sheet, first)) {
+                            Ok(()) => done = true,
+                            Err(e) => {
+                                window.error =
+                                    Some(format!("Couldn't write {}: {}", path.display(), e))
+                            }
+                        }
+                    }
+                }
+            });
+
+        if open && !done {
+            self.paper_log = Some(window);
+        }
+    }
+
+    fn month_grid_window_ui(&mut self, ctx: &egui::Context) {
+        let mut window = match self.month_grid.take() {
+            Some(window) => window,
+            None => return,
+        };
+        let time = ctx.input().time;
+        let sheet = &mut self.sheets[self.active_sheet];
+
+        let mut open = true;
+        let mut add = false;
+        egui::Window::new("Month Grid")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(
+                    "Type the minutes (or h:mm) practiced into each day's box, then add them all \
+                     at once. The greyed-out numbers are what's already logged.",
+                );
+                let (first, last) = paper::month_of(window.month);
+                ui.horizontal(|ui| {
+                    if ui.small_button("<").clicked() {
+                        window.month = first.pred();
+                    }
+                    ui.label(first.format("%B %Y").to_string());
+                    if ui.small_button(">").clicked() {
+                        window.month = last.succ();
+                    }
+                });
+
+                let days: Vec<NaiveDate> =
+                    first.iter_days().take_while(|day| *day <= last).collect();
+                let mut skills: Vec<(&Uuid, &Skill)> = sheet.skills_list.iter().collect();
+                skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+
+                egui::ScrollArea::both().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("month_grid").striped(true).show(ui, |ui| {
+                        ui.label("");
+                        days.iter().for_each(|day| {
+                            ui.strong(day.day().to_string())
+                                .on_hover_text(day.format("%A %-d %B").to_string());
+                        });
+                        ui.end_row();
+
+                        skills.iter().for_each(|(id, skill)| {
+                            ui.label(&skill.name);
+                            days.iter().for_each(|day| {
+                                let logged: u64 = skill
+                                    .records
+                                    .iter()
+                                    .filter(|r| r.date == *day)
+                                    .map(|r| r.duration)
+                                    .sum();
+                                let text = window.cells.entry((**id, *day)).or_default();
+                                let mut edit = egui::TextEdit::singleline(text)
+                                    .id(egui::Id::new(("month_grid", **id, *day)))
+                                    .desired_width(28.0);
+                                if logged > 0 {
+                                    edit = edit.hint_text(logged.to_string());
+                                }
+                                ui.add(edit);
+                            });
+                            ui.end_row();
+                        });
+                    });
+                });
+
+                let (entries, unreadable) = window.entries();
+                ui.horizontal(|ui| {
+                    add = ui
+                        .add_enabled(
+                            !entries.is_empty(),
+                            egui::Button::new(format!("Add {} records", entries.len())),
+                        )
+                        .clicked();
+                    if unreadable > 0 {
+                        ui.colored_label(
+                            Colors::of(ui.ctx()).error,
+                            format!("{} boxes can't be read and will be skipped", unreadable),
+                        );
+                    }
+                });
+            });
+
+        if add {
+            let approved = !sheet.require_approval;
+            let (entries, _) = window.entries();
+            let mut touched: Vec<Uuid> = Vec::new();
+            entries.into_iter().for_each(|(id, date, minutes)| {
+                if let Some(skill) = sheet.skills_list.get_mut(&id) {
+                    skill.records.push(SheetActionRecord {
+                        date,
+                        duration: minutes,
+                        approved,
+                        ..Default::default()
+                    });
+                    if !touched.contains(&id) {
+                        touched.push(id);
+                    }
+                }
+            });
+            touched.iter().for_each(|id| {
+                if let Some(skill) = sheet.skills_list.get_mut(id) {
+                    skill.sort_actions();
+                }
+                sheet.recalculate_skill(id);
+            });
+            sheet.mark_dirty(time);
+        } else if open {
+            self.month_grid = Some(window);
+        }
+    }
+
+    /// Saves any sheet which has been left alone for longer than the autosave interval.
+    fn autosave(&mut self, ctx: &egui::Context, frame: &epi::Frame) {
+        if !self.settings.autosave {
+            return;
+        }
+
+        let now = ctx.input().time;
+        let interval = self.settings.autosave_interval_secs;
+        let mut next_due: Option<f64> = None;
+        (0..self.sheets.len()).for_each(|idx| {
+            let sheet = &self.sheets[idx];
+            if !sheet.dirty || self.saves.iter().any(|save| save.path == sheet.path) {
+                return;
+            }
+            let due = self.sheets[idx].last_edit_time + interval;
+            if now >= due && !self.save_sheet(idx) {
+                // Wait a whole interval before trying again, rather than every frame.
+                self.sheets[idx].last_edit_time = now;
+                next_due = Some(next_due.map_or(now + interval, |d| d.min(now + interval)));
+            } else if now < due {
+                next_due = Some(next_due.map_or(due, |d| d.min(due)));
+            }
+        });
+
+        // egui only repaints on input, so make sure we wake up again once a save is due even if
+        // the user has stopped touching anything.
+        if let Some(due) = next_due {
+            let already_scheduled = self
+                .autosave_wakeup
+                .map_or(false, |wakeup| wakeup > now && wakeup <= due);
+            if !already_scheduled {
+                self.autosave_wakeup = Some(due);
+                request_repaint_after(frame, due - now);
+            }
+        } else {
+            self.autosave_wakeup = None;
+        }
+    }
+
+    /// Restyles egui when the appearance settings, or the system's theme, have changed.
+    fn apply_appearance(&mut self, ctx: &egui::Context, frame: &epi::Frame) {
+        let appearance = self.settings.appearance;
+        let prefer_dark = frame.info().prefer_dark_mode;
+        if self.applied_appearance != Some((appearance, prefer_dark)) {
+            appearance.apply(ctx, prefer_dark);
+            self.applied_appearance = Some((appearance, prefer_dark));
+        }
+    }
+
+    fn run_shortcuts(&mut self, ctx: &egui::Context) {
+        let actions = self.settings.shortcuts.pressed(&ctx.input());
+        let time = ctx.input().time;
+
+        // Pasting over a skill, with no text field to take it, adds the rows to that skill.
+        if self.page == Page::Skills && ctx.memory().focus().is_none() {
+            let pasted = ctx.input().events.iter().find_map(|event| match event {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            });
+            let focused = ctx.memory().data.get_temp::<Uuid>(focused_skill_id());
+            if let (Some(text), Some(skill_id)) = (pasted, focused) {
+                self.paste_rows = Some(PasteRowsWindow::new(skill_id, text));
+            }
+        }
+
+        actions.iter().for_each(|action| match action {
+            Action::Save => {
+                self.save_sheet(self.active_sheet);
+            }
+            Action::Open => {
+                if let Some(path) = dialogs::pick_file_to_open(&dialogs::SHEET) {
+                    self.open_sheet(&path);
+                }
+            }
+            Action::NewSkill => {
+                self.page = Page::Skills;
+                let sheet = self.active_sheet_mut();
+                sheet.skills_list.insert(Uuid::new_v4(), Skill::default());
+                sheet.mark_dirty(time);
+            }
+            Action::AddEntry => {
+                // Goes through the editor like the Add entry button, so nothing is added unless
+                // it's accepted.
+                let focused = ctx.memory().data.get_temp::<Uuid>(focused_skill_id());
+                let sheet = &self.sheets[self.active_sheet];
+                if let Some(id) = focused.filter(|id| sheet.skills_list.contains_key(id)) {
+                    let record = SheetActionRecord {
+                        approved: !sheet.require_approval,
+                        ..Default::default()
+                    };
+                    let counted = sheet.units.contains_key(&id);
+                    let style = sheet.duration_style;
+                    self.record_editor =
+                        Some(RecordEditorWindow::new(id, None, record, counted, style));
+                }
+            }
+            Action::ShowDashboard => self.page = Page::Dashboard,
+            Action::ShowToday => self.page = Page::Today,
+            Action::ShowSkills => self.page = Page::Skills,
+        });
+    }
+
+    /// Once the reminder time comes round, warns about any streaks which will run out unless
+    /// they're practiced today. In calm mode it's a gentle nudge instead, with no banner, and only
+    /// as often as the policy allows.
+    fn check_reminders(&mut self, frame: &epi::Frame) {
+        let now = clock::wall_clock();
+        let due = match self.settings.reminders.next_due(now, self.reminded_on) {
+            Some(due) => due,
+            None => {
+                self.reminder_wakeup = None;
+                return;
+            }
+        };
+        if now < due {
+            if self.reminder_wakeup != Some(due) {
+                self.reminder_wakeup = Some(due);
+                request_repaint_after(frame, (due - now).num_milliseconds() as f64 / 1000.0);
+            }
+            return;
+        }
+
+        self.reminded_on = Some(now.date());
+        let today = clock::today();
+        let nudging = self.settings.nudging;
+        if !nudging.reminder_due(today, self.nudged_on) {
+            return;
+        }
+        let at_risk: Vec<reminders::AtRisk> = self
+            .sheets
+            .iter()
+            .flat_map(|sheet| reminders::at_risk_streaks(sheet, today))
+            .collect();
+        if at_risk.is_empty() {
+            return;
+        }
+        self.nudged_on = Some(today);
+        if nudging.warns_of_risk() {
+            reminders::desktop_notification(
+                "Practice today to keep your streaks",
+                &reminders::describe(&at_risk),
+            );
+            self.reminder_banner = true;
+        } else {
+            reminders::desktop_notification(
+                "Time for a little practice?",
+                &reminders::names(&at_risk),
+            );
+        }
+    }
+
+    /// Closes the active sheet, asking first whether to save it if it has unsaved changes.
+    fn close_active_sheet(&mut self) {
+        if self.sheets.len() <= 1 {
+            return;
+        }
+        let sheet = &self.sheets[self.active_sheet];
+        if sheet.dirty {
+            self.closing_sheet = Some(sheet.path.clone());
+        } else {
+            self.close_sheet(self.active_sheet);
+        }
+    }
+
+    fn close_sheet(&mut self, idx: usize) {
+        self.sheets.remove(idx);
+        if self.active_sheet > idx {
+            self.active_sheet -= 1;
+        }
+        self.active_sheet = self.active_sheet.min(self.sheets.len() - 1);
+        self.save_config();
+    }
+
+    /// Asks whether to save a sheet with unsaved changes before it's closed.
+    fn close_sheet_window_ui(&mut self, ctx: &egui::Context) {
+        let path = match &self.closing_sheet {
+            Some(path) => path,
+            None => return,
+        };
+        let idx = match self.sheets.iter().position(|s| &s.path == path) {
+            Some(idx) if self.sheets.len() > 1 => idx,
+            _ => {
+                self.closing_sheet = None;
+                return;
+            }
+        };
+
+        let mut choice = None;
+        egui::Window::new("Close Sheet")
+            .id(egui::Id::new("close_sheet_window"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} has changes which haven't been saved.",
+                    self.sheets[idx].display_name()
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        choice = Some(CloseChoice::Save);
+                    }
+                    if ui
+                        .button("Discard")
+                        .on_hover_text("Close it and throw the changes away")
+                        .clicked()
+                    {
+                        choice = Some(CloseChoice::Discard);
+                    }
+                    if ui.button("Cancel").on_hover_text("Keep it open").clicked() {
+                        choice = Some(CloseChoice::Cancel);
+                    }
+                });
+            });
+
+        match choice {
+            None => {}
+            Some(CloseChoice::Save) => {
+                self.closing_sheet = None;
+                // One which couldn't be saved stays open, so the changes aren't lost.
+                if self.save_sheet(idx) {
+                    let path = self.sheets[idx].path.clone();
+                    if let Some(save) = self.save_of(&path) {
+                        save.close_after = true;
+                    }
+                }
+            }
+            Some(CloseChoice::Discard) => {
+                self.closing_sheet = None;
+                self.close_sheet(idx);
+            }
+            Some(CloseChoice::Cancel) => self.closing_sheet = None,
+        }
+    }
+
+    /// Asks whether to save the sheets with unsaved changes before quitting.
+    fn quit_window_ui(&mut self, ctx: &egui::Context, frame: &epi::Frame) {
+        if !self.quitting {
+            return;
+        }
+        let unsaved: Vec<String> = self
+            .sheets
+            .iter()
+            .filter(|sheet| sheet.dirty)
+            .map(|sheet| sheet.display_name())
+            .collect();
+
+        let mut choice = None;
+        egui::Window::new("Quit")
+            .id(egui::Id::new("quit_window"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} {} changes which haven't been saved.",
+                    unsaved.join(", "),
+                    if unsaved.len() == 1 { "has" } else { "have" }
+                ));
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Save")
+                        .on_hover_text("Save them and quit")
+                        .clicked()
+                    {
+                        choice = Some(CloseChoice::Save);
+                    }
+                    if ui
+                        .button("Discard")
+                        .on_hover_text("Quit and throw the changes away")
+                        .clicked()
+                    {
+                        choice = Some(CloseChoice::Discard);
+                    }
+                    if ui.button("Cancel").on_hover_text("Keep going").clicked() {
+                        choice = Some(CloseChoice::Cancel);
+                    }
+                });
+            });
+
+        match choice {
+            None => {}
+            // Quitting saves whatever's unsaved, so there's nothing more to do for Save.
+            Some(CloseChoice::Save) => {
+                self.quitting = false;
+                frame.quit();
+            }
+            Some(CloseChoice::Discard) => {
+                self.quitting = false;
+                self.discard_on_quit = true;
+                frame.quit();
+            }
+            Some(CloseChoice::Cancel) => self.quitting = false,
+        }
+    }
+
+    /// Asks for a password to encrypt a sheet's file with, or whether to take its password off,
+    /// then saves it that way.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn password_window_ui(&mut self, ctx: &egui::Context) {
+        let window = match &mut self.password_window {
+            Some(window) => window,
+            None => return,
+        };
+        let idx = match self.sheets.iter().position(|s| s.path == window.path) {
+            Some(idx) => idx,
+            None => {
+                self.password_window = None;
+                return;
+            }
+        };
+        let encrypted = self.sheets[idx].encryption.is_some();
+
+        let mut choice = None;
+        let title = if encrypted {
+            "Change Password"
+        } else {
+            "Set Password"
+        };
+        egui::Window::new(title)
+            .id(egui::Id::new("password_window"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(
+                    "The sheet's file is encrypted with this password, which is asked for \
+                     whenever it's opened. It can't be opened at all without it, so keep it \
+                     somewhere safe. Backups and versions from before are left as they were.",
+                );
+                egui::Grid::new("password_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Password:");
+                        ui.add(egui::TextEdit::singleline(&mut window.password).password(true));
+                        ui.end_row();
+                        ui.label("Again:");
+                        ui.add(egui::TextEdit::singleline(&mut window.confirm).password(true));
+                        ui.end_row();
+                    });
+                let same = window.password == window.confirm;
+                if !same && !window.confirm.is_empty() {
+                    ui.colored_label(Colors::of(ui.ctx()).error, "The passwords don't match");
+                }
+                ui.horizontal(|ui| {
+                    let can_set = same && !window.password.is_empty();
+                    if ui
+                        .add_enabled(can_set, egui::Button::new("Encrypt and Save"))
+                        .clicked()
+                    {
+                        choice = Some(PasswordChoice::Set);
+                    }
+                    if encrypted
+                        && ui
+                            .button("Remove Password")
+                            .on_hover_text("Save it unencrypted from now on")
+                            .clicked()
+                    {
+                        choice = Some(PasswordChoice::Remove);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(PasswordChoice::Cancel);
+                    }
+                });
+            });
+
+        let encryption = match choice {
+            None => return,
+            Some(PasswordChoice::Cancel) => {
+                self.password_window = None;
+                return;
+            }
+            Some(PasswordChoice::Set) => Some(Encryption::new(&window.password)),
+            Some(PasswordChoice::Remove) => None,
+        };
+        self.password_window = None;
+        self.sheets[idx].encryption = encryption;
+        self.save_sheet(idx);
+    }
+
+    fn active_sheet_mut(&mut self) -> &mut Sheet {
+        &mut self.sheets[self.active_sheet]
+    }
+
+    /// Opens a single sheet from `path`.
+    pub fn from_path(path: &Path) -> Self {
+        let mut app = Self {
+            open_sheet_paths: vec![path.to_path_buf()],
+            ..Default::default()
+        };
+        app.load_sheets();
+        app
+    }
+
+    /// The sheet the app had open last and the settings it was using, without loading any
+    /// sheets, for running commands from a terminal.
+    pub(crate) fn saved_sheet_and_settings() -> (PathBuf, Settings) {
+        let app = File::open(get_config_file_path())
+            .ok()
+            .and_then(|file| serde_json::from_reader::<_, Self>(BufReader::new(file)).ok())
+            .unwrap_or_default();
+        let path = app
+            .open_sheet_paths
+            .get(app.active_sheet)
+            .cloned()
+            .unwrap_or_else(|| get_default_file_path().to_path_buf());
+        (path, app.settings)
+    }
+
+    /// Serves the API on `port` once the app starts.
+    pub fn serve_on(&mut self, port: u16) {
+        self.serve_port = Some(port);
+    }
+
+    /// Reopens the sheets that were open last time, or the default sheet if there's no record
+    /// of any.
+    pub fn from_default_path() -> Self {
+        let mut app = File::open(get_config_file_path())
+            .ok()
+            .and_then(|file| serde_json::from_reader::<_, Self>(BufReader::new(file)).ok())
+            .unwrap_or_default();
+        app.load_sheets();
+        app
+    }
+}
+
+impl Default for SheetMyselfApp {
+    fn default() -> Self {
+        let path = get_default_file_path();
+        Self {
+            open_sheet_paths: vec![path.to_path_buf()],
+            active_sheet: 0,
+            settings: Settings::default(),
+            sheets: vec![Sheet::new(&path)],
+            page: Page::Skills,
+            autosave_wakeup: None,
+            csv_export: None,
+            date_shift: None,
+            csv_import: None,
+            data_health_open: false,
+            activity_import: None,
+            paper_log: None,
+            load_failures: Vec::new(),
+            month_grid: None,
+            text_export: None,
+            paste_rows: None,
+            skill_list: None,
+            skill_import: None,
+            exp_override: None,
+            record_editor: None,
+            unit_change: None,
+            complete_skill: None,
+            session_links: None,
+            reflection: None,
+            versions: None,
+            import_progress: None,
+            charts: None,
+            new_loot: Vec::new(),
+            reminded_on: None,
+            reminder_wakeup: None,
+            reminder_banner: false,
+            nudged_on: None,
+            save_failure: None,
+            saves: Vec::new(),
+            publish_waiting: None,
+            publishing: None,
+            notice: None,
+            update_check: None,
+            serve_port: None,
+            api_calls: None,
+            api_sheet: None,
+            milestones: HashMap::new(),
+            busy: None,
+            webhook_calls: Vec::new(),
+            sync_conflicts: Vec::new(),
+            closing_sheet: None,
+            quitting: false,
+            discard_on_quit: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            password_window: None,
+            note_poll_wakeup: None,
+            applied_appearance: None,
+        }
+    }
+}
+
+/// Lets the user pick one of the existing categories for a skill, or type in a new one.
+fn category_combo_ui(
+    ui: &mut egui::Ui,
+    skill_id: &Uuid,
+    category: &mut String,
+    categories: &[String],
+) {
+    // While a new category name is being typed we show a text field in place of the combo box.
+    let new_category_id = ui.make_persistent_id((skill_id, "new_category"));
+    let new_category = ui.memory().data.get_temp::<String>(new_category_id);
+
+    if let Some(mut new_category) = new_category {
+        let field = ui.add(
+            egui::TextEdit::singleline(&mut new_category)
+                .hint_text("New category")
+                .desired_width(100.0),
+        );
+        if field.lost_focus() {
+            if !ui.input().key_pressed(egui::Key::Escape) && !new_category.trim().is_empty() {
+                *category = new_category.trim().to_owned();
+            }
+            ui.memory().data.remove::<String>(new_category_id);
+        } else {
+            field.request_focus();
+            ui.memory().data.insert_temp(new_category_id, new_category);
+        }
+        return;
+    }
+
+    let selected_text = if category.is_empty() {
+        "Uncategorized"
+    } else {
+        category.as_str()
+    };
+    egui::ComboBox::from_id_source((skill_id, "category"))
+        .selected_text(selected_text.to_owned())
+        .show_ui(ui, |ui| {
+            ui.selectable_value(category, String::new(), "Uncategorized");
+            categories.iter().for_each(|c| {
+                ui.selectable_value(category, c.clone(), c);
+            });
+            ui.separator();
+            if ui.selectable_label(false, "New category...").clicked() {
+                ui.memory().data.insert_temp(new_category_id, String::new());
+            }
+        });
+}
+
+/// What the Skills page is narrowed down to. It's only a view, so it lives in egui's memory
+/// rather than the sheet.
+#[derive(Clone, Default)]
+struct SkillFilter {
+    /// Part of the skill's name, ignoring case.
+    text: String,
+    /// `Some("")` is the uncategorized skills.
+    category: Option<String>,
+    recent_only: bool,
+}
+
+impl SkillFilter {
+    /// How far back "recently active" looks.
+    const RECENT_DAYS: i64 = 14;
+
+    fn matches(&self, skill: &Skill, today: NaiveDate) -> bool {
+        let text = self.text.trim().to_lowercase();
+        let recent_since = today - Duration::days(Self::RECENT_DAYS - 1);
+        (text.is_empty() || skill.name.to_lowercase().contains(&text))
+            && self
+                .category
+                .as_ref()
+                .map_or(true, |c| *c == skill.category)
+            && (!self.recent_only
+                || skill
+                    .records
+                    .iter()
+                    .any(|r| r.approved && r.date >= recent_since))
+    }
+}
+
+fn skill_filter_ui(ui: &mut egui::Ui, filter: &mut SkillFilter, categories: &[String]) {
+    let category_label = |category: &Option<String>| match category.as_deref() {
+        None => "All categories".to_owned(),
+        Some("") => "Uncategorized".to_owned(),
+        Some(category) => category.to_owned(),
+    };
+    ui.horizontal(|ui| {
+        ui.label("Filter:");
+        ui.add(
+            egui::TextEdit::singleline(&mut filter.text)
+                .hint_text("Skill name")
+                .desired_width(150.0),
+        );
+        egui::ComboBox::from_id_source("skill_filter_category")
+            .selected_text(category_label(&filter.category))
+            .show_ui(ui, |ui| {
+                let options = std::iter::once(None)
+                    .chain(std::iter::once(Some(String::new())))
+                    .chain(categories.iter().cloned().map(Some));
+                options.for_each(|option| {
+                    let label = category_label(&option);
+                    ui.selectable_value(&mut filter.category, option, label);
+                });
+            });
+        ui.checkbox(&mut filter.recent_only, "Recently active")
+            .on_hover_text(format!(
+                "Practiced in the last {} days",
+                SkillFilter::RECENT_DAYS
+            ));
+        if ui.button("Clear").clicked() {
+            *filter = SkillFilter::default();
+        }
+    });
+}
+
+/// What every skill on the Skills page is shown with.
+#[derive(Clone, Copy)]
+struct SkillUiContext<'a> {
+    categories: &'a [String],
+    bonus_rules: &'a [BonusRule],
+    /// Recent days recorded when each skill was last recalculated.
+    recent_days: &'a HashMap<Uuid, RecentDays>,
+    require_approval: bool,
+    exp_format: &'a ExpFormat,
+    season: Option<Season>,
+    /// Where the sheet is, which relative paths in links are from.
+    sheet_folder: &'a Path,
+    nudging: &'a NudgePolicy,
+    duration_style: DurationStyle,
+    /// Whether the sheet is encrypted, whose notes shouldn't be written anywhere as plain text.
+    encrypted: bool,
+}
+
+/// A skill's session timer, while it's running.
+#[derive(Clone, Copy)]
+struct RunningTimer<'a> {
+    started: DateTime<Utc>,
+    /// When each interruption was logged.
+    distractions: &'a [DateTime<Utc>],
+}
+
+/// A skill's settings which are kept on the sheet rather than in the skill itself.
+struct SkillSettings<'a> {
+    sort: &'a mut RecordSort,
+    merge_same_day: &'a mut bool,
+    /// What the skill's records count, if it isn't minutes.
+    unit: &'a mut Option<Unit>,
+    journal: &'a mut [JournalEntry],
+}
+
+struct SkillUiResponse {
+    /// Whether anything about the skill was edited.
+    changed: bool,
+    /// The earliest date of any record which was edited in a way that changes the EXP, if there
+    /// was one. The records from then on need recalculating.
+    recalculate_since: Option<NaiveDate>,
+    /// Whether the session timer was started or stopped.
+    timer_toggled: bool,
+    /// Whether an interruption to the timer session was logged.
+    distracted: bool,
+    pomodoro_started: bool,
+    /// A record whose note should be opened in an external editor.
+    edit_note: Option<usize>,
+    /// A record whose EXP should be set by hand.
+    override_exp: Option<usize>,
+    /// A record to open in the record editor.
+    edit_record: Option<usize>,
+    /// Whether a new record was asked for, to fill in with the record editor.
+    add_record: bool,
+    paste_rows: bool,
+    /// Whether switching between minutes and another unit was asked for, which needs the
+    /// skill's records dealing with first.
+    change_unit: bool,
+    /// Whether marking the skill as completed was asked for.
+    complete: bool,
+    /// Whether starting its level over was asked for.
+    prestige: bool,
+}
+
+fn skill_ui(
+    ui: &mut egui::Ui,
+    skill_id: &Uuid,
+    skill: &mut Skill,
+    timer: Option<RunningTimer<'_>>,
+    settings: SkillSettings<'_>,
+    context: &SkillUiContext<'_>,
+) -> SkillUiResponse {
+    let SkillSettings {
+        sort,
+        merge_same_day,
+        unit,
+        journal,
+    } = settings;
+    let SkillUiContext {
+        categories,
+        bonus_rules,
+        recent_days,
+        require_approval: _,
+        exp_format,
+        season,
+        sheet_folder,
+        nudging,
+        duration_style,
+        encrypted,
+    } = *context;
+    let mut changed = false;
+    let mut timer_toggled = false;
+    let mut distracted = false;
+    let mut pomodoro_started = false;
+    let mut edit_note = None;
+    let mut override_exp = None;
+    let mut edit_record = None;
+    let mut add_record = false;
+    let mut change_unit = false;
+    let mut complete = false;
+    let mut prestige = false;
+    let mut paste_rows = false;
+    let mut recalculate_since = None;
+    let today = clock::today();
+    let lifetime = stats::lifetime(skill, today);
+    // Anything older than today is out of date, but cheap enough to work out again.
+    let recent_days = recent_days
+        .get(skill_id)
+        .copied()
+        .filter(|recent| recent.until == today)
+        .unwrap_or_else(|| RecentDays::of(skill, today));
+    let current_streak = stats::current_streak(skill, today);
+    let best_streak = stats::best_streak(skill);
+    let season_level = season.map(|season| (season, season.level(skill)));
+    let has_records = !skill.records.is_empty();
+    let prestige_rank = skill.prestige_rank();
+    let prestige_level = skill.prestige_level();
+    let can_prestige = skill.can_prestige();
+    let Skill {
+        name,
+        records,
+        category,
+        created_at: _,
+        prestige: _,
+        potential_bonus,
+        total_exp,
+    } = skill;
+    let collapse_id = ui.make_persistent_id(skill_id);
+
+    let mut expanded = ui
+        .memory()
+        .data
+        .get_persisted::<bool>(collapse_id)
+        .unwrap_or(true);
+
+    let expand_text = match expanded {
+        true => " v ",
+        false => " > ",
+    };
+
+    ui.horizontal(|ui| {
+        if ui.button(expand_text).clicked() {
+            expanded = !expanded;
+            ui.memory().data.insert_persisted(collapse_id, expanded);
+        }
+        changed |= ui.text_edit_singleline(name).changed();
+        recent_days_ui(ui, &recent_days);
+        match timer {
+            Some(RunningTimer {
+                started,
+                distractions,
+            }) => {
+                let elapsed = (Utc::now() - started).num_seconds().max(0);
+                let text = format!(
+                    "Stop {}:{:02}:{:02}",
+                    elapsed / 3600,
+                    elapsed / 60 % 60,
+                    elapsed % 60
+                );
+                timer_toggled = ui
+                    .button(text)
+                    .on_hover_text("Adds the time to today's record")
+                    .clicked();
+                distracted = ui
+                    .button("Distracted")
+                    .on_hover_text("Log an interruption to this session")
+                    .clicked();
+                if !distractions.is_empty() {
+                    let times: Vec<String> = distractions
+                        .iter()
+                        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+                        .collect();
+                    ui.label(format!("{} distractions", distractions.len()))
+                        .on_hover_text(format!("Logged at {}", times.join(", ")));
+                }
+                // Keep the clock ticking.
+                ui.ctx().request_repaint();
+            }
+            // Timing something which isn't counted in minutes wouldn't mean anything.
+            None if unit.is_some() => {}
+            None => timer_toggled = ui.button("Start").clicked(),
+        }
+        if unit.is_none() {
+            pomodoro_started = ui
+                .button("Pomodoro")
+                .on_hover_text("Work in intervals, logging each one as it's finished")
+                .clicked();
+        }
+        ui.label(exp_format.amount(*total_exp));
+        let lifetime_level = skill::level_for_exp(*total_exp);
+        match season_level {
+            Some((season, level)) => {
+                ui.label(format!("{} Level: {}", season.label(), level))
+                    .on_hover_text(format!("Lifetime level: {}", lifetime_level));
+            }
+            None if prestige_rank > 0 => {
+                ui.label(format!("Level: {}", prestige_level))
+                    .on_hover_text(format!("Lifetime level: {}", lifetime_level));
+            }
+            None => {
+                ui.label(format!("Level: {}", lifetime_level));
+            }
+        }
+        let bonus_percent =
+            |rank: u32| (rank as f64 * prestige::EXP_BONUS_PER_RANK * 100.0).round();
+        if prestige_rank > 0 {
+            ui.colored_label(
+                Colors::of(ui.ctx()).good,
+                format!("Prestige {}", prestige_rank),
+            )
+            .on_hover_text(format!("+{}% base EXP", bonus_percent(prestige_rank)));
+        }
+        if can_prestige {
+            prestige = ui
+                .button("Prestige")
+                .on_hover_text(format!(
+                    "Start the level over from 0, earning {}% more base EXP from tomorrow on. \
+                     Every record is kept.",
+                    bonus_percent(1)
+                ))
+                .clicked();
+        }
+        if best_streak > 0 {
+            let streak_name = nudging.streak_name();
+            ui.label(format!(
+                "Current {}: {} days",
+                streak_name.to_lowercase(),
+                current_streak
+            ));
+            ui.label(format!(
+                "Best {}: {} days",
+                streak_name.to_lowercase(),
+                best_streak
+            ));
+        }
+        let old_category = category.clone();
+        category_combo_ui(ui, skill_id, category, categories);
+        changed |= *category != old_category;
+    });
+    if expanded {
+        ui.indent(collapse_id, |ui| {
+            // Records are only edited in the record editor, which re-sorts them once it's
+            // accepted, so the rows never move while they're being typed in.
+            let order = sort.order(records);
+            let old_sort = *sort;
+            let mut remove = None;
+
+            // Only the rows which are scrolled into view get built, so skills with years of
+            // records stay quick to draw. The header is row 0.
+            let row_height = ui.spacing().interact_size.y;
+            egui::ScrollArea::vertical()
+                .id_source((skill_id, "records"))
+                .max_height(RECORDS_MAX_HEIGHT)
+                .show_rows(ui, row_height, order.len() + 1, |ui, rows| {
+                    egui::Grid::new("entry_grid").show(ui, |ui| {
+                        if rows.start == 0 {
+                            sort_header_ui(ui, "Date", SortColumn::Date, sort);
+                            let amount_header = unit.as_ref().map_or("Duration", |u| &u.name);
+                            sort_header_ui(ui, amount_header, SortColumn::Duration, sort);
+                            ui.label("Effort");
+                            ui.label("Intensity");
+                            ui.label("Note");
+                            sort_header_ui(ui, &exp_format.name, SortColumn::Exp, sort);
+                            ui.label("(bonus)");
+                            ui.label("");
+                            ui.end_row();
+                        }
+
+                        let visible = rows.start.saturating_sub(1)..rows.end.saturating_sub(1);
+                        order[visible].iter().for_each(|idx| {
+                            let idx = *idx;
+                            // Records are in date order, so the ones before this are its history.
+                            let rule_context = RuleContext {
+                                skill_id,
+                                history: &records[..idx],
+                                practice_days: &PracticeDays::new(),
+                            };
+                            // Rules aren't run on pending records or ones with EXP set by hand.
+                            let ruled =
+                                records[idx].approved && records[idx].exp_override.is_none();
+                            let explanation = if ruled {
+                                bonus::explain_rules(bonus_rules, &rule_context, &records[idx])
+                            } else {
+                                Vec::new()
+                            };
+                            let rec = &mut records[idx];
+
+                            if ui
+                                .button(rec.date.format("%Y-%m-%d").to_string())
+                                .on_hover_text("Edit this record")
+                                .clicked()
+                            {
+                                edit_record = Some(idx);
+                            }
+                            match unit {
+                                Some(_) => ui.label(rec.duration.to_string()),
+                                None => ui.label(duration_style.record(rec.duration)),
+                            };
+                            ui.label(rec.effort.label());
+                            ui.label(format!("{:.2}x", rec.intensity));
+
+                            ui.horizontal(|ui| {
+                                record_note_ui(ui, &rec.note);
+                                if ui
+                                    .add_enabled(!encrypted, egui::Button::new("...").small())
+                                    .on_hover_text("Edit note in external editor")
+                                    .on_disabled_hover_text(
+                                        "The sheet is encrypted, and an editor would need the \
+                                         note in a file as plain text",
+                                    )
+                                    .clicked()
+                                {
+                                    edit_note = Some(idx);
+                                }
+                                let links_id = ui.make_persistent_id((skill_id, idx, "links"));
+                                changed |= links_ui(ui, links_id, &mut rec.links, sheet_folder);
+                            });
+
+                            let exp = exp_format.number(rec.base_exp + rec.bonus_exp);
+                            let exp_label = match &rec.exp_override {
+                                Some(exp_override) => ui
+                                    .add(
+                                        egui::Label::new(
+                                            egui::RichText::new(format!("{}*", exp))
+                                                .color(Colors::of(ui.ctx()).overridden),
+                                        )
+                                        .sense(egui::Sense::click()),
+                                    )
+                                    .on_hover_text(format!(
+                                        "Set by hand: {}\nClick to change",
+                                        exp_override.reason
+                                    )),
+                                None => ui
+                                    .add(egui::Label::new(exp).sense(egui::Sense::click()))
+                                    .on_hover_text("Click to set by hand"),
+                            };
+                            if exp_label.clicked() {
+                                override_exp = Some(idx);
+                            }
+                            let bonus_label =
+                                ui.label(format!("({})", exp_format.number(rec.bonus_exp)));
+                            if !explanation.is_empty() {
+                                bonus_label.on_hover_text(explanation.join("\n"));
+                            }
+                            if rec.approved {
+                                ui.label("");
+                            } else {
+                                ui.label("pending");
+                            }
+
+                            if ui.button(" - ").clicked() {
+                                remove = Some(idx);
+                            }
+
+                            ui.end_row();
+                        });
+                    });
+                });
+
+            if let Some(idx) = remove {
+                let removed = records.remove(idx);
+                touch(&mut recalculate_since, removed.date);
+                changed = true;
+            }
+            changed |= *sort != old_sort;
+
+            ui.horizontal(|ui| {
+                add_record = ui.button("Add entry...").clicked();
+                paste_rows = ui
+                    .button("Paste rows...")
+                    .on_hover_text("Add rows copied from a spreadsheet: date, duration and note")
+                    .clicked();
+                if ui
+                    .checkbox(merge_same_day, "Merge same-day entries")
+                    .on_hover_text(
+                        "Keep one record per day, adding up the durations. A day's sessions \
+                         count toward the streak bonus together.",
+                    )
+                    .changed()
+                {
+                    touch(&mut recalculate_since, chrono::naive::MIN_DATE);
+                    changed = true;
+                }
+                ui.label(format!(
+                    "Next bonus: {}",
+                    exp_format.amount(*potential_bonus)
+                ));
+                complete = ui
+                    .button("Complete...")
+                    .on_hover_text("Mark it as achieved and move it to the Hall of Fame")
+                    .clicked();
+            });
+
+            egui::CollapsingHeader::new("Lifetime")
+                .id_source((skill_id, "lifetime"))
+                .show(ui, |ui| {
+                    lifetime_ui(ui, skill_id, &lifetime, duration_style)
+                });
+            if !journal.is_empty() {
+                egui::CollapsingHeader::new(format!("Journal ({})", journal.len()))
+                    .id_source((skill_id, "journal"))
+                    .show(ui, |ui| {
+                        changed |= journal_ui(ui, skill_id, journal);
+                    });
+            }
+            egui::CollapsingHeader::new("Unit")
+                .id_source((skill_id, "unit"))
+                .show(ui, |ui| {
+                    changed |= unit_ui(ui, unit, has_records, &mut change_unit);
+                });
+        });
+    }
+
+    SkillUiResponse {
+        changed,
+        recalculate_since,
+        timer_toggled,
+        distracted,
+        pomodoro_started,
+        edit_note,
+        override_exp,
+        edit_record,
+        add_record,
+        paste_rows,
+        change_unit,
+        complete,
+        prestige,
+    }
+}
+
+/// A record's note in the records grid, cut short if it's long, with all of it on hover.
+fn record_note_ui(ui: &mut egui::Ui, note: &str) {
+    const SHOWN_CHARS: usize = 30;
+
+    let first_line = note.lines().next().unwrap_or_default();
+    if first_line.chars().count() <= SHOWN_CHARS && first_line.len() == note.len() {
+        ui.label(note);
+        return;
+    }
+    let shown: String = first_line.chars().take(SHOWN_CHARS).collect();
+    ui.label(format!("{}...", shown)).on_hover_text(note);
+}
+
+/// A menu of the links on a record, which open from there, with a field to add more.
+fn links_ui(ui: &mut egui::Ui, id: egui::Id, links: &mut Vec<Link>, sheet_folder: &Path) -> bool {
+    let mut changed = false;
+    let title = match links.len() {
+        0 => "Link".to_owned(),
+        n => format!("Links ({})", n),
+    };
+    ui.menu_button(title, |ui| {
+        let mut remove = None;
+        links.iter().enumerate().for_each(|(idx, link)| {
+            ui.horizontal(|ui| {
+                ui.weak(link.kind.label());
+                match link.url(sheet_folder) {
+                    Some(url) => {
+                        ui.hyperlink_to(link.display(), url)
+                            .on_hover_text(link.target.as_str());
+                    }
+                    None => {
+                        let copy = ui
+                            .add(
+                                egui::Label::new(egui::RichText::new(link.display()).monospace())
+                                    .sense(egui::Sense::click()),
+                            )
+                            .on_hover_text(format!("{}\nClick to copy", link.target));
+                        if copy.clicked() {
+                            ui.output().copied_text = link.target.clone();
+                        }
+                    }
+                }
+                if ui.small_button(" - ").clicked() {
+                    remove = Some(idx);
+                }
+            });
+        });
+        if let Some(idx) = remove {
+            links.remove(idx);
+            changed = true;
+        }
+
+        let mut draft = ui.memory().data.get_temp::<String>(id).unwrap_or_default();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut draft)
+                    .hint_text("URL, commit or file")
+                    .desired_width(200.0),
+            );
+            let guessed = Link::guess(&draft);
+            let kind_id = id.with("kind");
+            let mut kind = ui
+                .memory()
+                .data
+                .get_temp::<LinkKind>(kind_id)
+                .filter(|_| !draft.trim().is_empty())
+                .unwrap_or(guessed.kind);
+            egui::ComboBox::from_id_source(kind_id)
+                .selected_text(kind.label())
+                .show_ui(ui, |ui| {
+                    LinkKind::ALL.iter().for_each(|k| {
+                        ui.selectable_value(&mut kind, *k, k.label());
+                    });
+                });
+            let add = ui.add_enabled(!draft.trim().is_empty(), egui::Button::new("Add"));
+            if add.clicked() {
+                links.push(Link { kind, ..guessed });
+                draft.clear();
+                ui.memory().data.remove::<LinkKind>(kind_id);
+                changed = true;
+            } else if kind != guessed.kind {
+                ui.memory().data.insert_temp(kind_id, kind);
+            } else {
+                ui.memory().data.remove::<LinkKind>(kind_id);
+            }
+        });
+        ui.memory().data.insert_temp(id, draft);
+    });
+    changed
+}
+
+/// A row of dots for the last two weeks, oldest first, filled in for each day that was logged.
+fn recent_days_ui(ui: &mut egui::Ui, recent_days: &RecentDays) {
+    const SPACING: f32 = 7.0;
+    const RADIUS: f32 = 2.5;
+
+    let size = egui::vec2(SPACING * recent_days.logged.len() as f32, SPACING * 2.0);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let filled = ui.visuals().selection.bg_fill;
+    let empty = ui.visuals().widgets.inactive.fg_stroke.color;
+    let painter = ui.painter();
+    recent_days
+        .logged
+        .iter()
+        .enumerate()
+        .for_each(|(idx, logged)| {
+            let center = egui::pos2(rect.left() + SPACING * (idx as f32 + 0.5), rect.center().y);
+            if *logged {
+                painter.circle_filled(center, RADIUS, filled);
+            } else {
+                painter.circle_stroke(center, RADIUS, egui::Stroke::new(1.0, empty));
+            }
+        });
+    let logged = recent_days.logged.iter().filter(|logged| **logged).count();
+    response.on_hover_text(format!(
+        "Practiced on {} of the last {} days",
+        logged,
+        recent_days.logged.len()
+    ));
+}
+
+/// Brings `since` back to `date`, if that's earlier.
+fn touch(since: &mut Option<NaiveDate>, date: NaiveDate) {
+    *since = Some(since.map_or(date, |since| since.min(date)));
+}
+
+/// A skill's journal, newest first, with a field for each entry's reflection.
+fn journal_ui(ui: &mut egui::Ui, skill_id: &Uuid, journal: &mut [JournalEntry]) -> bool {
+    let mut changed = false;
+    journal
+        .iter_mut()
+        .enumerate()
+        .rev()
+        .for_each(|(idx, entry)| {
+            ui.label(entry.text.as_str());
+            ui.horizontal(|ui| {
+                ui.add_space(12.0);
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut entry.reflection)
+                            .id(ui.make_persistent_id((skill_id, idx, "reflection")))
+                            .hint_text("Reflection")
+                            .desired_width(280.0),
+                    )
+                    .changed();
+            });
+        });
+    changed
+}
+
+/// One skill's row in the skill import window: what it matches on the sheet, and a choice of what
+/// to do with it. Gives back whether it's being renamed to a name the sheet already has.
+fn skill_import_row_ui(
+    ui: &mut egui::Ui,
+    idx: usize,
+    incoming: &mut IncomingSkill,
+    sheet: &Sheet,
+) -> bool {
+    ui.label(incoming.skill.name.as_str());
+    ui.label(incoming.skill.records.len().to_string());
+    let existing_name = |id: Uuid| sheet.skills_list.get(&id).map_or("", |s| s.name.as_str());
+    match incoming.identity {
+        Identity::New => ui.label("New"),
+        Identity::SameId(id) => ui.label(format!("Same skill as {}", existing_name(id))),
+        Identity::SameName(id) => ui.colored_label(
+            Colors::of(ui.ctx()).warning,
+            format!("Another skill called {}", existing_name(id)),
+        ),
+    };
+
+    let mut name_taken = false;
+    ui.horizontal(|ui| {
+        let resolutions = incoming.resolutions(sheet);
+        egui::ComboBox::from_id_source(("skill_import_resolution", idx))
+            .selected_text(incoming.resolution.label())
+            .show_ui(ui, |ui| {
+                resolutions.into_iter().for_each(|resolution| {
+                    let selected = std::mem::discriminant(&incoming.resolution)
+                        == std::mem::discriminant(&resolution);
+                    if ui.selectable_label(selected, resolution.label()).clicked() && !selected {
+                        incoming.resolution = resolution;
+                    }
+                });
+            });
+        if let Resolution::Rename(name) = &mut incoming.resolution {
+            ui.add(egui::TextEdit::singleline(name).desired_width(140.0));
+            name_taken = name.trim().is_empty() || import::find_skill(sheet, name).is_some();
+        }
+    });
+    name_taken
+}
+
+/// How tall a skill's list of records can get before it scrolls.
+const RECORDS_MAX_HEIGHT: f32 = 400.0;
+
+/// A column header which sorts the records by that column when clicked, showing which way
+/// they're sorted.
+fn sort_header_ui(ui: &mut egui::Ui, text: &str, column: SortColumn, sort: &mut RecordSort) {
+    let selected = sort.column == column;
+    let arrow = match (selected, sort.descending) {
+        (false, _) => "",
+        (true, false) => " ^",
+        (true, true) => " v",
+    };
+    if ui
+        .selectable_label(selected, format!("{}{}", text, arrow))
+        .clicked()
+    {
+        sort.click(column);
+    }
+}
+
+/// Whether a skill is logged in minutes or something else, and how that converts to time.
+/// Switching a skill which has records sets `change_unit` instead, as they need converting.
+fn unit_ui(
+    ui: &mut egui::Ui,
+    unit: &mut Option<Unit>,
+    has_records: bool,
+    change_unit: &mut bool,
+) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Logged in:");
+        let to_minutes = ui.radio(unit.is_none(), "Minutes").clicked() && unit.is_some();
+        let to_other = ui.radio(unit.is_some(), "Something else").clicked() && unit.is_none();
+        if (to_minutes || to_other) && has_records {
+            *change_unit = true;
+        } else if to_minutes {
+            *unit = None;
+            changed = true;
+        } else if to_other {
+            *unit = Some(Unit::default());
+            changed = true;
+        }
+    });
+    let unit = match unit {
+        Some(unit) => unit,
+        None => return changed,
+    };
+
+    ui.horizontal(|ui| {
+        ui.label("Called:");
+        changed |= ui
+            .add(egui::TextEdit::singleline(&mut unit.name).desired_width(100.0))
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        let mut estimated = unit.estimate.is_some();
+        if ui
+            .checkbox(&mut estimated, "Count toward time totals, as")
+            .on_hover_text(
+                "An estimate of how long they take, so this skill adds to the hours on the \
+                 Dashboard. Those hours are marked as estimated.",
+            )
+            .changed()
+        {
+            unit.estimate = estimated.then(UnitEstimate::default);
+            changed = true;
+        }
+        if let Some(estimate) = &mut unit.estimate {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut estimate.amount)
+                        .clamp_range(0.1..=f64::MAX)
+                        .speed(1.0),
+                )
+                .changed();
+            ui.label(format!("{} ≈", unit.name));
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut estimate.minutes)
+                        .clamp_range(0.0..=f64::MAX)
+                        .speed(1.0)
+                        .suffix(" min"),
+                )
+                .changed();
+        }
+    });
+    changed
+}
+
+/// Age, average pace and a running total of hours for one skill.
+fn lifetime_ui(
+    ui: &mut egui::Ui,
+    skill_id: &Uuid,
+    lifetime: &stats::Lifetime,
+    duration_style: DurationStyle,
+) {
+    ui.label(format!(
+        "Started {} days ago, {} in total",
+        lifetime.age_days,
+        duration_style.total(lifetime.total_minutes)
+    ));
+    ui.label(format!(
+        "Averaging {:.1} hours a week",
+        lifetime.weekly_hours
+    ));
+
+    let series = Colors::of(ui.ctx()).series;
+    let points = lifetime
+        .cumulative_hours
+        .iter()
+        .map(|(week, hours)| plot::Value::new(*week, *hours))
+        .collect();
+    plot::Plot::new((skill_id, "lifetime_plot"))
+        .height(120f32)
+        .include_x(0f64)
+        .include_y(0f64)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .x_axis_formatter(|week, _| format!("week {}", week))
+        .y_axis_formatter(|hours, _| format!("{}h", hours))
+        .show(ui, |plot_ui| {
+            plot_ui.line(
+                plot::Line::new(plot::Values::from_values(points))
+                    .color(series[0])
+                    .name("Hours"),
+            )
+        });
+}
+
+/// The character sheet's attributes, each with a menu for picking which skills feed into it.
+fn attributes_ui(ui: &mut egui::Ui, sheet: &mut Sheet, exp_format: &ExpFormat) -> bool {
+    let mut changed = false;
+    let mut skills: Vec<(&Uuid, &Skill)> = sheet.skills_list.iter().collect();
+    skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+
+    let mut remove = None;
+    egui::Grid::new("attributes_grid").show(ui, |ui| {
+        sheet
+            .attributes
+            .iter_mut()
+            .enumerate()
+            .for_each(|(idx, attribute)| {
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut attribute.name).desired_width(100.0))
+                    .changed();
+                ui.strong(format!("Level {}", attribute.level(&sheet.skills_list)));
+                ui.label(exp_format.amount(attribute.exp(&sheet.skills_list)));
+                ui.menu_button(format!("{} skills", attribute.skills.len()), |ui| {
+                    skills.iter().for_each(|(id, skill)| {
+                        let mut member = attribute.skills.contains(id);
+                        if ui.checkbox(&mut member, skill.name.as_str()).changed() {
+                            if member {
+                                attribute.skills.push(**id);
+                            } else {
+                                attribute.skills.retain(|s| s != *id);
+                            }
+                            changed = true;
+                        }
+                    });
+                });
+                if ui.button(" - ").clicked() {
+                    remove = Some(idx);
+                }
+                ui.end_row();
+            });
+    });
+    if let Some(idx) = remove {
+        sheet.attributes.remove(idx);
+        changed = true;
+    }
+    if ui.button("New Attribute").clicked() {
+        sheet.attributes.push(Attribute::default());
+        changed = true;
+    }
+    changed
+}
+
+/// The windows a skill on the Skills page can open.
+struct SkillWindows<'a> {
+    paste_rows: &'a mut Option<PasteRowsWindow>,
+    exp_override: &'a mut Option<ExpOverrideWindow>,
+    record_editor: &'a mut Option<RecordEditorWindow>,
+    unit_change: &'a mut Option<UnitChangeWindow>,
+    complete_skill: &'a mut Option<CompleteSkillWindow>,
+    session_links: &'a mut Option<SessionLinksWindow>,
+    skill_list: &'a mut Option<SkillListWindow>,
+}
+
+/// Every skill which is still being practiced, by category. Completed ones are on the Hall of
+/// Fame page instead.
+fn skills_page_ui(
+    ui: &mut egui::Ui,
+    sheet: &mut Sheet,
+    exp_format: &ExpFormat,
+    nudging: &NudgePolicy,
+    note_editor: &str,
+    windows: SkillWindows<'_>,
+) -> bool {
+    let SkillWindows {
+        paste_rows,
+        exp_override,
+        record_editor,
+        unit_change,
+        complete_skill,
+        session_links,
+        skill_list,
+    } = windows;
+    let note_error_id = egui::Id::new("note_editor_error");
+    let note_error = ui.memory().data.get_temp::<String>(note_error_id);
+    if let Some(error) = note_error {
+        ui.horizontal(|ui| {
+            ui.colored_label(Colors::of(ui.ctx()).error, error);
+            if ui.small_button("OK").clicked() {
+                ui.memory().data.remove::<String>(note_error_id);
+            }
+        });
+    }
+
+    let mut changed = false;
+    egui::CollapsingHeader::new("Attributes")
+        .default_open(true)
+        .show(ui, |ui| {
+            changed |= attributes_ui(ui, sheet, exp_format);
+        });
+    ui.separator();
+    let mut to_recalculate = Vec::new();
+    let season = sheet.seasons.current(clock::today());
+    let encrypted = sheet.is_encrypted();
+    let mut toggled_timers = Vec::new();
+    let mut distracted = Vec::new();
+    let mut pomodoro_skill = None;
+    let mut edit_note = None;
+    let Sheet {
+        skills_list,
+        require_approval,
+        timers,
+        distractions,
+        record_sorts,
+        merge_same_day,
+        units,
+        recent_days,
+        bonus_rules,
+        completed,
+        journals,
+        path,
+        duration_style,
+        ..
+    } = sheet;
+
+    let mut categories: BTreeMap<&str, Vec<Uuid>> = BTreeMap::new();
+    skills_list
+        .iter()
+        .filter(|(skill_id, _)| !completed.contains_key(skill_id))
+        .for_each(|(skill_id, skill)| {
+            categories
+                .entry(skill.category.as_str())
+                .or_default()
+                .push(*skill_id);
+        });
+    let category_names: Vec<String> = categories
+        .keys()
+        .filter(|c| !c.is_empty())
+        .map(|c| c.to_string())
+        .collect();
+    let categories: Vec<(String, Vec<Uuid>)> = categories
+        .into_iter()
+        .map(|(name, ids)| (name.to_owned(), ids))
+        .collect();
+
+    let filter_id = egui::Id::new("skill_filter");
+    let mut filter = ui
+        .memory()
+        .data
+        .get_temp::<SkillFilter>(filter_id)
+        .unwrap_or_default();
+    skill_filter_ui(ui, &mut filter, &category_names);
+    let today = clock::today();
+    let categories: Vec<(String, Vec<Uuid>)> = categories
+        .into_iter()
+        .map(|(category, mut ids)| {
+            ids.retain(|id| filter.matches(&skills_list[id], today));
+            (category, ids)
+        })
+        .filter(|(_, ids)| !ids.is_empty())
+        .collect();
+    if categories.is_empty() && !skills_list.is_empty() {
+        ui.label("No skills match the filter.");
+    }
+    ui.memory().data.insert_temp(filter_id, filter);
+
+    let sheet_folder = path.parent().unwrap_or_else(|| Path::new(""));
+    let context = SkillUiContext {
+        categories: &category_names,
+        bonus_rules,
+        recent_days,
+        require_approval: *require_approval,
+        exp_format,
+        season,
+        sheet_folder,
+        nudging,
+        duration_style: *duration_style,
+        encrypted,
+    };
+    for (category, skill_ids) in categories {
+        let header = if category.is_empty() {
+            "Uncategorized"
+        } else {
+            category.as_str()
+        };
+        egui::CollapsingHeader::new(header)
+            .id_source(("category", &category))
+            .default_open(true)
+            .show(ui, |ui| {
+                skill_ids.iter().for_each(|skill_id| {
+                    if let Some(skill) = skills_list.get_mut(skill_id) {
+                        let timer = timers.get(skill_id).map(|started| RunningTimer {
+                            started: *started,
+                            distractions: distractions.get(skill_id).map_or(&[], Vec::as_slice),
+                        });
+                        let mut sort = record_sorts.get(skill_id).copied().unwrap_or_default();
+                        let mut merge = merge_same_day.contains(skill_id);
+                        let mut unit = units.get(skill_id).cloned();
+                        let settings = SkillSettings {
+                            sort: &mut sort,
+                            merge_same_day: &mut merge,
+                            unit: &mut unit,
+                            journal: journals
+                                .get_mut(skill_id)
+                                .map_or(&mut [], Vec::as_mut_slice),
+                        };
+                        let scope =
+                            ui.scope(|ui| skill_ui(ui, skill_id, skill, timer, settings, &context));
+                        if sort != record_sorts.get(skill_id).copied().unwrap_or_default() {
+                            record_sorts.insert(*skill_id, sort);
+                        }
+                        if merge {
+                            merge_same_day.insert(*skill_id);
+                        } else {
+                            merge_same_day.remove(skill_id);
+                        }
+                        if scope.inner.change_unit {
+                            let current = units.get(skill_id);
+                            *unit_change = Some(UnitChangeWindow::new(*skill_id, skill, current));
+                        }
+                        match unit {
+                            Some(unit) => units.insert(*skill_id, unit),
+                            None => units.remove(skill_id),
+                        };
+                        if ui.rect_contains_pointer(scope.response.rect) {
+                            ui.memory().data.insert_temp(focused_skill_id(), *skill_id);
+                        }
+                        let response = scope.inner;
+                        changed |= response.changed;
+                        if let Some(since) = response.recalculate_since {
+                            to_recalculate.push((*skill_id, since));
+                        }
+                        if response.timer_toggled {
+                            toggled_timers.push(*skill_id);
+                        }
+                        if response.distracted {
+                            distracted.push(*skill_id);
+                        }
+                        if response.pomodoro_started {
+                            pomodoro_skill = Some(*skill_id);
+                        }
+                        if let Some(record) = response.edit_note {
+                            edit_note = Some((*skill_id, record));
+                        }
+                        if response.paste_rows {
+                            *paste_rows = Some(PasteRowsWindow::new(*skill_id, String::new()));
+                        }
+                        if response.complete {
+                            *complete_skill = Some(CompleteSkillWindow::new(*skill_id));
+                        }
+                        if response.prestige && skill.prestige(today) {
+                            changed = true;
+                            to_recalculate.push((*skill_id, today));
+                        }
+                        if let Some(idx) = response.override_exp {
+                            if let Some(rec) = skill.records.get(idx) {
+                                *exp_override = Some(ExpOverrideWindow::new(*skill_id, idx, rec));
+                            }
+                        }
+                        let counted = units.contains_key(skill_id);
+                        let style = context.duration_style;
+                        if let Some(idx) = response.edit_record {
+                            if let Some(rec) = skill.records.get(idx) {
+                                let rec = rec.clone();
+                                let window = RecordEditorWindow::new(
+                                    *skill_id,
+                                    Some(idx),
+                                    rec,
+                                    counted,
+                                    style,
+                                );
+                                *record_editor = Some(window);
+                            }
+                        }
+                        if response.add_record {
+                            let rec = SheetActionRecord {
+                                approved: !context.require_approval,
+                                ..Default::default()
+                            };
+                            let window =
+                                RecordEditorWindow::new(*skill_id, None, rec, counted, style);
+                            *record_editor = Some(window);
+                        }
+                    }
+                });
+            });
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("New Skill").clicked() {
+            skills_list.insert(Uuid::new_v4(), Skill::default());
+            changed = true;
+        }
+        if ui.button("New Skills from List...").clicked() {
+            *skill_list = Some(SkillListWindow::default());
+        }
+    });
+
+    to_recalculate
+        .iter()
+        .for_each(|(skill_id, since)| sheet.recalculate_skill_since(skill_id, *since));
+    let now = Utc::now();
+    distracted
+        .iter()
+        .for_each(|skill_id| sheet.log_distraction(skill_id, now));
+    changed |= !distracted.is_empty();
+    toggled_timers
+        .iter()
+        .for_each(|skill_id| match sheet.stop_timer(skill_id, now) {
+            Some(0) => {}
+            Some(_) => {
+                *session_links = Some(SessionLinksWindow {
+                    skill_id: *skill_id,
+                    date: clock::date_at(now),
+                    text: String::new(),
+                });
+            }
+            None => sheet.start_timer(*skill_id, now),
+        });
+    changed |= !toggled_timers.is_empty();
+    if let Some(skill_id) = pomodoro_skill {
+        sheet.pomodoro = Some(Pomodoro::start(skill_id, now));
+    }
+    let note_to_edit = edit_note.and_then(|(skill_id, record)| {
+        let rec = sheet.skills_list.get(&skill_id)?.records.get(record)?;
+        Some(NoteEdit::start(skill_id, record, rec, note_editor))
+    });
+    match note_to_edit {
+        Some(Ok(edit)) => sheet.note_edits.push(edit),
+        Some(Err(e)) => {
+            let error = format!("Couldn't open the note in an editor: {}", e);
+            ui.memory().data.insert_temp(note_error_id, error);
+        }
+        None => {}
+    }
+
+    changed
+}
+
+/// Where the skill the keyboard shortcuts act on is kept in `egui`'s memory. It's the last skill
+/// the pointer was over on the skills page.
+fn focused_skill_id() -> egui::Id {
+    egui::Id::new("focused_skill")
+}
+
+/// A week of planned sessions, a column per day plus one for sessions not on a day yet. Sessions
+/// are moved between days by dragging them by their name.
+fn planner_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet) -> bool {
+    const COLUMN_HEIGHT: f32 = 200.0;
+
+    let today = clock::today();
+    let mut skills: Vec<(Uuid, String)> = sheet
+        .skills_list
+        .iter()
+        .map(|(id, skill)| (*id, skill.name.clone()))
+        .collect();
+    skills.sort_by(|a, b| a.1.cmp(&b.1));
+    let skill_name = |id: &Uuid| {
+        skills
+            .iter()
+            .find(|(skill_id, _)| skill_id == id)
+            .map_or("(deleted skill)", |(_, name)| name.as_str())
+    };
+    let mut changed = false;
+
+    let week_id = egui::Id::new("planner_week");
+    let mut week = ui
+        .memory()
+        .data
+        .get_temp::<NaiveDate>(week_id)
+        .unwrap_or_else(|| stats::week_start(today));
+    ui.horizontal(|ui| {
+        if ui.button(" < ").clicked() {
+            week -= Duration::weeks(1);
+        }
+        ui.strong(format!("Week of {}", week.format("%B %-d, %Y")));
+        if ui.button(" > ").clicked() {
+            week += Duration::weeks(1);
+        }
+        if ui.button("This week").clicked() {
+            week = stats::week_start(today);
+        }
+    });
+    ui.memory().data.insert_temp(week_id, week);
+
+    let draft_id = egui::Id::new("planner_draft");
+    let mut draft = ui
+        .memory()
+        .data
+        .get_temp::<PlannedSession>(draft_id)
+        .unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.label("Plan");
+        ui.add(
+            egui::DragValue::new(&mut draft.minutes)
+                .clamp_range(1..=600)
+                .suffix(" min"),
+        );
+        ui.label("of");
+        let selected = skills.iter().find(|(id, _)| *id == draft.skill_id);
+        egui::ComboBox::from_id_source("planner_skill")
+            .selected_text(selected.map_or("Pick a skill", |(_, name)| name.as_str()))
+            .show_ui(ui, |ui| {
+                skills.iter().for_each(|(id, name)| {
+                    ui.selectable_value(&mut draft.skill_id, *id, name);
+                });
+            });
+        if ui
+            .add_enabled(selected.is_some(), egui::Button::new("Add"))
+            .clicked()
+        {
+            sheet.planned.push(draft.clone());
+            changed = true;
+        }
+    });
+    ui.memory().data.insert_temp(draft_id, draft);
+    ui.label("Drag sessions onto a day to plan them. Planned sessions show up on the Today page.");
+    ui.separator();
+
+    let days: Vec<Option<NaiveDate>> = std::iter::once(None)
+        .chain((0..7).map(|offset| Some(week + Duration::days(offset))))
+        .collect();
+    let mut dragged = None;
+    let mut dropped_on = None;
+    let mut completed = None;
+    let mut removed = None;
+    ui.columns(days.len(), |columns| {
+        columns.iter_mut().zip(&days).for_each(|(ui, day)| {
+            let title = day.map_or("Unplanned".to_owned(), |day| {
+                day.format("%a %-d").to_string()
+            });
+            if *day == Some(today) {
+                ui.strong(title);
+            } else {
+                ui.label(title);
+            }
+
+            sheet
+                .planned
+                .iter()
+                .enumerate()
+                .filter(|(_, session)| session.date == *day)
+                .for_each(|(idx, session)| {
+                    egui::Frame::group(ui.style()).show(ui, |ui| {
+                        let name = skill_name(&session.skill_id);
+                        let handle = ui
+                            .add(
+                                egui::Label::new(egui::RichText::new(name).strong())
+                                    .sense(egui::Sense::drag()),
+                            )
+                            .on_hover_cursor(egui::CursorIcon::Grab);
+                        if handle.dragged() {
+                            dragged = Some(idx);
+                            ui.output().cursor_icon = egui::CursorIcon::Grabbing;
+                            egui::show_tooltip_at_pointer(ui.ctx(), handle.id, |ui| {
+                                ui.label(format!("{} ({} min)", name, session.minutes));
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{} min", session.minutes));
+                            if ui.small_button("Done").clicked() {
+                                completed = Some(idx);
+                            }
+                            if ui.small_button("x").clicked() {
+                                removed = Some(idx);
+                            }
+                        });
+                    });
+                });
+
+            // Empty days still need somewhere to drop onto.
+            let used = ui.min_rect().height();
+            ui.allocate_space(egui::vec2(
+                ui.available_width(),
+                (COLUMN_HEIGHT - used).max(0.0),
+            ));
+            if ui.rect_contains_pointer(ui.min_rect()) {
+                dropped_on = Some(*day);
+            }
+        });
+    });
+
+    if let (Some(idx), Some(day)) = (dragged, dropped_on) {
+        if ui.input().pointer.any_released() && sheet.planned[idx].date != day {
+            sheet.planned[idx].date = day;
+            changed = true;
+        }
+    }
+    if let Some(idx) = completed {
+        sheet.complete_planned(idx, today);
+        changed = true;
+    } else if let Some(idx) = removed {
+        sheet.planned.remove(idx);
+        changed = true;
+    }
+
+    ui.separator();
+    egui::CollapsingHeader::new("Recurring")
+        .default_open(!sheet.recurring.is_empty())
+        .show(ui, |ui| {
+            changed |= recurring_ui(ui, &mut sheet.recurring, &skills);
+        });
+    changed
+}
+
+/// Fixed commitments which log themselves as pending records each time they come round.
+fn recurring_ui(
+    ui: &mut egui::Ui,
+    recurring: &mut Vec<Recurring>,
+    skills: &[(Uuid, String)],
+) -> bool {
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+    ui.label("These are added to the Pending page on the days they happen, ready to confirm.");
+    let mut changed = false;
+    let mut remove = None;
+    recurring
+        .iter_mut()
+        .enumerate()
+        .for_each(|(idx, recurring)| {
+            let before = recurring.clone();
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut recurring.name).desired_width(100.0));
+                ui.add(
+                    egui::DragValue::new(&mut recurring.minutes)
+                        .clamp_range(1..=600)
+                        .suffix(" min"),
+                );
+                ui.label("of");
+                let selected = skills.iter().find(|(id, _)| *id == recurring.skill_id);
+                egui::ComboBox::from_id_source(("recurring_skill", idx))
+                    .selected_text(selected.map_or("Pick a skill", |(_, name)| name.as_str()))
+                    .show_ui(ui, |ui| {
+                        skills.iter().for_each(|(id, name)| {
+                            ui.selectable_value(&mut recurring.skill_id, *id, name);
+                        });
+                    });
+                ui.label("every");
+                recurring
+                    .weekdays
+                    .iter_mut()
+                    .zip(WEEKDAYS.iter())
+                    .for_each(|(on, name)| {
+                        if ui.selectable_label(*on, *name).clicked() {
+                            *on = !*on;
+                        }
+                    });
+                if ui.button(" - ").clicked() {
+                    remove = Some(idx);
+                }
+            });
+            changed |= *recurring != before;
+        });
+    if let Some(idx) = remove {
+        recurring.remove(idx);
+        changed = true;
+    }
+    if ui.button("Add recurring session").clicked() {
+        recurring.push(Recurring::default());
+        changed = true;
+    }
+    changed
+}
+
+/// Every goal, with how far along it is. Goals can be edited in place.
+fn goals_page_ui(
+    ui: &mut egui::Ui,
+    sheet: &mut Sheet,
+    exp_format: &ExpFormat,
+    nudging: &NudgePolicy,
+) -> bool {
+    let today = clock::today();
+    let mut skills: Vec<(Uuid, String)> = sheet
+        .skills_list
+        .iter()
+        .map(|(id, skill)| (*id, skill.name.clone()))
+        .collect();
+    skills.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let mut changed = false;
+    let mut remove = None;
+    if sheet.goals.is_empty() {
+        ui.label("No goals yet.");
+    }
+    let Sheet {
+        goals, skills_list, ..
+    } = sheet;
+    egui::Grid::new("goals_grid").show(ui, |ui| {
+        goals.iter_mut().enumerate().for_each(|(idx, goal)| {
+            let skill_name = skills.iter().find(|(id, _)| *id == goal.skill_id);
+            let old_skill = goal.skill_id;
+            egui::ComboBox::from_id_source(("goal_skill", idx))
+                .selected_text(skill_name.map_or("(deleted skill)", |(_, name)| name.as_str()))
+                .show_ui(ui, |ui| {
+                    skills.iter().for_each(|(id, name)| {
+                        ui.selectable_value(&mut goal.skill_id, *id, name);
+                    });
+                });
+            changed |= goal.skill_id != old_skill;
+
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut goal.target)
+                        .clamp_range(0f64..=f64::MAX)
+                        .speed(0.5),
+                )
+                .changed();
+            let old_measure = goal.measure;
+            egui::ComboBox::from_id_source(("goal_measure", idx))
+                .selected_text(goal.measure.label(&exp_format.name))
+                .show_ui(ui, |ui| {
+                    GoalMeasure::ALL.iter().for_each(|measure| {
+                        ui.selectable_value(
+                            &mut goal.measure,
+                            *measure,
+                            measure.label(&exp_format.name),
+                        );
+                    });
+                });
+            changed |= goal.measure != old_measure;
+
+            ui.label("from");
+            changed |= date_picker_ui(
+                ui,
+                ui.make_persistent_id(("goal_start", idx)),
+                &mut goal.start,
+            );
+            ui.label("by");
+            changed |= date_picker_ui(
+                ui,
+                ui.make_persistent_id(("goal_deadline", idx)),
+                &mut goal.deadline,
+            );
+
+            let progress = goal.progress(skills_list.get(&goal.skill_id), today);
+            let amount = match goal.measure {
+                GoalMeasure::Exp => exp_format.number(progress.amount),
+                _ => format!("{:.1}", progress.amount),
+            };
+            ui.add(
+                egui::ProgressBar::new(progress.fraction as f32)
+                    .desired_width(150.0)
+                    .text(amount),
+            );
+            match progress.state {
+                GoalState::InProgress => {
+                    let days_left = goal.deadline.signed_duration_since(today).num_days();
+                    ui.label(format!("{} days left", days_left));
+                }
+                GoalState::Completed => {
+                    ui.colored_label(Colors::of(ui.ctx()).good, progress.state.label());
+                }
+                GoalState::Failed if nudging.shows_losses() => {
+                    ui.colored_label(Colors::of(ui.ctx()).bad, progress.state.label());
+                }
+                GoalState::Failed => {
+                    ui.label(nudging.goal_state_label(progress.state));
+                }
+            }
+            if ui.button(" - ").clicked() {
+                remove = Some(idx);
+            }
+            ui.end_row();
+        });
+    });
+    if let Some(idx) = remove {
+        goals.remove(idx);
+        changed = true;
+    }
+
+    if ui
+        .add_enabled(!skills.is_empty(), egui::Button::new("New Goal"))
+        .clicked()
+    {
+        goals.push(Goal {
+            skill_id: skills[0].0,
+            ..Default::default()
+        });
+        changed = true;
+    }
+    changed
+}
+
+/// Lists every record still waiting for approval, across all skills on the sheet.
+fn pending_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet) -> bool {
+    if sheet.pending_count() == 0 {
+        ui.label("Nothing is waiting for approval.");
+        return false;
+    }
+
+    let mut to_recalculate = Vec::new();
+    let approve_all = ui.button("Approve all").clicked();
+    let duration_style = sheet.duration_style;
+
+    egui::Grid::new("pending_grid").show(ui, |ui| {
+        ui.label("Skill");
+        ui.label("Date");
+        ui.label("Duration");
+        ui.label("Note");
+        ui.end_row();
+
+        sheet.skills_list.iter_mut().for_each(|(skill_id, skill)| {
+            let mut changed = false;
+            let mut idx = 0;
+            while idx < skill.records.len() {
+                let rec = &mut skill.records[idx];
+                if rec.approved {
+                    idx += 1;
+                    continue;
+                }
+
+                ui.label(skill.name.as_str());
+                ui.label(rec.date.to_string());
+                ui.label(duration_style.record(rec.duration));
+                ui.label(rec.note.as_str());
+                let approve = ui.button("Approve").clicked();
+                let reject = ui.button("Reject").clicked();
+                ui.end_row();
+
+                if approve || approve_all {
+                    rec.approved = true;
+                    changed = true;
+                    idx += 1;
+                } else if reject {
+                    skill.records.remove(idx);
+                    changed = true;
+                } else {
+                    idx += 1;
+                }
+            }
+
+            if changed {
+                to_recalculate.push(*skill_id);
+            }
+        });
+    });
+
+    to_recalculate
+        .iter()
+        .for_each(|skill_id| sheet.recalculate_skill(skill_id));
+
+    !to_recalculate.is_empty()
+}
+
+/// Totals and leaderboards across every skill on the sheet.
+fn dashboard_page_ui(
+    ui: &mut egui::Ui,
+    sheet: &Sheet,
+    exp_format: &ExpFormat,
+    nudging: &NudgePolicy,
+) {
+    const ROWS_SHOWN: usize = 5;
+
+    let dashboard = Dashboard::new(sheet, clock::today());
+
+    // Time from other units is only ever an estimate, so it's kept apart and marked as one.
+    let duration_style = sheet.duration_style;
+    let hours_ui = |ui: &mut egui::Ui, minutes: u64, estimated_minutes: f64| {
+        ui.strong(duration_style.total(minutes));
+        if !dashboard.estimates.is_empty() {
+            let mut hover = format!(
+                "Estimated from other units:\n{}",
+                dashboard.estimates.join("\n")
+            );
+            if !dashboard.unconverted.is_empty() {
+                hover.push_str(&format!(
+                    "\nLeft out, with no estimate: {}",
+                    dashboard.unconverted.join(", ")
+                ));
+            }
+            ui.label(
+                egui::RichText::new(format!(
+                    "+ ~{}",
+                    duration_style.total(estimated_minutes.round() as u64)
+                ))
+                .italics(),
+            )
+            .on_hover_text(hover);
+        } else if !dashboard.unconverted.is_empty() {
+            ui.label("*").on_hover_text(format!(
+                "Left out, with no estimate of their time: {}",
+                dashboard.unconverted.join(", ")
+            ));
+        }
+    };
+    ui.horizontal(|ui| {
+        ui.label("This week:");
+        hours_ui(ui, dashboard.week_minutes, dashboard.week_estimated_minutes);
+        ui.separator();
+        ui.label("This month:");
+        hours_ui(
+            ui,
+            dashboard.month_minutes,
+            dashboard.month_estimated_minutes,
+        );
+        if dashboard.pomodoros_today > 0 {
+            ui.separator();
+            ui.label("Pomodoros today:");
+            ui.strong(dashboard.pomodoros_today.to_string());
+        }
+    });
+    ui.separator();
+
+    fn leaderboard_ui<T>(
+        ui: &mut egui::Ui,
+        heading: &str,
+        empty_text: &str,
+        rows: &[Ranked<T>],
+        value_text: impl Fn(&T) -> String,
+    ) {
+        ui.heading(heading);
+        if rows.is_empty() {
+            ui.label(empty_text);
+            return;
+        }
+        egui::Grid::new(heading).show(ui, |ui| {
+            rows.iter().take(ROWS_SHOWN).for_each(|ranked| {
+                ui.label(ranked.name.as_str());
+                ui.label(value_text(&ranked.value));
+                ui.end_row();
+            });
+        });
+    }
+
+    leaderboard_ui(
+        ui,
+        "Top skills this week",
+        "Nothing earned yet this week.",
+        &dashboard.top_gains,
+        |exp| exp_format.signed_number(*exp),
+    );
+    ui.separator();
+    let no_streaks = format!("No {} going.", nudging.streak_name().to_lowercase());
+    leaderboard_ui(
+        ui,
+        nudging.streak_name(),
+        &no_streaks,
+        &dashboard.streaks,
+        |days| nudging.streak_days(*days),
+    );
+    ui.separator();
+    leaderboard_ui(
+        ui,
+        "Potential bonuses",
+        "No bonuses waiting.",
+        &dashboard.potential_bonuses,
+        |bonus| exp_format.signed_number(*bonus),
+    );
+
+    if let Some((this_season, last_season)) = &dashboard.seasons {
+        ui.separator();
+        ui.heading("This season");
+        egui::Grid::new("dashboard_seasons").show(ui, |ui| {
+            ui.label("");
+            ui.label(&exp_format.name);
+            ui.label("Hours");
+            ui.label("Sessions");
+            ui.end_row();
+            let season_row = |ui: &mut egui::Ui, summary: &SeasonSummary| {
+                ui.label(summary.season.label());
+                ui.label(exp_format.number(summary.exp));
+                ui.label(format!("{:.1}", summary.minutes as f64 / 60f64));
+                ui.label(summary.sessions.to_string());
+                ui.end_row();
+            };
+            season_row(ui, this_season);
+            season_row(ui, last_season);
+        });
+    }
+}
+
+/// What to work on today, and how hard to go at it.
+fn today_page_ui(
+    ui: &mut egui::Ui,
+    sheet: &mut Sheet,
+    exp_format: &ExpFormat,
+    nudging: &NudgePolicy,
+) -> bool {
+    let today = clock::today();
+    let mut completed = None;
+    let quests: Vec<(usize, &PlannedSession)> = sheet
+        .planned
+        .iter()
+        .enumerate()
+        .filter(|(_, session)| session.date.map_or(false, |date| date <= today))
+        .collect();
+    if !quests.is_empty() {
+        ui.heading("Planned quests");
+        quests.iter().for_each(|(idx, session)| {
+            let name = sheet
+                .skills_list
+                .get(&session.skill_id)
+                .map_or("(deleted skill)", |skill| skill.name.as_str());
+            ui.horizontal(|ui| {
+                ui.strong(name);
+                ui.label(sheet.duration_style.record(session.minutes));
+                if session.is_overdue(today) {
+                    if let Some(date) = session.date {
+                        let from = format!("from {}", date);
+                        if nudging.shows_losses() {
+                            ui.colored_label(Colors::of(ui.ctx()).warning, from);
+                        } else {
+                            ui.label(from);
+                        }
+                    }
+                }
+                if ui.button("Done").clicked() {
+                    completed = Some(*idx);
+                }
+            });
+        });
+        ui.separator();
+    }
+
+    const SUGGESTIONS_SHOWN: usize = 3;
+
+    ui.heading("Up next");
+    suggest::rank(sheet, today, exp_format, nudging)
+        .iter()
+        .filter(|suggestion| suggestion.score > 0f64)
+        .take(SUGGESTIONS_SHOWN)
+        .for_each(|suggestion| {
+            if let Some(skill) = sheet.skills_list.get(&suggestion.skill_id) {
+                ui.horizontal(|ui| {
+                    ui.strong(skill.name.as_str());
+                    ui.label(suggestion.reasons.join("; "));
+                });
+            }
+        });
+    ui.separator();
+
+    ui.heading("Today's note");
+    let mut note = sheet.daily_notes.get(&today).cloned().unwrap_or_default();
+    let noted = daily_note_ui(ui, &mut note);
+    if noted {
+        sheet.set_daily_note(today, note);
+    }
+    ui.separator();
+
+    let mut skills: Vec<(&Uuid, &Skill)> = sheet.active_skills().collect();
+    skills.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+
+    egui::Grid::new("today_grid").show(ui, |ui| {
+        ui.label("Skill");
+        ui.label("Today");
+        ui.label("Fatigue");
+        ui.label("Suggested");
+        ui.end_row();
+
+        skills.iter().for_each(|(skill_id, skill)| {
+            ui.label(skill.name.as_str());
+            let minutes = stats::minutes_per_day([*skill])
+                .get(&today)
+                .copied()
+                .unwrap_or(0);
+            let zones = sheet.zones_for(skill_id);
+            match zones.zone(minutes) {
+                Some(zone) => {
+                    let text = sheet.duration_style.record(minutes);
+                    ui.colored_label(Colors::of(ui.ctx()).zone(zone), text)
+                }
+                None => ui.label(sheet.duration_style.record(minutes)),
+            }
+            .on_hover_text(zone_hover_text(&zones, minutes));
+            match stats::recovery(skill, today) {
+                Some(recovery) => {
+                    ui.add(
+                        egui::ProgressBar::new((recovery.fatigue / 2f64).min(1f64) as f32)
+                            .desired_width(100.0)
+                            .text(format!("{:.2}", recovery.fatigue)),
+                    )
+                    .on_hover_text("Last week's effort compared to the last four weeks'");
+                    ui.label(recovery.suggested_effort.label());
+                    ui.label(recovery.reason);
+                }
+                None => {
+                    ui.label("-");
+                    ui.label("Any");
+                    ui.label("No recent sessions");
+                }
+            }
+            ui.end_row();
+        });
+    });
+
+    match completed {
+        Some(idx) => {
+            sheet.complete_planned(idx, today);
+            true
+        }
+        None => noted,
+    }
+}
+
+/// Mood, sleep and anything else about a day. Gives back whether any of it changed.
+fn daily_note_ui(ui: &mut egui::Ui, note: &mut DailyNote) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Mood:");
+        Mood::ALL.iter().for_each(|mood| {
+            let picked = note.mood == Some(*mood);
+            // Clicking the mood which is already picked takes it off again.
+            if ui.selectable_label(picked, mood.label()).clicked() {
+                note.mood = (!picked).then_some(*mood);
+                changed = true;
+            }
+        });
+    });
+    ui.horizontal(|ui| {
+        let mut slept = note.sleep_hours.is_some();
+        if ui.checkbox(&mut slept, "Slept").changed() {
+            note.sleep_hours = slept.then_some(8.0);
+            changed = true;
+        }
+        if let Some(hours) = &mut note.sleep_hours {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(hours)
+                        .speed(0.25)
+                        .clamp_range(0.0..=24.0)
+                        .suffix(" h"),
+                )
+                .changed();
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Notes:");
+        changed |= ui
+            .add(
+                egui::TextEdit::singleline(&mut note.text).hint_text("e.g. travelling, had a cold"),
+            )
+            .changed();
+    });
+    changed
+}
+
+/// Compares the hours logged each week against the hours you were awake for.
+fn time_audit_page_ui(ui: &mut egui::Ui, sheet: &Sheet, settings: &mut Settings) {
+    const WEEKS_SHOWN: i64 = 8;
+
+    ui.horizontal(|ui| {
+        ui.label("Waking hours per day:");
+        ui.add(
+            egui::DragValue::new(&mut settings.waking_hours_per_day)
+                .speed(0.25)
+                .clamp_range(1.0..=24.0),
+        );
+    });
+    ui.separator();
+
+    let today = clock::today();
+    let this_week = stats::week_start(today);
+    let minutes_per_week = stats::minutes_per_week(sheet.skills_list.values());
+
+    egui::Grid::new("time_audit_grid").show(ui, |ui| {
+        ui.label("Week of");
+        ui.label("Tracked");
+        ui.label("Untracked");
+        ui.label("");
+        ui.label("Change");
+        ui.end_row();
+
+        let mut previous_hours: Option<f64> = None;
+        (0..WEEKS_SHOWN).rev().for_each(|weeks_ago| {
+            let week = this_week - Duration::weeks(weeks_ago);
+            // The current week only counts the days which have happened so far.
+            let days = if week == this_week {
+                (today - week).num_days() + 1
+            } else {
+                7
+            };
+            let baseline_hours = settings.waking_hours_per_day * days as f64;
+            let tracked_hours = *minutes_per_week.get(&week).unwrap_or(&0) as f64 / 60f64;
+            let untracked_hours = (baseline_hours - tracked_hours).max(0f64);
+            let tracked_fraction = (tracked_hours / baseline_hours).min(1f64) as f32;
+
+            ui.label(week.to_string());
+            ui.label(format!("{:.1}h", tracked_hours));
+            ui.label(format!("{:.1}h", untracked_hours));
+            ui.add(
+                egui::ProgressBar::new(tracked_fraction)
+                    .desired_width(120.0)
+                    .text(format!("{:.0}%", tracked_fraction * 100f32)),
+            );
+            match previous_hours {
+                Some(prev) => ui.label(format!("{:+.1}h", tracked_hours - prev)),
+                None => ui.label(""),
+            };
+            ui.end_row();
+
+            previous_hours = Some(tracked_hours);
+        });
+    });
+}
+
+/// Edits the settings of a single bonus rule.
+fn bonus_rule_ui(
+    ui: &mut egui::Ui,
+    idx: usize,
+    rule: &mut BonusRule,
+    skills: &[(Uuid, String)],
+    exp_format: &ExpFormat,
+) -> bool {
+    let exp = exp_format.name.as_str();
+    let mut changed = false;
+    match rule {
+        BonusRule::Streak(streak) => {
+            ui.horizontal(|ui| {
+                ui.label(format!("Share of {} passed on:", exp));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut streak.max_daily_bonus)
+                            .speed(0.01)
+                            .clamp_range(0.0..=2.0),
+                    )
+                    .changed();
+                ui.label("fading out over");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut streak.max_bonus_days).clamp_range(1..=60))
+                    .changed();
+                ui.label("days");
+            });
+            ui.horizontal(|ui| {
+                ui.label(format!("Streak length skills get base {} +", exp));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut streak.per_streak_day)
+                            .speed(0.005)
+                            .clamp_range(0.0..=1.0),
+                    )
+                    .changed();
+                ui.label("per day in a row, up to");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut streak.max_streak_bonus)
+                            .speed(0.01)
+                            .clamp_range(0.0..=5.0),
+                    )
+                    .changed();
+            });
+            ui.label("Skills using streak length instead:");
+            skills.iter().for_each(|(skill_id, name)| {
+                let mut selected = streak.length_skills.contains(skill_id);
+                if ui.checkbox(&mut selected, name.as_str()).changed() {
+                    if selected {
+                        streak.length_skills.push(*skill_id);
+                    } else {
+                        streak.length_skills.retain(|id| id != skill_id);
+                    }
+                    changed = true;
+                }
+            });
+        }
+        BonusRule::Synergy(synergy) => {
+            ui.horizontal(|ui| {
+                ui.label(format!("Bonus share of base {}:", exp));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut synergy.bonus)
+                            .speed(0.01)
+                            .clamp_range(0.0..=2.0),
+                    )
+                    .changed();
+            });
+            ui.label("When practiced on the same day as any of:");
+            skills.iter().for_each(|(skill_id, name)| {
+                let mut selected = synergy.skills.contains(skill_id);
+                if ui.checkbox(&mut selected, name.as_str()).changed() {
+                    if selected {
+                        synergy.skills.push(*skill_id);
+                    } else {
+                        synergy.skills.retain(|id| id != skill_id);
+                    }
+                    changed = true;
+                }
+            });
+        }
+        BonusRule::Event(event) => {
+            ui.horizontal(|ui| {
+                ui.label("From");
+                changed |=
+                    date_picker_ui(ui, ui.make_persistent_id((idx, "start")), &mut event.start);
+                ui.label("to");
+                changed |= date_picker_ui(ui, ui.make_persistent_id((idx, "end")), &mut event.end);
+                ui.label(format!("multiply {} by", exp));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut event.multiplier)
+                            .speed(0.05)
+                            .clamp_range(0.0..=10.0),
+                    )
+                    .changed();
+            });
+        }
+        BonusRule::Cap(cap) => {
+            ui.horizontal(|ui| {
+                ui.label(format!("Most bonus {} per session:", exp));
+                changed |= ui
+                    .add(egui::DragValue::new(&mut cap.max_bonus).clamp_range(0.0..=f64::MAX))
+                    .changed();
+            });
+        }
+        BonusRule::Decay(decay) => {
+            ui.horizontal(|ui| {
+                ui.label("After");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut decay.grace_days).clamp_range(0..=365))
+                    .changed();
+                ui.label("days off, lose");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut decay.per_day)
+                            .speed(0.005)
+                            .clamp_range(0.0..=1.0),
+                    )
+                    .changed();
+                ui.label(format!("of base {} per day, up to", exp));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut decay.max_penalty)
+                            .speed(0.01)
+                            .clamp_range(0.0..=1.0),
+                    )
+                    .changed();
+            });
+        }
+        BonusRule::Cooldown(cooldown) => {
+            ui.horizontal(|ui| {
+                changed |= optional_limit_ui(ui, &mut cooldown.max_sessions, 2, "sessions");
+                ui.label("or");
+                changed |= optional_limit_ui(ui, &mut cooldown.max_minutes, 120, "minutes");
+                ui.label("a day earn in full");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Past that, lose");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut cooldown.penalty)
+                            .speed(0.01)
+                            .clamp_range(0.0..=1.0),
+                    )
+                    .changed();
+                ui.label(format!("of the {}", exp));
+            });
+            ui.label("For skills:");
+            skills.iter().for_each(|(skill_id, name)| {
+                let mut selected = cooldown.skills.contains(skill_id);
+                if ui.checkbox(&mut selected, name.as_str()).changed() {
+                    if selected {
+                        cooldown.skills.push(*skill_id);
+                    } else {
+                        cooldown.skills.retain(|id| id != skill_id);
+                    }
+                    changed = true;
+                }
+            });
+        }
+    }
+    changed
+}
+
+/// A limit which can be turned off, as a checkbox and the limit itself. Returns whether it changed.
+fn optional_limit_ui<T: egui::emath::Numeric>(
+    ui: &mut egui::Ui,
+    limit: &mut Option<T>,
+    default: T,
+    unit: &str,
+) -> bool {
+    let mut enabled = limit.is_some();
+    let mut changed = ui.checkbox(&mut enabled, "").changed();
+    match (limit.as_mut(), enabled) {
+        (Some(value), true) => {
+            changed |= ui
+                .add(
+                    egui::DragValue::new(value)
+                        .clamp_range(1.0..=1440.0)
+                        .suffix(format!(" {}", unit)),
+                )
+                .changed();
+        }
+        (Some(_), false) => *limit = None,
+        (None, true) => *limit = Some(default),
+        (None, false) => {
+            ui.add_enabled(false, egui::Label::new(unit));
+        }
+    }
+    changed
+}
+
+/// Lists the sheet's bonus pipeline in the order it runs, and lets rules be added, removed and
+/// reordered.
+/// Seasonal mode settings, with every skill's lifetime totals and how each season went.
+fn legacy_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet, exp_format: &ExpFormat) -> bool {
+    let today = clock::today();
+    let seasons = &mut sheet.seasons;
+
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui
+            .checkbox(&mut seasons.enabled, "Seasonal mode")
+            .on_hover_text("Levels start again from nothing each season")
+            .changed();
+        ui.add_enabled_ui(seasons.enabled, |ui| {
+            let old_length = seasons.length;
+            egui::ComboBox::from_id_source("season_length")
+                .selected_text(seasons.length.label())
+                .show_ui(ui, |ui| {
+                    SeasonLength::ALL.iter().for_each(|length| {
+                        ui.selectable_value(&mut seasons.length, *length, length.label());
+                    });
+                });
+            changed |= seasons.length != old_length;
+        });
+    });
+    let current = seasons.current(today);
+    if let Some(season) = current {
+        ui.label(format!(
+            "It's season {}, which ends on {}.",
+            season.label(),
+            season.end().pred()
+        ));
+    }
+    ui.separator();
+
+    ui.heading("Lifetime");
+    let mut skills: Vec<&Skill> = sheet.skills_list.values().collect();
+    skills.sort_by(|a, b| a.name.cmp(&b.name));
+    egui::Grid::new("legacy_grid").show(ui, |ui| {
+        ui.label("Skill");
+        ui.label("Level");
+        ui.label(&exp_format.name);
+        ui.label("Hours");
+        if let Some(season) = current {
+            ui.label(format!("{} Level", season.label()));
+        }
+        ui.end_row();
+
+        skills.iter().for_each(|skill| {
+            let minutes: u64 = skill
+                .records
+                .iter()
+                .filter(|r| r.approved)
+                .map(|r| r.duration)
+                .sum();
+            ui.label(skill.name.as_str());
+            ui.label(skill.level().to_string());
+            ui.label(exp_format.number(skill.total_exp));
+            ui.label(format!("{:.1}", minutes as f64 / 60f64));
+            if let Some(season) = current {
+                ui.label(season.level(skill).to_string());
+            }
+            ui.end_row();
+        });
+    });
+    ui.separator();
+
+    ui.heading("Seasons");
+    let history = season::history(sheet, sheet.seasons.length, today);
+    egui::Grid::new("season_history_grid").show(ui, |ui| {
+        ui.label("Season");
+        ui.label(&exp_format.name);
+        ui.label("Change");
+        ui.label("Hours");
+        ui.label("Sessions");
+        ui.label("Top skill");
+        ui.end_row();
+
+        // Newest first, so each season is compared against the one on the row below it.
+        history.iter().enumerate().for_each(|(idx, summary)| {
+            ui.label(summary.season.label());
+            ui.label(exp_format.number(summary.exp));
+            match history.get(idx + 1) {
+                Some(previous) if previous.exp > 0f64 => {
+                    let change = (summary.exp - previous.exp) / previous.exp * 100f64;
+                    ui.label(format!("{:+.0}%", change));
+                }
+                _ => {
+                    ui.label("-");
+                }
+            }
+            ui.label(format!("{:.1}", summary.minutes as f64 / 60f64));
+            ui.label(summary.sessions.to_string());
+            ui.label(summary.top_skill.as_deref().unwrap_or("-"));
+            ui.end_row();
+        });
+    });
+    changed
+}
+
+/// This week's boss and how close it is to being beaten, then the badges from the ones which
+/// have been.
+fn boss_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet, exp_format: &ExpFormat) -> bool {
+    let today = clock::today();
+    let Sheet {
+        challenges,
+        skills_list,
+        units,
+        goals,
+        ..
+    } = sheet;
+    // The week can turn over while the app is open, with nothing being recalculated.
+    let this_week = stats::week_start(today);
+    let rolled = challenges
+        .current
+        .as_ref()
+        .map_or(true, |boss| boss.week != this_week);
+    if rolled {
+        challenges.update(skills_list, units, goals, today);
+    }
+
+    if let Some(boss) = &challenges.current {
+        ui.heading(format!("This week's boss: {}", boss.name));
+        match boss.defeated {
+            Some(date) => {
+                ui.colored_label(Colors::of(ui.ctx()).good, format!("Beaten on {}!", date));
+            }
+            None => {
+                ui.label(format!(
+                    "Do all of these by {} to beat it, for {} in the bonus pool.",
+                    boss.week + Duration::days(6),
+                    exp_format.amount(boss.reward_exp)
+                ));
+            }
+        }
+        egui::Grid::new("boss_parts").show(ui, |ui| {
+            boss.progress(skills_list, units)
+                .iter()
+                .for_each(|progress| {
+                    let amount = match progress.part {
+                        BossPart::TotalMinutes(target) => format!(
+                            "{:.1}h / {:.1}h",
+                            progress.amount / 60.0,
+                            target as f64 / 60.0
+                        ),
+                        BossPart::Skills(target) => format!("{} / {}", progress.amount, target),
+                        BossPart::LongSession(target) => {
+                            format!("{} / {} min", progress.amount, target)
+                        }
+                    };
+                    ui.label(progress.part.describe());
+                    ui.add(
+                        egui::ProgressBar::new(progress.fraction as f32)
+                            .desired_width(200.0)
+                            .text(amount),
+                    );
+                    ui.end_row();
+                });
+        });
+        ui.weak("It's rolled each Monday from the goals running that week.");
+    }
+    ui.separator();
+
+    ui.heading("Badges");
+    ui.label(format!(
+        "Bonus pool: {}",
+        exp_format.amount(challenges.pool_exp)
+    ));
+    if challenges.badges.is_empty() {
+        ui.label("No bosses beaten yet.");
+    }
+    egui::Grid::new("boss_badges").show(ui, |ui| {
+        challenges.badges.iter().rev().for_each(|boss| {
+            ui.strong(&boss.name);
+            ui.label(format!("Week of {}", boss.week));
+            ui.label(exp_format.signed_number(boss.reward_exp));
+            ui.end_row();
+        });
+    });
+    rolled
+}
+
+/// Every achievement, unlocked ones first.
+fn achievements_page_ui(ui: &mut egui::Ui, sheet: &Sheet) {
+    let unlocked = &sheet.achievements.unlocked;
+    ui.label(format!(
+        "{} of {} unlocked",
+        unlocked.len(),
+        Achievement::ALL.len()
+    ));
+    ui.separator();
+
+    let mut achievements = Achievement::ALL.to_vec();
+    achievements.sort_by_key(|a| unlocked.get(a).is_none());
+    egui::Grid::new("achievements_grid").show(ui, |ui| {
+        achievements.iter().for_each(|achievement| {
+            match unlocked.get(achievement) {
+                Some(date) => {
+                    ui.strong(achievement.label());
+                    ui.label(achievement.description());
+                    ui.label(format!("Unlocked {}", date));
+                }
+                None => {
+                    ui.weak(achievement.label());
+                    ui.weak(achievement.description());
+                    ui.weak("Locked");
+                }
+            }
+            ui.end_row();
+        });
+    });
+}
+
+/// The inventory of loot found so far, and the table it drops from.
+fn loot_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet) -> bool {
+    let loot = &mut sheet.loot;
+    let mut changed = ui
+        .checkbox(&mut loot.enabled, "Sessions can drop loot")
+        .on_hover_text("Each skill's first session of the day has a chance to drop a cosmetic item")
+        .changed();
+    if !loot.enabled {
+        return changed;
+    }
+    ui.separator();
+
+    ui.heading("Inventory");
+    if loot.inventory.is_empty() {
+        ui.label("Nothing yet. Keep practicing!");
+    }
+    LootKind::ALL.iter().for_each(|kind| {
+        let mut names: BTreeMap<&str, usize> = BTreeMap::new();
+        loot.inventory
+            .iter()
+            .filter(|found| found.item.kind == *kind)
+            .for_each(|found| *names.entry(found.item.name.as_str()).or_insert(0) += 1);
+        if names.is_empty() {
+            return;
+        }
+
+        ui.strong(kind.label());
+        let mut equip = None;
+        names.iter().for_each(|(name, count)| {
+            let equipped = loot.equipped.get(kind).map(|e| e.as_str()) == Some(*name);
+            let text = if *count > 1 {
+                format!("{} (x{})", name, count)
+            } else {
+                name.to_string()
+            };
+            if ui.selectable_label(equipped, text).clicked() {
+                // Clicking what's already equipped takes it off again.
+                equip = Some((!equipped).then(|| name.to_string()));
+            }
+        });
+        match equip {
+            Some(Some(name)) => {
+                loot.equipped.insert(*kind, name);
+                changed = true;
+            }
+            Some(None) => {
+                loot.equipped.remove(kind);
+                changed = true;
+            }
+            None => {}
+        }
+    });
+    ui.separator();
+
+    egui::CollapsingHeader::new("Loot table").show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Drop chance:");
+            let mut percent = loot.drop_chance * 100f64;
+            if ui
+                .add(
+                    egui::DragValue::new(&mut percent)
+                        .clamp_range(0f64..=100f64)
+                        .suffix("%"),
+                )
+                .changed()
+            {
+                loot.drop_chance = percent / 100f64;
+                changed = true;
+            }
+        });
+
+        let mut remove = None;
+        egui::Grid::new("loot_table").show(ui, |ui| {
+            ui.label("Kind");
+            ui.label("Name");
+            ui.label("Weight");
+            ui.end_row();
+            loot.table.iter_mut().enumerate().for_each(|(idx, entry)| {
+                let old_kind = entry.item.kind;
+                egui::ComboBox::from_id_source(("loot_kind", idx))
+                    .selected_text(entry.item.kind.label())
+                    .show_ui(ui, |ui| {
+                        LootKind::ALL.iter().for_each(|kind| {
+                            ui.selectable_value(&mut entry.item.kind, *kind, kind.label());
+                        });
+                    });
+                changed |= entry.item.kind != old_kind;
+                changed |= ui.text_edit_singleline(&mut entry.item.name).changed();
+                changed |= ui
+                    .add(egui::DragValue::new(&mut entry.weight).clamp_range(0..=1000))
+                    .changed();
+                if ui.button(" - ").clicked() {
+                    remove = Some(idx);
+                }
+                ui.end_row();
+            });
+        });
+        if let Some(idx) = remove {
+            loot.table.remove(idx);
+            changed = true;
+        }
+        if ui.button("Add item").clicked() {
+            loot.table.push(LootEntry::default());
+            changed = true;
+        }
+    });
+    changed
+}
+
+fn zone_hover_text(zones: &Zones, minutes: u64) -> String {
+    let zone = zones
+        .zone(minutes)
+        .map_or("below every zone", |zone| zone.label());
+    format!(
+        "{} (maintenance {} / growth {} / stretch {} min)",
+        zone, zones.maintenance, zones.growth, zones.stretch
+    )
+}
+
+/// A year of days laid out a week per column, with the current week on the right. Each day is
+/// colored by which of `zones` it reached, or without zones shaded by how long was logged on it.
+/// Days with a note in `notes` are marked with a dot, and can be clicked on. Gives back the day
+/// which was clicked, if any.
+fn heatmap_ui(
+    ui: &mut egui::Ui,
+    minutes_per_day: &BTreeMap<NaiveDate, u64>,
+    zones: Option<&Zones>,
+    notes: Option<&BTreeMap<NaiveDate, DailyNote>>,
+    today: NaiveDate,
+) -> Option<NaiveDate> {
+    const WEEKS: i64 = 53;
+    const CELL: f32 = 11.0;
+    const GAP: f32 = 2.0;
+    // How many shades there are beyond "nothing logged".
+    const SHADES: u64 = 4;
+
+    let first_day = stats::week_start(today) - Duration::weeks(WEEKS - 1);
+    let busiest = minutes_per_day
+        .range(first_day..=today)
+        .map(|(_, minutes)| *minutes)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let size = egui::vec2(WEEKS as f32 * (CELL + GAP), 7f32 * (CELL + GAP));
+    let sense = if notes.is_some() {
+        egui::Sense::click()
+    } else {
+        egui::Sense::hover()
+    };
+    let (rect, response) = ui.allocate_exact_size(size, sense);
+    let painter = ui.painter_at(rect);
+    let colors = Colors::of(ui.ctx());
+    let empty = ui.visuals().extreme_bg_color;
+    let full = colors.heat;
+
+    let mut hovered = None;
+    (0..WEEKS * 7)
+        .map(|offset| first_day + Duration::days(offset))
+        .take_while(|day| *day <= today)
+        .for_each(|day| {
+            let offset = day.signed_duration_since(first_day).num_days();
+            let min = rect.min
+                + egui::vec2(
+                    (offset / 7) as f32 * (CELL + GAP),
+                    (offset % 7) as f32 * (CELL + GAP),
+                );
+            let cell = egui::Rect::from_min_size(min, egui::vec2(CELL, CELL));
+
+            let minutes = minutes_per_day.get(&day).copied().unwrap_or(0);
+            let color = if minutes == 0 {
+                empty
+            } else if let Some(zones) = zones {
+                // Practice short of maintenance still shows up, just barely.
+                zones
+                    .zone(minutes)
+                    .map_or_else(|| lerp_color(empty, full, 0.15), |zone| colors.zone(zone))
+            } else {
+                // Any time at all gets the lightest shade; the busiest day gets the darkest.
+                let shade = (minutes * SHADES + busiest - 1) / busiest;
+                lerp_color(empty, full, shade as f32 / SHADES as f32)
+            };
+            painter.rect_filled(cell, 2f32, color);
+            if notes.map_or(false, |notes| notes.contains_key(&day)) {
+                painter.circle_filled(cell.center(), 1.5, ui.visuals().text_color());
+            }
+
+            if response
+                .hover_pos()
+                .map_or(false, |pos| cell.expand(GAP / 2f32).contains(pos))
+            {
+                hovered = Some((day, minutes));
+            }
+        });
+
+    let (day, minutes) = hovered?;
+    let clicked = response.clicked();
+    let zone = zones
+        .and_then(|zones| zones.zone(minutes))
+        .map_or(String::new(), |zone| format!(" ({})", zone.label()));
+    let note = notes
+        .and_then(|notes| notes.get(&day))
+        .map_or(String::new(), |note| format!("\n{}", note.summary()));
+    response.on_hover_text_at_pointer(format!("{}: {} min{}{}", day, minutes, zone, note));
+    clicked.then_some(day)
+}
+
+fn lerp_color(from: egui::Color32, to: egui::Color32, t: f32) -> egui::Color32 {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    egui::Color32::from_rgb(
+        channel(from.r(), to.r()),
+        channel(from.g(), to.g()),
+        channel(from.b(), to.b()),
+    )
+}
+
+/// A year's heatmap for all the skills together, and then for each skill on its own colored by
+/// its daily zones, with how often each zone was reached.
+fn activity_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet) -> bool {
+    const DISTRIBUTION_DAYS: [i64; 2] = [30, 365];
+
+    let today = clock::today();
+    let mut skills: Vec<(Uuid, &Skill)> =
+        sheet.skills_list.iter().map(|(id, s)| (*id, s)).collect();
+    skills.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+    let noted_day_id = ui.make_persistent_id("noted_day");
+    let mut noted_day = ui.memory().data.get_temp::<NaiveDate>(noted_day_id);
+
+    let mut edited_zones = None;
+    let mut edited_note = None;
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.strong("All skills");
+        let all_skills = skills.iter().map(|(_, skill)| *skill);
+        let minutes_per_day = stats::minutes_per_day(all_skills);
+        let notes = Some(&sheet.daily_notes);
+        if let Some(day) = heatmap_ui(ui, &minutes_per_day, None, notes, today) {
+            noted_day = Some(day);
+        }
+        match noted_day {
+            Some(day) => {
+                ui.horizontal(|ui| {
+                    ui.strong(format!("Note for {}", day.format("%A %-d %B %Y")));
+                    if ui.small_button("Close").clicked() {
+                        noted_day = None;
+                    }
+                });
+                let mut note = sheet.daily_notes.get(&day).cloned().unwrap_or_default();
+                if daily_note_ui(ui, &mut note) {
+                    edited_note = Some((day, note));
+                }
+            }
+            None => {
+                ui.weak("Click on a day to write a note about it.");
+            }
+        }
+        skills.iter().for_each(|(skill_id, skill)| {
+            ui.add_space(8f32);
+            let mut zones = sheet.zones_for(skill_id);
+            ui.horizontal(|ui| {
+                ui.strong(skill.name.as_str());
+                ui.label("Zones:");
+                Zone::ALL.iter().for_each(|zone| {
+                    ui.add(
+                        egui::DragValue::new(zones.minutes_mut(*zone))
+                            .clamp_range(1..=1440)
+                            .suffix(" min"),
+                    )
+                    .on_hover_text(zone.label());
+                });
+            });
+            if zones != sheet.zones_for(skill_id) {
+                edited_zones = Some((*skill_id, zones));
+            }
+
+            let minutes_per_day = stats::minutes_per_day([*skill]);
+            heatmap_ui(ui, &minutes_per_day, Some(&zones), None, today);
+            DISTRIBUTION_DAYS.iter().for_each(|days| {
+                let from = today - Duration::days(days - 1);
+                let distribution = zones.distribution(&minutes_per_day, from, today);
+                ui.horizontal(|ui| {
+                    ui.label(format!("Last {} days:", days));
+                    Zone::ALL.iter().rev().for_each(|zone| {
+                        let count = distribution.days.get(zone).copied().unwrap_or(0);
+                        let color = Colors::of(ui.ctx()).zone(*zone);
+                        ui.colored_label(color, format!("{} {}", count, zone.label()));
+                    });
+                    ui.label(format!("{} below", distribution.missed));
+                });
+            });
+        });
+    });
+
+    match noted_day {
+        Some(day) => ui.memory().data.insert_temp(noted_day_id, day),
+        None => ui.memory().data.remove::<NaiveDate>(noted_day_id),
+    }
+    let noted = edited_note.is_some();
+    if let Some((day, note)) = edited_note {
+        sheet.set_daily_note(day, note);
+    }
+    match edited_zones {
+        Some((skill_id, zones)) => {
+            sheet.zones.insert(skill_id, zones);
+            true
+        }
+        None => noted,
+    }
+}
+
+/// Which skills tend to be practiced in the same weeks, or one the week after the other.
+fn correlations_page_ui(ui: &mut egui::Ui, sheet: &Sheet) {
+    const WEEKS: usize = 26;
+    const MIN_ACTIVE_WEEKS: usize = 4;
+    const NOTE_DAYS: i64 = 90;
+    const MIN_NOTED_DAYS: usize = 14;
+    // Below this, with only half a year of weeks, a correlation is as likely to be chance.
+    const NOTEWORTHY: f64 = 0.4;
+
+    ui.label(format!(
+        "How weekly minutes for each pair of skills moved together over the last {} weeks, from \
+         -1 (one goes up as the other goes down) to 1 (they go up and down together).",
+        WEEKS
+    ));
+    ui.label(format!(
+        "These are only patterns, not causes: both skills might just follow a busy or quiet \
+         week. With this few weeks, anything closer to 0 than ±{} is probably noise, and skills \
+         practiced in fewer than {} of the weeks are left out.",
+        NOTEWORTHY, MIN_ACTIVE_WEEKS
+    ));
+    ui.separator();
+
+    let today = clock::today();
+    let pairs = stats::correlations(&sheet.skills_list, today, WEEKS, MIN_ACTIVE_WEEKS);
+
+    let name = |id: &Uuid| sheet.skills_list.get(id).map_or("", |s| s.name.as_str());
+    let value_ui = |ui: &mut egui::Ui, r: Option<f64>| match r {
+        Some(r) if r.abs() >= NOTEWORTHY => {
+            let colors = Colors::of(ui.ctx());
+            let color = if r > 0f64 { colors.good } else { colors.bad };
+            ui.colored_label(color, format!("{:+.2}", r));
+        }
+        Some(r) => {
+            ui.label(format!("{:+.2}", r));
+        }
+        None => {
+            ui.label("-");
+        }
+    };
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        if pairs.is_empty() {
+            ui.label("Not enough skills with regular practice to compare yet.");
+        } else {
+            egui::Grid::new("correlations_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Skills");
+                    ui.strong("Same week");
+                    ui.strong("First, then second").on_hover_text(
+                        "Each week of the first skill against the next week of the second",
+                    );
+                    ui.strong("Second, then first").on_hover_text(
+                        "Each week of the second skill against the next week of the first",
+                    );
+                    ui.end_row();
+                    pairs.iter().for_each(|pair| {
+                        ui.label(format!("{} & {}", name(&pair.first), name(&pair.second)));
+                        value_ui(ui, pair.same_week);
+                        value_ui(ui, pair.first_leads);
+                        value_ui(ui, pair.second_leads);
+                        ui.end_row();
+                    });
+                });
+        }
+        ui.separator();
+
+        ui.heading("Mood and sleep");
+        ui.label(format!(
+            "How the mood and sleep in your daily notes over the last {} days moved with the \
+             minutes practiced that day and the day after. Days without a note are left out, \
+             and so is anything noted on fewer than {} days.",
+            NOTE_DAYS, MIN_NOTED_DAYS
+        ));
+        let found = daily_notes::note_correlations(sheet, today, NOTE_DAYS, MIN_NOTED_DAYS);
+        if found.is_empty() {
+            ui.label("Not enough days noted to compare yet.");
+            return;
+        }
+        egui::Grid::new("note_correlations_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Noted");
+                ui.strong("Practice");
+                ui.strong("Days");
+                ui.strong("Same day");
+                ui.strong("Day after");
+                ui.end_row();
+                found.iter().for_each(|correlation| {
+                    ui.label(correlation.measure.label());
+                    ui.label(correlation.skill.as_ref().map_or("All skills", name));
+                    ui.label(correlation.days.to_string());
+                    value_ui(ui, correlation.same_day);
+                    value_ui(ui, correlation.next_day);
+                    ui.end_row();
+                });
+            });
+    });
+}
+
+/// Plot x values are days, counted the way `NaiveDate::num_days_from_ce` counts them.
+fn plot_date_formatter(x: f64, _range: &std::ops::RangeInclusive<f64>) -> String {
+    NaiveDate::from_num_days_from_ce_opt(x.round() as i32)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Cumulative and per-day EXP for one skill at a time.
+fn charts_page_ui(
+    ui: &mut egui::Ui,
+    sheet: &Sheet,
+    exp_format: &ExpFormat,
+    charts: &mut Option<ChartsInProgress>,
+    frame: &epi::Frame,
+) {
+    let selected_id = ui.make_persistent_id("charted_skill");
+    let mut skills: Vec<(&Uuid, &Skill)> = sheet.skills_list.iter().collect();
+    skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+
+    let mut selected = ui
+        .memory()
+        .data
+        .get_temp::<Uuid>(selected_id)
+        .filter(|id| sheet.skills_list.contains_key(id))
+        .or_else(|| skills.first().map(|(id, _)| **id));
+    let skill = match selected.and_then(|id| sheet.skills_list.get(&id)) {
+        Some(skill) => skill,
+        None => {
+            ui.label("Add a skill to see its progress here.");
+            return;
+        }
+    };
+
+    egui::ComboBox::from_label("Skill")
+        .selected_text(skill.name.as_str())
+        .show_ui(ui, |ui| {
+            skills.iter().for_each(|(id, skill)| {
+                ui.selectable_value(&mut selected, Some(**id), &skill.name);
+            });
+        });
+    if let Some(id) = selected {
+        ui.memory().data.insert_temp(selected_id, id);
+    }
+
+    let today = clock::today();
+    let slipping: Vec<&str> = skills
+        .iter()
+        .filter(|(_, skill)| stats::quality_trend(skill, today).map_or(false, |t| t.is_slipping()))
+        .map(|(_, skill)| skill.name.as_str())
+        .collect();
+    if !slipping.is_empty() {
+        ui.colored_label(
+            Colors::of(ui.ctx()).warning,
+            format!(
+                "More time but lighter sessions over the last four weeks: {}",
+                slipping.join(", ")
+            ),
+        );
+    }
+
+    let skill_id = selected.unwrap_or_default();
+    let charts = match charts {
+        Some(charts) if charts.skill_id == skill_id && charts.edited == sheet.last_edit_time => {
+            charts
+        }
+        _ => charts.insert(ChartsInProgress::start(
+            frame,
+            skill_id,
+            skill,
+            sheet.last_edit_time,
+        )),
+    };
+    charts.update();
+    if charts.worker.is_some() {
+        let fraction = charts.processed as f32 / charts.total.max(1) as f32;
+        ui.add(egui::ProgressBar::new(fraction).text("Working out the charts..."));
+    }
+    let history = &charts.stats.history;
+    if history.is_empty() {
+        if charts.worker.is_none() {
+            ui.label("Nothing logged for this skill yet.");
+        }
+        return;
+    }
+    // All the charts show the same days, so keep them scrolled and zoomed together.
+    let x_axis = plot::LinkedAxisGroup::x();
+    let focus = &charts.stats.focus;
+    // Days noted across the skill's history, on the same timeline as the rest.
+    let notes = sheet.daily_notes.range(history[0].date..=today);
+    let mood_points: Vec<plot::Value> = notes
+        .clone()
+        .filter_map(|(day, note)| {
+            Some(plot::Value::new(day.num_days_from_ce(), note.mood?.score()))
+        })
+        .collect();
+    let sleep_points: Vec<plot::Value> = notes
+        .filter_map(|(day, note)| Some(plot::Value::new(day.num_days_from_ce(), note.sleep_hours?)))
+        .collect();
+    let noted = !mood_points.is_empty() || !sleep_points.is_empty();
+    let plot_count = 3f32
+        + [!focus.is_empty(), noted]
+            .iter()
+            .filter(|shown| **shown)
+            .count() as f32;
+    let plot_height = (ui.available_height() / plot_count - 20f32).max(100f32);
+    let series = Colors::of(ui.ctx()).series;
+
+    ui.strong(format!("Total {}", exp_format.name));
+    let total_format = exp_format.clone();
+    let points = history
+        .iter()
+        .map(|day| plot::Value::new(day.date.num_days_from_ce(), day.cumulative_exp))
+        .collect();
+    plot::Plot::new((selected, "cumulative_exp"))
+        .height(plot_height)
+        .include_y(0f64)
+        .link_axis(x_axis.clone())
+        .x_axis_formatter(plot_date_formatter)
+        .y_axis_formatter(move |exp, _| total_format.number(exp))
+        .show(ui, |plot_ui| {
+            plot_ui.line(
+                plot::Line::new(plot::Values::from_values(points))
+                    .color(series[0])
+                    .name("Total"),
+            )
+        });
+
+    ui.strong(format!("{} per day", exp_format.name));
+    let daily_format = exp_format.clone();
+    let bars = history
+        .iter()
+        .map(|day| plot::Bar::new(day.date.num_days_from_ce() as f64, day.exp).width(0.8))
+        .collect();
+    plot::Plot::new((selected, "daily_exp"))
+        .height(plot_height)
+        .include_y(0f64)
+        .link_axis(x_axis.clone())
+        .x_axis_formatter(plot_date_formatter)
+        .y_axis_formatter(move |exp, _| daily_format.number(exp))
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(plot::BarChart::new(bars).color(series[0]).name("Per day"))
+        });
+
+    ui.strong("Hours and effort per week");
+    let weeks = &charts.stats.weeks;
+    let hour_bars = weeks
+        .iter()
+        .map(|w| plot::Bar::new(w.week.num_days_from_ce() as f64 + 3f64, w.hours).width(6.0))
+        .collect();
+    let effort_points = weeks
+        .iter()
+        .map(|w| plot::Value::new(w.week.num_days_from_ce() as f64 + 3f64, w.average_effort))
+        .collect();
+    plot::Plot::new((selected, "weekly_quality"))
+        .height(plot_height)
+        .include_y(0f64)
+        .include_y(3f64)
+        .link_axis(x_axis.clone())
+        .legend(plot::Legend::default())
+        .x_axis_formatter(plot_date_formatter)
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(
+                plot::BarChart::new(hour_bars)
+                    .color(series[0])
+                    .name("Hours"),
+            );
+            plot_ui.line(
+                plot::Line::new(plot::Values::from_values(effort_points))
+                    .color(series[1])
+                    .name("Average effort (1 light - 3 heavy)"),
+            );
+        });
+
+    if noted {
+        ui.strong("Mood and sleep");
+        plot::Plot::new((selected, "daily_notes"))
+            .height(plot_height)
+            .include_y(0f64)
+            .link_axis(x_axis.clone())
+            .legend(plot::Legend::default())
+            .x_axis_formatter(plot_date_formatter)
+            .show(ui, |plot_ui| {
+                plot_ui.points(
+                    plot::Points::new(plot::Values::from_values(mood_points))
+                        .radius(3.0)
+                        .color(series[0])
+                        .name("Mood (1 awful - 5 great)"),
+                );
+                plot_ui.line(
+                    plot::Line::new(plot::Values::from_values(sleep_points))
+                        .color(series[1])
+                        .name("Hours slept"),
+                );
+            });
+    }
+
+    if focus.is_empty() {
+        return;
+    }
+    ui.strong("Distractions per hour of timed sessions");
+    let focus_points = focus
+        .iter()
+        .map(|w| {
+            let x = w.week.num_days_from_ce() as f64 + 3f64;
+            plot::Value::new(x, w.distractions_per_hour())
+        })
+        .collect();
+    plot::Plot::new((selected, "weekly_focus"))
+        .height(plot_height)
+        .include_y(0f64)
+        .link_axis(x_axis)
+        .x_axis_formatter(plot_date_formatter)
+        .show(ui, |plot_ui| {
+            plot_ui.line(
+                plot::Line::new(plot::Values::from_values(focus_points))
+                    .color(series[0])
+                    .name("Per hour"),
+            );
+        });
+}
+
+fn snapshots_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet, exp_format: &ExpFormat) -> bool {
+    let name_id = ui.make_persistent_id("new_snapshot_name");
+    let selected_id = ui.make_persistent_id("selected_snapshot");
+    let mut name = ui
+        .memory()
+        .data
+        .get_temp::<String>(name_id)
+        .unwrap_or_default();
+    let mut selected = ui
+        .memory()
+        .data
+        .get_temp::<usize>(selected_id)
+        .filter(|idx| *idx < sheet.snapshots.len());
+
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Name:");
+        ui.text_edit_singleline(&mut name);
+        if ui
+            .add_enabled(!name.trim().is_empty(), egui::Button::new("Take Snapshot"))
+            .clicked()
+        {
+            sheet.snapshots.push(Snapshot::take(name.trim(), sheet));
+            selected = Some(sheet.snapshots.len() - 1);
+            name.clear();
+            changed = true;
+        }
+    });
+    ui.separator();
+
+    if sheet.snapshots.is_empty() {
+        ui.label("No save points yet. Take one to compare your progress against later.");
+    }
+    let mut remove = None;
+    sheet
+        .snapshots
+        .iter()
+        .enumerate()
+        .for_each(|(idx, snapshot)| {
+            ui.horizontal(|ui| {
+                let text = format!("{} ({})", snapshot.name, clock::date_at(snapshot.taken_at));
+                if ui.selectable_label(selected == Some(idx), text).clicked() {
+                    selected = Some(idx);
+                }
+                if ui.button(" - ").clicked() {
+                    remove = Some(idx);
+                }
+            });
+        });
+    if let Some(idx) = remove {
+        sheet.snapshots.remove(idx);
+        selected = None;
+        changed = true;
+    }
+
+    if let Some(snapshot) = selected.and_then(|idx| sheet.snapshots.get(idx)) {
+        ui.separator();
+        ui.strong(format!("Since \"{}\"", snapshot.name));
+        egui::Grid::new("snapshot_comparison").show(ui, |ui| {
+            ui.label("Skill");
+            ui.label("Level");
+            ui.label(format!("{} gained", exp_format.name));
+            ui.label("Hours gained");
+            ui.end_row();
+            snapshot::compare(sheet, snapshot).iter().for_each(|c| {
+                ui.label(&c.name);
+                let level_text = match (&c.then, &c.now) {
+                    (Some(then), Some(now)) => format!("{} -> {}", then.level, now.level),
+                    (None, Some(now)) => format!("new, now {}", now.level),
+                    (Some(then), None) => format!("{}, since deleted", then.level),
+                    (None, None) => String::new(),
+                };
+                ui.label(level_text);
+                ui.label(exp_format.signed_number(c.exp_gained()));
+                ui.label(format!("{:+.1}", c.minutes_gained() as f64 / 60f64));
+                ui.end_row();
+            });
+        });
+    }
+
+    ui.memory().data.insert_temp(name_id, name);
+    match selected {
+        Some(idx) => ui.memory().data.insert_temp(selected_id, idx),
+        None => ui.memory().data.remove::<usize>(selected_id),
+    }
+    changed
+}
+
+fn bonus_rules_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet, exp_format: &ExpFormat) -> bool {
+    let mut skills: Vec<(Uuid, String)> = sheet
+        .skills_list
+        .iter()
+        .map(|(id, skill)| (*id, skill.name.clone()))
+        .collect();
+    skills.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let mut changed = false;
+    ui.heading(format!("Base {}", exp_format.name));
+    ui.label(format!(
+        "Leave the formula blank for the built-in {} an hour, or write your own using minutes, \
+         hours, base, streak, days_since, sessions_today, level, weekday, day and month, e.g. \
+         \"hours * 60 + if(streak >= 7, 20, 0)\". Sessions it can't work out use the built-in \
+         one.",
+        exp_format.number(55.0)
+    ));
+    ui.horizontal(|ui| {
+        ui.label("Every skill:");
+        changed |= formula_ui(ui, &mut sheet.exp_formulas.sheet, exp_format);
+    });
+    let mut remove_formula = None;
+    skills.iter().for_each(|(id, name)| {
+        if let Some(text) = sheet.exp_formulas.skills.get_mut(id) {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", name));
+                changed |= formula_ui(ui, text, exp_format);
+                if ui.button(" - ").clicked() {
+                    remove_formula = Some(*id);
+                }
+            });
+        }
+    });
+    if let Some(id) = remove_formula {
+        sheet.exp_formulas.skills.remove(&id);
+        changed = true;
+    }
+    ui.menu_button("Own formula for...", |ui| {
+        let formulas = &mut sheet.exp_formulas;
+        let picked = skills
+            .iter()
+            .filter(|(id, _)| !formulas.skills.contains_key(id))
+            .find(|(_, name)| ui.button(name).clicked());
+        if let Some((id, _)) = picked {
+            formulas.skills.insert(*id, formulas.sheet.clone());
+            ui.close_menu();
+        }
+    });
+    ui.separator();
+
+    ui.heading("Bonus rules");
+    ui.label(format!(
+        "Each session's bonus {} is worked out by running these rules from top to bottom.",
+        exp_format.name
+    ));
+    ui.separator();
+
+    let mut move_up = None;
+    let mut remove = None;
+    let rule_count = sheet.bonus_rules.len();
+    sheet
+        .bonus_rules
+        .iter_mut()
+        .enumerate()
+        .for_each(|(idx, rule)| {
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.strong(format!("{}. {}", idx + 1, rule.name()));
+                    if ui.add_enabled(idx > 0, egui::Button::new("^")).clicked() {
+                        move_up = Some(idx);
+                    }
+                    if ui
+                        .add_enabled(idx + 1 < rule_count, egui::Button::new("v"))
+                        .clicked()
+                    {
+                        move_up = Some(idx + 1);
+                    }
+                    if ui.button(" - ").clicked() {
+                        remove = Some(idx);
+                    }
+                });
+                changed |= bonus_rule_ui(ui, idx, rule, &skills, exp_format);
+            });
+        });
+
+    if let Some(idx) = move_up {
+        sheet.bonus_rules.swap(idx - 1, idx);
+        changed = true;
+    }
+    if let Some(idx) = remove {
+        sheet.bonus_rules.remove(idx);
+        changed = true;
+    }
+
+    ui.menu_button("Add rule...", |ui| {
+        let new_rule = if ui.button("Streak").clicked() {
+            Some(BonusRule::Streak(Default::default()))
+        } else if ui.button("Synergy").clicked() {
+            Some(BonusRule::Synergy(Default::default()))
+        } else if ui.button("Event multiplier").clicked() {
+            Some(BonusRule::Event(Default::default()))
+        } else if ui.button("Cap").clicked() {
+            Some(BonusRule::Cap(Default::default()))
+        } else if ui.button("Decay").clicked() {
+            Some(BonusRule::Decay(Default::default()))
+        } else if ui.button("Cooldown").clicked() {
+            Some(BonusRule::Cooldown(Default::default()))
+        } else {
+            None
+        };
+        if let Some(rule) = new_rule {
+            sheet.bonus_rules.push(rule);
+            changed = true;
+            ui.close_menu();
+        }
+    });
+
+    if changed {
+        sheet.recalculate_all();
+    }
+    changed
+}
+
+/// Edits a base EXP formula, showing what's wrong with it or, if nothing is, what an hour today
+/// would earn by it.
+fn formula_ui(ui: &mut egui::Ui, text: &mut String, exp_format: &ExpFormat) -> bool {
+    let changed = ui
+        .add(
+            egui::TextEdit::singleline(text)
+                .hint_text("built-in")
+                .desired_width(300.0),
+        )
+        .changed();
+    if text.trim().is_empty() {
+        return changed;
+    }
+    match Formula::parse(text) {
+        Ok(formula) => {
+            let hour = FormulaInputs {
+                minutes: 60.0,
+                base: 55.0,
+                streak: 0,
+                days_since: 1,
+                sessions_today: 0,
+                level: 0,
+                date: clock::today(),
+            };
+            match formula.base_exp(&hour) {
+                Some(exp) => ui.weak(format!(
+                    "An hour today: {} {}",
+                    exp_format.number(exp),
+                    exp_format.name
+                )),
+                None => ui.colored_label(
+                    Colors::of(ui.ctx()).warning,
+                    "An hour today: not a number, so the built-in formula",
+                ),
+            };
+        }
+        Err(e) => {
+            ui.colored_label(Colors::of(ui.ctx()).error, e.to_string());
+        }
+    }
+    changed
+}
+
+/// What a completed skill reached, e.g. "Level 5, 1,500 EXP, 42.5 hours over 30 days, best
+/// streak 12 days".
+/// A menu for where an imported project's entries go. `new_name` describes the skill they'd
+/// make if they go to a new one.
+fn project_target_ui(
+    ui: &mut egui::Ui,
+    id_source: egui::Id,
+    target: &mut ProjectTarget,
+    new_name: &str,
+    skills: &[(&Uuid, &Skill)],
+) {
+    let selected_text = match target {
+        ProjectTarget::Skip => "Don't import".to_owned(),
+        ProjectTarget::NewSkill => format!("New skill {}", new_name),
+        ProjectTarget::Existing(id) => skills
+            .iter()
+            .find(|(skill_id, _)| *skill_id == id)
+            .map(|(_, skill)| skill.name.clone())
+            .unwrap_or_default(),
+    };
+    egui::ComboBox::from_id_source(id_source)
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(target, ProjectTarget::Skip, "Don't import");
+            ui.selectable_value(
+                target,
+                ProjectTarget::NewSkill,
+                format!("New skill {}", new_name),
+            );
+            skills.iter().for_each(|(id, skill)| {
+                ui.selectable_value(target, ProjectTarget::Existing(**id), &skill.name);
+            });
+        });
+}
+
+fn completed_summary(
+    completed: &Completion,
+    unit: Option<&Unit>,
+    exp_format: &ExpFormat,
+) -> String {
+    let amount = match unit {
+        Some(unit) => format!("{} {}", completed.minutes, unit.name),
+        None => format!("{:.1} hours", completed.minutes as f64 / 60.0),
+    };
+    format!(
+        "Level {}, {}, {} over {} days, best streak {} days",
+        completed.level,
+        exp_format.amount(completed.exp),
+        amount,
+        completed.days,
+        completed.best_streak
+    )
+}
+
+/// Every completed skill, most recent first, with what it reached.
+fn hall_of_fame_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet, exp_format: &ExpFormat) -> bool {
+    if sheet.completed.is_empty() {
+        ui.label(
+            "Skills you've seen through to the end, like passing an exam, end up here. Complete \
+             one from its row on the Skills page.",
+        );
+        return false;
+    }
+
+    let mut completed: Vec<(&Uuid, &Completion)> = sheet.completed.iter().collect();
+    completed.sort_by(|a, b| (b.1.date, &b.1.achievement).cmp(&(a.1.date, &a.1.achievement)));
+    let mut reopen = None;
+    completed.iter().for_each(|(skill_id, completed)| {
+        let name = sheet
+            .skills_list
+            .get(skill_id)
+            .map_or("(deleted skill)", |skill| skill.name.as_str());
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.heading(name);
+                ui.strong(completed.achievement.as_str());
+                ui.label(completed.date.format("%B %-d, %Y").to_string());
+            });
+            ui.horizontal(|ui| {
+                let unit = sheet.units.get(skill_id);
+                ui.label(completed_summary(completed, unit, exp_format));
+                if ui
+                    .small_button("Reopen")
+                    .on_hover_text("Put it back on the Skills page to carry on practicing")
+                    .clicked()
+                {
+                    reopen = Some(**skill_id);
+                }
+            });
+        });
+    });
+
+    match reopen {
+        Some(skill_id) => sheet.reopen_skill(&skill_id).is_some(),
+        None => false,
+    }
+}
+
+/// Every skill laid out by how far down the tree it is, with lines from the skills each one
+/// needs, and the picked skill's prerequisites to edit underneath.
+fn skill_tree_page_ui(ui: &mut egui::Ui, sheet: &mut Sheet) -> bool {
+    let selected_id = ui.make_persistent_id("tree_skill");
+    let mut skills: Vec<(Uuid, &Skill)> =
+        sheet.skills_list.iter().map(|(id, s)| (*id, s)).collect();
+    skills.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+    let remembered = ui.memory().data.get_temp::<Uuid>(selected_id);
+    let mut selected = match remembered
+        .filter(|id| sheet.skills_list.contains_key(id))
+        .or_else(|| skills.first().map(|(id, _)| *id))
+    {
+        Some(selected) => selected,
+        None => {
+            ui.label("Add some skills to plan which ones unlock which here.");
+            return false;
+        }
+    };
+
+    let tiers = sheet.skill_tiers();
+    let mut columns: Vec<Vec<(Uuid, &Skill)>> =
+        vec![Vec::new(); tiers.values().max().map_or(1, |t| t + 1)];
+    skills
+        .iter()
+        .for_each(|(id, skill)| columns[tiers[id]].push((*id, *skill)));
+
+    let colors = Colors::of(ui.ctx());
+    let mut nodes: HashMap<Uuid, egui::Rect> = HashMap::new();
+    egui::ScrollArea::both()
+        .max_height(ui.available_height() * 0.6)
+        .show(ui, |ui| {
+            // Saved a place before the skills, so the lines go underneath them.
+            let lines = ui.painter().add(egui::Shape::Noop);
+            ui.horizontal_top(|ui| {
+                columns.iter().for_each(|column| {
+                    ui.vertical(|ui| {
+                        column.iter().for_each(|(id, skill)| {
+                            let unmet = sheet.unmet_prerequisites(id);
+                            let response = ui
+                                .group(|ui| {
+                                    let name = if unmet.is_empty() {
+                                        egui::RichText::new(&skill.name).strong()
+                                    } else {
+                                        egui::RichText::new(&skill.name).weak()
+                                    };
+                                    if ui.selectable_label(selected == *id, name).clicked() {
+                                        selected = *id;
+                                    }
+                                    if unmet.is_empty() {
+                                        ui.label(format!("Level {}", skill.level()));
+                                    } else {
+                                        ui.weak("Locked");
+                                    }
+                                })
+                                .response;
+                            nodes.insert(*id, response.rect);
+                            if !unmet.is_empty() {
+                                let needs: Vec<String> = unmet
+                                    .iter()
+                                    .map(|(prerequisite, level)| {
+                                        format!(
+                                            "Needs {} level {} (at {})",
+                                            sheet.skills_list[&prerequisite.skill_id].name,
+                                            prerequisite.level,
+                                            level
+                                        )
+                                    })
+                                    .collect();
+                                response.on_hover_text(needs.join("\n"));
+                            }
+                            ui.add_space(8.0);
+                        });
+                    });
+                    ui.add_space(32.0);
+                });
+            });
+
+            let shapes = sheet
+                .prerequisites
+                .iter()
+                .flat_map(|(id, needs)| needs.iter().map(move |p| (id, p)))
+                .filter_map(|(id, prerequisite)| {
+                    let from = nodes.get(&prerequisite.skill_id)?;
+                    let to = nodes.get(id)?;
+                    let met =
+                        sheet.skills_list[&prerequisite.skill_id].level() >= prerequisite.level;
+                    let color = if met {
+                        colors.good
+                    } else {
+                        ui.visuals().weak_text_color()
+                    };
+                    Some(egui::Shape::line_segment(
+                        [from.right_center(), to.left_center()],
+                        egui::Stroke::new(1.5, color),
+                    ))
+                })
+                .collect();
+            ui.painter().set(lines, egui::Shape::Vec(shapes));
+        });
+    ui.memory().data.insert_temp(selected_id, selected);
+    ui.separator();
+
+    let name = sheet.skills_list[&selected].name.clone();
+    ui.heading(format!("What {} needs", name));
+    let mut changed = false;
+    let mut remove = None;
+    let needs = sheet
+        .prerequisites
+        .get(&selected)
+        .cloned()
+        .unwrap_or_default();
+    egui::Grid::new("prerequisites_grid").show(ui, |ui| {
+        needs.iter().enumerate().for_each(|(idx, prerequisite)| {
+            let skill = match sheet.skills_list.get(&prerequisite.skill_id) {
+                Some(skill) => skill,
+                None => return,
+            };
+            ui.label(&skill.name);
+            let mut level = prerequisite.level;
+            if ui
+                .add(
+                    egui::DragValue::new(&mut level)
+                        .clamp_range(1..=1000)
+                        .prefix("Level "),
+                )
+                .changed()
+            {
+                if let Some(p) = sheet
+                    .prerequisites
+                    .get_mut(&selected)
+                    .and_then(|p| p.get_mut(idx))
+                {
+                    p.level = level;
+                }
+                changed = true;
+            }
+            ui.weak(format!("at {}", skill.level()));
+            if ui.button(" - ").clicked() {
+                remove = Some(idx);
+            }
+            ui.end_row();
+        });
+    });
+    if let Some(idx) = remove {
+        if let Some(needs) = sheet.prerequisites.get_mut(&selected) {
+            needs.remove(idx);
+            if needs.is_empty() {
+                sheet.prerequisites.remove(&selected);
+            }
+        }
+        changed = true;
+    }
+
+    // Skills which would end up needing themselves can't be picked.
+    let mut add = None;
+    egui::ComboBox::from_id_source("add_prerequisite")
+        .selected_text("Add a prerequisite")
+        .show_ui(ui, |ui| {
+            skills
+                .iter()
+                .filter(|(id, _)| !needs.iter().any(|p| p.skill_id == *id))
+                .filter(|(id, _)| !sheet.would_loop(&selected, id))
+                .for_each(|(id, skill)| {
+                    if ui.selectable_label(false, &skill.name).clicked() {
+                        add = Some(*id);
+                    }
+                });
+        });
+    if let Some(skill_id) = add {
+        sheet
+            .prerequisites
+            .entry(selected)
+            .or_default()
+            .push(Prerequisite { skill_id, level: 1 });
+        changed = true;
+    }
+    changed
 }
 
-impl SheetMyselfApp {
-    fn save_json(&self) {
-        if let Ok(json_data) = serde_json::to_string(&self) {
-            let path = get_default_file_path();
-            if let Ok(mut file) = File::create(path) {
-                file.write_all(json_data.as_bytes());
+/// A path near `near` which nothing has been saved to yet, for an empty sheet to stand in when
+/// none of the sheets could be opened.
+fn untitled_path(near: &Path) -> PathBuf {
+    let dir = near.parent().unwrap_or_else(|| Path::new(""));
+    (1..)
+        .map(|n| match n {
+            1 => dir.join("untitled.sht"),
+            n => dir.join(format!("untitled {}.sht", n)),
+        })
+        .find(|path| !path.exists())
+        .unwrap_or_else(|| dir.join("untitled.sht"))
+}
+
+/// Names a backup file along with when it was written, for the Restore Backup menu.
+fn backup_label(backup: &Path) -> String {
+    let name = backup
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match std::fs::metadata(backup).and_then(|meta| meta.modified()) {
+        Ok(modified) => {
+            let modified = chrono::DateTime::<chrono::Local>::from(modified);
+            format!("{} ({})", name, modified.format("%Y-%m-%d %H:%M"))
+        }
+        Err(_) => name,
+    }
+}
+
+fn settings_page_ui(ui: &mut egui::Ui, settings: &mut Settings) {
+    ui.heading("Appearance");
+    let appearance = &mut settings.appearance;
+    ui.horizontal(|ui| {
+        ui.label("Theme:");
+        Theme::ALL.iter().for_each(|theme| {
+            ui.radio_value(&mut appearance.theme, *theme, theme.label());
+        });
+    });
+    ui.horizontal(|ui| {
+        let mut custom_accent = appearance.accent.is_some();
+        ui.checkbox(&mut custom_accent, "Accent color");
+        match (&mut appearance.accent, custom_accent) {
+            (Some(accent), true) => {
+                ui.color_edit_button_srgb(accent);
+            }
+            (Some(_), false) => appearance.accent = None,
+            (None, true) => {
+                let default = ui.visuals().selection.bg_fill;
+                appearance.accent = Some([default.r(), default.g(), default.b()]);
             }
+            (None, false) => {}
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Colors:");
+        egui::ComboBox::from_id_source("palette")
+            .selected_text(appearance.palette.label())
+            .show_ui(ui, |ui| {
+                Palette::ALL.iter().for_each(|palette| {
+                    ui.selectable_value(&mut appearance.palette, *palette, palette.label());
+                });
+            })
+            .response
+            .on_hover_text("Used for heatmaps, charts, and anything marked good or bad");
+    });
+    ui.checkbox(&mut appearance.high_contrast, "High contrast");
+    ui.separator();
+
+    ui.heading("Display");
+    let exp_format = &mut settings.exp_format;
+    ui.horizontal(|ui| {
+        ui.label("Call EXP:");
+        ui.add(egui::TextEdit::singleline(&mut exp_format.name).desired_width(80.0));
+        ui.label("with icon");
+        ui.add(egui::TextEdit::singleline(&mut exp_format.icon).desired_width(30.0));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Numbers:");
+        NumberStyle::ALL.iter().for_each(|style| {
+            ui.radio_value(&mut exp_format.style, *style, style.label());
+        });
+    });
+    ui.label(format!("e.g. {}", exp_format.amount(12345.6)));
+    ui.separator();
+
+    ui.heading("Days");
+    let day_boundary = &mut settings.day_boundary;
+    ui.horizontal(|ui| {
+        ui.label("Time zone:");
+        egui::ComboBox::from_id_source("clock_zone")
+            .selected_text(day_boundary.zone.label())
+            .show_ui(ui, |ui| {
+                let offset = match day_boundary.zone {
+                    ClockZone::Offset(minutes) => minutes,
+                    _ => 0,
+                };
+                [ClockZone::Local, ClockZone::Utc, ClockZone::Offset(offset)]
+                    .iter()
+                    .for_each(|zone| {
+                        ui.selectable_value(&mut day_boundary.zone, *zone, zone.label());
+                    });
+            });
+        if let ClockZone::Offset(minutes) = &mut day_boundary.zone {
+            let mut hours = *minutes as f64 / 60f64;
+            ui.add(
+                egui::DragValue::new(&mut hours)
+                    .clamp_range(-12.0..=14.0)
+                    .speed(0.25)
+                    .prefix("UTC ")
+                    .suffix(" h"),
+            );
+            *minutes = (hours * 60f64).round() as i32;
         }
+    });
+    ui.horizontal(|ui| {
+        ui.label("New day starts at");
+        ui.add(
+            egui::DragValue::new(&mut day_boundary.start_hour)
+                .clamp_range(0..=23)
+                .suffix(":00"),
+        )
+        .on_hover_text("Anything before this counts toward the day before");
+    });
+    ui.label(format!("Today is {}", day_boundary.date_at(Utc::now())));
+    ui.separator();
+
+    ui.heading("Pomodoro");
+    ui.horizontal(|ui| {
+        ui.label("Work for");
+        ui.add(
+            egui::DragValue::new(&mut settings.pomodoro.work_minutes)
+                .clamp_range(1..=180)
+                .suffix(" min"),
+        );
+        ui.label("then break for");
+        ui.add(
+            egui::DragValue::new(&mut settings.pomodoro.break_minutes)
+                .clamp_range(1..=60)
+                .suffix(" min"),
+        );
+    });
+    ui.separator();
+
+    ui.heading("Notes");
+    ui.horizontal(|ui| {
+        ui.label("Edit notes with:");
+        ui.add(
+            egui::TextEdit::singleline(&mut settings.note_editor)
+                .hint_text("$EDITOR")
+                .desired_width(200.0),
+        )
+        .on_hover_text("A program and its arguments, e.g. \"code --wait\"");
+    });
+    ui.separator();
+
+    ui.heading("Calm mode");
+    let nudging = &mut settings.nudging;
+    ui.checkbox(&mut nudging.calm, "Take the pressure off")
+        .on_hover_text(
+            "Streaks are called momentum, nothing is ever at risk, and missed goals and \
+             sessions aren't shown as bad news",
+        );
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(nudging.calm, |ui| {
+            ui.label("Gentle reminders at most every");
+            ui.add(egui::DragValue::new(&mut nudging.calm_reminder_days).clamp_range(0..=30));
+            ui.label("days (0 for never)");
+        });
+    });
+    ui.separator();
+
+    ui.heading("Journal");
+    ui.checkbox(
+        &mut settings.reflect_on_level_up,
+        "Ask for a reflection when a skill levels up",
+    )
+    .on_hover_text("Level ups are always written in the skill's journal");
+    ui.separator();
+
+    ui.heading("Reminders");
+    let calm = settings.nudging.calm;
+    let reminders = &mut settings.reminders;
+    let reminder_text = if calm {
+        "Gently remind about skills with momentum at"
+    } else {
+        "Warn about streaks at risk at"
+    };
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut reminders.enabled, reminder_text)
+            .on_hover_text("Skills practiced yesterday but not yet today");
+        ui.add_enabled(
+            reminders.enabled,
+            egui::DragValue::new(&mut reminders.hour).clamp_range(0..=23),
+        );
+        ui.label(":");
+        ui.add_enabled(
+            reminders.enabled,
+            egui::DragValue::new(&mut reminders.minute).clamp_range(0..=59),
+        );
+    });
+    ui.separator();
+
+    ui.heading("Busy while practicing");
+    let busy = &mut settings.busy;
+    ui.checkbox(&mut busy.enabled, "Show as busy while a timer runs");
+    ui.add_enabled_ui(busy.enabled, |ui| {
+        egui::Grid::new("busy_settings").show(ui, |ui| {
+            ui.label("Calendar file").on_hover_text(
+                "An .ics file for a calendar to subscribe to, with a busy event in it",
+            );
+            ui.text_edit_singleline(&mut busy.ics_path);
+            ui.end_row();
+            ui.label("When a timer starts, run");
+            ui.add(
+                egui::TextEdit::singleline(&mut busy.start_command)
+                    .hint_text("gsettings set org.gnome.desktop.notifications show-banners false"),
+            );
+            ui.end_row();
+            ui.label("When they've all stopped, run");
+            ui.add(
+                egui::TextEdit::singleline(&mut busy.stop_command)
+                    .hint_text("gsettings set org.gnome.desktop.notifications show-banners true"),
+            );
+            ui.end_row();
+            ui.label("Busy for");
+            ui.add(
+                egui::DragValue::new(&mut busy.block_minutes)
+                    .clamp_range(5..=480)
+                    .suffix(" min at a time"),
+            );
+            ui.end_row();
+        });
+    });
+    ui.separator();
+
+    ui.heading("Security");
+    ui.label(
+        "Tokens for the API that --serve starts, each sent as \"Authorization: Bearer <token>\". \
+         Until there's at least one, anything on this computer can use the API.",
+    );
+    let mut remove = None;
+    settings
+        .api_tokens
+        .iter_mut()
+        .enumerate()
+        .for_each(|(idx, token)| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut token.name)
+                        .hint_text("Phone shortcut")
+                        .desired_width(140.0),
+                );
+                egui::ComboBox::from_id_source(("token_scope", idx))
+                    .selected_text(token.scope.label())
+                    .show_ui(ui, |ui| {
+                        TokenScope::ALL.iter().for_each(|scope| {
+                            ui.selectable_value(&mut token.scope, *scope, scope.label())
+                                .on_hover_text(scope.description());
+                        });
+                    });
+                ui.monospace(token.secret.as_str());
+                if ui.button("Copy").clicked() {
+                    ui.output().copied_text = token.secret.clone();
+                }
+                if ui.button(" - ").clicked() {
+                    remove = Some(idx);
+                }
+            });
+            egui::CollapsingHeader::new(format!("Audit log ({})", token.audit_log.len()))
+                .id_source(("token_audit_log", idx))
+                .show(ui, |ui| {
+                    if token.audit_log.is_empty() {
+                        ui.weak("It hasn't been used yet.");
+                    }
+                    egui::Grid::new(("token_audit_grid", idx))
+                        .striped(true)
+                        .show(ui, |ui| {
+                            token.audit_log.iter().rev().for_each(|entry| {
+                                let time = entry.time.with_timezone(&chrono::Local);
+                                ui.label(time.format("%Y-%m-%d %H:%M").to_string());
+                                ui.label(entry.request.as_str());
+                                ui.label(entry.status.to_string());
+                                ui.end_row();
+                            });
+                        });
+                    if !token.audit_log.is_empty() && ui.button("Clear").clicked() {
+                        token.audit_log.clear();
+                    }
+                });
+        });
+    if let Some(idx) = remove {
+        settings.api_tokens.remove(idx);
     }
+    if ui.button("Add token").clicked() {
+        settings
+            .api_tokens
+            .push(ApiToken::new("", TokenScope::LogOnly));
+    }
+    ui.separator();
 
-    fn reload_from_json(&mut self) {
-        let other = Self::from_default_path();
+    ui.heading("Webhooks");
+    ui.label(
+        "Each URL is sent a JSON POST when a skill levels up, a streak reaches 3, 7, 14, 30 or \
+         more days, or a goal is completed. The description is also sent as value1, for IFTTT.",
+    );
+    let mut remove = None;
+    settings
+        .webhooks
+        .iter_mut()
+        .enumerate()
+        .for_each(|(idx, hook)| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut hook.url)
+                        .hint_text("https://maker.ifttt.com/trigger/...")
+                        .desired_width(300.0),
+                );
+                ui.checkbox(&mut hook.level_up, "Level ups");
+                ui.checkbox(&mut hook.streak, "Streaks");
+                ui.checkbox(&mut hook.goal_completed, "Goals");
+                if ui.button(" - ").clicked() {
+                    remove = Some(idx);
+                }
+            });
+        });
+    if let Some(idx) = remove {
+        settings.webhooks.remove(idx);
+    }
+    if ui.button("Add webhook").clicked() {
+        settings.webhooks.push(Webhook::default());
+    }
+    ui.separator();
 
-        self.player_name = other.player_name;
-        self.skills_list = other.skills_list;
+    ui.heading("Status Bar");
+    ui.label("What it shows, in this order:");
+    let status_bar = &mut settings.status_bar;
+    let mut move_up = None;
+    let mut remove = None;
+    let pinned_count = status_bar.len();
+    status_bar.iter().enumerate().for_each(|(idx, metric)| {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}. {}", idx + 1, metric.label()));
+            if ui.add_enabled(idx > 0, egui::Button::new("^")).clicked() {
+                move_up = Some(idx);
+            }
+            if ui
+                .add_enabled(idx + 1 < pinned_count, egui::Button::new("v"))
+                .clicked()
+            {
+                move_up = Some(idx + 1);
+            }
+            if ui.button(" - ").clicked() {
+                remove = Some(idx);
+            }
+        });
+    });
+    if let Some(idx) = move_up {
+        status_bar.swap(idx - 1, idx);
+    }
+    if let Some(idx) = remove {
+        status_bar.remove(idx);
+    }
+    let unpinned: Vec<Metric> = Metric::ALL
+        .iter()
+        .copied()
+        .filter(|metric| !status_bar.contains(metric))
+        .collect();
+    if !unpinned.is_empty() {
+        ui.menu_button("Add...", |ui| {
+            unpinned.iter().for_each(|metric| {
+                if ui.button(metric.label()).clicked() {
+                    status_bar.push(*metric);
+                    ui.close_menu();
+                }
+            });
+        });
     }
+    ui.separator();
 
-    pub fn from_path(path: &Path) -> Self {
-        if path.exists() {
-            if let Ok(file) = File::open(path) {
-                let reader = BufReader::new(file);
-                if let Ok(SheetMyselfApp {
-                    player_name,
-                    mut skills_list,
-                }) = serde_json::from_reader(reader)
-                {
-                    skills_list.iter_mut().for_each(|(_uuid, skill)| {
-                        skill.sort_actions();
-                        skill.calculate_exp();
+    ui.heading("Updates");
+    ui.checkbox(
+        &mut settings.check_for_updates,
+        "Check for a new version when the app starts",
+    )
+    .on_hover_text(
+        "Asks GitHub for the latest release, and only says anything if there's a newer one. \
+         Help > Check for Updates works either way.",
+    );
+    ui.separator();
+
+    ui.heading("Keyboard Shortcuts");
+    egui::Grid::new("shortcuts_grid").show(ui, |ui| {
+        Action::ALL.iter().for_each(|action| {
+            ui.label(action.label());
+            shortcut_ui(ui, *action, settings);
+            ui.end_row();
+        });
+        ui.label("Undo");
+        ui.label("Ctrl+Z");
+        ui.end_row();
+        ui.label("Redo");
+        ui.label("Ctrl+Shift+Z or Ctrl+Y");
+        ui.end_row();
+    });
+    ui.separator();
+
+    ui.heading("Progress Page");
+    let publish = &mut settings.publish;
+    ui.checkbox(
+        &mut publish.after_save,
+        "Publish it whenever a sheet is saved",
+    )
+    .on_hover_text(
+        "A read-only page of levels, hours and streaks, with no notes. File > Export > \
+             Publish Progress Page does it once.",
+    );
+    egui::Grid::new("publish_grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            let mut upload = publish.upload();
+            ui.label("Upload it to:");
+            egui::ComboBox::from_id_source("publish_upload")
+                .selected_text(upload.label())
+                .show_ui(ui, |ui| {
+                    Upload::ALL.iter().for_each(|choice| {
+                        ui.selectable_value(&mut upload, *choice, choice.label());
                     });
+                });
+            publish.upload = Some(upload);
+            ui.end_row();
 
-                    return SheetMyselfApp {
-                        player_name,
-                        skills_list,
-                    };
+            let text_row = |ui: &mut egui::Ui, label: &str, text: &mut String, hint: &str| {
+                ui.label(label);
+                ui.add(
+                    egui::TextEdit::singleline(text)
+                        .hint_text(hint)
+                        .desired_width(300.0),
+                );
+                ui.end_row();
+            };
+            let secret_row = |ui: &mut egui::Ui, label: &str, text: &mut String, hint: &str| {
+                ui.label(label);
+                ui.add(
+                    egui::TextEdit::singleline(text)
+                        .password(true)
+                        .hint_text(hint)
+                        .desired_width(300.0),
+                )
+                .on_hover_text("Kept as plain text in the settings file if it's typed in");
+                ui.end_row();
+            };
+            match upload {
+                Upload::Nowhere | Upload::Command => {}
+                Upload::S3 => {
+                    let s3 = &mut publish.s3;
+                    text_row(ui, "Bucket:", &mut s3.bucket, "my-bucket");
+                    text_row(ui, "Region:", &mut s3.region, "us-east-1");
+                    text_row(ui, "Folder in it:", &mut s3.prefix, "the top");
+                    text_row(ui, "Endpoint:", &mut s3.endpoint, "Amazon's own");
+                    text_row(
+                        ui,
+                        "Access key ID:",
+                        &mut s3.access_key_id,
+                        "$AWS_ACCESS_KEY_ID",
+                    );
+                    secret_row(
+                        ui,
+                        "Secret access key:",
+                        &mut s3.secret_access_key,
+                        "$AWS_SECRET_ACCESS_KEY",
+                    );
+                }
+                Upload::GitHubPages => {
+                    let github = &mut publish.github_pages;
+                    text_row(ui, "Repository:", &mut github.repository, "owner/name");
+                    text_row(ui, "Branch:", &mut github.branch, "the default one");
+                    text_row(ui, "Folder in it:", &mut github.folder, "the top");
+                    secret_row(ui, "Token:", &mut github.token, "$GITHUB_TOKEN");
                 }
             }
-        }
 
-        Self::default()
+            let folder_hint = match upload {
+                Upload::Nowhere | Upload::Command => "folder",
+                Upload::S3 | Upload::GitHubPages => "no copy",
+            };
+            ui.label("Write it to:");
+            ui.add(
+                egui::TextEdit::singleline(&mut publish.folder)
+                    .hint_text(folder_hint)
+                    .desired_width(300.0),
+            )
+            .on_hover_text("index.html and progress.json go in this folder on this computer");
+            ui.end_row();
+            if upload == Upload::Command {
+                ui.label("Then run:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut publish.upload_command)
+                        .hint_text("command")
+                        .desired_width(300.0),
+                )
+                .on_hover_text(
+                    "Run in that folder to upload it, e.g. \"rsync -a . me@host:www\". It signs \
+                     in with that tool's own config.",
+                );
+                ui.end_row();
+            }
+        });
+    if matches!(publish.upload(), Upload::S3 | Upload::GitHubPages) {
+        ui.colored_label(
+            Colors::of(ui.ctx()).warning,
+            format!(
+                "A key or token typed in here is kept as plain text in {}. Leave it blank to \
+                 use the environment variable shown instead, and give it as little access as \
+                 will do.",
+                get_config_file_path().display()
+            ),
+        );
     }
+    ui.separator();
 
-    pub fn from_default_path() -> Self {
-        let path = get_default_file_path();
-        Self::from_path(&path)
-    }
+    ui.heading("Saving");
+    ui.checkbox(&mut settings.autosave, "Autosave")
+        .on_hover_text("Save changes automatically once you've stopped editing");
+    ui.add_enabled_ui(settings.autosave, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Save after");
+            ui.add(
+                egui::DragValue::new(&mut settings.autosave_interval_secs)
+                    .speed(1.0)
+                    .clamp_range(1.0..=3600.0)
+                    .suffix(" s"),
+            );
+            ui.label("without any edits");
+        });
+    });
+    ui.horizontal(|ui| {
+        ui.label("Save new sheets as:");
+        egui::ComboBox::from_id_source("sheet_format")
+            .selected_text(settings.sheet_format.label())
+            .show_ui(ui, |ui| {
+                FileFormat::ALL.iter().for_each(|format| {
+                    ui.selectable_value(&mut settings.sheet_format, *format, format.label());
+                });
+            })
+            .response
+            .on_hover_text(
+                "For .sht files. Sheets which already exist stay in their own format, and .json, \
+                 .ron and .toml files are always saved in the one their name says.",
+            );
+    });
+    ui.horizontal(|ui| {
+        ui.label("Backups to keep:");
+        ui.add(egui::DragValue::new(&mut settings.backup_count).clamp_range(0..=20));
+    });
+    ui.checkbox(&mut settings.git_history, "Keep a version history")
+        .on_hover_text(
+            "Commits the sheet to a git repository next to it every time it's saved, so File > \
+             Version History can restore any of them. Needs git to be installed.",
+        );
 }
 
-impl Default for SheetMyselfApp {
-    fn default() -> Self {
-        Self {
-            // Example stuff:
-            player_name: "New Player Name".to_owned(),
-            skills_list: HashMap::<Uuid, Skill>::new(),
+/// Lets the user rebind, unbind or reset one action's shortcut.
+fn shortcut_ui(ui: &mut egui::Ui, action: Action, settings: &mut Settings) {
+    let shortcuts = &mut settings.shortcuts;
+    let mut binding = shortcuts.get(action);
+    ui.horizontal(|ui| {
+        match &mut binding {
+            Some(shortcut) => {
+                ui.checkbox(&mut shortcut.command, "Ctrl");
+                ui.checkbox(&mut shortcut.shift, "Shift");
+                ui.checkbox(&mut shortcut.alt, "Alt");
+                egui::ComboBox::from_id_source(("shortcut_key", action))
+                    .selected_text(shortcut.key.as_str())
+                    .width(80.0)
+                    .show_ui(ui, |ui| {
+                        shortcuts::KEYS.iter().for_each(|(_, name)| {
+                            ui.selectable_value(&mut shortcut.key, name.to_string(), *name);
+                        });
+                    });
+                if ui.small_button("Unbind").clicked() {
+                    binding = None;
+                }
+            }
+            None => {
+                ui.label("Not bound");
+                if ui.small_button("Bind").clicked() {
+                    binding = Some(Shortcut::default());
+                }
+            }
         }
-    }
+        if ui.small_button("Reset").clicked() {
+            shortcuts.bindings.remove(&action);
+        } else if binding != shortcuts.get(action) {
+            shortcuts.bindings.insert(action, binding);
+        }
+    });
 }
 
 impl epi::App for SheetMyselfApp {
     /// Called each time the UI needs repainting, which may be many times per second.
     /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
     fn update(&mut self, ctx: &egui::Context, frame: &epi::Frame) {
+        if clock::boundary() != self.settings.day_boundary {
+            clock::set(self.settings.day_boundary);
+            // What counts as today has moved, and with it the bonus each skill is expecting next.
+            self.sheets
+                .iter_mut()
+                .for_each(|sheet| sheet.recalculate_all());
+        }
+        let today = clock::today();
+        let time = ctx.input().time;
+        self.sheets.iter_mut().for_each(|sheet| {
+            if sheet.log_recurring(today) {
+                sheet.mark_dirty(time);
+            }
+        });
+        self.answer_api_calls(time);
+
+        // The undo history keeps its own copy of the skills up to date as edits are recorded, so
+        // this only copies them the first time a sheet is edited after it's opened.
+        let edited_sheet = self.active_sheet;
+        self.sheets[edited_sheet].track_changes();
+        let mut undo = false;
+        let mut redo = false;
+        self.apply_appearance(ctx, frame);
+        self.run_shortcuts(ctx);
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    if ui.button("New Sheet...").clicked() {
+                        ui.close_menu();
+                        if let Some(path) =
+                            dialogs::pick_file_to_save(&dialogs::SHEET, Path::new("new_sheet.sht"))
+                        {
+                            self.new_sheet(&path);
+                        }
+                    }
+                    let open_text =
+                        format!("Open...{}", self.settings.shortcuts.hint(Action::Open));
+                    if ui.button(open_text).clicked() {
+                        ui.close_menu();
+                        if let Some(path) = dialogs::pick_file_to_open(&dialogs::SHEET) {
+                            self.open_sheet(&path);
+                        }
+                    }
+                    if ui
+                        .add_enabled(self.sheets.len() > 1, egui::Button::new("Close Sheet"))
+                        .clicked()
+                    {
+                        self.close_active_sheet();
+                        ui.close_menu();
+                    }
+                    ui.separator();
                     if ui.button("Reload").clicked() {
-                        self.reload_from_json();
+                        let path = self.sheets[self.active_sheet].path.clone();
+                        self.load_sheet(&path);
                         ui.close_menu();
                     }
-                    if ui.button("Save").clicked() {
-                        self.save_json();
+                    let time = ctx.input().time;
+                    ui.menu_button("Restore Backup", |ui| {
+                        let backups = self.active_sheet_mut().existing_backups();
+                        if backups.is_empty() {
+                            ui.label("No backups yet");
+                        }
+                        backups.iter().for_each(|backup| {
+                            if ui.button(backup_label(backup)).clicked() {
+                                self.restore_backup(backup, time);
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                    if ui.button("Version History...").clicked() {
+                        let path = &self.sheets[self.active_sheet].path;
+                        self.versions = Some(VersionsWindow::open(path));
+                        ui.close_menu();
+                    }
+                    let save_text = format!("Save{}", self.settings.shortcuts.hint(Action::Save));
+                    if ui.button(save_text).clicked() {
+                        self.save_sheet(self.active_sheet);
+                        ui.close_menu();
+                    }
+                    if ui.button("Save As...").clicked() {
+                        ui.close_menu();
+                        let current = self.active_sheet_mut().path.clone();
+                        if let Some(path) = dialogs::pick_file_to_save(&dialogs::SHEET, &current) {
+                            self.save_active_sheet_as(&path);
+                        }
+                    }
+                    let path = self.active_sheet_mut().path.clone();
+                    let in_file = DirectoryStorage::folder_of(&path).is_none()
+                        && !SqliteStorage::is_database(&path);
+                    if ui
+                        .add_enabled(in_file, egui::Button::new("Keep in a Folder"))
+                        .on_hover_text(
+                            "Split the sheet into a file per skill, so saving only rewrites the \
+                             skills which changed. Good for big sheets.",
+                        )
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.move_active_sheet_to(DirectoryStorage::path_for(&path));
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui
+                        .add_enabled(in_file, egui::Button::new("Keep in a Database"))
+                        .on_hover_text(
+                            "Copy the sheet into a SQLite database next to it, so saving only \
+                             rewrites what changed and other programs can query it.",
+                        )
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.move_active_sheet_to(SqliteStorage::path_for(&path));
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if ui
+                        .add_enabled(in_file, egui::Button::new("Set Password..."))
+                        .on_hover_text("Encrypt the sheet's file, so it can't be read without it")
+                        .clicked()
+                    {
                         ui.close_menu();
+                        self.password_window = Some(PasswordWindow {
+                            path,
+                            password: String::new(),
+                            confirm: String::new(),
+                        });
                     }
                     ui.separator();
+                    ui.menu_button("Import", |ui| {
+                        if ui.button("CSV...").clicked() {
+                            ui.close_menu();
+                            if let Some(path) = dialogs::pick_file_to_open(&dialogs::CSV) {
+                                self.csv_import = CsvImportWindow::open(&path);
+                            }
+                        }
+                        if ui
+                            .button("Skills from a Sheet...")
+                            .on_hover_text("Such as a template someone shared")
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            if let Some(path) = dialogs::pick_file_to_open(&dialogs::SHEET) {
+                                self.open_skill_import(&path);
+                            }
+                        }
+                        ACTIVITY_SOURCES.iter().for_each(|source| {
+                            if ui.button(format!("{}...", source.name)).clicked() {
+                                ui.close_menu();
+                                if let Some(path) = dialogs::pick_file_to_open(&source.kind) {
+                                    let sheet = &self.sheets[self.active_sheet];
+                                    self.activity_import =
+                                        Some(ActivityImportWindow::open(source, &path, sheet));
+                                }
+                            }
+                        });
+                    });
+                    ui.menu_button("Export", |ui| {
+                        if ui.button("CSV...").clicked() {
+                            self.csv_export = Some(CsvExportWindow::default());
+                            ui.close_menu();
+                        }
+                        if ui.button("Quantified Self CSV...").clicked() {
+                            let sheet = &self.sheets[self.active_sheet];
+                            self.text_export = Some(TextExportWindow {
+                                title: "Quantified Self CSV",
+                                text: export::records_to_qs_csv(sheet),
+                                kind: &dialogs::CSV,
+                                file_name: format!("{}.csv", sheet.display_name()),
+                                error: None,
+                            });
+                            ui.close_menu();
+                        }
+                        if ui.button("Calendar (iCal)...").clicked() {
+                            let sheet = &self.sheets[self.active_sheet];
+                            self.text_export = Some(TextExportWindow {
+                                title: "Calendar (iCal)",
+                                text: export::records_to_ics(sheet),
+                                kind: &dialogs::ICALENDAR,
+                                file_name: format!("{}.ics", sheet.display_name()),
+                                error: None,
+                            });
+                            ui.close_menu();
+                        }
+                        if ui.button("Markdown Report...").clicked() {
+                            let today = clock::today();
+                            let sheet = &self.sheets[self.active_sheet];
+                            let report = Report::new(sheet, today);
+                            self.text_export = Some(TextExportWindow {
+                                title: "Markdown Report",
+                                text: report.to_markdown(&self.settings.exp_format),
+                                kind: &dialogs::MARKDOWN,
+                                file_name: format!("{}.md", sheet.display_name()),
+                                error: None,
+                            });
+                            ui.close_menu();
+                        }
+                        if ui.button("Publish Progress Page").clicked() {
+                            self.publish_waiting = Some(self.active_sheet_mut().path.clone());
+                            ui.close_menu();
+                        }
+                        if ui.button("Blank Paper Log...").clicked() {
+                            self.paper_log = Some(PaperLogWindow {
+                                month: clock::today(),
+                                error: None,
+                            });
+                            ui.close_menu();
+                        }
+                    });
+                    ui.separator();
                     if ui.button("Quit").clicked() {
-                        frame.quit();
+                        if self.sheets.iter().any(|sheet| sheet.dirty) {
+                            self.quitting = true;
+                        } else {
+                            frame.quit();
+                        }
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("Edit", |ui| {
+                    let history = &self.sheets[self.active_sheet].history;
+                    if ui
+                        .add_enabled(history.can_undo(), egui::Button::new("Undo (Ctrl+Z)"))
+                        .clicked()
+                    {
+                        undo = true;
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(history.can_redo(), egui::Button::new("Redo (Ctrl+Shift+Z)"))
+                        .clicked()
+                    {
+                        redo = true;
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("Tools", |ui| {
+                    if ui.button("Shift Dates...").clicked() {
+                        self.date_shift = Some(DateShiftWindow::default());
+                        ui.close_menu();
+                    }
+                    if ui.button("Month Grid...").clicked() {
+                        self.month_grid = Some(MonthGridWindow::new(clock::today()));
+                        ui.close_menu();
+                    }
+                    if ui.button("Data Health...").clicked() {
+                        self.data_health_open = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("Export Schema...").clicked() {
+                        self.text_export = Some(TextExportWindow {
+                            title: "Save File Schema",
+                            text: migration::schema(),
+                            kind: &dialogs::JSON_SCHEMA,
+                            file_name: format!(
+                                "sheet.v{}.schema.json",
+                                migration::CURRENT_FORMAT_VERSION
+                            ),
+                            error: None,
+                        });
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("Help", |ui| {
+                    let checking = self
+                        .update_check
+                        .as_ref()
+                        .map_or(false, |check| check.pending.is_some());
+                    if ui
+                        .add_enabled(!checking, egui::Button::new("Check for Updates"))
+                        .clicked()
+                    {
+                        self.start_update_check(true, frame);
+                        ui.close_menu();
+                    }
+                });
+
+                ui.separator();
+                let mut switch_to = None;
+                self.sheets.iter().enumerate().for_each(|(idx, sheet)| {
+                    let mut tab_text = sheet.display_name();
+                    if sheet.dirty {
+                        tab_text.push('*');
+                    }
+                    let tab = ui
+                        .selectable_label(idx == self.active_sheet, tab_text)
+                        .on_hover_text(sheet.path.display().to_string());
+                    if tab.clicked() {
+                        switch_to = Some(idx);
                     }
                 });
+                if let Some(idx) = switch_to {
+                    if idx != self.active_sheet {
+                        self.active_sheet = idx;
+                        self.save_config();
+                    }
+                }
             });
         });
 
-        let Self {
-            player_name,
-            skills_list,
-        } = self;
+        self.load_failure_window_ui(ctx);
+        self.csv_export_window_ui(ctx);
+        self.paper_log_window_ui(ctx);
+        self.date_shift_window_ui(ctx);
+        self.month_grid_window_ui(ctx);
+        self.csv_import_window_ui(ctx);
+        self.data_health_window_ui(ctx);
+        self.activity_import_window_ui(ctx);
+        self.text_export_window_ui(ctx);
+        self.import_progress_window_ui(ctx, frame);
+        self.pomodoro_window_ui(ctx);
+        self.note_edits_window_ui(ctx, frame);
+        self.paste_rows_window_ui(ctx);
+        self.skill_list_window_ui(ctx);
+        self.skill_import_window_ui(ctx);
+        self.exp_override_window_ui(ctx);
+        self.record_editor_window_ui(ctx);
+        self.unit_change_window_ui(ctx);
+        self.complete_skill_window_ui(ctx);
+        self.session_links_window_ui(ctx);
+        self.reflection_window_ui(ctx);
+        self.versions_window_ui(ctx);
 
-        // Info bar at the bottom...?
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
-            egui::warn_if_debug_build(ui);
+            ui.horizontal(|ui| {
+                let sheet = &self.sheets[self.active_sheet];
+                self.settings.status_bar.iter().for_each(|metric| {
+                    let settings = &self.settings;
+                    ui.label(metric.show(sheet, &settings.exp_format, &settings.nudging, today));
+                    ui.separator();
+                });
+                egui::warn_if_debug_build(ui);
+            });
         });
 
         egui::SidePanel::left("section_chooser").show(ctx, |ui| {
             ui.vertical_centered_justified(|ui| {
-                if ui.button("Skills").clicked() {
-                    // TODO: swap to skills page if we're not there...
+                if ui
+                    .selectable_label(self.page == Page::Dashboard, "Dashboard")
+                    .clicked()
+                {
+                    self.page = Page::Dashboard;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Today, "Today")
+                    .clicked()
+                {
+                    self.page = Page::Today;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Skills, "Skills")
+                    .clicked()
+                {
+                    self.page = Page::Skills;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Goals, "Goals")
+                    .clicked()
+                {
+                    self.page = Page::Goals;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Boss, "Weekly Boss")
+                    .clicked()
+                {
+                    self.page = Page::Boss;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Planner, "Planner")
+                    .clicked()
+                {
+                    self.page = Page::Planner;
+                }
+                if ui
+                    .selectable_label(self.page == Page::TimeAudit, "Time Audit")
+                    .clicked()
+                {
+                    self.page = Page::TimeAudit;
+                }
+                let pending_count = self.sheets[self.active_sheet].pending_count();
+                if self.sheets[self.active_sheet].require_approval || pending_count > 0 {
+                    let pending_text = format!("Pending ({})", pending_count);
+                    if ui
+                        .selectable_label(self.page == Page::Pending, pending_text)
+                        .clicked()
+                    {
+                        self.page = Page::Pending;
+                    }
+                }
+                if ui
+                    .selectable_label(self.page == Page::Activity, "Activity")
+                    .clicked()
+                {
+                    self.page = Page::Activity;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Charts, "Charts")
+                    .clicked()
+                {
+                    self.page = Page::Charts;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Correlations, "Correlations")
+                    .clicked()
+                {
+                    self.page = Page::Correlations;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Snapshots, "Save Points")
+                    .clicked()
+                {
+                    self.page = Page::Snapshots;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Loot, "Loot")
+                    .clicked()
+                {
+                    self.page = Page::Loot;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Legacy, "Legacy")
+                    .clicked()
+                {
+                    self.page = Page::Legacy;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Achievements, "Achievements")
+                    .clicked()
+                {
+                    self.page = Page::Achievements;
+                }
+                if ui
+                    .selectable_label(self.page == Page::HallOfFame, "Hall of Fame")
+                    .clicked()
+                {
+                    self.page = Page::HallOfFame;
+                }
+                if ui
+                    .selectable_label(self.page == Page::SkillTree, "Skill Tree")
+                    .clicked()
+                {
+                    self.page = Page::SkillTree;
+                }
+                ui.separator();
+                if ui
+                    .selectable_label(
+                        self.page == Page::BonusRules,
+                        format!("{} Rules", self.settings.exp_format.name),
+                    )
+                    .clicked()
+                {
+                    self.page = Page::BonusRules;
+                }
+                if ui
+                    .selectable_label(self.page == Page::Settings, "Settings")
+                    .clicked()
+                {
+                    self.page = Page::Settings;
                 }
             });
         });
 
-        egui::TopBottomPanel::top("player_info_top").show(ctx, |ui| {
-            ui.text_edit_singleline(player_name);
-            // TODO: Add a button to edit the player's name... when you hover over the label...?
-        });
-
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // The central panel the region left after adding TopPanel's and SidePanel's
-            skills_list.iter_mut().for_each(|(skill_id, skill)| {
-                // TODO: The sorting is WAY too aggressive -- it sorts any time a value changes
-                // and additionally your cursor stays in the same physical place even though the
-                // row you were editing has shifted.
-                //
-                // I think I'll need to add some UUIDs or something to each action struct so that
-                // the UI can track which one you were editing and make sure you're always scrolled
-                // to it if nothing else.
-                //
-                // Alternatively, I could implement an editor for rows and then only change and sort
-                // and recalculate when the editor closes with "accept" rather than "cancel"?
-                //
-                // Alter-alternatively, go through and look for a focus lost but none gained across
-                // all the text edit fields?
-                let mut need_sort = false;
-                let Skill {
-                    name,
-                    records,
-                    potential_bonus,
-                    total_exp,
-                } = skill;
-                let collapse_id = ui.make_persistent_id(skill_id);
-
-                let mut expanded =
-                    if let Some(b) = ui.memory().data.get_persisted::<bool>(collapse_id) {
-                        b
-                    } else {
-                        true
-                    };
-
-                let expand_text = match expanded {
-                    true => " v ",
-                    false => " > ",
-                };
-
-                ui.horizontal(|ui| {
-                    if ui.button(expand_text).clicked() {
-                        expanded = !expanded;
-                        ui.memory().data.insert_persisted(collapse_id, expanded);
-                    }
-                    ui.text_edit_singleline(name);
-                    let rounded_total = (*total_exp * 10f64).round() / 10f64;
-                    ui.label(format!("EXP: {}", rounded_total));
-                    ui.label(format!("Level: <<TODO>>"));
-                });
-                if expanded {
-                    ui.indent(collapse_id, |ui| {
-                        egui::Grid::new("entry_grid").show(ui, |ui| {
-                            // TODO: Add little arrow buttons to sort by year/month/day/etc
-                            ui.label("Year");
-                            ui.label("Month");
-                            ui.label("Day");
-                            ui.label("Duration");
-                            ui.label("EXP");
-                            ui.label("(from streak)");
-                            ui.end_row();
-
-                            let mut idx = 0;
-                            while idx < records.len() {
-                                let mut rec = &mut records[idx];
-                                let mut year = rec.date.year().to_string();
-                                let mut month = rec.date.month().to_string();
-                                let mut day = rec.date.day().to_string();
-                                let mut duration = rec.duration.to_string();
-
-                                let year_field = ui.text_edit_singleline(&mut year);
-                                let month_field = ui.text_edit_singleline(&mut month);
-                                let day_field = ui.text_edit_singleline(&mut day);
-                                let duration_field = ui.text_edit_singleline(&mut duration);
-
-                                let total_exp = rec.base_exp + rec.bonus_exp;
-                                let rounded_total = (total_exp * 10f64).round() / 10f64;
-                                let rounded_bonus = (rec.bonus_exp * 10f64).round() / 10f64;
-                                ui.label(rounded_total.to_string());
-                                ui.label(format!("({})", rounded_bonus));
-
-                                if year_field.changed() {
-                                    if let Ok(i) = year.parse::<i32>() {
-                                        rec.date = if let Some(new_rec) = rec.date.with_year(i) {
-                                            new_rec
-                                        } else {
-                                            rec.date
-                                        };
-                                    }
-                                }
-                                if month_field.changed() {
-                                    if let Ok(i) = month.parse::<u32>() {
-                                        rec.date = if let Some(new_rec) = rec.date.with_month(i) {
-                                            new_rec
-                                        } else {
-                                            rec.date
-                                        };
-                                    }
-                                }
-                                if day_field.changed() {
-                                    if let Ok(i) = day.parse::<u32>() {
-                                        rec.date = if let Some(new_rec) = rec.date.with_day(i) {
-                                            new_rec
-                                        } else {
-                                            rec.date
-                                        };
-                                    }
-                                }
-                                if duration_field.changed() {
-                                    if let Ok(i) = duration.parse::<u64>() {
-                                        rec.duration = i;
-                                    }
-                                }
-
-                                // Hack to prevent the UI from sorting while you're editing fields
-                                // This should execute when you press enter, click outside the
-                                // fields, or tab away from the fields in this record.
-                                if !year_field.has_focus()
-                                    && !month_field.has_focus()
-                                    && !day_field.has_focus()
-                                    && !duration_field.has_focus()
-                                {
-                                    if year_field.lost_focus()
-                                        || month_field.lost_focus()
-                                        || day_field.lost_focus()
-                                        || duration_field.lost_focus()
-                                    {
-                                        need_sort = true;
-                                    }
-                                }
+        self.save_failure_banner_ui(ctx);
+        self.notice_banner_ui(ctx);
+        self.dropped_files_ui(ctx);
+        self.sync_conflict_window_ui(ctx);
+        self.close_sheet_window_ui(ctx);
+        self.quit_window_ui(ctx, frame);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.password_window_ui(ctx);
+        self.update_check_window_ui(ctx);
+        self.check_reminders(frame);
+        if self.reminder_banner && self.settings.nudging.warns_of_risk() {
+            let today = clock::today();
+            let at_risk = reminders::at_risk_streaks(&self.sheets[self.active_sheet], today);
+            if !at_risk.is_empty() {
+                egui::TopBottomPanel::top("reminder_banner").show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(
+                            Colors::of(ui.ctx()).warning,
+                            format!(
+                                "Practice today to keep your streak: {}",
+                                reminders::describe(&at_risk)
+                            ),
+                        );
+                        if ui.button("Dismiss").clicked() {
+                            self.reminder_banner = false;
+                        }
+                    });
+                });
+            }
+        }
 
-                                if ui.button(" - ").clicked() {
-                                    records.remove(idx);
-                                } else {
-                                    idx += 1;
-                                }
+        let page = self.page;
+        let sheet = &mut self.sheets[self.active_sheet];
+        let settings = &mut self.settings;
 
-                                ui.end_row();
-                            }
-                        });
+        let mut sheet_changed = false;
 
-                        ui.horizontal(|ui| {
-                            if ui.button("Add entry...").clicked() {
-                                records.push(SheetActionRecord::default());
-                            }
-                            ui.label(format!("Next bonus: {} exp", potential_bonus));
+        egui::TopBottomPanel::top("player_info_top").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                sheet_changed |= ui.text_edit_singleline(&mut sheet.player_name).changed();
+                if let Some(title) = sheet.loot.equipped.get(&LootKind::Title) {
+                    ui.label(title);
+                }
+                // TODO: Add a button to edit the player's name... when you hover over the label...?
+                sheet_changed |= ui
+                    .checkbox(&mut sheet.require_approval, "Require approval")
+                    .on_hover_text("New entries won't count until they're approved")
+                    .changed();
+                let old_style = sheet.duration_style;
+                egui::ComboBox::from_id_source("duration_style")
+                    .selected_text(format!("Durations: {}", old_style.label()))
+                    .show_ui(ui, |ui| {
+                        DurationStyle::ALL.iter().for_each(|style| {
+                            ui.selectable_value(&mut sheet.duration_style, *style, style.label());
                         });
                     });
+                sheet_changed |= sheet.duration_style != old_style;
+            });
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // The central panel the region left after adding TopPanel's and SidePanel's
+            sheet_changed |= match page {
+                Page::Dashboard => {
+                    dashboard_page_ui(ui, sheet, &settings.exp_format, &settings.nudging);
+                    false
+                }
+                Page::Today => today_page_ui(ui, sheet, &settings.exp_format, &settings.nudging),
+                Page::Skills => skills_page_ui(
+                    ui,
+                    sheet,
+                    &settings.exp_format,
+                    &settings.nudging,
+                    &settings.note_editor,
+                    SkillWindows {
+                        paste_rows: &mut self.paste_rows,
+                        exp_override: &mut self.exp_override,
+                        record_editor: &mut self.record_editor,
+                        unit_change: &mut self.unit_change,
+                        complete_skill: &mut self.complete_skill,
+                        session_links: &mut self.session_links,
+                        skill_list: &mut self.skill_list,
+                    },
+                ),
+                Page::Goals => goals_page_ui(ui, sheet, &settings.exp_format, &settings.nudging),
+                Page::Boss => boss_page_ui(ui, sheet, &settings.exp_format),
+                Page::Planner => planner_page_ui(ui, sheet),
+                Page::Pending => pending_page_ui(ui, sheet),
+                Page::TimeAudit => {
+                    time_audit_page_ui(ui, sheet, settings);
+                    false
+                }
+                Page::BonusRules => bonus_rules_page_ui(ui, sheet, &settings.exp_format),
+                Page::Snapshots => snapshots_page_ui(ui, sheet, &settings.exp_format),
+                Page::Correlations => {
+                    correlations_page_ui(ui, sheet);
+                    false
                 }
+                Page::Charts => {
+                    charts_page_ui(ui, sheet, &settings.exp_format, &mut self.charts, frame);
+                    false
+                }
+                Page::Activity => activity_page_ui(ui, sheet),
+                Page::Loot => loot_page_ui(ui, sheet),
+                Page::Legacy => legacy_page_ui(ui, sheet, &settings.exp_format),
+                Page::Achievements => {
+                    achievements_page_ui(ui, sheet);
+                    false
+                }
+                Page::HallOfFame => hall_of_fame_page_ui(ui, sheet, &settings.exp_format),
+                Page::SkillTree => skill_tree_page_ui(ui, sheet),
+                Page::Settings => {
+                    settings_page_ui(ui, settings);
+                    false
+                }
+            };
+        });
 
-                if need_sort {
-                    skill.sort_actions();
-                    skill.calculate_exp();
+        if sheet_changed {
+            sheet.mark_dirty(ctx.input().time);
+        }
+        self.roll_loot(ctx, edited_sheet);
+        self.update_history(ctx, edited_sheet, undo, redo);
+        self.new_loot_window_ui(ctx);
+        self.autosave(ctx, frame);
+        self.save_in_background(ctx, frame);
+        self.publish_in_background(frame);
+        self.check_milestones(ctx, frame);
+        self.update_busy_status(frame);
+    }
+
+    fn on_exit(&mut self) {
+        // Saves still going on are waited for, or done here if they hadn't started.
+        let backup_count = self.settings.backup_count;
+        let git_history = self.settings.git_history;
+        std::mem::take(&mut self.saves)
+            .into_iter()
+            .for_each(|save| {
+                let saved = match (save.worker, save.pending) {
+                    (Some(worker), _) => worker.wait(),
+                    (None, Some(pending)) => {
+                        Some(write_sheet(pending, &save.path, backup_count, git_history))
+                    }
+                    (None, None) => None,
+                };
+                match saved.map(|saved| (saved.result, saved.on_disk)) {
+                    Some((Ok(()), on_disk)) => {
+                        if let Some(sheet) = self.sheets.iter_mut().find(|s| s.path == save.path) {
+                            sheet.saved_as_of(save.as_of, on_disk);
+                        }
+                    }
+                    Some((Err(e), _)) => eprintln!("Couldn't save {}: {}", save.path.display(), e),
+                    None => eprintln!("Couldn't save {}", save.path.display()),
                 }
             });
-
-            if ui.button("New Skill").clicked() {
-                skills_list.insert(Uuid::new_v4(), Skill::default());
-            }
-        });
+        // Unsaved changes are saved whether autosave is on or not, unless Quit was told to throw
+        // them away; closing the window doesn't get a chance to ask.
+        if !self.discard_on_quit {
+            // There's no window left to show a problem in, so it goes to the terminal.
+            let backup_count = self.settings.backup_count;
+            let git_history = self.settings.git_history;
+            self.sheets
+                .iter_mut()
+                .filter(|sheet| sheet.dirty)
+                .for_each(|sheet| {
+                    // Nobody is left to ask, so a version changed somewhere else is kept too.
+                    let path = sheet.path.clone();
+                    if sheet.changed_on_disk() {
+                        let mut copy = path.clone().into_os_string();
+                        copy.push(".conflict");
+                        sheet.path = PathBuf::from(copy);
+                        eprintln!(
+                            "{} was changed somewhere else, so the changes made here are being \
+                             saved to {} instead",
+                            path.display(),
+                            sheet.path.display()
+                        );
+                    }
+                    // The copy isn't the sheet, so it's left out of the sheet's history.
+                    match sheet.save(backup_count) {
+                        Ok(()) if git_history && sheet.path == path => {
+                            if let Err(e) = VersionHistory::for_sheet(&path).commit() {
+                                eprintln!("Couldn't keep a version of {}: {}", path.display(), e);
+                            }
+                        }
+                        Ok(()) => {}
+                        Err(e) => eprintln!("Couldn't save {}: {}", sheet.path.display(), e),
+                    }
+                    sheet.path = path;
+                });
+        }
+        self.save_config();
     }
 
     /// Called once before the first frame.
     fn setup(
         &mut self,
         _ctx: &egui::Context,
-        _frame: &epi::Frame,
+        frame: &epi::Frame,
         _storage: Option<&dyn epi::Storage>,
     ) {
+        let serve_port = self.serve_port;
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
         #[cfg(feature = "persistence")]
         if let Some(storage) = _storage {
-            *self = epi::get_value(storage, epi::APP_KEY).unwrap_or_default()
+            *self = epi::get_value(storage, epi::APP_KEY).unwrap_or_default();
+            self.load_sheets();
+        }
+        if let Some(port) = serve_port {
+            let frame = frame.clone();
+            match server::start(port, move || frame.request_repaint()) {
+                Ok(calls) => {
+                    self.api_calls = Some(calls);
+                    self.api_sheet = Some(self.sheets[self.active_sheet].path.clone());
+                }
+                Err(e) => {
+                    self.notice = Some(format!("Couldn't serve the API on port {}: {}", port, e))
+                }
+            }
+        }
+        if self.settings.check_for_updates {
+            self.start_update_check(false, frame);
         }
     }
 
@@ -0,0 +1,232 @@
+use eframe::egui::{self, Color32, Stroke, Visuals};
+use serde::{Deserialize, Serialize};
+use sheet_core::zones::Zone;
+
+//====================================================
+// Appearance
+//====================================================
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub enum Theme {
+    /// Dark or light, whichever the system prefers.
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Dark, Theme::Light];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::System => "System",
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+        }
+    }
+}
+
+/// Which colors mean what, for heatmaps, charts and good or bad news. All but the standard one
+/// avoid leaning on colors that some kind of color blindness makes hard to tell apart.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub enum Palette {
+    /// Greens for progress and red for problems.
+    #[default]
+    Standard,
+    /// Blues and orange, for red-green color blindness.
+    BlueOrange,
+    /// Teals and red, for blue-yellow color blindness.
+    TealRed,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Standard, Palette::BlueOrange, Palette::TealRed];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Palette::Standard => "Standard",
+            Palette::BlueOrange => "Blue & orange (red-green safe)",
+            Palette::TealRed => "Teal & red (blue-yellow safe)",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub theme: Theme,
+    /// Used for selections and links. `None` keeps the theme's own color.
+    pub accent: Option<[u8; 3]>,
+    pub palette: Palette,
+    /// Pure black and white behind and around everything, with outlined widgets.
+    pub high_contrast: bool,
+}
+
+impl Appearance {
+    /// Sets egui up with these visuals, and keeps their [`Colors`] for widgets to draw with.
+    pub fn apply(&self, ctx: &egui::Context, prefer_dark: Option<bool>) {
+        let dark = self.is_dark(prefer_dark);
+        ctx.set_visuals(self.visuals(prefer_dark));
+        ctx.memory()
+            .data
+            .insert_temp(colors_id(), Colors::new(self.palette, dark));
+    }
+
+    /// `prefer_dark` is what the system prefers, if it's known; when it isn't, the system theme
+    /// is taken to be dark, as that's egui's default.
+    fn is_dark(&self, prefer_dark: Option<bool>) -> bool {
+        match self.theme {
+            Theme::System => prefer_dark.unwrap_or(true),
+            Theme::Dark => true,
+            Theme::Light => false,
+        }
+    }
+
+    /// The visuals to give egui.
+    fn visuals(&self, prefer_dark: Option<bool>) -> Visuals {
+        let dark = self.is_dark(prefer_dark);
+        let mut visuals = if dark {
+            Visuals::dark()
+        } else {
+            Visuals::light()
+        };
+        if self.high_contrast {
+            high_contrast(&mut visuals, dark);
+        }
+
+        if let Some([r, g, b]) = self.accent {
+            let accent = Color32::from_rgb(r, g, b);
+            visuals.selection.bg_fill = accent;
+            visuals.selection.stroke = egui::Stroke::new(1.0, contrasting(accent));
+            visuals.hyperlink_color = accent;
+        }
+        visuals
+    }
+}
+
+/// Takes the grays out of `visuals`: text and outlines are all white on black, or black on white.
+fn high_contrast(visuals: &mut Visuals, dark: bool) {
+    let (text, background) = if dark {
+        (Color32::WHITE, Color32::BLACK)
+    } else {
+        (Color32::BLACK, Color32::WHITE)
+    };
+    visuals.override_text_color = Some(text);
+    visuals.extreme_bg_color = background;
+    visuals.faint_bg_color = if dark {
+        Color32::from_gray(30)
+    } else {
+        Color32::from_gray(225)
+    };
+    let widgets = &mut visuals.widgets;
+    widgets.noninteractive.bg_fill = background;
+    widgets.noninteractive.bg_stroke = Stroke::new(1.0, text);
+    widgets.inactive.bg_stroke = Stroke::new(1.0, text);
+    widgets.hovered.bg_stroke = Stroke::new(2.0, text);
+    widgets.active.bg_stroke = Stroke::new(2.0, text);
+    [
+        &mut widgets.noninteractive,
+        &mut widgets.inactive,
+        &mut widgets.hovered,
+        &mut widgets.active,
+        &mut widgets.open,
+    ]
+    .iter_mut()
+    .for_each(|widget| widget.fg_stroke.color = text);
+}
+
+//====================================================
+// Colors
+//====================================================
+/// The colors which mean something, as the palette and theme have them. Widgets take them from
+/// here rather than picking their own, so they all change together.
+#[derive(Clone, Copy)]
+pub struct Colors {
+    /// Good news, like a goal reached or a boss beaten.
+    pub good: Color32,
+    /// Bad news, like a goal missed.
+    pub bad: Color32,
+    pub error: Color32,
+    pub warning: Color32,
+    /// EXP which was set by hand rather than worked out.
+    pub overridden: Color32,
+    /// The busiest day on a heatmap; quieter ones shade from it down to the background.
+    pub heat: Color32,
+    /// Each daily zone's color, from maintenance to stretch.
+    pub zones: [Color32; 3],
+    /// Chart series, in the order they're drawn.
+    pub series: [Color32; 2],
+}
+
+impl Colors {
+    fn new(palette: Palette, dark: bool) -> Self {
+        let rgb = Color32::from_rgb;
+        // Text colors go darker on light backgrounds, so they can still be read.
+        let pick = |on_dark: Color32, on_light: Color32| if dark { on_dark } else { on_light };
+        match palette {
+            Palette::Standard => Self {
+                good: pick(Color32::GREEN, rgb(0, 140, 0)),
+                bad: pick(Color32::RED, rgb(200, 0, 0)),
+                error: pick(Color32::RED, rgb(200, 0, 0)),
+                warning: pick(Color32::YELLOW, rgb(160, 110, 0)),
+                overridden: rgb(230, 170, 60),
+                heat: rgb(57, 211, 83),
+                zones: [rgb(155, 233, 168), rgb(64, 196, 99), rgb(33, 110, 57)],
+                series: [rgb(64, 196, 99), rgb(230, 170, 60)],
+            },
+            // From Okabe and Ito's palette.
+            Palette::BlueOrange => Self {
+                good: pick(rgb(86, 180, 233), rgb(0, 114, 178)),
+                bad: pick(rgb(230, 159, 0), rgb(213, 94, 0)),
+                error: pick(rgb(230, 159, 0), rgb(213, 94, 0)),
+                warning: pick(rgb(240, 228, 66), rgb(150, 120, 0)),
+                overridden: rgb(204, 121, 167),
+                heat: pick(rgb(86, 180, 233), rgb(0, 114, 178)),
+                zones: [rgb(170, 215, 240), rgb(86, 180, 233), rgb(0, 114, 178)],
+                series: [rgb(86, 180, 233), rgb(230, 159, 0)],
+            },
+            Palette::TealRed => Self {
+                good: pick(rgb(0, 200, 200), rgb(0, 130, 130)),
+                bad: pick(rgb(240, 80, 80), rgb(190, 30, 30)),
+                error: pick(rgb(240, 80, 80), rgb(190, 30, 30)),
+                warning: pick(rgb(255, 120, 180), rgb(180, 40, 120)),
+                overridden: rgb(255, 150, 120),
+                heat: rgb(0, 170, 170),
+                zones: [rgb(150, 220, 220), rgb(0, 170, 170), rgb(0, 100, 100)],
+                series: [rgb(0, 170, 170), rgb(230, 60, 60)],
+            },
+        }
+    }
+
+    pub fn zone(&self, zone: Zone) -> Color32 {
+        match zone {
+            Zone::Maintenance => self.zones[0],
+            Zone::Growth => self.zones[1],
+            Zone::Stretch => self.zones[2],
+        }
+    }
+
+    /// The colors the app's appearance was last applied with.
+    pub fn of(ctx: &egui::Context) -> Self {
+        ctx.memory()
+            .data
+            .get_temp(colors_id())
+            .unwrap_or_else(|| Colors::new(Palette::default(), true))
+    }
+}
+
+fn colors_id() -> egui::Id {
+    egui::Id::new("appearance_colors")
+}
+
+/// Black or white, whichever is easier to read on `background`.
+fn contrasting(background: Color32) -> Color32 {
+    let luma = 0.299 * background.r() as f32
+        + 0.587 * background.g() as f32
+        + 0.114 * background.b() as f32;
+    if luma > 140.0 {
+        Color32::BLACK
+    } else {
+        Color32::WHITE
+    }
+}
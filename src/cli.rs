@@ -0,0 +1,254 @@
+//! Commands for working with a sheet from a terminal or a script, without opening the window.
+//! They use the sheet the app had open last, unless `--sheet` says otherwise.
+
+use crate::app::SheetMyselfApp;
+use crate::clock;
+use crate::import;
+use crate::links::Link;
+use crate::paper;
+use crate::report::Report;
+use crate::server;
+use crate::settings::Settings;
+use crate::sheet::Sheet;
+use crate::skill::{SheetActionRecord, Skill};
+use crate::storage::SqliteStorage;
+use crate::versions::VersionHistory;
+use chrono::NaiveDate;
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+Usage: sheet_myself [--sheet FILE] <command>
+
+Commands:
+  add SKILL --minutes N [--date DATE] [--note TEXT] [--link TARGET]...
+        Logs a session. N can also be h:mm. DATE is YYYY-MM-DD, today or yesterday.
+        Each TARGET is a URL, commit hash or file the session produced.
+        A skill which isn't on the sheet yet is added.
+  list  Lists every skill with its level, EXP and hours.
+  report [--month YYYY-MM]
+        Writes a Markdown progress report, for this month unless another is given.
+  to-database
+        Copies the sheet into a SQLite database next to it, leaving the file as it was.
+  help  Shows this.
+
+With no command, the app opens as usual. With --serve [PORT] in place of one, it opens with an
+HTTP API on localhost:PORT, 7526 unless another is given, for other tools to log sessions with.";
+
+/// A command line which has been understood, ready to run.
+enum Command {
+    Add {
+        skill: String,
+        minutes: u64,
+        date: Option<NaiveDate>,
+        note: String,
+        links: Vec<Link>,
+    },
+    List,
+    Report {
+        month: Option<NaiveDate>,
+    },
+    ToDatabase,
+    Help,
+}
+
+/// Runs the command in `args`, which doesn't include the program's own name, and gives back the
+/// exit code: 0 if it worked, 1 if it failed, and 2 if the command line didn't make sense.
+pub fn run(args: &[String]) -> i32 {
+    let (sheet_path, command) = match parse(args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("sheet_myself: {}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+
+    let (saved_path, settings) = SheetMyselfApp::saved_sheet_and_settings();
+    clock::set(settings.day_boundary);
+    let path = sheet_path.unwrap_or(saved_path);
+    match execute(command, path, &settings) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("sheet_myself: {}", e);
+            1
+        }
+    }
+}
+
+/// The port asked for by `--serve [PORT]`, which opens the app with its API running rather than
+/// being a command of its own. `None` if it wasn't asked for.
+pub fn serve_port(args: &[String]) -> Option<Result<u16, String>> {
+    if args.first().map(String::as_str) != Some("--serve") {
+        return None;
+    }
+    Some(match &args[1..] {
+        [] => Ok(server::DEFAULT_PORT),
+        [port] => port
+            .parse()
+            .map_err(|_| format!("\"{}\" isn't a port number", port)),
+        _ => Err("--serve only takes a port number after it".to_owned()),
+    })
+}
+
+fn parse(args: &[String]) -> Result<(Option<PathBuf>, Command), String> {
+    let mut sheet = None;
+    let mut positional = Vec::new();
+    let mut minutes = None;
+    let mut date = None;
+    let mut note = String::new();
+    let mut links = Vec::new();
+    let mut month = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{} needs a value after it", name))
+        };
+        match arg.as_str() {
+            "--sheet" => sheet = Some(PathBuf::from(value(arg)?)),
+            "--minutes" => {
+                let text = value(arg)?;
+                minutes = Some(
+                    import::parse_duration_minutes(text)
+                        .ok_or_else(|| format!("\"{}\" isn't a number of minutes", text))?,
+                );
+            }
+            "--date" => {
+                let text = value(arg)?;
+                date = Some(parse_date(text)?);
+            }
+            "--note" => note = value(arg)?.clone(),
+            "--link" => links.push(Link::guess(value(arg)?)),
+            "--month" => {
+                let text = value(arg)?;
+                month = Some(
+                    NaiveDate::parse_from_str(&format!("{}-01", text), "%Y-%m-%d")
+                        .map_err(|_| format!("\"{}\" isn't a month like 2024-05", text))?,
+                );
+            }
+            "-h" | "--help" => positional.insert(0, "help".to_owned()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let command = match positional.first().map(String::as_str) {
+        Some("add") => {
+            let skill = match positional.get(1) {
+                Some(skill) if !skill.trim().is_empty() => skill.clone(),
+                _ => return Err("add needs the name of a skill".to_owned()),
+            };
+            let minutes = minutes.ok_or("add needs --minutes")?;
+            Command::Add {
+                skill,
+                minutes,
+                date,
+                note,
+                links,
+            }
+        }
+        Some("list") => Command::List,
+        Some("report") => Command::Report { month },
+        Some("to-database") => Command::ToDatabase,
+        Some("help") => Command::Help,
+        Some(other) => return Err(format!("unknown command {}", other)),
+        None => return Err("no command given".to_owned()),
+    };
+    Ok((sheet, command))
+}
+
+fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    match text {
+        "today" => Ok(clock::today()),
+        "yesterday" => Ok(clock::today().pred()),
+        _ => import::parse_date(text).ok_or_else(|| format!("\"{}\" isn't a date", text)),
+    }
+}
+
+fn execute(command: Command, path: PathBuf, settings: &Settings) -> Result<(), String> {
+    if let Command::Help = command {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    if let Command::ToDatabase = command {
+        let sheet = SqliteStorage::import(&path)
+            .map_err(|e| format!("couldn't copy {} into a database: {}", path.display(), e))?;
+        println!(
+            "Copied the sheet into {}. Open it from there with --sheet, or File > Open in the app.",
+            sheet.path.display()
+        );
+        return Ok(());
+    }
+
+    // No sheet there yet is the same as an empty one; `add` saves it as a new one.
+    let mut sheet = Sheet::open_or_new(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    match command {
+        Command::Add {
+            skill,
+            minutes,
+            date,
+            note,
+            links,
+        } => {
+            let record = SheetActionRecord {
+                date: date.unwrap_or_else(clock::today),
+                duration: minutes,
+                note,
+                links,
+                approved: !sheet.require_approval,
+                ..Default::default()
+            };
+            let date = record.date;
+            let mut handle = sheet.skill(&skill);
+            handle.add_record(record);
+            println!(
+                "Logged {} minutes of {} on {}. It's now level {} with {} {}.",
+                minutes,
+                handle.get().name,
+                date,
+                handle.level(),
+                settings.exp_format.number(handle.total_exp()),
+                settings.exp_format.name
+            );
+            sheet
+                .save(settings.backup_count)
+                .map_err(|e| format!("couldn't save {}: {}", path.display(), e))?;
+            if settings.git_history {
+                VersionHistory::for_sheet(&path)
+                    .commit()
+                    .map_err(|e| format!("couldn't keep a version of {}: {}", path.display(), e))?;
+            }
+        }
+        Command::List => {
+            let mut skills: Vec<&Skill> = sheet.skills_list.values().collect();
+            skills.sort_by(|a, b| a.name.cmp(&b.name));
+            skills.iter().for_each(|skill| {
+                let minutes: u64 = skill
+                    .records
+                    .iter()
+                    .filter(|r| r.approved)
+                    .map(|r| r.duration)
+                    .sum();
+                println!(
+                    "{}\tlevel {}\t{} {}\t{:.1}h",
+                    skill.name,
+                    skill.level(),
+                    settings.exp_format.number(skill.total_exp),
+                    settings.exp_format.name,
+                    minutes as f64 / 60.0
+                );
+            });
+        }
+        Command::Report { month } => {
+            // A past month's minutes and streaks are counted up to its last day.
+            let today = clock::today();
+            let date = month.map_or(today, |month| paper::month_of(month).1.min(today));
+            print!(
+                "{}",
+                Report::new(&sheet, date).to_markdown(&settings.exp_format)
+            );
+        }
+        Command::ToDatabase | Command::Help => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! A button showing a date, which opens a small calendar for picking a new one.
+
+use crate::clock;
+use chrono::{Datelike, Duration, NaiveDate};
+use eframe::egui;
+
+/// Shows `date` as a button which opens a calendar popup. Returns whether a different date was
+/// picked. Every date the calendar offers is a real one, so there's nothing to validate.
+pub fn date_picker_ui(ui: &mut egui::Ui, id: egui::Id, date: &mut NaiveDate) -> bool {
+    let popup_id = id.with("date_picker_popup");
+    let button = ui.button(date.format("%Y-%m-%d").to_string());
+    if button.clicked() {
+        ui.memory().toggle_popup(popup_id);
+        // Start on the month of the current date each time it opens.
+        ui.memory().data.remove::<NaiveDate>(popup_id);
+    }
+    if !ui.memory().is_popup_open(popup_id) {
+        return false;
+    }
+
+    let mut picked = None;
+    let area = egui::Area::new(popup_id)
+        .order(egui::Order::Foreground)
+        .fixed_pos(button.rect.left_bottom())
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                picked = calendar_ui(ui, popup_id, *date);
+            });
+        });
+
+    // egui's own popups close on any click away from the button, including clicks inside the
+    // popup, so this one checks for itself.
+    let clicked_outside = {
+        let input = ui.input();
+        input.pointer.any_click()
+            && input
+                .pointer
+                .interact_pos()
+                .map_or(false, |pos| !area.response.rect.contains(pos))
+            && !button.hovered()
+    };
+    if picked.is_some() || clicked_outside || ui.input().key_pressed(egui::Key::Escape) {
+        ui.memory().close_popup();
+    }
+
+    match picked {
+        Some(new_date) if new_date != *date => {
+            *date = new_date;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// One month of days to pick from, with buttons to flip between months and jump to recent days.
+fn calendar_ui(ui: &mut egui::Ui, id: egui::Id, selected: NaiveDate) -> Option<NaiveDate> {
+    const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+    let today = clock::today();
+    let mut month = ui
+        .memory()
+        .data
+        .get_temp::<NaiveDate>(id)
+        .unwrap_or_else(|| first_of_month(selected));
+    let mut picked = None;
+
+    ui.horizontal(|ui| {
+        if ui.button("<").clicked() {
+            month = first_of_month(month - Duration::days(1));
+        }
+        ui.label(month.format("%B %Y").to_string());
+        if ui.button(">").clicked() {
+            month = first_of_month(month + Duration::days(31));
+        }
+    });
+
+    egui::Grid::new(id.with("days")).show(ui, |ui| {
+        WEEKDAYS.iter().for_each(|day| {
+            ui.label(*day);
+        });
+        ui.end_row();
+
+        (0..month.weekday().num_days_from_monday()).for_each(|_| {
+            ui.label("");
+        });
+        let mut day = month;
+        while day.month() == month.month() {
+            let text = egui::RichText::new(day.day().to_string());
+            let text = if day == today { text.strong() } else { text };
+            if ui.selectable_label(day == selected, text).clicked() {
+                picked = Some(day);
+            }
+            if day.weekday().num_days_from_monday() == 6 {
+                ui.end_row();
+            }
+            day += Duration::days(1);
+        }
+    });
+
+    ui.horizontal(|ui| {
+        if ui.button("Today").clicked() {
+            picked = Some(today);
+        }
+        if ui.button("Yesterday").clicked() {
+            picked = Some(today - Duration::days(1));
+        }
+    });
+
+    ui.memory().data.insert_temp(id, month);
+    picked
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd(date.year(), date.month(), 1)
+}
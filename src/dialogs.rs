@@ -0,0 +1,81 @@
+//! Native file dialogs. There's no file system to browse on the web, so these always come back
+//! empty there.
+
+use std::path::{Path, PathBuf};
+
+/// A type of file the dialogs can filter for.
+pub struct FileKind {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+}
+
+pub const SHEET: FileKind = FileKind {
+    name: "Sheet",
+    extensions: &["sht", "json", "ron", "toml", "sqlite"],
+};
+
+pub const CSV: FileKind = FileKind {
+    name: "CSV",
+    extensions: &["csv", "tsv", "txt"],
+};
+
+pub const MARKDOWN: FileKind = FileKind {
+    name: "Markdown",
+    extensions: &["md"],
+};
+
+pub const JSON_SCHEMA: FileKind = FileKind {
+    name: "JSON Schema",
+    extensions: &["json"],
+};
+
+pub const PDF: FileKind = FileKind {
+    name: "PDF",
+    extensions: &["pdf"],
+};
+
+pub const ICALENDAR: FileKind = FileKind {
+    name: "iCalendar",
+    extensions: &["ics"],
+};
+
+pub const HABITICA: FileKind = FileKind {
+    name: "Habitica data export",
+    extensions: &["json"],
+};
+
+pub const TOGGL: FileKind = FileKind {
+    name: "Toggl Track export",
+    extensions: &["csv", "json"],
+};
+
+/// Asks the user to pick an existing file to open.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_file_to_open(kind: &FileKind) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter(kind.name, kind.extensions)
+        .pick_file()
+}
+
+/// Asks the user where a file should be saved, starting from `current` if there is one.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn pick_file_to_save(kind: &FileKind, current: &Path) -> Option<PathBuf> {
+    let mut dialog = rfd::FileDialog::new().add_filter(kind.name, kind.extensions);
+    if let Some(name) = current.file_name() {
+        dialog = dialog.set_file_name(&name.to_string_lossy());
+    }
+    if let Some(dir) = current.parent().filter(|dir| dir.is_dir()) {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog.save_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn pick_file_to_open(_kind: &FileKind) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn pick_file_to_save(_kind: &FileKind, _current: &Path) -> Option<PathBuf> {
+    None
+}
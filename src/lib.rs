@@ -3,8 +3,32 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
-mod skill;
+mod appearance;
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+mod date_picker;
+mod dialogs;
+mod reminders;
+mod server;
+mod settings;
+mod shortcuts;
+mod updates;
+mod worker;
+// The sheet model lives in its own crate; these keep `crate::skill` and friends working here.
+#[cfg(not(target_arch = "wasm32"))]
+use sheet_core::encryption;
+use sheet_core::{
+    achievements, attributes, bonus, busy, challenges, clock, completion, daily_notes, dashboard,
+    editor, export, file_format, format, formula, goals, identity, import, journal, links, loot,
+    metrics, migration, nudging, paper, planner, pomodoro, prerequisites, prestige, publish,
+    recurring, report, season, sheet, skill, snapshot, stats, storage, suggest, sync, tools, units,
+    versions, webhooks, zones,
+};
+
 pub use app::SheetMyselfApp;
+#[cfg(not(target_arch = "wasm32"))]
+pub use cli::{run as run_cli, serve_port};
+pub use sheet_core::{Effort, LoadError, Sheet, SheetActionRecord, SheetStats, Skill, SkillHandle};
 
 // ----------------------------------------------------------------------------
 // When compiling for web:
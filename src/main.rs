@@ -5,7 +5,26 @@
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() {
-    let app = smlib::SheetMyselfApp::from_default_path();
-    let native_options = eframe::NativeOptions::default();
+    // Any arguments are a command to run instead of opening the window.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let serve_port = match smlib::serve_port(&args) {
+        Some(Ok(port)) => Some(port),
+        Some(Err(e)) => {
+            eprintln!("sheet_myself: {}", e);
+            std::process::exit(2);
+        }
+        None if !args.is_empty() => std::process::exit(smlib::run_cli(&args)),
+        None => None,
+    };
+
+    let mut app = smlib::SheetMyselfApp::from_default_path();
+    if let Some(port) = serve_port {
+        app.serve_on(port);
+    }
+    let native_options = eframe::NativeOptions {
+        // For importing files dropped onto the window.
+        drag_and_drop_support: true,
+        ..Default::default()
+    };
     eframe::run_native(Box::new(app), native_options);
 }
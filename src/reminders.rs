@@ -0,0 +1,131 @@
+use crate::bonus::BonusRule;
+use crate::sheet::Sheet;
+use crate::stats;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+//====================================================
+// Reminders
+//====================================================
+/// When to nag about streaks which are about to run out.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ReminderSettings {
+    pub enabled: bool,
+    /// The local time of day to check at.
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl Default for ReminderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hour: 19,
+            minute: 0,
+        }
+    }
+}
+
+impl ReminderSettings {
+    pub fn time(&self) -> NaiveTime {
+        NaiveTime::from_hms(self.hour.min(23), self.minute.min(59), 0)
+    }
+
+    /// When the next reminder is due, given the day the last one went off. Returns `None` if
+    /// reminders are turned off.
+    pub fn next_due(
+        &self,
+        now: NaiveDateTime,
+        last_reminded: Option<NaiveDate>,
+    ) -> Option<NaiveDateTime> {
+        if !self.enabled {
+            return None;
+        }
+        let mut day = now.date();
+        if last_reminded.map_or(false, |last| last >= day) {
+            day = day.succ();
+        }
+        Some(day.and_time(self.time()))
+    }
+}
+
+/// A skill which will lose its streak bonus unless it's practiced today.
+pub struct AtRisk {
+    pub name: String,
+    /// How many days in a row the skill has been practiced, up to yesterday.
+    pub streak: u32,
+}
+
+/// Skills practiced yesterday but not yet today, longest streak first, leaving out completed ones.
+/// Sheets without a streak rule have no streak bonus to lose, so nothing on them is at risk.
+pub fn at_risk_streaks(sheet: &Sheet, today: NaiveDate) -> Vec<AtRisk> {
+    let has_streak_rule = sheet
+        .bonus_rules
+        .iter()
+        .any(|rule| matches!(rule, BonusRule::Streak(_)));
+    if !has_streak_rule {
+        return Vec::new();
+    }
+
+    let mut at_risk: Vec<AtRisk> = sheet
+        .active_skills()
+        .map(|(_, skill)| skill)
+        .filter(|skill| !skill.records.iter().any(|r| r.approved && r.date == today))
+        .map(|skill| AtRisk {
+            name: skill.name.clone(),
+            streak: stats::current_streak(skill, today),
+        })
+        .filter(|a| a.streak > 0)
+        .collect();
+    at_risk.sort_by_key(|a| std::cmp::Reverse(a.streak));
+    at_risk
+}
+
+/// A list of the skills, e.g. "Guitar (5 days), Japanese (2 days)".
+pub fn describe(at_risk: &[AtRisk]) -> String {
+    at_risk
+        .iter()
+        .map(|a| format!("{} ({} days)", a.name, a.streak))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Just the skills' names, for reminders which shouldn't count up what's at stake.
+pub fn names(at_risk: &[AtRisk]) -> String {
+    at_risk
+        .iter()
+        .map(|a| a.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Pops up a notification through the desktop's own notifier. This is best effort: if there's no
+/// notifier to run, nothing happens, and the in-app banner is still there.
+#[cfg(target_os = "linux")]
+pub fn desktop_notification(title: &str, body: &str) {
+    let _ = std::process::Command::new("notify-send")
+        .arg("--app-name=Sheet Myself")
+        .arg(title)
+        .arg(body)
+        .spawn();
+}
+
+#[cfg(target_os = "macos")]
+pub fn desktop_notification(title: &str, body: &str) {
+    let quote = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "display notification \"{}\" with title \"{}\"",
+        quote(body),
+        quote(title)
+    );
+    let _ = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .spawn();
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn desktop_notification(_title: &str, _body: &str) {
+    // No notifier we can reach without extra dependencies; the in-app banner has to do.
+}
@@ -0,0 +1,726 @@
+//! The `--serve` mode: a small HTTP API on localhost, so other tools, such as phone shortcuts,
+//! can log sessions into the open sheet while the app runs. Requests are handed over to the app,
+//! which answers them between frames against the sheet that was active when it started serving,
+//! so nothing is ever saved over its changes.
+//!
+//! - `GET /skills` lists every skill with its level, EXP and minutes.
+//! - `POST /records` logs a session, e.g. `{"skill": "Guitar", "minutes": 45}`. `minutes` can
+//!   also be "h:mm", and `date` and `note` can be given too, as can `links` to what the session
+//!   produced: each one either text, whose kind is guessed, or `{"kind": "commit", "target":
+//!   "3f9c2ab"}`. A skill which isn't on the sheet yet is added.
+//! - `DELETE /records` takes back the latest session logged to a skill on a day, e.g.
+//!   `{"skill": "Guitar", "date": "yesterday"}`. `date` is today if it isn't given.
+//! - `GET /stats` gives the totals across every skill, and the minutes for each week.
+//!
+//! Only requests addressed to localhost are answered, and only from pages on localhost if
+//! they come from a browser at all. Otherwise a website could point a name of its own at
+//! 127.0.0.1 and have the browser talk to the API as though it were the site.
+//!
+//! Once any [`ApiToken`]s have been made, every request has to come with one, as
+//! `Authorization: Bearer <token>`, whose [`TokenScope`] allows it. Requests made with a token
+//! are kept in its audit log.
+
+use crate::clock;
+use crate::format::ExpFormat;
+use crate::import;
+use crate::links::Link;
+use crate::sheet::Sheet;
+use crate::skill::{SheetActionRecord, Skill};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+pub const DEFAULT_PORT: u16 = 7526;
+/// The most a request's body can be. Anything the API takes is far smaller.
+const MAX_BODY: usize = 64 * 1024;
+/// The most a request's line and headers can be, all together.
+const MAX_HEAD: u64 = 16 * 1024;
+/// How long a client has to send its whole request, and each read or write can wait for it.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a request waits for the app to get to it, e.g. while a dialog is open.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(10);
+/// How many of its latest requests each token's audit log keeps.
+const AUDIT_LOG_LENGTH: usize = 100;
+
+//====================================================
+// Tokens
+//====================================================
+/// What an [`ApiToken`] lets whoever has it do.
+#[derive(Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum TokenScope {
+    /// Looking at skills and stats, but not changing anything.
+    ReadOnly,
+    /// Logging sessions, but not reading anything back or taking anything away.
+    LogOnly,
+    /// Everything, including taking sessions back.
+    Full,
+}
+
+impl TokenScope {
+    pub const ALL: [TokenScope; 3] = [TokenScope::ReadOnly, TokenScope::LogOnly, TokenScope::Full];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TokenScope::ReadOnly => "Read only",
+            TokenScope::LogOnly => "Log only",
+            TokenScope::Full => "Full",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            TokenScope::ReadOnly => "Can list skills and stats, but not change anything",
+            TokenScope::LogOnly => "Can log sessions, but not read anything or remove sessions",
+            TokenScope::Full => "Can do anything the API can, including removing sessions",
+        }
+    }
+
+    fn allows(&self, request: &Request) -> bool {
+        matches!(
+            (self, request),
+            (TokenScope::Full, _)
+                | (TokenScope::ReadOnly, Request::Skills | Request::Stats)
+                | (TokenScope::LogOnly, Request::AddRecord(_))
+        )
+    }
+}
+
+/// A request made with a token, kept so what each one was used for can be looked back on.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AuditEntry {
+    pub time: DateTime<Utc>,
+    /// What was asked for, e.g. "POST /records: 45 min of Guitar".
+    pub request: String,
+    /// The HTTP status it was answered with.
+    pub status: u16,
+}
+
+/// A key to the API, for one tool or device to use.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ApiToken {
+    /// What it's for, e.g. "Phone shortcut".
+    pub name: String,
+    pub secret: String,
+    pub scope: TokenScope,
+    /// The latest requests made with it, oldest first.
+    #[serde(default)]
+    pub audit_log: Vec<AuditEntry>,
+}
+
+impl ApiToken {
+    /// A token with a new random secret.
+    pub fn new(name: &str, scope: TokenScope) -> Self {
+        Self {
+            name: name.to_owned(),
+            secret: Uuid::new_v4().to_simple().to_string(),
+            scope,
+            audit_log: Vec::new(),
+        }
+    }
+
+    fn audit(&mut self, request: String, status: u16) {
+        self.audit_log.push(AuditEntry {
+            time: Utc::now(),
+            request,
+            status,
+        });
+        let over = self.audit_log.len().saturating_sub(AUDIT_LOG_LENGTH);
+        self.audit_log.drain(..over);
+    }
+}
+
+//====================================================
+// Requests
+//====================================================
+enum Request {
+    Skills,
+    AddRecord(NewRecord),
+    RemoveRecord(RemovedRecord),
+    Stats,
+}
+
+impl Request {
+    /// What was asked for, for audit logs.
+    fn describe(&self) -> String {
+        match self {
+            Request::Skills => "GET /skills".to_owned(),
+            Request::AddRecord(new) => {
+                format!("POST /records: {} min of {}", new.minutes, new.skill.trim())
+            }
+            Request::RemoveRecord(removed) => {
+                let day = removed
+                    .date
+                    .map_or_else(|| "today".to_owned(), |date| date.to_string());
+                format!("DELETE /records: {} on {}", removed.skill.trim(), day)
+            }
+            Request::Stats => "GET /stats".to_owned(),
+        }
+    }
+}
+
+/// A session to log, checked over before it's handed to the app.
+struct NewRecord {
+    skill: String,
+    minutes: u64,
+    date: Option<NaiveDate>,
+    note: String,
+    links: Vec<Link>,
+}
+
+/// What `POST /records` takes, as it's sent.
+#[derive(Deserialize)]
+struct RecordBody {
+    skill: String,
+    minutes: Value,
+    #[serde(default)]
+    date: Option<String>,
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    links: Vec<LinkBody>,
+}
+
+/// A session to take back: the latest one logged to `skill` on `date`, or today.
+struct RemovedRecord {
+    skill: String,
+    date: Option<NaiveDate>,
+}
+
+/// What `DELETE /records` takes, as it's sent.
+#[derive(Deserialize)]
+struct RemovedRecordBody {
+    skill: String,
+    #[serde(default)]
+    date: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LinkBody {
+    Typed(Link),
+    Guessed(String),
+}
+
+impl NewRecord {
+    fn parse(body: &str) -> Result<Self, String> {
+        let body: RecordBody = serde_json::from_str(body).map_err(|e| e.to_string())?;
+        if body.skill.trim().is_empty() {
+            return Err("\"skill\" can't be blank".to_owned());
+        }
+        let minutes = match &body.minutes {
+            Value::Number(number) => number
+                .as_u64()
+                .filter(|minutes| *minutes <= import::MAX_DURATION_MINUTES),
+            Value::String(text) => import::parse_duration_minutes(text),
+            _ => None,
+        }
+        .ok_or_else(|| format!("{} isn't a number of minutes", body.minutes))?;
+        Ok(Self {
+            skill: body.skill,
+            minutes,
+            date: parse_day(body.date.as_deref())?,
+            note: body.note,
+            links: body
+                .links
+                .into_iter()
+                .map(|link| match link {
+                    LinkBody::Typed(link) => link,
+                    LinkBody::Guessed(text) => Link::guess(&text),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl RemovedRecord {
+    fn parse(body: &str) -> Result<Self, String> {
+        let body: RemovedRecordBody = serde_json::from_str(body).map_err(|e| e.to_string())?;
+        Ok(Self {
+            skill: body.skill,
+            date: parse_day(body.date.as_deref())?,
+        })
+    }
+}
+
+/// A date as the API takes one: `YYYY-MM-DD` and the like, `today` or `yesterday`. Today is
+/// given back as `None`, since it may be a different day by the time the app gets to it.
+fn parse_day(text: Option<&str>) -> Result<Option<NaiveDate>, String> {
+    match text {
+        None | Some("today") => Ok(None),
+        Some("yesterday") => Ok(Some(clock::today().pred())),
+        Some(text) => import::parse_date(text)
+            .map(Some)
+            .ok_or_else(|| format!("\"{}\" isn't a date", text)),
+    }
+}
+
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+/// A request waiting for the app to answer it.
+pub struct Call {
+    request: Request,
+    /// The token it was sent with, if any.
+    token: Option<String>,
+    reply: mpsc::Sender<Response>,
+}
+
+impl Call {
+    /// Answers the request from `sheet`, logging to it if that's what was asked. If there are
+    /// any `tokens`, it's only answered if it came with one whose scope allows it, and it goes in
+    /// that token's audit log.
+    /// A sheet which is changed is marked dirty at `time`, so it's saved once it's gone quiet.
+    pub fn answer(
+        self,
+        sheet: &mut Sheet,
+        exp_format: &ExpFormat,
+        tokens: &mut [ApiToken],
+        time: f64,
+    ) {
+        let response = if tokens.is_empty() {
+            respond(sheet, self.request, exp_format, time)
+        } else {
+            let secret = self.token.as_deref().unwrap_or_default();
+            match tokens
+                .iter_mut()
+                .find(|token| same_secret(&token.secret, secret))
+            {
+                None => Response::error(401, "send a token as \"Authorization: Bearer <token>\""),
+                Some(token) => {
+                    let request = self.request.describe();
+                    let response = if token.scope.allows(&self.request) {
+                        respond(sheet, self.request, exp_format, time)
+                    } else {
+                        let scope = token.scope.label().to_lowercase();
+                        Response::error(403, &format!("a {} token can't do that", scope))
+                    };
+                    token.audit(request, response.status);
+                    response
+                }
+            }
+        };
+        // The connection may have given up waiting, which is fine.
+        let _ = self.reply.send(response);
+    }
+
+    /// Answers the request with an error, without looking at it.
+    pub fn refuse(self, status: u16, message: &str) {
+        let _ = self.reply.send(Response::error(status, message));
+    }
+}
+
+/// Whether `a` and `b` are the same, taking as long to find out however much of them matches,
+/// so a token can't be guessed a character at a time by timing the answers.
+fn same_secret(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let differences = a
+        .iter()
+        .zip(b)
+        .fold(a.len() ^ b.len(), |differences, (a, b)| {
+            differences | usize::from(a ^ b)
+        });
+    differences == 0 && !a.is_empty()
+}
+
+//====================================================
+// Answering
+//====================================================
+fn respond(sheet: &mut Sheet, request: Request, exp_format: &ExpFormat, time: f64) -> Response {
+    match request {
+        Request::Skills => {
+            let mut skills: Vec<&Skill> = sheet.skills_list.values().collect();
+            skills.sort_by(|a, b| a.name.cmp(&b.name));
+            Response::ok(
+                skills
+                    .iter()
+                    .map(|skill| skill_json(skill, exp_format))
+                    .collect(),
+            )
+        }
+        Request::AddRecord(new) => {
+            let record = SheetActionRecord {
+                date: new.date.unwrap_or_else(clock::today),
+                duration: new.minutes,
+                note: new.note,
+                links: new.links,
+                approved: !sheet.require_approval,
+                ..Default::default()
+            };
+            let mut handle = sheet.skill(&new.skill);
+            handle.add_record(record);
+            let body = skill_json(handle.get(), exp_format);
+            sheet.mark_dirty(time);
+            Response { status: 201, body }
+        }
+        Request::RemoveRecord(removed) => {
+            let date = removed.date.unwrap_or_else(clock::today);
+            let removed = import::find_skill(sheet, &removed.skill).and_then(|skill_id| {
+                let records = &mut sheet.skills_list.get_mut(&skill_id)?.records;
+                let idx = records.iter().rposition(|r| r.date == date)?;
+                records.remove(idx);
+                Some(skill_id)
+            });
+            match removed {
+                Some(skill_id) => {
+                    sheet.recalculate_skill_since(&skill_id, date);
+                    sheet.mark_dirty(time);
+                    Response::ok(skill_json(&sheet.skills_list[&skill_id], exp_format))
+                }
+                None => Response::error(404, "there's no session of that skill on that day"),
+            }
+        }
+        Request::Stats => {
+            let stats = sheet.stats();
+            Response::ok(json!({
+                "total_minutes": stats.total_minutes(),
+                "total_exp": stats.total_exp().round(),
+                "exp_name": exp_format.name,
+                "weekly_minutes": stats.weekly(),
+            }))
+        }
+    }
+}
+
+fn skill_json(skill: &Skill, exp_format: &ExpFormat) -> Value {
+    let minutes: u64 = skill
+        .records
+        .iter()
+        .filter(|r| r.approved)
+        .map(|r| r.duration)
+        .sum();
+    json!({
+        "name": skill.name,
+        "level": skill.level(),
+        "exp": skill.total_exp.round(),
+        "exp_name": exp_format.name,
+        "minutes": minutes,
+    })
+}
+
+//====================================================
+// Serving
+//====================================================
+/// Starts listening on `port` on localhost only, on a thread of its own. Each request comes out
+/// of the receiver for the app to answer, after `wake` has been called to get its attention.
+pub fn start(
+    port: u16,
+    wake: impl Fn() + Send + Sync + 'static,
+) -> io::Result<mpsc::Receiver<Call>> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    let (calls, receiver) = mpsc::channel();
+    let wake = Arc::new(wake);
+    std::thread::spawn(move || {
+        // Each connection is read on a thread of its own, so one which stalls can't hold up the
+        // rest. The app still answers them one at a time, in the order they finish arriving.
+        listener.incoming().flatten().for_each(|stream| {
+            let calls = calls.clone();
+            let wake = wake.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = handle(stream, port, &calls, &*wake) {
+                    eprintln!("sheet_myself: API request failed: {}", e);
+                }
+            });
+        });
+    });
+    Ok(receiver)
+}
+
+fn handle(
+    stream: TcpStream,
+    port: u16,
+    calls: &mpsc::Sender<Call>,
+    wake: &dyn Fn(),
+) -> io::Result<()> {
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(Deadline {
+        stream: stream.try_clone()?,
+        deadline: Instant::now() + CLIENT_TIMEOUT,
+    });
+    let mut head_left = MAX_HEAD;
+    let mut read_head_line = |reader: &mut BufReader<Deadline>, line: &mut String| {
+        let read = reader.take(head_left).read_line(line)?;
+        head_left -= read as u64;
+        if head_left == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the request's headers are too long",
+            ));
+        }
+        Ok(read)
+    };
+    let mut request_line = String::new();
+    read_head_line(&mut reader, &mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let target = parts.next().unwrap_or_default();
+    let path = target.split('?').next().unwrap_or_default().to_owned();
+
+    let mut content_length = 0;
+    let mut json_body = false;
+    let mut token = None;
+    let mut local = true;
+    loop {
+        let mut header = String::new();
+        if read_head_line(&mut reader, &mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            match name.trim().to_lowercase().as_str() {
+                "content-length" => content_length = value.parse().unwrap_or(0),
+                "content-type" => json_body = value.starts_with("application/json"),
+                "authorization" => {
+                    token = value.strip_prefix("Bearer ").map(|t| t.trim().to_owned())
+                }
+                "host" => local &= is_local(value, Some(port)),
+                "origin" => {
+                    let value = value.to_ascii_lowercase();
+                    let site = value
+                        .strip_prefix("http://")
+                        .or_else(|| value.strip_prefix("https://"));
+                    local &= site.map_or(false, |site| is_local(site, None));
+                }
+                _ => {}
+            }
+        }
+    }
+    if !local {
+        return write_response(
+            stream,
+            &Response::error(403, "only requests to and from localhost are answered"),
+        );
+    }
+    if content_length > MAX_BODY {
+        let message = format!("the body can be at most {} bytes", MAX_BODY);
+        return write_response(stream, &Response::error(413, &message));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8_lossy(&body);
+
+    let request = match (method.as_str(), path.as_str()) {
+        ("GET", "/skills") => Ok(Request::Skills),
+        ("GET", "/stats") => Ok(Request::Stats),
+        // Web pages can't send JSON to another site without asking first, and nothing here
+        // says yes, so insisting on it keeps a page that's open in a browser from logging.
+        ("POST", "/records") | ("DELETE", "/records") if !json_body => Err(Response::error(
+            415,
+            "the body has to be sent as application/json",
+        )),
+        ("POST", "/records") => NewRecord::parse(&body)
+            .map(Request::AddRecord)
+            .map_err(|e| Response::error(400, &e)),
+        ("DELETE", "/records") => RemovedRecord::parse(&body)
+            .map(Request::RemoveRecord)
+            .map_err(|e| Response::error(400, &e)),
+        (_, "/skills") | (_, "/stats") | (_, "/records") => {
+            Err(Response::error(405, "method not allowed"))
+        }
+        _ => Err(Response::error(404, "not found")),
+    };
+    let response = match request {
+        Ok(request) => ask_app(request, token, calls, wake),
+        Err(response) => response,
+    };
+    write_response(stream, &response)
+}
+
+/// Whether `authority`, a `Host` header or an `Origin` without its scheme, names this computer.
+/// With a `port` it has to name that port too, or else any port will do.
+fn is_local(authority: &str, port: Option<u16>) -> bool {
+    let authority = authority.to_ascii_lowercase();
+    ["localhost", "127.0.0.1", "[::1]"]
+        .iter()
+        .any(|host| match authority.strip_prefix(host) {
+            Some("") => port.is_none(),
+            Some(rest) => rest
+                .strip_prefix(':')
+                .and_then(|given| given.parse::<u16>().ok())
+                .map_or(false, |given| port.map_or(true, |port| given == port)),
+            None => false,
+        })
+}
+
+/// A connection which gives up reading once `deadline` has passed, however slowly the request
+/// is trickling in.
+struct Deadline {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self
+            .deadline
+            .checked_duration_since(Instant::now())
+            .filter(|left| !left.is_zero())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "the request took too long"))?;
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Hands `request` to the app and waits for its answer.
+fn ask_app(
+    request: Request,
+    token: Option<String>,
+    calls: &mpsc::Sender<Call>,
+    wake: &dyn Fn(),
+) -> Response {
+    let (reply, answer) = mpsc::channel();
+    let call = Call {
+        request,
+        token,
+        reply,
+    };
+    if calls.send(call).is_err() {
+        return Response::error(503, "the app is closing");
+    }
+    wake();
+    answer
+        .recv_timeout(ANSWER_TIMEOUT)
+        .unwrap_or_else(|_| Response::error(503, "the app didn't answer in time"))
+}
+
+fn write_response(mut stream: TcpStream, response: &Response) -> io::Result<()> {
+    let body = response.body.to_string();
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn free_port() -> u16 {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    fn send(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut answer = String::new();
+        stream.read_to_string(&mut answer).unwrap();
+        answer
+    }
+
+    #[test]
+    fn secrets_only_match_exactly() {
+        assert!(same_secret("3f9c2ab", "3f9c2ab"));
+        assert!(!same_secret("3f9c2ab", "3f9c2ac"));
+        assert!(!same_secret("3f9c2ab", "3f9c2a"));
+        assert!(!same_secret("3f9c2ab", ""));
+        assert!(!same_secret("", ""));
+    }
+
+    #[test]
+    fn only_requests_to_and_from_localhost_are_answered() {
+        let port = free_port();
+        let calls = start(port, || {}).unwrap();
+        std::thread::spawn(move || {
+            calls.iter().for_each(|call| {
+                let _ = call.reply.send(Response::ok(json!({})));
+            });
+        });
+        let get = |headers: &str| send(port, &format!("GET /skills HTTP/1.1\r\n{}\r\n", headers));
+
+        let host = format!("Host: localhost:{}\r\n", port);
+        assert!(get(&host).starts_with("HTTP/1.1 200 "));
+        assert!(get(&format!("Host: 127.0.0.1:{}\r\n", port)).starts_with("HTTP/1.1 200 "));
+        let from_local_page = format!("{}Origin: http://localhost:3000\r\n", host);
+        assert!(get(&from_local_page).starts_with("HTTP/1.1 200 "));
+
+        // What a page which rebound its own name to 127.0.0.1 would send.
+        let rebound = format!("Host: evil.example:{}\r\n", port);
+        assert!(get(&rebound).starts_with("HTTP/1.1 403 "));
+        assert!(get("Host: localhost\r\n").starts_with("HTTP/1.1 403 "));
+        assert!(
+            get(&format!("Host: localhost.evil.example:{}\r\n", port)).starts_with("HTTP/1.1 403 ")
+        );
+        for origin in [
+            "http://evil.example",
+            "null",
+            "http://localhost.evil.example",
+        ] {
+            let request = format!("{}Origin: {}\r\n", host, origin);
+            assert!(get(&request).starts_with("HTTP/1.1 403 "), "{}", origin);
+        }
+    }
+
+    #[test]
+    fn oversized_bodies_are_refused_while_another_client_stalls() {
+        let port = free_port();
+        let _calls = start(port, || {}).unwrap();
+        // Connects and then sends nothing, which mustn't hold up anyone else.
+        let _stalled = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+
+        let started = Instant::now();
+        let answer = send(
+            port,
+            &format!(
+                "POST /records HTTP/1.1\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\n\r\n{{}}",
+                MAX_BODY + 1
+            ),
+        );
+        assert!(answer.starts_with("HTTP/1.1 413 "), "{}", answer);
+        assert!(started.elapsed() < CLIENT_TIMEOUT);
+    }
+
+    #[test]
+    fn headers_which_never_end_are_cut_off() {
+        let port = free_port();
+        let _calls = start(port, || {}).unwrap();
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let long_header = format!(
+            "GET /skills HTTP/1.1\r\nX-Padding: {}",
+            "a".repeat(64 * 1024)
+        );
+        // The server may stop reading and hang up before all of it has been sent.
+        let _ = stream.write_all(long_header.as_bytes());
+        let mut answer = String::new();
+        let _ = stream.read_to_string(&mut answer);
+        assert!(answer.is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::appearance::Appearance;
+use crate::busy::BusySettings;
+use crate::clock::DayBoundary;
+use crate::file_format::FileFormat;
+use crate::format::ExpFormat;
+use crate::metrics::Metric;
+use crate::nudging::NudgePolicy;
+use crate::pomodoro::PomodoroSettings;
+use crate::publish::PublishSettings;
+use crate::reminders::ReminderSettings;
+use crate::server::ApiToken;
+use crate::shortcuts::Shortcuts;
+use crate::webhooks::Webhook;
+use serde::{Deserialize, Serialize};
+
+//====================================================
+// Settings
+//====================================================
+/// App-wide preferences, stored alongside the list of open sheets rather than in any one sheet.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    /// How many hours a day you're awake for; the time audit compares logged time against this.
+    pub waking_hours_per_day: f64,
+    pub autosave: bool,
+    /// How long a sheet has to go without edits before it's autosaved.
+    pub autosave_interval_secs: f64,
+    /// How many previous versions of a sheet to keep next to it when saving.
+    pub backup_count: usize,
+    /// Whether every save is also committed to the sheet's version history.
+    pub git_history: bool,
+    /// What new `.sht` sheets are written as.
+    pub sheet_format: FileFormat,
+    pub appearance: Appearance,
+    /// When each day starts, for everything that goes by "today".
+    pub day_boundary: DayBoundary,
+    pub exp_format: ExpFormat,
+    pub pomodoro: PomodoroSettings,
+    /// The program to edit notes with, e.g. `code --wait`. Blank means `$EDITOR`.
+    pub note_editor: String,
+    pub reminders: ReminderSettings,
+    /// How hard streaks, reminders and misses are pushed, including calm mode.
+    pub nudging: NudgePolicy,
+    /// Whether to ask for a line about it when a skill levels up, for its journal.
+    pub reflect_on_level_up: bool,
+    pub shortcuts: Shortcuts,
+    /// The read-only progress page for a website.
+    pub publish: PublishSettings,
+    /// Keys to the `--serve` API. With none, anything on this computer can use it.
+    pub api_tokens: Vec<ApiToken>,
+    /// Told about level ups, streaks and completed goals.
+    pub webhooks: Vec<Webhook>,
+    /// Showing as busy while a timer runs.
+    pub busy: BusySettings,
+    /// What the status bar shows, in order.
+    pub status_bar: Vec<Metric>,
+    /// Whether to ask GitHub for a newer version when the app starts. Off unless turned on, so
+    /// there are no network requests that weren't asked for.
+    pub check_for_updates: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            waking_hours_per_day: 16.0,
+            autosave: true,
+            autosave_interval_secs: 30.0,
+            backup_count: 3,
+            git_history: false,
+            sheet_format: FileFormat::default(),
+            appearance: Appearance::default(),
+            day_boundary: DayBoundary::default(),
+            exp_format: ExpFormat::default(),
+            pomodoro: PomodoroSettings::default(),
+            note_editor: String::new(),
+            reminders: ReminderSettings::default(),
+            nudging: NudgePolicy::default(),
+            reflect_on_level_up: true,
+            shortcuts: Shortcuts::default(),
+            publish: PublishSettings::default(),
+            check_for_updates: false,
+            api_tokens: Vec::new(),
+            webhooks: Vec::new(),
+            busy: BusySettings::default(),
+            status_bar: vec![
+                Metric::TodayMinutes,
+                Metric::GoalProgress,
+                Metric::ActiveStreaks,
+            ],
+        }
+    }
+}
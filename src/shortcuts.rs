@@ -0,0 +1,205 @@
+use eframe::egui::{self, Key};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Every key a shortcut can be bound to, with the name it's saved and shown as.
+pub const KEYS: [(Key, &str); 46] = [
+    (Key::A, "A"),
+    (Key::B, "B"),
+    (Key::C, "C"),
+    (Key::D, "D"),
+    (Key::E, "E"),
+    (Key::F, "F"),
+    (Key::G, "G"),
+    (Key::H, "H"),
+    (Key::I, "I"),
+    (Key::J, "J"),
+    (Key::K, "K"),
+    (Key::L, "L"),
+    (Key::M, "M"),
+    (Key::N, "N"),
+    (Key::O, "O"),
+    (Key::P, "P"),
+    (Key::Q, "Q"),
+    (Key::R, "R"),
+    (Key::S, "S"),
+    (Key::T, "T"),
+    (Key::U, "U"),
+    (Key::V, "V"),
+    (Key::W, "W"),
+    (Key::X, "X"),
+    (Key::Y, "Y"),
+    (Key::Z, "Z"),
+    (Key::Num0, "0"),
+    (Key::Num1, "1"),
+    (Key::Num2, "2"),
+    (Key::Num3, "3"),
+    (Key::Num4, "4"),
+    (Key::Num5, "5"),
+    (Key::Num6, "6"),
+    (Key::Num7, "7"),
+    (Key::Num8, "8"),
+    (Key::Num9, "9"),
+    (Key::Enter, "Enter"),
+    (Key::Space, "Space"),
+    (Key::Tab, "Tab"),
+    (Key::Insert, "Insert"),
+    (Key::Delete, "Delete"),
+    (Key::Home, "Home"),
+    (Key::End, "End"),
+    (Key::PageUp, "PageUp"),
+    (Key::PageDown, "PageDown"),
+    (Key::Escape, "Escape"),
+];
+
+//====================================================
+// Shortcut
+//====================================================
+/// Something which can be done from the keyboard.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Action {
+    Save,
+    Open,
+    NewSkill,
+    /// Adds an entry for today to the skill under the pointer, or the last one it was over.
+    AddEntry,
+    ShowDashboard,
+    ShowToday,
+    ShowSkills,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::Save,
+        Action::Open,
+        Action::NewSkill,
+        Action::AddEntry,
+        Action::ShowDashboard,
+        Action::ShowToday,
+        Action::ShowSkills,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Save => "Save",
+            Action::Open => "Open sheet",
+            Action::NewSkill => "New skill",
+            Action::AddEntry => "Add today's entry to the hovered skill",
+            Action::ShowDashboard => "Go to Dashboard",
+            Action::ShowToday => "Go to Today",
+            Action::ShowSkills => "Go to Skills",
+        }
+    }
+
+    fn default_shortcut(&self) -> Shortcut {
+        let key = match self {
+            Action::Save => "S",
+            Action::Open => "O",
+            Action::NewSkill => "N",
+            Action::AddEntry => "Enter",
+            Action::ShowDashboard => "1",
+            Action::ShowToday => "2",
+            Action::ShowSkills => "3",
+        };
+        Shortcut {
+            key: key.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A key, and which modifiers have to be held with it. `command` is Ctrl, or Cmd on a Mac.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Shortcut {
+    /// One of the names in [`KEYS`].
+    pub key: String,
+    pub command: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Default for Shortcut {
+    fn default() -> Self {
+        Self {
+            key: "A".to_owned(),
+            command: true,
+            shift: false,
+            alt: false,
+        }
+    }
+}
+
+impl Shortcut {
+    pub fn key(&self) -> Option<Key> {
+        KEYS.iter()
+            .find(|(_, name)| *name == self.key)
+            .map(|(key, _)| *key)
+    }
+
+    /// Exact modifiers only, so Ctrl+S doesn't also fire a Ctrl+Shift+S binding.
+    fn pressed(&self, input: &egui::InputState) -> bool {
+        let modifiers = input.modifiers;
+        self.key().map_or(false, |key| input.key_pressed(key))
+            && modifiers.command == self.command
+            && modifiers.shift == self.shift
+            && modifiers.alt == self.alt
+    }
+
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if self.command {
+            label.push_str(if cfg!(target_os = "macos") {
+                "Cmd+"
+            } else {
+                "Ctrl+"
+            });
+        }
+        if self.shift {
+            label.push_str("Shift+");
+        }
+        if self.alt {
+            label.push_str("Alt+");
+        }
+        label.push_str(&self.key);
+        label
+    }
+}
+
+//====================================================
+// Shortcuts
+//====================================================
+/// The key bindings, as saved in the settings. Actions without a binding use their default;
+/// `None` means the action has been unbound.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Shortcuts {
+    pub bindings: HashMap<Action, Option<Shortcut>>,
+}
+
+impl Shortcuts {
+    pub fn get(&self, action: Action) -> Option<Shortcut> {
+        self.bindings
+            .get(&action)
+            .cloned()
+            .unwrap_or_else(|| Some(action.default_shortcut()))
+    }
+
+    /// e.g. " (Ctrl+S)", for putting after a menu item's name.
+    pub fn hint(&self, action: Action) -> String {
+        self.get(action)
+            .map_or(String::new(), |shortcut| format!(" ({})", shortcut.label()))
+    }
+
+    /// Which actions' shortcuts were pressed this frame.
+    pub fn pressed(&self, input: &egui::InputState) -> Vec<Action> {
+        Action::ALL
+            .iter()
+            .filter(|action| {
+                self.get(**action)
+                    .map_or(false, |shortcut| shortcut.pressed(input))
+            })
+            .copied()
+            .collect()
+    }
+}
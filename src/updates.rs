@@ -0,0 +1,66 @@
+//! Asking GitHub whether there's a newer release than this one. Nothing is sent apart from the
+//! request itself.
+
+use serde::Deserialize;
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/AberrantWolf/sheet_myself_rs/releases/latest";
+
+/// The version of this build, e.g. "0.1.0".
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A published release, as GitHub's API describes it.
+#[derive(Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The release notes, in Markdown.
+    #[serde(default)]
+    pub body: Option<String>,
+    /// The release's page, which has the downloads.
+    pub html_url: String,
+}
+
+impl Release {
+    pub fn title(&self) -> &str {
+        self.name
+            .as_deref()
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or(&self.tag_name)
+    }
+
+    pub fn notes(&self) -> &str {
+        self.body.as_deref().unwrap_or_default()
+    }
+
+    /// Whether this is a later version than the one running.
+    pub fn is_newer(&self) -> bool {
+        version_numbers(&self.tag_name) > version_numbers(CURRENT_VERSION)
+    }
+}
+
+/// `v1.2.0-beta` gives `[1, 2, 0]`. Anything after the numbers is ignored.
+fn version_numbers(version: &str) -> Vec<u64> {
+    let numbers = version.trim().trim_start_matches('v');
+    let numbers = numbers.split(['-', '+']).next().unwrap_or_default();
+    numbers
+        .split('.')
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Fetches the latest release. This waits for the network, so it shouldn't be called on the UI
+/// thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn latest_release() -> Result<Release, String> {
+    let response = sheet_core::http::agent()
+        .get(LATEST_RELEASE_URL)
+        .set("Accept", "application/vnd.github+json")
+        .call()
+        .map_err(|e| format!("couldn't ask GitHub: {}", e))?;
+    let text = response
+        .into_string()
+        .map_err(|e| format!("couldn't read GitHub's answer: {}", e))?;
+    serde_json::from_str(&text).map_err(|e| format!("GitHub's answer didn't make sense: {}", e))
+}
@@ -0,0 +1,108 @@
+//! Long-running work which happens away from the UI, such as importing records or working out
+//! the charts for a big history. The work runs on its own thread and sends updates back over a
+//! channel as it goes, waking the UI for each one, so it can show what's been done so far
+//! instead of a frame waiting on it. The web has no threads, so there the work is done as soon
+//! as it's started and only its result is seen.
+
+use eframe::epi;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+
+/// What a worker sends back to the UI.
+pub enum Update<P, D> {
+    /// How far it's got, and whatever it's worked out so far.
+    Progress(P),
+    /// What it finished with.
+    Done(D),
+    /// It ended without finishing, e.g. it panicked.
+    Stopped,
+}
+
+//====================================================
+// Reporter
+//====================================================
+/// Handed to the work, to send its progress back with and find out whether it's still wanted.
+pub struct Reporter<P> {
+    sender: mpsc::Sender<P>,
+    stop: Arc<AtomicBool>,
+    wake: Box<dyn Fn() + Send>,
+}
+
+impl<P> Reporter<P> {
+    /// Sends `progress` back. Gives back whether the work should carry on, which it shouldn't
+    /// once it's been asked to stop or nobody is waiting on it any more.
+    pub fn progress(&self, progress: P) -> bool {
+        if self.sender.send(progress).is_err() {
+            return false;
+        }
+        (self.wake)();
+        !self.stop.load(Ordering::Relaxed)
+    }
+}
+
+//====================================================
+// Worker
+//====================================================
+/// A piece of work going on in the background, which sends back progress of type `P` and ends
+/// with a `D`. Dropping it lets the work know it's no longer wanted.
+pub struct Worker<P, D> {
+    progress: mpsc::Receiver<P>,
+    done: mpsc::Receiver<D>,
+    stop: Arc<AtomicBool>,
+}
+
+impl<P: Send + 'static, D: Send + 'static> Worker<P, D> {
+    pub fn spawn<F>(frame: &epi::Frame, work: F) -> Self
+    where
+        F: FnOnce(&Reporter<P>) -> D + Send + 'static,
+    {
+        let (progress_sender, progress) = mpsc::channel();
+        let (done_sender, done) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let waker = frame.clone();
+        let reporter = Reporter {
+            sender: progress_sender,
+            stop: stop.clone(),
+            wake: Box::new(move || waker.request_repaint()),
+        };
+        let frame = frame.clone();
+        let run = move || {
+            let _ = done_sender.send(work(&reporter));
+            frame.request_repaint();
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(run);
+        #[cfg(target_arch = "wasm32")]
+        run();
+        Self {
+            progress,
+            done,
+            stop,
+        }
+    }
+}
+
+impl<P, D> Worker<P, D> {
+    /// Asks the work to finish up early, at the next progress it sends back. It still ends with
+    /// whatever it makes of what it's done.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    /// Waits for the work to end, giving back what it finished with, or `None` if it stopped
+    /// without finishing.
+    pub fn wait(self) -> Option<D> {
+        self.done.recv().ok()
+    }
+
+    /// The latest of what's been sent back since last time, if anything has. Earlier progress
+    /// is passed over, as it's out of date.
+    pub fn poll(&self) -> Option<Update<P, D>> {
+        match self.done.try_recv() {
+            Ok(done) => return Some(Update::Done(done)),
+            Err(mpsc::TryRecvError::Disconnected) => return Some(Update::Stopped),
+            Err(mpsc::TryRecvError::Empty) => {}
+        }
+        self.progress.try_iter().last().map(Update::Progress)
+    }
+}